@@ -0,0 +1,14 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Generated with protox so building the gRPC server doesn't need protoc installed
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/scanner.proto");
+        let descriptors = protox::compile(["proto/scanner.proto"], ["proto"]).expect("Invalid scanner.proto");
+        tonic_build::configure()
+            .build_client(false)
+            .compile_fds(descriptors)
+            .expect("Failed to generate gRPC code");
+    }
+}
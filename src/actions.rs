@@ -0,0 +1,1019 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::alerts::{ActionResult, AlertEvent, AlertRecord};
+use crate::config::{is_retryable, HttpConfig};
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::StockQuote;
+use crate::output::{self, Summary};
+use crate::rt;
+
+/// Header carrying the hex HMAC-SHA256 of the request body
+const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Action performed when an alert fires or a scan completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AlertAction {
+    /// Print to the terminal
+    Log,
+    /// Pop a native desktop notification
+    Desktop,
+    /// POST a JSON payload to a URL
+    Webhook {
+        url: String,
+        /// Shared secret used to sign the body with HMAC-SHA256
+        #[serde(default)]
+        secret: Option<String>,
+        /// Additional attempts after a failed delivery
+        #[serde(default = "default_webhook_retries")]
+        retries: u32,
+    },
+    /// Post a message to Slack using `[notifications.slack]`
+    Slack {
+        /// Channel for this action, overriding the configured default
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Post a colored embed to a Discord webhook
+    Discord { url: String },
+    /// Send an email through `[notifications.email]`
+    Email {
+        to: Vec<String>,
+        /// Subject line, replacing the generated one
+        #[serde(default)]
+        subject: Option<String>,
+    },
+    /// Run a program with quote fields in `SCANNER_*` environment variables
+    Exec {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        /// Also write the webhook JSON payload to the program's stdin
+        #[serde(default)]
+        stdin: bool,
+    },
+}
+
+impl AlertAction {
+    /// The `type` name used in config and the alert history
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlertAction::Log => "log",
+            AlertAction::Desktop => "desktop",
+            AlertAction::Webhook { .. } => "webhook",
+            AlertAction::Slack { .. } => "slack",
+            AlertAction::Discord { .. } => "discord",
+            AlertAction::Email { .. } => "email",
+            AlertAction::Exec { .. } => "exec",
+        }
+    }
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+/// `[notifications]` section of the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Actions to run after every `scan`
+    #[serde(default)]
+    pub on_scan_complete: Vec<AlertAction>,
+
+    /// Actions to run periodically during `watch`
+    #[serde(default)]
+    pub on_watch_summary: Vec<AlertAction>,
+
+    /// Seconds between `on_watch_summary` deliveries
+    #[serde(default = "default_watch_summary_interval")]
+    pub watch_summary_interval_secs: u64,
+
+    /// Slack credentials shared by all `slack` actions
+    pub slack: Option<SlackConfig>,
+
+    /// SMTP server used by `email` actions
+    pub email: Option<SmtpConfig>,
+}
+
+fn default_watch_summary_interval() -> u64 {
+    3600
+}
+
+/// `[notifications.slack]`: either an incoming webhook or a bot token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackConfig {
+    /// Incoming webhook URL
+    pub webhook_url: Option<String>,
+
+    /// Bot token used with `chat.postMessage`
+    pub token: Option<String>,
+
+    /// Default channel, required when using a bot token
+    pub channel: Option<String>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            on_scan_complete: Vec::new(),
+            on_watch_summary: Vec::new(),
+            watch_summary_interval_secs: default_watch_summary_interval(),
+            slack: None,
+            email: None,
+        }
+    }
+}
+
+/// `[notifications.email]`: SMTP server and sender address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+
+    /// Defaults to 587 for STARTTLS, 465 for TLS and 25 without encryption
+    pub port: Option<u16>,
+
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    /// Sender, e.g. `Scanner <scanner@example.com>`
+    pub from: String,
+
+    #[serde(default)]
+    pub tls: SmtpTls,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with STARTTLS
+    #[default]
+    Starttls,
+    /// Connect over implicit TLS
+    Tls,
+    /// No encryption, for local relays only
+    None,
+}
+
+impl SmtpTls {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    fn default_port(self) -> u16 {
+        match self {
+            SmtpTls::Starttls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        }
+    }
+}
+
+const SLACK_POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+/// Embed colors used for Discord messages
+const DISCORD_GREEN: u32 = 0x2ecc71;
+const DISCORD_RED: u32 = 0xe74c3c;
+
+/// Movers listed per embed in Discord summaries
+const DISCORD_MOVERS: usize = 5;
+
+/// Results of a finished scan, as delivered to notification actions
+#[derive(Debug, Serialize)]
+pub struct ScanReport {
+    pub timestamp: String,
+    pub summary: Summary,
+    pub quotes: Vec<StockQuote>,
+}
+
+impl ScanReport {
+    pub fn new(quotes: &[StockQuote]) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            summary: output::calculate_summary(quotes),
+            quotes: quotes.to_vec(),
+        }
+    }
+}
+
+/// JSON body sent to webhooks
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Payload<'a> {
+    Alert(&'a AlertEvent),
+    ScanComplete(&'a ScanReport),
+}
+
+/// Longest wait between delivery attempts, however many retries an action allows
+const MAX_RETRY_DELAY_MS: u64 = 30_000;
+
+/// Wait before delivery retry `attempt` (0-based): half a second, doubling up to the cap
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(500u64.saturating_mul(2u64.saturating_pow(attempt)).min(MAX_RETRY_DELAY_MS))
+}
+
+/// Executes notification actions, sharing one HTTP client
+pub struct ActionRunner {
+    http: reqwest::Client,
+    timeout: Duration,
+    config: NotificationsConfig,
+    /// Ring the terminal bell when alerts fire
+    bell: bool,
+    /// Sound file played along with the bell
+    bell_sound: Option<PathBuf>,
+    /// Whether the `log` action prints to stdout
+    terminal_log: bool,
+}
+
+impl ActionRunner {
+    pub fn new(timeout_secs: u64, config: NotificationsConfig) -> Result<Self> {
+        let timeout = Duration::from_secs(timeout_secs);
+        let builder = reqwest::Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(timeout);
+        let http = builder
+            .build()
+            .map_err(|e| ScannerError::Config(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            timeout,
+            config,
+            bell: false,
+            bell_sound: None,
+            terminal_log: true,
+        })
+    }
+
+    /// Send webhooks and chat messages with the API client's connection and proxy settings
+    pub fn with_http(mut self, http: &HttpConfig) -> Self {
+        match http.client_builder(self.timeout).and_then(|builder| Ok(builder.build()?)) {
+            Ok(client) => self.http = client,
+            Err(e) => log::warn!("Ignoring [http] settings for notifications: {}", e),
+        }
+        self
+    }
+
+    /// Skip printing for the `log` action, e.g. while a full-screen dashboard is drawn
+    pub fn without_terminal_log(mut self) -> Self {
+        self.terminal_log = false;
+        self
+    }
+
+    /// Ring the bell (or play `sound_file`) whenever a refresh fires at least one alert
+    pub fn with_bell(mut self, sound_file: Option<PathBuf>) -> Self {
+        self.bell = true;
+        self.bell_sound = sound_file;
+        self
+    }
+
+    /// Run the actions of every fired alert, logging failures instead of stopping
+    ///
+    /// Returns one history record per alert with the outcome of each action.
+    pub async fn dispatch(&self, fired: Vec<(AlertEvent, Vec<AlertAction>)>) -> Vec<AlertRecord> {
+        let mut records = Vec::with_capacity(fired.len());
+
+        if self.bell && !fired.is_empty() {
+            ring(self.bell_sound.as_deref());
+        }
+
+        for (event, actions) in fired {
+            let mut results = Vec::with_capacity(actions.len());
+            for action in &actions {
+                let error = match self.alert(&event, action).await {
+                    Ok(()) => None,
+                    Err(e) => {
+                        log::error!("Alert action for '{}' failed: {}", event.rule, e);
+                        Some(e.to_string())
+                    }
+                };
+                results.push(ActionResult {
+                    action: action.name().to_string(),
+                    ok: error.is_none(),
+                    error,
+                });
+            }
+            records.push(AlertRecord::new(&event, results));
+        }
+
+        records
+    }
+
+    /// Run one action for a fired alert
+    pub async fn alert(&self, event: &AlertEvent, action: &AlertAction) -> Result<()> {
+        match action {
+            AlertAction::Log if !self.terminal_log => Ok(()),
+            AlertAction::Log => {
+                println!(
+                    "🔔 [{}] {} {} — {:.2} ({:+.2}%) matched `{}`",
+                    chrono::Local::now().format("%H:%M:%S"),
+                    event.rule,
+                    event.symbol,
+                    event.quote.price,
+                    event.quote.change_pct,
+                    event.condition
+                );
+                Ok(())
+            }
+            AlertAction::Desktop => {
+                let summary = format!("{}: {}", event.symbol, event.rule);
+                let body = format!(
+                    "{:.2} ({:+.2}%)\n{}",
+                    event.quote.price, event.quote.change_pct, event.condition
+                );
+                desktop_notification(summary, body).await
+            }
+            AlertAction::Webhook { url, secret, retries } => {
+                self.post_webhook(url, secret.as_deref(), *retries, &Payload::Alert(event))
+                    .await
+            }
+            AlertAction::Slack { channel } => {
+                self.post_slack(channel.as_deref(), &slack_alert_text(event)).await
+            }
+            AlertAction::Discord { url } => self.post_discord(url, discord_alert_message(event)).await,
+            AlertAction::Email { to, subject } => {
+                let subject = subject
+                    .clone()
+                    .unwrap_or_else(|| format!("Alert: {} on {}", event.rule, event.symbol));
+                let quotes = std::slice::from_ref(&event.quote);
+                let text = format!("{} matched `{}`\n\n{}", event.rule, event.condition, output::render_text(quotes));
+                let html = format!(
+                    "<p><b>{}</b> matched <code>{}</code></p>\n{}",
+                    output::html_escape(&event.rule),
+                    output::html_escape(&event.condition),
+                    output::render_html(quotes)
+                );
+                self.send_email(to, &subject, text, html).await
+            }
+            AlertAction::Exec { command, args, stdin } => {
+                let mut env = vec![
+                    ("SCANNER_EVENT", "alert".to_string()),
+                    ("SCANNER_RULE", event.rule.clone()),
+                    ("SCANNER_CONDITION", event.condition.clone()),
+                    ("SCANNER_TIMESTAMP", event.timestamp.clone()),
+                ];
+                env.extend(quote_env(&event.quote));
+                self.exec(command, args, &env, stdin.then_some(&Payload::Alert(event))).await
+            }
+        }
+    }
+
+    /// Run every scan-completion action, logging failures instead of stopping
+    pub async fn report(&self, report: &ScanReport, actions: &[AlertAction]) {
+        for action in actions {
+            if let Err(e) = self.scan_complete(report, action).await {
+                log::error!("Scan notification failed: {}", e);
+            }
+        }
+    }
+
+    async fn scan_complete(&self, report: &ScanReport, action: &AlertAction) -> Result<()> {
+        match action {
+            AlertAction::Log => {
+                log::info!(
+                    "Scan complete: {} symbols, {} gainers, {} losers",
+                    report.summary.total,
+                    report.summary.gainers,
+                    report.summary.losers
+                );
+                Ok(())
+            }
+            AlertAction::Desktop => {
+                let body = format!(
+                    "{} symbols | {} gainers | {} losers | avg {:+.2}%",
+                    report.summary.total,
+                    report.summary.gainers,
+                    report.summary.losers,
+                    report.summary.avg_change
+                );
+                desktop_notification("Scan complete".to_string(), body).await
+            }
+            AlertAction::Webhook { url, secret, retries } => {
+                self.post_webhook(url, secret.as_deref(), *retries, &Payload::ScanComplete(report))
+                    .await
+            }
+            AlertAction::Slack { channel } => {
+                self.post_slack(channel.as_deref(), &slack_summary_text(report)).await
+            }
+            AlertAction::Discord { url } => self.post_discord(url, discord_summary_message(report)).await,
+            AlertAction::Email { to, subject } => {
+                let subject = subject
+                    .clone()
+                    .unwrap_or_else(|| format!("Scan report: {} symbols", report.summary.total));
+                let text = output::render_text(&report.quotes);
+                let html = output::render_html(&report.quotes);
+                self.send_email(to, &subject, text, html).await
+            }
+            AlertAction::Exec { command, args, stdin } => {
+                let summary = &report.summary;
+                let env = [
+                    ("SCANNER_EVENT", "scan_complete".to_string()),
+                    ("SCANNER_TIMESTAMP", report.timestamp.clone()),
+                    ("SCANNER_TOTAL", summary.total.to_string()),
+                    ("SCANNER_GAINERS", summary.gainers.to_string()),
+                    ("SCANNER_LOSERS", summary.losers.to_string()),
+                    ("SCANNER_AVG_CHANGE", format!("{:.4}", summary.avg_change)),
+                ];
+                self.exec(command, args, &env, stdin.then_some(&Payload::ScanComplete(report))).await
+            }
+        }
+    }
+
+    async fn post_webhook(&self, url: &str, secret: Option<&str>, retries: u32, payload: &Payload<'_>) -> Result<()> {
+        let body = serde_json::to_vec(payload)?;
+        let headers: Vec<(&str, String)> = secret
+            .map(|secret| vec![(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)))])
+            .unwrap_or_default();
+
+        self.post_with_retry(url, body, &headers, retries).await?;
+        Ok(())
+    }
+
+    /// Post `text` via the Slack webhook, or `chat.postMessage` when a bot token is configured
+    async fn post_slack(&self, channel: Option<&str>, text: &str) -> Result<()> {
+        let slack = self.config.slack.as_ref().ok_or_else(|| {
+            ScannerError::Config("slack action used but [notifications.slack] is not configured".to_string())
+        })?;
+        let channel = channel.or(slack.channel.as_deref());
+
+        let mut message = serde_json::json!({ "text": text, "mrkdwn": true });
+        if let Some(channel) = channel {
+            message["channel"] = serde_json::json!(channel);
+        }
+        let body = serde_json::to_vec(&message)?;
+
+        if let Some(token) = &slack.token {
+            if channel.is_none() {
+                return Err(ScannerError::Config(
+                    "Slack bot token requires a channel in [notifications.slack] or on the action".to_string(),
+                ));
+            }
+
+            let headers = [("Authorization", format!("Bearer {}", token))];
+            let response = self
+                .post_with_retry(SLACK_POST_MESSAGE_URL, body, &headers, default_webhook_retries())
+                .await?;
+
+            // The Web API reports failures in the body with HTTP 200
+            let result: serde_json::Value = response.json().await?;
+            if result["ok"].as_bool() != Some(true) {
+                return Err(ScannerError::Api(format!(
+                    "Slack rejected message: {}",
+                    result["error"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            Ok(())
+        } else if let Some(url) = &slack.webhook_url {
+            self.post_with_retry(url, body, &[], default_webhook_retries()).await?;
+            Ok(())
+        } else {
+            Err(ScannerError::Config(
+                "[notifications.slack] needs either webhook_url or token".to_string(),
+            ))
+        }
+    }
+
+    async fn post_discord(&self, url: &str, message: serde_json::Value) -> Result<()> {
+        let body = serde_json::to_vec(&message)?;
+        self.post_with_retry(url, body, &[], default_webhook_retries()).await?;
+        Ok(())
+    }
+
+    /// Spawn `command`, optionally feeding it the JSON payload, and wait up to the timeout
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn exec(
+        &self,
+        command: &str,
+        args: &[String],
+        env: &[(&str, String)],
+        payload: Option<&Payload<'_>>,
+    ) -> Result<()> {
+        use std::process::Stdio;
+        use tokio::io::AsyncWriteExt;
+
+        let mut child = tokio::process::Command::new(command)
+            .args(args)
+            .envs(env.iter().map(|(key, value)| (*key, value)))
+            .stdin(if payload.is_some() { Stdio::piped() } else { Stdio::null() })
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| ScannerError::Io(format!("Failed to run '{}': {}", command, e)))?;
+
+        let body = payload.map(serde_json::to_vec).transpose()?;
+        let stdin = child.stdin.take();
+        // A program that never reads its input can block the write, so the timeout covers both
+        let run = async {
+            if let (Some(body), Some(mut stdin)) = (body, stdin) {
+                match stdin.write_all(&body).await {
+                    Ok(()) => {}
+                    // It exited or closed stdin without reading everything, which is its choice
+                    Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                        log::debug!("'{}' didn't read all of its input", command);
+                    }
+                    Err(e) => return Err(ScannerError::Io(format!("Failed to write to '{}': {}", command, e))),
+                }
+                // Dropping stdin closes the pipe so the program sees EOF
+            }
+            child
+                .wait()
+                .await
+                .map_err(|e| ScannerError::Io(format!("Failed to wait for '{}': {}", command, e)))
+        };
+        let status = tokio::time::timeout(self.timeout, run)
+            .await
+            .map_err(|_| ScannerError::Io(format!("'{}' timed out after {:?}", command, self.timeout)))??;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(ScannerError::Io(format!("'{}' exited with {}", command, status)))
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn exec(
+        &self,
+        command: &str,
+        _args: &[String],
+        _env: &[(&str, String)],
+        _payload: Option<&Payload<'_>>,
+    ) -> Result<()> {
+        Err(unsupported(&format!("Running '{}'", command)))
+    }
+
+    /// Send a multipart plain-text/HTML email
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send_email(&self, to: &[String], subject: &str, text: String, html: String) -> Result<()> {
+        use lettre::message::{Mailbox, MultiPart};
+        use lettre::transport::smtp::authentication::Credentials;
+        use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+        let smtp = self.config.email.as_ref().ok_or_else(|| {
+            ScannerError::Config("email action used but [notifications.email] is not configured".to_string())
+        })?;
+
+        let parse_mailbox = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| ScannerError::Config(format!("Invalid email address '{}': {}", address, e)))
+        };
+
+        let mut message = Message::builder().from(parse_mailbox(&smtp.from)?).subject(subject);
+        for address in to {
+            message = message.to(parse_mailbox(address)?);
+        }
+        let message = message
+            .multipart(MultiPart::alternative_plain_html(text, html))
+            .map_err(|e| ScannerError::Config(format!("Failed to build email: {}", e)))?;
+
+        let mut transport = match smtp.tls {
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host),
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host)),
+        }
+        .map_err(|e| ScannerError::Config(format!("Invalid SMTP host '{}': {}", smtp.host, e)))?
+        .port(smtp.port.unwrap_or_else(|| smtp.tls.default_port()))
+        .timeout(Some(self.timeout));
+
+        if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+            transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        transport
+            .build()
+            .send(message)
+            .await
+            .map_err(|e| ScannerError::Network(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn send_email(&self, _to: &[String], _subject: &str, _text: String, _html: String) -> Result<()> {
+        Err(unsupported("Sending email"))
+    }
+
+    /// POST a JSON body, retrying network errors, 429s and 5xx with exponential backoff
+    async fn post_with_retry(
+        &self,
+        url: &str,
+        body: Vec<u8>,
+        headers: &[(&str, String)],
+        retries: u32,
+    ) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .http
+                .post(url)
+                .timeout(self.timeout)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            for (name, value) in headers {
+                request = request.header(*name, value);
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if !is_retryable(response.status()) => {
+                    return Err(ScannerError::Api(format!("{} returned HTTP {}", url, response.status())));
+                }
+                Ok(response) => ScannerError::Api(format!("{} returned HTTP {}", url, response.status())),
+                Err(e) => ScannerError::from(e),
+            };
+
+            if attempt >= retries {
+                return Err(error);
+            }
+
+            let delay = retry_delay(attempt);
+            log::warn!("Delivery failed ({}), retrying in {:?}", error, delay);
+            rt::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn slack_alert_text(event: &AlertEvent) -> String {
+    let emoji = if event.quote.change_pct >= 0.0 { ":chart_with_upwards_trend:" } else { ":chart_with_downwards_trend:" };
+    format!(
+        "{} *{}* fired for *{}*: {:.2} ({:+.2}%)\n`{}`",
+        emoji, event.rule, event.symbol, event.quote.price, event.quote.change_pct, event.condition
+    )
+}
+
+fn slack_summary_text(report: &ScanReport) -> String {
+    let summary = &report.summary;
+    let mut text = format!(
+        "*Scan complete* — {} symbols | {} gainers | {} losers | avg {:+.2}%",
+        summary.total, summary.gainers, summary.losers, summary.avg_change
+    );
+
+    if let Some(top) = &summary.top_gainer {
+        text.push_str(&format!("\nTop gainer: *{}* ({:+.2}%)", top.symbol, top.change_pct));
+    }
+    if let Some(top) = &summary.top_loser {
+        text.push_str(&format!("\nTop loser: *{}* ({:+.2}%)", top.symbol, top.change_pct));
+    }
+
+    text
+}
+
+fn quote_env(quote: &StockQuote) -> Vec<(&'static str, String)> {
+    vec![
+        ("SCANNER_SYMBOL", quote.symbol.clone()),
+        ("SCANNER_PRICE", quote.price.to_string()),
+        ("SCANNER_PREV_CLOSE", quote.prev_close.to_string()),
+        ("SCANNER_CHANGE_PCT", format!("{:.4}", quote.change_pct)),
+        ("SCANNER_HIGH", quote.high.to_string()),
+        ("SCANNER_LOW", quote.low.to_string()),
+        ("SCANNER_OPEN", quote.open.to_string()),
+    ]
+}
+
+fn change_color(change_pct: f64) -> u32 {
+    if change_pct >= 0.0 {
+        DISCORD_GREEN
+    } else {
+        DISCORD_RED
+    }
+}
+
+fn discord_alert_message(event: &AlertEvent) -> serde_json::Value {
+    let quote = &event.quote;
+    serde_json::json!({
+        "embeds": [{
+            "title": format!("{}: {}", event.symbol, event.rule),
+            "description": format!("`{}`", event.condition),
+            "color": change_color(quote.change_pct),
+            "timestamp": event.timestamp,
+            "fields": [
+                { "name": "Price", "value": format!("{:.2}", quote.price), "inline": true },
+                { "name": "Change", "value": format!("{:+.2}%", quote.change_pct), "inline": true },
+                { "name": "Range", "value": format!("{:.2} – {:.2}", quote.low, quote.high), "inline": true },
+            ],
+        }]
+    })
+}
+
+/// One summary embed plus green/red embeds listing the biggest gainers and losers
+fn discord_summary_message(report: &ScanReport) -> serde_json::Value {
+    let summary = &report.summary;
+    let mut embeds = vec![serde_json::json!({
+        "title": "Market summary",
+        "description": format!(
+            "{} symbols | {} gainers | {} losers | avg {:+.2}%",
+            summary.total, summary.gainers, summary.losers, summary.avg_change
+        ),
+        "color": change_color(summary.avg_change),
+        "timestamp": report.timestamp,
+    })];
+
+    // Sorted by absolute change, so the first matches on each side are the biggest movers
+    let sorted = output::sort_by_change(report.quotes.clone());
+    let gainers: Vec<&StockQuote> = sorted.iter().filter(|q| q.change_pct > 0.0).take(DISCORD_MOVERS).collect();
+    let losers: Vec<&StockQuote> = sorted.iter().filter(|q| q.change_pct < 0.0).take(DISCORD_MOVERS).collect();
+
+    for (title, movers, color) in [("Gainers", gainers, DISCORD_GREEN), ("Losers", losers, DISCORD_RED)] {
+        if movers.is_empty() {
+            continue;
+        }
+        let lines: Vec<String> = movers
+            .iter()
+            .map(|q| format!("**{}** {:.2} ({:+.2}%)", q.symbol, q.price, q.change_pct))
+            .collect();
+        embeds.push(serde_json::json!({ "title": title, "description": lines.join("\n"), "color": color }));
+    }
+
+    serde_json::json!({ "embeds": embeds })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn desktop_notification(summary: String, body: String) -> Result<()> {
+    // notify-rust blocks on the platform notification service
+    tokio::task::spawn_blocking(move || {
+        notify_rust::Notification::new()
+            .appname("finnhub-scanner")
+            .summary(&summary)
+            .body(&body)
+            .show()
+            .map(|_| ())
+            .map_err(|e| ScannerError::Io(format!("Desktop notification failed: {}", e)))
+    })
+    .await
+    .map_err(|e| ScannerError::Io(format!("Notification task failed: {}", e)))?
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn desktop_notification(_summary: String, _body: String) -> Result<()> {
+    Err(unsupported("Desktop notifications"))
+}
+
+/// Ring the terminal bell and, if given, play a sound file in the background
+#[cfg(not(target_arch = "wasm32"))]
+fn ring(sound_file: Option<&Path>) {
+    print!("\x07");
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+
+    let Some(path) = sound_file else {
+        return;
+    };
+    let (player, args) = sound_player(path);
+    match tokio::process::Command::new(player)
+        .args(&args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+    {
+        // Reap the player when it finishes without holding up the refresh
+        Ok(mut child) => {
+            tokio::spawn(async move {
+                let _ = child.wait().await;
+            });
+        }
+        Err(e) => log::warn!("Failed to play {} with {}: {}", path.display(), player, e),
+    }
+}
+
+/// Browsers have no terminal bell or audio player to run
+#[cfg(target_arch = "wasm32")]
+fn ring(_sound_file: Option<&Path>) {}
+
+/// Actions that need a process, SMTP connection or desktop, none of which a browser has
+#[cfg(target_arch = "wasm32")]
+fn unsupported(what: &str) -> ScannerError {
+    ScannerError::Io(format!("{} is not supported on wasm32", what))
+}
+
+/// Platform command that plays an audio file
+#[cfg(not(target_arch = "wasm32"))]
+fn sound_player(path: &Path) -> (&'static str, Vec<String>) {
+    let file = path.display().to_string();
+    if cfg!(target_os = "macos") {
+        ("afplay", vec![file])
+    } else if cfg!(windows) {
+        let script = format!("(New-Object Media.SoundPlayer '{}').PlaySync()", file.replace('\'', "''"));
+        ("powershell", vec!["-NoProfile".to_string(), "-Command".to_string(), script])
+    } else {
+        // PulseAudio and PipeWire both provide paplay
+        ("paplay", vec![file])
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of `body`
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_event() -> AlertEvent {
+        AlertEvent {
+            rule: "big-move".to_string(),
+            condition: "change_pct > 3".to_string(),
+            symbol: "TSLA".to_string(),
+            timestamp: "2026-01-02T15:30:00+00:00".to_string(),
+            quote: StockQuote {
+                symbol: "TSLA".to_string(),
+                price: 250.0,
+                prev_close: 240.0,
+                change_pct: 4.17,
+                high: 251.0,
+                low: 239.0,
+                open: 241.0,
+                name: None,
+                sector: None,
+                source: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_hmac_signature() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_alert_payload_shape() {
+        let event = test_event();
+        let value = serde_json::to_value(Payload::Alert(&event)).unwrap();
+
+        assert_eq!(value["event"], "alert");
+        assert_eq!(value["rule"], "big-move");
+        assert_eq!(value["quote"]["price"], 250.0);
+    }
+
+    #[test]
+    fn test_scan_payload_shape() {
+        let report = ScanReport::new(&[test_event().quote]);
+        let value = serde_json::to_value(Payload::ScanComplete(&report)).unwrap();
+
+        assert_eq!(value["event"], "scan_complete");
+        assert_eq!(value["summary"]["total"], 1);
+        assert_eq!(value["quotes"][0]["symbol"], "TSLA");
+    }
+
+    #[test]
+    fn test_webhook_action_from_toml() {
+        let config: NotificationsConfig = toml::from_str(
+            r#"on_scan_complete = [{ type = "webhook", url = "https://example.com/hook", secret = "s3cret" }]"#,
+        )
+        .unwrap();
+
+        match &config.on_scan_complete[0] {
+            AlertAction::Webhook { url, secret, retries } => {
+                assert_eq!(url, "https://example.com/hook");
+                assert_eq!(secret.as_deref(), Some("s3cret"));
+                assert_eq!(*retries, 3);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_slack_messages() {
+        let event = test_event();
+        let text = slack_alert_text(&event);
+        assert!(text.contains("*big-move* fired for *TSLA*: 250.00 (+4.17%)"));
+
+        let report = ScanReport::new(&[event.quote]);
+        let text = slack_summary_text(&report);
+        assert!(text.starts_with("*Scan complete* — 1 symbols"));
+        assert!(text.contains("Top gainer: *TSLA*"));
+    }
+
+    #[test]
+    fn test_slack_config_from_toml() {
+        let config: NotificationsConfig = toml::from_str(
+            r##"
+            on_scan_complete = [{ type = "slack", channel = "#eod" }]
+
+            [slack]
+            token = "xoxb-test"
+            channel = "#alerts"
+            "##,
+        )
+        .unwrap();
+
+        let slack = config.slack.unwrap();
+        assert_eq!(slack.channel.as_deref(), Some("#alerts"));
+        assert!(matches!(
+            &config.on_scan_complete[0],
+            AlertAction::Slack { channel: Some(channel) } if channel == "#eod"
+        ));
+    }
+
+    #[test]
+    fn test_discord_embeds() {
+        let event = test_event();
+        let message = discord_alert_message(&event);
+        assert_eq!(message["embeds"][0]["title"], "TSLA: big-move");
+        assert_eq!(message["embeds"][0]["color"], DISCORD_GREEN);
+
+        let mut loser = event.quote.clone();
+        loser.symbol = "AAPL".to_string();
+        loser.change_pct = -2.0;
+        let message = discord_summary_message(&ScanReport::new(&[loser, event.quote]));
+        let embeds = message["embeds"].as_array().unwrap();
+
+        assert_eq!(embeds.len(), 3);
+        assert_eq!(embeds[1]["title"], "Gainers");
+        assert_eq!(embeds[2]["color"], DISCORD_RED);
+        assert!(embeds[2]["description"].as_str().unwrap().contains("**AAPL**"));
+    }
+
+    #[test]
+    fn test_email_config_from_toml() {
+        let config: NotificationsConfig = toml::from_str(
+            r#"
+            on_scan_complete = [{ type = "email", to = ["me@example.com"] }]
+
+            [email]
+            host = "smtp.example.com"
+            from = "Scanner <scanner@example.com>"
+            tls = "tls"
+            "#,
+        )
+        .unwrap();
+
+        let smtp = config.email.unwrap();
+        assert_eq!(smtp.tls, SmtpTls::Tls);
+        assert_eq!(smtp.port.unwrap_or_else(|| smtp.tls.default_port()), 465);
+        assert!(matches!(
+            &config.on_scan_complete[0],
+            AlertAction::Email { to, subject: None } if to == &["me@example.com"]
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_passes_env_and_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let action = AlertAction::Exec {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), format!("(echo $SCANNER_SYMBOL; cat) > {}", out.display())],
+            stdin: true,
+        };
+
+        let runner = ActionRunner::new(5, NotificationsConfig::default()).unwrap();
+        runner.alert(&test_event(), &action).await.unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        let (symbol, json) = written.split_once('\n').unwrap();
+        assert_eq!(symbol, "TSLA");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(json).unwrap()["rule"], "big-move");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_reports_failure() {
+        let action = AlertAction::Exec {
+            command: "false".to_string(),
+            args: Vec::new(),
+            stdin: false,
+        };
+
+        let runner = ActionRunner::new(5, NotificationsConfig::default()).unwrap();
+        assert!(matches!(runner.alert(&test_event(), &action).await, Err(ScannerError::Io(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_ignores_unread_stdin() {
+        let action = AlertAction::Exec {
+            command: "true".to_string(),
+            args: Vec::new(),
+            stdin: true,
+        };
+
+        let runner = ActionRunner::new(5, NotificationsConfig::default()).unwrap();
+        runner.alert(&test_event(), &action).await.unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sound_player() {
+        let (player, args) = sound_player(Path::new("/tmp/ding.wav"));
+        assert_eq!(player, "paplay");
+        assert_eq!(args, vec!["/tmp/ding.wav"]);
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(0), Duration::from_millis(500));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(64), Duration::from_millis(MAX_RETRY_DELAY_MS));
+        assert_eq!(retry_delay(u32::MAX), Duration::from_millis(MAX_RETRY_DELAY_MS));
+    }
+}
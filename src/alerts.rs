@@ -0,0 +1,1011 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::actions::ActionRunner;
+use crate::actions::AlertAction;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::expr::{self, Env, Expr};
+use crate::finnhub::{Candles, FinnhubClient, StockQuote};
+use crate::history;
+use crate::output;
+use crate::paths;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::reload::ConfigFile;
+use crate::rt::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::sinks::Sinks;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::systemd;
+
+/// `[alerts]` section of the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    /// Seconds between evaluations in `alerts run`
+    #[serde(default = "default_alert_interval")]
+    pub interval_secs: u64,
+
+    /// Seconds a rule stays quiet for a symbol after firing, unless the rule sets its own
+    #[serde(default = "default_cooldown")]
+    pub cooldown_secs: u64,
+
+    /// Prior sessions averaged when computing `volume_ratio`
+    #[serde(default = "default_volume_lookback")]
+    pub volume_lookback_days: usize,
+
+    /// Sound played by `--bell` instead of just the terminal bell
+    pub sound_file: Option<PathBuf>,
+
+    /// NDJSON log of every fired alert and the outcome of its actions
+    #[serde(default = "default_history_file")]
+    pub history_file: PathBuf,
+
+    /// Alert rules, evaluated in order
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_alert_interval(),
+            cooldown_secs: default_cooldown(),
+            volume_lookback_days: default_volume_lookback(),
+            sound_file: None,
+            history_file: default_history_file(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn default_alert_interval() -> u64 {
+    60
+}
+
+fn default_cooldown() -> u64 {
+    300
+}
+
+fn default_volume_lookback() -> usize {
+    10
+}
+
+fn default_history_file() -> PathBuf {
+    paths::data_file("alerts.ndjson")
+}
+
+/// Intraday candle resolution used for volume ratios, and how often they are refetched
+const VOLUME_RESOLUTION: &str = "5";
+const VOLUME_REFRESH: Duration = Duration::from_secs(300);
+
+/// A single `[[alerts.rules]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Name shown when the rule fires
+    pub name: String,
+
+    /// Symbol to watch; `*` matches every symbol and `TS*` matches a prefix
+    #[serde(default = "default_symbol_pattern")]
+    pub symbol: String,
+
+    /// Condition expression, e.g. `change_pct > 3 and price < 200`
+    pub condition: String,
+
+    /// What to do when the condition is true
+    #[serde(default = "default_actions")]
+    pub actions: Vec<AlertAction>,
+
+    /// Overrides `[alerts] cooldown_secs` for this rule; 0 fires on every refresh
+    pub cooldown_secs: Option<u64>,
+}
+
+fn default_symbol_pattern() -> String {
+    String::from("*")
+}
+
+fn default_actions() -> Vec<AlertAction> {
+    vec![AlertAction::Log]
+}
+
+/// A rule that fired for a specific quote
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub condition: String,
+    pub symbol: String,
+    pub timestamp: String,
+    pub quote: StockQuote,
+}
+
+/// Outcome of one action run for a fired alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub action: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One line of the alert history log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub timestamp: String,
+    pub rule: String,
+    pub symbol: String,
+    pub condition: String,
+    pub price: f64,
+    pub change_pct: f64,
+    pub results: Vec<ActionResult>,
+}
+
+impl AlertRecord {
+    pub fn new(event: &AlertEvent, results: Vec<ActionResult>) -> Self {
+        Self {
+            timestamp: event.timestamp.clone(),
+            rule: event.rule.clone(),
+            symbol: event.symbol.clone(),
+            condition: event.condition.clone(),
+            price: event.quote.price,
+            change_pct: event.quote.change_pct,
+            results,
+        }
+    }
+
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        history::parse_timestamp(&self.timestamp)
+    }
+
+    /// Action outcomes as `log:ok;webhook:error`
+    fn results_summary(&self) -> String {
+        self.results
+            .iter()
+            .map(|result| format!("{}:{}", result.action, if result.ok { "ok" } else { "error" }))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+}
+
+/// Output formats for `alerts history`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum HistoryFormat {
+    /// Aligned table for the terminal
+    Table,
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line, as stored
+    Ndjson,
+}
+
+/// What a session has observed for one symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolStats {
+    /// First price seen in this session
+    pub first: f64,
+    /// Highest and lowest prices seen in this session
+    pub high: f64,
+    pub low: f64,
+}
+
+impl SymbolStats {
+    fn new(price: f64) -> Self {
+        Self {
+            first: price,
+            high: price,
+            low: price,
+        }
+    }
+
+    fn observe(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+    }
+
+    /// Percent change from the first observed price
+    pub fn since_start(&self, price: f64) -> f64 {
+        if self.first > 0.0 {
+            crate::decimal::change_pct(price, self.first)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Fold fresh quotes into each symbol's session stats, after the refresh's rules are evaluated
+pub fn observe(stats: &mut HashMap<String, SymbolStats>, quotes: &[StockQuote]) {
+    for quote in quotes {
+        stats
+            .entry(quote.symbol.clone())
+            .or_insert_with(|| SymbolStats::new(quote.price))
+            .observe(quote.price);
+    }
+}
+
+struct CompiledRule {
+    rule: AlertRule,
+    condition: Expr,
+    cooldown: Duration,
+}
+
+/// Evaluates alert rules against fresh quotes, remembering when each rule last fired
+pub struct AlertEngine {
+    rules: Vec<CompiledRule>,
+    /// Last firing per (rule index, symbol), used to enforce cooldowns
+    last_fired: HashMap<(usize, String), Instant>,
+    /// Quote seen on the previous refresh per symbol, used by `crosses`
+    previous: HashMap<String, StockQuote>,
+    /// Recent prices per symbol, used by `pct_move`
+    prices: HashMap<String, VecDeque<(Instant, f64)>>,
+    /// How long to keep prices: the longest `pct_move` window of any rule
+    history_window: Duration,
+    volume_lookback_days: usize,
+    /// Latest `volume_ratio` per symbol and when it was computed
+    volume_ratios: HashMap<String, (Instant, f64)>,
+    history_file: PathBuf,
+}
+
+/// A quote together with what the engine remembers about its symbol
+struct RuleEnv<'a> {
+    quote: &'a StockQuote,
+    volume_ratio: Option<f64>,
+    /// Session (high, low) before this refresh; the current price on the first one
+    extremes: (f64, f64),
+    previous: Option<&'a StockQuote>,
+    prices: Option<&'a VecDeque<(Instant, f64)>>,
+    now: Instant,
+}
+
+impl Env for RuleEnv<'_> {
+    fn field(&self, name: &str) -> Option<f64> {
+        match name {
+            "volume_ratio" => self.volume_ratio,
+            "session_high" => Some(self.extremes.0),
+            "session_low" => Some(self.extremes.1),
+            _ => self.quote.field(name),
+        }
+    }
+
+    fn previous(&self) -> Option<&dyn Env> {
+        self.previous.map(|quote| quote as &dyn Env)
+    }
+
+    /// Change from the oldest price still inside the window
+    fn pct_move(&self, window_secs: f64) -> Option<f64> {
+        let window = Duration::try_from_secs_f64(window_secs).ok()?;
+        let (_, base) = self
+            .prices?
+            .iter()
+            .find(|(at, _)| self.now.duration_since(*at) <= window)?;
+
+        if *base > 0.0 {
+            Some(crate::decimal::change_pct(self.quote.price, *base))
+        } else {
+            None
+        }
+    }
+}
+
+impl AlertEngine {
+    /// Compile every rule, failing on the first invalid condition
+    pub fn new(config: &AlertsConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let condition = expr::parse_alert(&rule.condition).map_err(|e| {
+                    ScannerError::Config(format!("Alert rule '{}': {}", rule.name, e))
+                })?;
+                let window = match condition.max_window() {
+                    Some(secs) => Duration::try_from_secs_f64(secs).map_err(|_| {
+                        ScannerError::Config(format!(
+                            "Alert rule '{}': pct_move window of {}s is out of range",
+                            rule.name, secs
+                        ))
+                    })?,
+                    None => Duration::ZERO,
+                };
+                Ok((
+                    CompiledRule {
+                        rule: rule.clone(),
+                        condition,
+                        cooldown: Duration::from_secs(rule.cooldown_secs.unwrap_or(config.cooldown_secs)),
+                    },
+                    window,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let history_window = rules.iter().map(|(_, window)| *window).max().unwrap_or_default();
+        let rules = rules.into_iter().map(|(compiled, _)| compiled).collect();
+
+        Ok(Self {
+            rules,
+            last_fired: HashMap::new(),
+            previous: HashMap::new(),
+            prices: HashMap::new(),
+            history_window,
+            volume_lookback_days: config.volume_lookback_days,
+            volume_ratios: HashMap::new(),
+            history_file: config.history_file.clone(),
+        })
+    }
+
+    /// Swap in edited rules, keeping what the engine remembers
+    ///
+    /// Cooldowns carry over for rules whose name, symbol and condition are unchanged, and the
+    /// quotes and prices seen so far for all of them. On an invalid rule the old ones stay.
+    pub fn reload(&mut self, config: &AlertsConfig) -> Result<()> {
+        let mut reloaded = Self::new(config)?;
+        for ((idx, symbol), at) in self.last_fired.drain() {
+            let old = &self.rules[idx].rule;
+            let kept = reloaded.rules.iter().position(|compiled| {
+                compiled.rule.name == old.name
+                    && compiled.rule.symbol == old.symbol
+                    && compiled.rule.condition == old.condition
+            });
+            if let Some(new_idx) = kept {
+                reloaded.last_fired.insert((new_idx, symbol), at);
+            }
+        }
+        reloaded.previous = std::mem::take(&mut self.previous);
+        reloaded.prices = std::mem::take(&mut self.prices);
+        if reloaded.volume_lookback_days == self.volume_lookback_days {
+            reloaded.volume_ratios = std::mem::take(&mut self.volume_ratios);
+        }
+        *self = reloaded;
+        Ok(())
+    }
+
+    /// Append dispatched alerts to the history log, logging rather than failing on errors
+    pub fn record(&self, records: &[AlertRecord]) {
+        if let Err(e) = append_history(&self.history_file, records) {
+            log::error!("Failed to record alert history: {}", e);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Symbols named explicitly by rules (wildcard patterns are skipped)
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .rules
+            .iter()
+            .map(|compiled| compiled.rule.symbol.to_uppercase())
+            .filter(|symbol| !symbol.contains('*'))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Refetch intraday candles for symbols whose rules use `volume_ratio`, at most every few minutes
+    pub async fn refresh_volume(&mut self, client: &FinnhubClient, quotes: &[StockQuote]) {
+        let now = Instant::now();
+        let to = chrono::Utc::now().timestamp();
+        // Calendar days to fetch so weekends and holidays still leave enough sessions
+        let from = to - (self.volume_lookback_days as i64 * 7 / 5 + 4) * 86_400;
+
+        for quote in quotes {
+            let wanted = self.rules.iter().any(|compiled| {
+                compiled.condition.references("volume_ratio") && symbol_matches(&compiled.rule.symbol, &quote.symbol)
+            });
+            let fresh = self
+                .volume_ratios
+                .get(&quote.symbol)
+                .is_some_and(|(at, _)| now.duration_since(*at) < VOLUME_REFRESH);
+            if !wanted || fresh {
+                continue;
+            }
+
+            match client.fetch_candles(&quote.symbol, VOLUME_RESOLUTION, from, to).await {
+                Ok(candles) => match volume_ratio(&candles, to, self.volume_lookback_days) {
+                    Some(ratio) => {
+                        self.volume_ratios.insert(quote.symbol.clone(), (now, ratio));
+                    }
+                    None => log::debug!("Not enough volume history for {}", quote.symbol),
+                },
+                Err(e) => log::warn!("Failed to fetch volume for {}: {}", quote.symbol, e),
+            }
+        }
+    }
+
+    /// Return every rule that fires for these quotes, with the actions to run
+    ///
+    /// `sessions` holds the stats from before this refresh, for `session_high` and `session_low`.
+    pub fn evaluate(
+        &mut self,
+        quotes: &[StockQuote],
+        sessions: &HashMap<String, SymbolStats>,
+    ) -> Vec<(AlertEvent, Vec<AlertAction>)> {
+        self.evaluate_at(quotes, sessions, Instant::now())
+    }
+
+    fn evaluate_at(
+        &mut self,
+        quotes: &[StockQuote],
+        sessions: &HashMap<String, SymbolStats>,
+        now: Instant,
+    ) -> Vec<(AlertEvent, Vec<AlertAction>)> {
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        let mut fired = Vec::new();
+
+        if !self.history_window.is_zero() {
+            for quote in quotes {
+                let prices = self.prices.entry(quote.symbol.clone()).or_default();
+                prices.push_back((now, quote.price));
+                while prices
+                    .front()
+                    .is_some_and(|(at, _)| now.duration_since(*at) > self.history_window)
+                {
+                    prices.pop_front();
+                }
+            }
+        }
+
+        for (idx, compiled) in self.rules.iter().enumerate() {
+            for quote in quotes.iter().filter(|q| symbol_matches(&compiled.rule.symbol, &q.symbol)) {
+                let env = RuleEnv {
+                    quote,
+                    volume_ratio: self.volume_ratios.get(&quote.symbol).map(|(_, ratio)| *ratio),
+                    extremes: sessions
+                        .get(&quote.symbol)
+                        .map_or((quote.price, quote.price), |stats| (stats.high, stats.low)),
+                    previous: self.previous.get(&quote.symbol),
+                    prices: self.prices.get(&quote.symbol),
+                    now,
+                };
+                match compiled.condition.matches(&env) {
+                    Ok(true) => {
+                        let key = (idx, quote.symbol.clone());
+                        let cooling = self
+                            .last_fired
+                            .get(&key)
+                            .is_some_and(|last| now.duration_since(*last) < compiled.cooldown);
+                        if cooling {
+                            log::debug!("Alert rule '{}' on {} is cooling down", compiled.rule.name, quote.symbol);
+                            continue;
+                        }
+                        self.last_fired.insert(key, now);
+
+                        let event = AlertEvent {
+                            rule: compiled.rule.name.clone(),
+                            condition: compiled.rule.condition.clone(),
+                            symbol: quote.symbol.clone(),
+                            timestamp: timestamp.clone(),
+                            quote: quote.clone(),
+                        };
+                        fired.push((event, compiled.rule.actions.clone()));
+                    }
+                    Ok(false) => {}
+                    Err(e) => log::warn!("Alert rule '{}' on {}: {}", compiled.rule.name, quote.symbol, e),
+                }
+            }
+        }
+
+        for quote in quotes {
+            self.previous.insert(quote.symbol.clone(), quote.clone());
+        }
+
+        fired
+    }
+}
+
+pub fn append_history(path: &Path, records: &[AlertRecord]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| ScannerError::Io(format!("Failed to create alert history directory: {}", e)))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to open alert history {}: {}", path.display(), e)))?;
+
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Read the alert history, skipping lines that don't parse
+pub fn read_history(path: &Path) -> Result<Vec<AlertRecord>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to read alert history {}: {}", path.display(), e)))?;
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                log::warn!("Skipping malformed alert history line: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Keep records within `[from, to)` that match the rule name and symbol pattern, if given
+pub fn filter_history(
+    records: Vec<AlertRecord>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    rule: Option<&str>,
+    symbol: Option<&str>,
+) -> Vec<AlertRecord> {
+    records
+        .into_iter()
+        .filter(|record| !matches!(rule, Some(rule) if record.rule != rule))
+        .filter(|record| !matches!(symbol, Some(pattern) if !symbol_matches(pattern, &record.symbol)))
+        .filter(|record| {
+            if from.is_none() && to.is_none() {
+                return true;
+            }
+            match record.time() {
+                Some(ts) => !matches!(from, Some(from) if ts < from) && !matches!(to, Some(to) if ts >= to),
+                None => false,
+            }
+        })
+        .collect()
+}
+
+pub fn write_history(records: &[AlertRecord], format: HistoryFormat, writer: &mut impl Write) -> Result<()> {
+    match format {
+        HistoryFormat::Table => {
+            writeln!(
+                writer,
+                "{:<19}  {:<20} {:<8} {:>10} {:>9}  ACTIONS",
+                "TIME", "RULE", "SYMBOL", "PRICE", "CHANGE"
+            )?;
+            for record in records {
+                let time = record
+                    .time()
+                    .map(|ts| ts.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| record.timestamp.clone());
+                let actions: Vec<String> = record
+                    .results
+                    .iter()
+                    .map(|result| format!("{} {}", result.action, if result.ok { "✓" } else { "✗" }))
+                    .collect();
+                writeln!(
+                    writer,
+                    "{:<19}  {:<20} {:<8} {:>10.2} {:>+8.2}%  {}",
+                    time,
+                    record.rule,
+                    record.symbol,
+                    record.price,
+                    record.change_pct,
+                    actions.join(", ")
+                )?;
+            }
+        }
+        HistoryFormat::Csv => {
+            writeln!(writer, "timestamp,rule,symbol,price,change_pct,condition,results")?;
+            for record in records {
+                writeln!(
+                    writer,
+                    "{},{},{},{:.2},{:.2},{},{}",
+                    record.timestamp,
+                    output::csv_field(&record.rule),
+                    record.symbol,
+                    record.price,
+                    record.change_pct,
+                    output::csv_field(&record.condition),
+                    output::csv_field(&record.results_summary())
+                )?;
+            }
+        }
+        HistoryFormat::Ndjson => {
+            for record in records {
+                writeln!(writer, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Today's volume so far divided by the average volume at the same time of day over prior sessions
+///
+/// Days and times of day are taken in UTC, which keeps each US session within one date.
+pub fn volume_ratio(candles: &Candles, now: i64, lookback_days: usize) -> Option<f64> {
+    let cutoff = now.rem_euclid(86_400);
+    let mut cumulative: BTreeMap<i64, f64> = BTreeMap::new();
+
+    for (ts, volume) in candles.t.iter().zip(&candles.v) {
+        if ts.rem_euclid(86_400) <= cutoff {
+            *cumulative.entry(ts.div_euclid(86_400)).or_default() += volume;
+        }
+    }
+
+    let today = cumulative.remove(&now.div_euclid(86_400))?;
+    let prior: Vec<f64> = cumulative.values().rev().take(lookback_days).copied().collect();
+    if prior.is_empty() {
+        return None;
+    }
+
+    let average = prior.iter().sum::<f64>() / prior.len() as f64;
+    (average > 0.0).then(|| today / average)
+}
+
+/// Match a symbol against a rule pattern with `*` wildcards
+pub fn symbol_matches(pattern: &str, symbol: &str) -> bool {
+    let pattern = pattern.to_uppercase();
+    let symbol = symbol.to_uppercase();
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == symbol;
+    }
+
+    let mut rest = symbol.as_str();
+    for (idx, part) in parts.iter().enumerate() {
+        if idx == 0 {
+            match rest.strip_prefix(part) {
+                Some(tail) => rest = tail,
+                None => return false,
+            }
+        } else if idx == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+
+    true
+}
+
+/// Swap in edited alert rules for `alerts run`, watching any symbols they newly name
+#[cfg(not(target_arch = "wasm32"))]
+fn reload_rules(
+    config: &Config,
+    changed: &[String],
+    engine: &mut AlertEngine,
+    symbols: &mut Vec<String>,
+) {
+    for section in changed.iter().filter(|section| *section != "alerts") {
+        log::warn!("{} changed; restart alerts run to apply it", section);
+    }
+    if !changed.iter().any(|section| section == "alerts") {
+        return;
+    }
+
+    match engine.reload(&config.alerts) {
+        Ok(()) => {
+            for symbol in engine.symbols() {
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+            log::info!("Reloaded {} alert rules", engine.rules.len());
+        }
+        Err(e) => log::warn!("Keeping the previous alert rules: {}", e),
+    }
+}
+
+/// Evaluate rules on every refresh until Ctrl+C or SIGTERM
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run(
+    client: &FinnhubClient,
+    runner: &ActionRunner,
+    symbols: &[String],
+    engine: &mut AlertEngine,
+    sinks: &Sinks,
+    mut interval: u64,
+    mut config_file: Option<ConfigFile>,
+) -> Result<()> {
+    log::info!(
+        "Evaluating {} alert rules against {} symbols every {}s. Press Ctrl+C to exit.",
+        engine.rules.len(),
+        symbols.len(),
+        interval
+    );
+
+    systemd::ready(&format!("Evaluating {} alert rules", engine.rules.len()));
+    let shutdown = systemd::shutdown_signal();
+    tokio::pin!(shutdown);
+
+    let mut symbols = symbols.to_vec();
+    let mut sessions = HashMap::new();
+    loop {
+        if let Some(file) = config_file.as_mut() {
+            let previous_interval = file.current().alerts.interval_secs;
+            if let Some((config, changed)) = file.reload() {
+                reload_rules(&config, &changed, engine, &mut symbols);
+                // An --interval given on the command line stands until the config changes it
+                if config.alerts.interval_secs != previous_interval {
+                    interval = config.alerts.interval_secs.max(1);
+                    log::info!("Evaluating alert rules every {}s", interval);
+                }
+            }
+        }
+
+        match client.fetch_quotes(&symbols).await {
+            Ok(quotes) => {
+                sinks.quotes(&Utc::now().to_rfc3339(), &quotes).await;
+                engine.refresh_volume(client, &quotes).await;
+                let records = runner.dispatch(engine.evaluate(&quotes, &sessions)).await;
+                observe(&mut sessions, &quotes);
+                engine.record(&records);
+                sinks.alerts(&records).await;
+                let updated = chrono::Local::now().format("%H:%M:%S");
+                systemd::status(&format!("Evaluated {} quotes at {}", quotes.len(), updated));
+            }
+            Err(e) => log::error!("Failed to fetch quotes: {}", e),
+        }
+        systemd::watchdog();
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+            _ = &mut shutdown => break,
+        }
+    }
+    systemd::stopping();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, symbol: &str, condition: &str) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            condition: condition.to_string(),
+            actions: default_actions(),
+            cooldown_secs: None,
+        }
+    }
+
+    fn engine(rules: Vec<AlertRule>) -> Result<AlertEngine> {
+        AlertEngine::new(&AlertsConfig {
+            rules,
+            ..AlertsConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_symbol_patterns() {
+        assert!(symbol_matches("*", "AAPL"));
+        assert!(symbol_matches("aapl", "AAPL"));
+        assert!(symbol_matches("TS*", "TSLA"));
+        assert!(symbol_matches("*LA", "TSLA"));
+        assert!(symbol_matches("T*A", "TSLA"));
+        assert!(!symbol_matches("TS*", "AAPL"));
+        assert!(!symbol_matches("AAPL", "AAPLX"));
+    }
+
+    #[test]
+    fn test_engine_fires_matching_rules() {
+        let mut engine = engine(vec![
+            rule("big-move", "*", "abs(change_pct) > 3"),
+            rule("aapl-200", "AAPL", "price > 200"),
+        ])
+        .unwrap();
+
+        let quotes = [StockQuote::test("AAPL", 210.0, 208.0), StockQuote::test("TSLA", 250.0, 260.0)];
+        let fired = engine.evaluate(&quotes, &HashMap::new());
+        let names: Vec<(&str, &str)> = fired
+            .iter()
+            .map(|(event, _)| (event.rule.as_str(), event.symbol.as_str()))
+            .collect();
+
+        assert_eq!(names, vec![("big-move", "TSLA"), ("aapl-200", "AAPL")]);
+        assert_eq!(engine.symbols(), vec!["AAPL"]);
+    }
+
+    #[test]
+    fn test_invalid_condition_is_config_error() {
+        let result = engine(vec![rule("bad", "*", "price >")]);
+        assert!(matches!(result, Err(ScannerError::Config(_))));
+
+        let result = engine(vec![rule("forever", "*", "pct_move(99999999999999999999999) > 1")]);
+        assert!(matches!(result, Err(ScannerError::Config(msg)) if msg.contains("out of range")));
+    }
+
+    #[test]
+    fn test_cooldown_suppresses_repeats() {
+        let mut quiet = rule("aapl-200", "AAPL", "price > 200");
+        quiet.cooldown_secs = Some(60);
+        let mut noisy = rule("every-time", "AAPL", "price > 200");
+        noisy.cooldown_secs = Some(0);
+        let mut engine = engine(vec![quiet, noisy]).unwrap();
+
+        let quotes = [StockQuote::test("AAPL", 210.0, 208.0)];
+        let start = Instant::now();
+
+        assert_eq!(engine.evaluate_at(&quotes, &HashMap::new(), start).len(), 2);
+        assert_eq!(engine.evaluate_at(&quotes, &HashMap::new(), start + Duration::from_secs(30)).len(), 1);
+        assert_eq!(engine.evaluate_at(&quotes, &HashMap::new(), start + Duration::from_secs(61)).len(), 2);
+    }
+
+    #[test]
+    fn test_reload_keeps_cooldowns() {
+        let mut quiet = rule("aapl-200", "AAPL", "price > 200");
+        quiet.cooldown_secs = Some(60);
+        let mut engine = engine(vec![quiet.clone()]).unwrap();
+
+        let quotes = [StockQuote::test("AAPL", 210.0, 208.0)];
+        let start = Instant::now();
+        assert_eq!(engine.evaluate_at(&quotes, &HashMap::new(), start).len(), 1);
+
+        // A rule added in front moves the unchanged one to another index
+        let rules = vec![rule("tsla-300", "TSLA", "price > 300"), quiet];
+        let config = AlertsConfig {
+            rules,
+            ..AlertsConfig::default()
+        };
+        engine.reload(&config).unwrap();
+        assert!(engine.evaluate_at(&quotes, &HashMap::new(), start + Duration::from_secs(30)).is_empty());
+        assert_eq!(engine.evaluate_at(&quotes, &HashMap::new(), start + Duration::from_secs(61)).len(), 1);
+    }
+
+    #[test]
+    fn test_crossing_rules_fire_once_per_cross() {
+        let mut crossing = rule("tsla-250", "TSLA", "price crosses above 250");
+        crossing.cooldown_secs = Some(0);
+        let mut engine = engine(vec![crossing]).unwrap();
+
+        let fired: Vec<usize> = [240.0, 252.0, 255.0, 245.0, 251.0]
+            .iter()
+            .map(|price| engine.evaluate(&[StockQuote::test("TSLA", *price, *price)], &HashMap::new()).len())
+            .collect();
+        assert_eq!(fired, vec![0, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_new_session_high() {
+        let mut high = rule("new-high", "*", "price > session_high");
+        high.cooldown_secs = Some(0);
+        let mut engine = engine(vec![high]).unwrap();
+
+        let mut sessions = HashMap::new();
+        let fired: Vec<usize> = [100.0, 101.0, 100.5, 101.0, 102.0]
+            .iter()
+            .map(|price| {
+                let quotes = [StockQuote::test("AAPL", *price, *price)];
+                let fired = engine.evaluate(&quotes, &sessions).len();
+                observe(&mut sessions, &quotes);
+                fired
+            })
+            .collect();
+        assert_eq!(fired, vec![0, 1, 0, 0, 1]);
+        assert_eq!((sessions["AAPL"].low, sessions["AAPL"].high), (100.0, 102.0));
+    }
+
+    #[test]
+    fn test_symbol_stats() {
+        let mut stats = SymbolStats::new(200.0);
+        for price in [205.0, 190.0, 210.0] {
+            stats.observe(price);
+        }
+
+        assert_eq!((stats.low, stats.high), (190.0, 210.0));
+        assert_eq!(stats.since_start(210.0), 5.0);
+        assert_eq!(SymbolStats::new(0.0).since_start(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_pct_move_uses_price_history() {
+        let mut velocity = rule("fast", "AAPL", "pct_move(15m) > 2");
+        velocity.cooldown_secs = Some(0);
+        let mut engine = engine(vec![velocity]).unwrap();
+        let start = Instant::now();
+        let at = |mins: u64| start + Duration::from_secs(mins * 60);
+
+        let aapl = |price: f64| [StockQuote::test("AAPL", price, price)];
+
+        assert!(engine.evaluate_at(&aapl(100.0), &HashMap::new(), at(0)).is_empty());
+        assert!(engine.evaluate_at(&aapl(101.0), &HashMap::new(), at(5)).is_empty());
+        assert_eq!(engine.evaluate_at(&aapl(102.5), &HashMap::new(), at(10)).len(), 1);
+
+        // The 100.0 sample has aged out, so the move is measured from 101.0
+        assert!(engine.evaluate_at(&aapl(102.5), &HashMap::new(), at(16)).is_empty());
+        assert_eq!(engine.prices["AAPL"].len(), 3);
+    }
+
+    #[test]
+    fn test_volume_ratio_compares_same_time_of_day() {
+        let day = 86_400;
+        let base = 20_000 * day;
+        let at = |d: i64, hours: i64| base + d * day + hours * 3600;
+        let candles = Candles {
+            c: vec![0.0; 6],
+            h: vec![0.0; 6],
+            l: vec![0.0; 6],
+            o: vec![0.0; 6],
+            // 14:00 and 18:00 bars on two prior days, then today's 14:00 bar
+            t: vec![at(0, 14), at(0, 18), at(1, 14), at(1, 18), at(2, 14), at(2, 18)],
+            v: vec![100.0, 900.0, 300.0, 900.0, 600.0, 0.0],
+            s: "ok".to_string(),
+        };
+
+        // At 15:00 only the 14:00 bars count: 600 against an average of 200
+        assert_eq!(volume_ratio(&candles, at(2, 15), 10), Some(3.0));
+        // With one day of lookback the average is just the day before
+        assert_eq!(volume_ratio(&candles, at(2, 15), 1), Some(2.0));
+        // Nothing traded yet today
+        assert_eq!(volume_ratio(&candles, at(3, 15), 10), None);
+    }
+
+    #[test]
+    fn test_history_round_trip_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.ndjson");
+
+        let mut engine = engine(vec![rule("big-move", "*", "abs(change_pct) > 3")]).unwrap();
+        let quotes = [StockQuote::test("AAPL", 104.0, 100.0), StockQuote::test("TSLA", 237.5, 250.0)];
+        let records: Vec<AlertRecord> = engine
+            .evaluate(&quotes, &HashMap::new())
+            .into_iter()
+            .map(|(event, _)| {
+                let results = vec![ActionResult {
+                    action: "webhook".to_string(),
+                    ok: false,
+                    error: Some("HTTP 500".to_string()),
+                }];
+                AlertRecord::new(&event, results)
+            })
+            .collect();
+        append_history(&path, &records).unwrap();
+        append_history(&path, &records[..1]).unwrap();
+
+        let history = read_history(&path).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[1].results[0].error.as_deref(), Some("HTTP 500"));
+
+        let tsla = filter_history(history.clone(), None, None, Some("big-move"), Some("TS*"));
+        assert_eq!(tsla.len(), 1);
+        assert!(filter_history(history, None, None, Some("other"), None).is_empty());
+
+        let mut csv = Vec::new();
+        write_history(&tsla, HistoryFormat::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.lines().nth(1).unwrap().ends_with(",TSLA,237.50,-5.00,abs(change_pct) > 3,webhook:error"));
+    }
+
+    #[test]
+    fn test_rules_parse_from_toml() {
+        let config: AlertsConfig = toml::from_str(
+            r#"
+            interval_secs = 30
+
+            [[rules]]
+            name = "gap-up"
+            symbol = "TS*"
+            condition = "change_pct > 5"
+            actions = [{ type = "log" }, { type = "desktop" }]
+            cooldown_secs = 900
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.interval_secs, 30);
+        assert_eq!(config.rules[0].symbol, "TS*");
+        assert_eq!(config.cooldown_secs, 300);
+        assert_eq!(config.rules[0].cooldown_secs, Some(900));
+        assert_eq!(config.rules[0].actions.len(), 2);
+        assert!(matches!(config.rules[0].actions[1], AlertAction::Desktop));
+    }
+}
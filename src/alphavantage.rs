@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Quote, StockQuote};
+use crate::provider::{ProviderFuture, QuoteProvider};
+use crate::ratelimit::TokenBucket;
+
+const BASE_URL: &str = "https://www.alphavantage.co/query";
+
+/// `[alphavantage]`: key and rate limit for `--provider alphavantage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlphaVantageConfig {
+    /// Also read from `ALPHAVANTAGE_API_KEY`
+    #[serde(default)]
+    pub api_key: String,
+
+    /// The free tier allows 5 requests a minute
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+}
+
+impl Default for AlphaVantageConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            requests_per_minute: default_requests_per_minute(),
+        }
+    }
+}
+
+fn default_requests_per_minute() -> u32 {
+    5
+}
+
+/// Quotes from Alpha Vantage's `GLOBAL_QUOTE` function
+///
+/// Requests are sent one at a time, paced by the `[alphavantage]` rate limit rather than
+/// Finnhub's.
+pub struct AlphaVantageProvider {
+    api_key: String,
+    client: reqwest::Client,
+    /// Also set per request, see [`crate::config::HttpConfig::client_builder`]
+    timeout: Duration,
+    limiter: TokenBucket,
+}
+
+/// The `Global Quote` object, whose values are all strings
+#[derive(Deserialize)]
+struct GlobalQuote {
+    #[serde(rename = "02. open")]
+    open: String,
+    #[serde(rename = "03. high")]
+    high: String,
+    #[serde(rename = "04. low")]
+    low: String,
+    #[serde(rename = "05. price")]
+    price: String,
+    #[serde(rename = "08. previous close")]
+    prev_close: String,
+}
+
+impl AlphaVantageProvider {
+    pub fn new(config: &Config) -> Result<Self> {
+        let settings = &config.alphavantage;
+        if settings.api_key.is_empty() {
+            return Err(ScannerError::Config(
+                "--provider alphavantage needs an API key. Set ALPHAVANTAGE_API_KEY or [alphavantage] api_key".to_string(),
+            ));
+        }
+
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let client = config.http.client_builder(timeout)?.build()?;
+        Ok(Self {
+            api_key: settings.api_key.clone(),
+            client,
+            timeout,
+            // Alpha Vantage rejects bursts, so requests are spaced evenly
+            limiter: TokenBucket::new(settings.requests_per_minute, 1),
+        })
+    }
+}
+
+impl QuoteProvider for AlphaVantageProvider {
+    fn name(&self) -> &str {
+        "alphavantage"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            self.limiter.acquire().await;
+            log::debug!("Fetching Alpha Vantage quote for {}", symbol);
+            let response = self
+                .client
+                .get(BASE_URL)
+                .query(&[("function", "GLOBAL_QUOTE"), ("symbol", symbol), ("apikey", &self.api_key)])
+                .timeout(self.timeout)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(ScannerError::Http {
+                    status: response.status().as_u16(),
+                    context: symbol.to_string(),
+                });
+            }
+
+            let body: HashMap<String, serde_json::Value> = response.json().await?;
+            let quote = parse_global_quote(symbol, body)?;
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+}
+
+/// Alpha Vantage answers HTTP 200 even when it refuses a request, with the reason in the body
+fn parse_global_quote(symbol: &str, mut body: HashMap<String, serde_json::Value>) -> Result<Quote> {
+    // "Note" and "Information" are how the per-minute and daily limits are reported
+    if body.contains_key("Note") || body.contains_key("Information") {
+        return Err(ScannerError::RateLimited { retry_after: None });
+    }
+    if let Some(message) = body.get("Error Message") {
+        return Err(ScannerError::Api(format!("Alpha Vantage: {}", message.as_str().unwrap_or_default())));
+    }
+
+    let raw = body
+        .remove("Global Quote")
+        .filter(|quote| quote.as_object().is_some_and(|fields| !fields.is_empty()))
+        .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+    let quote: GlobalQuote = serde_json::from_value(raw)?;
+
+    let number = |field: &str, value: &str| {
+        value
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| ScannerError::Parse(format!("Alpha Vantage {} for {}: '{}'", field, symbol, value)))
+    };
+    Ok(Quote {
+        c: number("price", &quote.price)?,
+        pc: number("previous close", &quote.prev_close)?,
+        h: number("high", &quote.high)?,
+        l: number("low", &quote.low)?,
+        o: number("open", &quote.open)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(json: &str) -> HashMap<String, serde_json::Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_global_quote() {
+        let quote = parse_global_quote(
+            "IBM",
+            body(
+                r#"{"Global Quote": {"01. symbol": "IBM", "02. open": "170.00", "03. high": "172.50",
+                "04. low": "169.10", "05. price": "171.20", "06. volume": "3000000",
+                "07. latest trading day": "2026-10-14", "08. previous close": "170.50",
+                "09. change": "0.70", "10. change percent": "0.4106%"}}"#,
+            ),
+        )
+        .unwrap();
+        assert_eq!(quote.c, 171.2);
+        assert_eq!(quote.pc, 170.5);
+        assert_eq!(quote.h, 172.5);
+    }
+
+    #[test]
+    fn test_parse_refusals() {
+        assert!(matches!(
+            parse_global_quote("IBM", body(r#"{"Note": "Thank you for using Alpha Vantage!"}"#)),
+            Err(ScannerError::RateLimited { .. })
+        ));
+        assert!(matches!(
+            parse_global_quote("NOPE", body(r#"{"Global Quote": {}}"#)),
+            Err(ScannerError::SymbolNotFound(_))
+        ));
+        assert!(matches!(
+            parse_global_quote("IBM", body(r#"{"Error Message": "Invalid API call."}"#)),
+            Err(ScannerError::Api(_))
+        ));
+    }
+}
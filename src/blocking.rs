@@ -0,0 +1,99 @@
+use chrono::NaiveDate;
+use tokio::runtime::Runtime;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{BasicFinancials, Candles, CompanyProfile, FinnhubClient, MarketStatus, NewsItem, StockQuote};
+
+/// Wraps a [`FinnhubClient`] and a single-threaded runtime that each call blocks on
+///
+/// Like `reqwest::blocking`, calling these methods from inside an async runtime panics; use the
+/// async client there instead.
+///
+/// ```no_run
+/// let client = finnhub_scanner::blocking::Client::new("your_api_key")?;
+/// let quote = client.quote("AAPL")?;
+/// println!("{} {:.2}", quote.symbol, quote.price);
+/// # Ok::<(), finnhub_scanner::ScannerError>(())
+/// ```
+pub struct Client {
+    inner: FinnhubClient,
+    runtime: Runtime,
+}
+
+impl Client {
+    /// A client with default settings
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Self::from_client(FinnhubClient::builder().api_key(api_key).build()?)
+    }
+
+    /// Block on an async client configured with [`FinnhubClient::builder`]
+    pub fn from_client(inner: FinnhubClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ScannerError::Io(format!("Failed to start runtime: {}", e)))?;
+        Ok(Self { inner, runtime })
+    }
+
+    pub fn quote(&self, symbol: &str) -> Result<StockQuote> {
+        let quote = self.runtime.block_on(self.inner.fetch_quote(symbol))?;
+        Ok(StockQuote::from_quote(symbol.to_string(), quote))
+    }
+
+    /// Quotes in the order given; symbols that fail are left out unless they all do
+    pub fn quotes(&self, symbols: &[String]) -> Result<Vec<StockQuote>> {
+        self.runtime.block_on(self.inner.fetch_quotes(symbols))
+    }
+
+    pub fn profile(&self, symbol: &str) -> Result<CompanyProfile> {
+        self.runtime.block_on(self.inner.fetch_profile(symbol))
+    }
+
+    pub fn metrics(&self, symbol: &str) -> Result<BasicFinancials> {
+        self.runtime.block_on(self.inner.fetch_metrics(symbol))
+    }
+
+    /// `from` and `to` are unix timestamps; see [`crate::finnhub::CANDLE_RESOLUTIONS`]
+    pub fn candles(&self, symbol: &str, resolution: &str, from: i64, to: i64) -> Result<Candles> {
+        self.runtime.block_on(self.inner.fetch_candles(symbol, resolution, from, to))
+    }
+
+    pub fn company_news(&self, symbol: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<NewsItem>> {
+        self.runtime.block_on(self.inner.fetch_company_news(symbol, from, to))
+    }
+
+    pub fn market_status(&self, exchange: &str) -> Result<MarketStatus> {
+        self.runtime.block_on(self.inner.fetch_market_status(exchange))
+    }
+
+    /// The async client underneath, e.g. to hand to code that does run a runtime
+    pub fn into_inner(self) -> FinnhubClient {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::Cassette;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_quote_from_cassette() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quotes.ndjson");
+        std::fs::write(&path, r#"{"request":"/quote?symbol=AAPL","body":{"c":110.0,"pc":100.0}}"#).unwrap();
+
+        let inner = FinnhubClient::builder()
+            .api_key("test")
+            .build()
+            .unwrap()
+            .with_cassette(Arc::new(Cassette::replay(&path).unwrap()));
+        let client = Client::from_client(inner).unwrap();
+
+        let quote = client.quote("AAPL").unwrap();
+        assert_eq!(quote.price, 110.0);
+        assert_eq!(quote.change_pct, 10.0);
+        assert_eq!(client.quotes(&["AAPL".to_string()]).unwrap().len(), 1);
+        assert!(client.quote("MSFT").is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use std::fs;
+use std::path::Path;
+use crate::errors::{Result, ScannerError};
+use crate::exchange;
+use crate::portfolio::{Holding, Side};
+
+/// Layout of a broker's positions export
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BrokerFormat {
+    /// Charles Schwab positions export
+    Schwab,
+    /// Interactive Brokers activity statement or Flex query with open positions
+    Ibkr,
+    /// Fidelity positions export (`Portfolio_Positions_*.csv`)
+    Fidelity,
+    /// Any CSV with symbol and quantity columns and a per-share or total cost column
+    #[default]
+    Generic,
+}
+
+/// Header names a format's columns go by, lowercase
+struct Layout {
+    symbol: &'static [&'static str],
+    quantity: &'static [&'static str],
+    /// Cost per share
+    average_cost: &'static [&'static str],
+    /// Cost of the whole position, divided by the quantity when there's no per-share column
+    total_cost: &'static [&'static str],
+}
+
+impl BrokerFormat {
+    fn layout(self) -> Layout {
+        match self {
+            BrokerFormat::Schwab => Layout {
+                symbol: &["symbol"],
+                quantity: &["quantity", "qty (quantity)", "qty"],
+                average_cost: &["cost/share"],
+                total_cost: &["cost basis"],
+            },
+            BrokerFormat::Ibkr => Layout {
+                symbol: &["symbol"],
+                quantity: &["quantity", "position"],
+                average_cost: &["cost price", "costbasisprice"],
+                total_cost: &["cost basis", "costbasismoney"],
+            },
+            BrokerFormat::Fidelity => Layout {
+                symbol: &["symbol"],
+                quantity: &["quantity"],
+                average_cost: &["average cost basis"],
+                total_cost: &["cost basis total", "cost basis"],
+            },
+            BrokerFormat::Generic => Layout {
+                symbol: &["symbol", "ticker", "instrument", "security"],
+                quantity: &["quantity", "qty", "shares", "position", "units"],
+                average_cost: &[
+                    "cost_basis",
+                    "average cost",
+                    "avg cost",
+                    "average cost basis",
+                    "cost per share",
+                    "cost/share",
+                    "average price",
+                    "avg price",
+                    "cost price",
+                ],
+                total_cost: &["cost basis total", "total cost", "cost basis", "book value"],
+            },
+        }
+    }
+}
+
+/// Read a broker export as holdings, one lot per position at its average cost
+pub fn read_export(format: BrokerFormat, path: &Path) -> Result<Vec<Holding>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+    parse_export(format, &text).map_err(|e| ScannerError::Parse(format!("{}: {}", path.display(), e)))
+}
+
+/// Positions from a broker export
+///
+/// Exports lead with titles and account lines, so the header is the first row naming both a
+/// symbol and a quantity column. Rows without a numeric quantity (cash, totals, pending
+/// activity, disclaimers) are skipped, as are options and other symbols with spaces in them.
+fn parse_export(format: BrokerFormat, text: &str) -> std::result::Result<Vec<Holding>, String> {
+    let layout = format.layout();
+    let mut rows: Vec<(usize, Vec<String>)> = text
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| (idx + 1, split_row(line)))
+        .collect();
+
+    // Activity statements hold every section in one file, each row led by its section and kind
+    let statement = rows.iter().any(|(_, fields)| fields.first().is_some_and(|f| f == "Open Positions"));
+    if format == BrokerFormat::Ibkr && statement {
+        rows.retain(|(_, fields)| fields.first().is_some_and(|f| f == "Open Positions"));
+        for (_, fields) in &mut rows {
+            fields.drain(..2.min(fields.len()));
+        }
+    }
+
+    let find = |header: &[String], names: &[&str]| {
+        header.iter().position(|name| names.contains(&name.to_lowercase().as_str()))
+    };
+    let start = rows
+        .iter()
+        .position(|(_, fields)| find(fields, layout.symbol).and(find(fields, layout.quantity)).is_some())
+        .ok_or("no header row with symbol and quantity columns")?;
+    let header = &rows[start].1;
+    let (symbol, quantity) = (find(header, layout.symbol).unwrap(), find(header, layout.quantity).unwrap());
+    let average_cost = find(header, layout.average_cost);
+    let total_cost = find(header, layout.total_cost);
+    if average_cost.is_none() && total_cost.is_none() {
+        return Err(format!("no {} or {} column", layout.average_cost[0], layout.total_cost[0]));
+    }
+    // IBKR statements repeat each position's lots and subtotals under the summary row
+    let discriminator = find(header, &["datadiscriminator"]);
+
+    let mut holdings = Vec::new();
+    for (line, fields) in &rows[start + 1..] {
+        let field = |index: usize| fields.get(index).map(String::as_str).unwrap_or_default();
+        if discriminator.is_some_and(|index| !field(index).eq_ignore_ascii_case("summary")) {
+            continue;
+        }
+        let Some(shares) = number(field(quantity)) else {
+            continue;
+        };
+        let ticker = field(symbol);
+        if ticker.is_empty() || ticker.ends_with("**") || ticker.eq_ignore_ascii_case("cash") {
+            continue;
+        }
+        if ticker.contains(char::is_whitespace) {
+            log::warn!("line {}: skipping '{}', which isn't a stock or fund symbol", line, ticker);
+            continue;
+        }
+        if shares <= 0.0 {
+            return Err(format!(
+                "line {}: {} {} is a short position, which holdings can't hold",
+                line, shares, ticker
+            ));
+        }
+
+        let cost_basis = match (average_cost.and_then(|index| number(field(index))), total_cost) {
+            (Some(average), _) => average,
+            (None, Some(index)) => number(field(index))
+                .map(|total| total / shares)
+                .ok_or_else(|| format!("line {}: no cost for {}", line, ticker))?,
+            (None, None) => return Err(format!("line {}: no cost for {}", line, ticker)),
+        };
+        holdings.push(Holding {
+            symbol: exchange::normalize(&ticker.to_uppercase()),
+            side: Side::Buy,
+            quantity: shares,
+            cost_basis,
+        });
+    }
+
+    if holdings.is_empty() {
+        return Err("no positions found".to_string());
+    }
+    Ok(holdings)
+}
+
+/// Split a CSV row, honouring quoted fields with commas and doubled quotes in them
+fn split_row(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// A number as brokers print it: `$1,234.50`, `(12.00)` or `-3`; `None` for `--`, `N/A` and blanks
+fn number(text: &str) -> Option<f64> {
+    let cleaned: String = text.chars().filter(|c| !matches!(c, '$' | ',' | ' ')).collect();
+    match cleaned.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        Some(negative) => negative.parse::<f64>().ok().map(|n| -n),
+        None => cleaned.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_broker_exports() {
+        let schwab = "\"Positions for account Individual ...123 as of 04:00 PM ET, 2026/10/14\"\n\
+            \n\
+            \"Symbol\",\"Description\",\"Qty (Quantity)\",\"Price\",\"Mkt Val (Market Value)\",\
+            \"Cost Basis\"\n\
+            \"AAPL\",\"APPLE INC\",\"10\",\"$230.00\",\"$2,300.00\",\"$1,502.50\"\n\
+            \"AAPL 01/16/2027 250.00 C\",\"CALL APPLE INC\",\"1\",\"$12.00\",\"$1,200.00\",\"$900.00\"\n\
+            \"Cash & Cash Investments\",\"--\",\"--\",\"--\",\"$512.00\",\"--\"\n\
+            \"Account Total\",\"--\",\"--\",\"--\",\"$4,012.00\",\"$2,402.50\"\n";
+        let holdings = parse_export(BrokerFormat::Schwab, schwab).unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!((holdings[0].symbol.as_str(), holdings[0].quantity), ("AAPL", 10.0));
+        assert_eq!(holdings[0].cost_basis, 150.25);
+
+        let fidelity = "Account Number,Account Name,Symbol,Description,Quantity,Last Price,\
+            Cost Basis Total,Average Cost Basis,Type\n\
+            Z123,Individual,SPAXX**,HELD IN MONEY MARKET,,,,,Cash\n\
+            Z123,Individual,MSFT,MICROSOFT CORP,5,$420.00,\"$1,500.00\",$300.00,Cash\n\
+            Z123,Individual,Pending Activity,,,,,,\n\
+            \n\
+            \"The data and information in this spreadsheet is provided to you solely for your use.\"\n";
+        let holdings = parse_export(BrokerFormat::Fidelity, fidelity).unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!((holdings[0].symbol.as_str(), holdings[0].cost_basis), ("MSFT", 300.0));
+
+        let ibkr = "Statement,Header,Field Name,Field Value\n\
+            Statement,Data,Period,\"October 14, 2026\"\n\
+            Open Positions,Header,DataDiscriminator,Asset Category,Currency,Symbol,Quantity,Mult,\
+            Cost Price,Cost Basis\n\
+            Open Positions,Data,Summary,Stocks,CAD,SHOP,20,1,82.4,1648\n\
+            Open Positions,Data,Lot,Stocks,CAD,SHOP,20,1,82.4,1648\n\
+            Open Positions,Total,,Stocks,CAD,,,,,1648\n";
+        let holdings = parse_export(BrokerFormat::Ibkr, ibkr).unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!((holdings[0].quantity, holdings[0].cost_basis), (20.0, 82.4));
+
+        let generic = "Ticker,Shares,Total Cost\nnvda,4,\"1,000\"\n";
+        assert_eq!(parse_export(BrokerFormat::Generic, generic).unwrap()[0].cost_basis, 250.0);
+        let error = |text: &str| parse_export(BrokerFormat::Generic, text).unwrap_err();
+        assert!(error("Ticker,Shares\nNVDA,4\n").contains("no cost_basis"));
+        assert!(error("Name,Value\nx,1\n").contains("no header row"));
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number("$1,234.50"), Some(1234.5));
+        assert_eq!(number("(12.00)"), Some(-12.0));
+        assert_eq!(number("--"), None);
+        assert_eq!(number(""), None);
+    }
+}
@@ -0,0 +1,275 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::config::CacheTtls;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::Quote;
+use crate::paths;
+use crate::rt::{SystemTime, UNIX_EPOCH};
+
+const QUOTES_FILE: &str = "quotes.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedQuote {
+    /// Unix time the quote was fetched
+    fetched_at: u64,
+    quote: Quote,
+}
+
+/// In-process quote cache backed by a JSON file so repeated runs share results
+pub struct QuoteCache {
+    ttl_secs: u64,
+    path: PathBuf,
+    entries: Mutex<HashMap<String, CachedQuote>>,
+}
+
+impl QuoteCache {
+    /// Open the cache in `dir`, loading any entries left by earlier runs
+    pub fn open(dir: &Path, ttl_secs: u64) -> Self {
+        let path = dir.join(QUOTES_FILE);
+
+        // A missing or corrupt cache file just means a cold cache
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<HashMap<String, CachedQuote>>(&content).ok())
+            .unwrap_or_default();
+
+        let cache = Self {
+            ttl_secs,
+            path,
+            entries: Mutex::new(entries),
+        };
+        cache.evict_expired();
+        cache
+    }
+
+    /// Return a cached quote if it is younger than the TTL
+    pub fn get(&self, symbol: &str) -> Option<Quote> {
+        let now = now_secs();
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(symbol)
+            .filter(|entry| now.saturating_sub(entry.fetched_at) < self.ttl_secs)
+            .map(|entry| entry.quote.clone())
+    }
+
+    /// Store a freshly fetched quote
+    pub fn insert(&self, symbol: &str, quote: Quote) {
+        self.insert_at(symbol, quote, now_secs());
+    }
+
+    fn insert_at(&self, symbol: &str, quote: Quote, fetched_at: u64) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(symbol.to_string(), CachedQuote { fetched_at, quote });
+    }
+
+    fn evict_expired(&self) {
+        let now = now_secs();
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| now.saturating_sub(entry.fetched_at) < self.ttl_secs);
+    }
+
+    /// Write unexpired entries back to disk
+    pub fn save(&self) -> Result<()> {
+        self.evict_expired();
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ScannerError::Io(format!("Failed to create cache directory: {}", e)))?;
+        }
+
+        let content = serde_json::to_string(&*self.entries.lock().unwrap())?;
+        fs::write(&self.path, content)
+            .map_err(|e| ScannerError::Io(format!("Failed to write cache file: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Slow-changing endpoints stored in the disk cache
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endpoint {
+    Profile,
+    Peers,
+    Symbols,
+    Metrics,
+    Constituents,
+}
+
+impl Endpoint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Endpoint::Profile => "profile",
+            Endpoint::Peers => "peers",
+            Endpoint::Symbols => "symbols",
+            Endpoint::Metrics => "metrics",
+            Endpoint::Constituents => "constituents",
+        }
+    }
+
+    /// Configured time-to-live for this endpoint
+    pub fn ttl(&self, ttls: &CacheTtls) -> u64 {
+        match self {
+            Endpoint::Profile => ttls.profile_secs,
+            Endpoint::Peers => ttls.peers_secs,
+            Endpoint::Symbols => ttls.symbols_secs,
+            Endpoint::Metrics => ttls.metrics_secs,
+            Endpoint::Constituents => ttls.constituents_secs,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry<T> {
+    fetched_at: u64,
+    value: T,
+}
+
+/// File-per-key cache for endpoint responses, laid out as `<dir>/<endpoint>/<key>.json`
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    fn entry_path(&self, endpoint: Endpoint, key: &str) -> PathBuf {
+        let file_name: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect();
+
+        self.dir.join(endpoint.name()).join(format!("{}.json", file_name))
+    }
+
+    /// Return the cached value if it is younger than `ttl_secs`
+    pub fn get<T: DeserializeOwned>(&self, endpoint: Endpoint, key: &str, ttl_secs: u64) -> Option<T> {
+        let content = fs::read_to_string(self.entry_path(endpoint, key)).ok()?;
+        let entry: DiskEntry<T> = serde_json::from_str(&content).ok()?;
+
+        if now_secs().saturating_sub(entry.fetched_at) < ttl_secs {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn put<T: Serialize>(&self, endpoint: Endpoint, key: &str, value: &T) -> Result<()> {
+        self.put_at(endpoint, key, value, now_secs())
+    }
+
+    fn put_at<T: Serialize>(&self, endpoint: Endpoint, key: &str, value: &T, fetched_at: u64) -> Result<()> {
+        let path = self.entry_path(endpoint, key);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ScannerError::Io(format!("Failed to create cache directory: {}", e)))?;
+        }
+
+        let content = serde_json::to_string(&DiskEntry { fetched_at, value })?;
+        fs::write(&path, content)
+            .map_err(|e| ScannerError::Io(format!("Failed to write cache file: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Default cache location when `cache_dir` is not configured
+pub fn default_cache_dir() -> PathBuf {
+    paths::cache_dir().unwrap_or_else(|| std::env::temp_dir().join("finnhub-scanner"))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn test_quote(price: f64) -> Quote {
+        Quote {
+            c: price,
+            pc: 100.0,
+            h: 0.0,
+            l: 0.0,
+            o: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_fresh_entry_is_returned() {
+        let dir = tempdir().unwrap();
+        let cache = QuoteCache::open(dir.path(), 30);
+        cache.insert("AAPL", test_quote(150.0));
+
+        assert_eq!(cache.get("AAPL").map(|q| q.c), Some(150.0));
+        assert!(cache.get("MSFT").is_none());
+    }
+
+    #[test]
+    fn test_expired_entry_is_ignored() {
+        let dir = tempdir().unwrap();
+        let cache = QuoteCache::open(dir.path(), 30);
+        cache.insert_at("AAPL", test_quote(150.0), now_secs() - 60);
+
+        assert!(cache.get("AAPL").is_none());
+    }
+
+    #[test]
+    fn test_cache_persists_between_runs() {
+        let dir = tempdir().unwrap();
+
+        let cache = QuoteCache::open(dir.path(), 30);
+        cache.insert("AAPL", test_quote(150.0));
+        cache.save().unwrap();
+
+        let reopened = QuoteCache::open(dir.path(), 30);
+        assert_eq!(reopened.get("AAPL").map(|q| q.c), Some(150.0));
+    }
+
+    #[test]
+    fn test_disk_cache_round_trip() {
+        let dir = tempdir().unwrap();
+        let cache = DiskCache::new(dir.path());
+
+        cache.put(Endpoint::Peers, "AAPL", &vec!["MSFT".to_string()]).unwrap();
+        let peers: Option<Vec<String>> = cache.get(Endpoint::Peers, "AAPL", 60);
+        assert_eq!(peers, Some(vec!["MSFT".to_string()]));
+
+        // Different endpoints don't collide on the same key
+        assert!(cache.get::<serde_json::Value>(Endpoint::Profile, "AAPL", 60).is_none());
+    }
+
+    #[test]
+    fn test_disk_cache_respects_ttl() {
+        let dir = tempdir().unwrap();
+        let cache = DiskCache::new(dir.path());
+
+        cache.put_at(Endpoint::Metrics, "AAPL", &1.5, now_secs() - 120).unwrap();
+        assert!(cache.get::<serde_json::Value>(Endpoint::Metrics, "AAPL", 300).is_some());
+        assert!(cache.get::<serde_json::Value>(Endpoint::Metrics, "AAPL", 60).is_none());
+    }
+
+    #[test]
+    fn test_disk_cache_sanitizes_keys() {
+        let cache = DiskCache::new(Path::new("/cache"));
+        let path = cache.entry_path(Endpoint::Symbols, "^GSPC/../x");
+        assert_eq!(path, Path::new("/cache/symbols/_GSPC_.._x.json"));
+    }
+}
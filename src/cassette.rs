@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use crate::errors::{Result, ScannerError};
+
+/// Query parameters left out of a request's key: the token is a secret, and `from`/`to` windows
+/// are relative to the current time, so they'd never match on replay
+const IGNORED_PARAMS: &[&str] = &["token", "from", "to"];
+
+/// API responses recorded to, or replayed from, an NDJSON file
+///
+/// Recording appends each successful response as `{"request": ..., "body": ...}`, keyed by
+/// endpoint and parameters (without the API key). Replaying serves a request's recorded
+/// responses in order and then keeps repeating the last one, so runs are deterministic.
+pub struct Cassette {
+    mode: Mode,
+}
+
+enum Mode {
+    Record(Mutex<File>),
+    Replay(Mutex<HashMap<String, VecDeque<serde_json::Value>>>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    request: String,
+    body: serde_json::Value,
+}
+
+impl Cassette {
+    /// Record to `path`, replacing any earlier recording
+    pub fn record(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)
+            .map_err(|e| ScannerError::Io(format!("Failed to create {}: {}", path.display(), e)))?;
+        Ok(Self {
+            mode: Mode::Record(Mutex::new(file)),
+        })
+    }
+
+    /// Serve responses recorded in `path`
+    pub fn replay(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| ScannerError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        let mut responses: HashMap<String, VecDeque<serde_json::Value>> = HashMap::new();
+        for (idx, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let interaction: Interaction = serde_json::from_str(line).map_err(|e| {
+                ScannerError::Parse(format!("{} line {}: {}", path.display(), idx + 1, e))
+            })?;
+            responses.entry(interaction.request).or_default().push_back(interaction.body);
+        }
+
+        Ok(Self {
+            mode: Mode::Replay(Mutex::new(responses)),
+        })
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    /// The next recorded response for `request`
+    pub fn replay_response(&self, request: &str) -> Result<serde_json::Value> {
+        let Mode::Replay(responses) = &self.mode else {
+            return Err(ScannerError::InvalidInput("Cassette is recording, not replaying".to_string()));
+        };
+
+        let key = request_key(request);
+        let mut responses = responses.lock().expect("cassette lock poisoned");
+        let queue = responses
+            .get_mut(&key)
+            .ok_or_else(|| ScannerError::Api(format!("No recorded response for {}", key)))?;
+        match queue.len() {
+            0 => Err(ScannerError::Api(format!("No recorded response for {}", key))),
+            1 => Ok(queue[0].clone()),
+            _ => Ok(queue.pop_front().expect("queue is not empty")),
+        }
+    }
+
+    /// Append a response; a no-op when replaying
+    pub fn record_response(&self, request: &str, body: &serde_json::Value) -> Result<()> {
+        let Mode::Record(file) = &self.mode else {
+            return Ok(());
+        };
+
+        let interaction = Interaction {
+            request: request_key(request),
+            body: body.clone(),
+        };
+        // One write per line, so concurrent requests don't interleave
+        let line = format!("{}\n", serde_json::to_string(&interaction)?);
+        file.lock().expect("cassette lock poisoned").write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// `/quote?symbol=AAPL` for `/quote?symbol=AAPL&token=...`
+fn request_key(request: &str) -> String {
+    let (path, query) = request.split_once('?').unwrap_or((request, ""));
+    let params: Vec<&str> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or(param);
+            !IGNORED_PARAMS.contains(&name)
+        })
+        .collect();
+
+    if params.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, params.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_request_key() {
+        assert_eq!(request_key("/quote?symbol=AAPL&token=secret"), "/quote?symbol=AAPL");
+        assert_eq!(
+            request_key("/stock/candle?symbol=AAPL&resolution=D&from=1&to=2&token=secret"),
+            "/stock/candle?symbol=AAPL&resolution=D"
+        );
+        assert_eq!(request_key("/stock/market-status?token=secret"), "/stock/market-status");
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("run.ndjson");
+
+        let recorder = Cassette::record(&path).unwrap();
+        recorder.record_response("/quote?symbol=AAPL&token=a", &json!({"c": 1.0, "pc": 1.0})).unwrap();
+        recorder.record_response("/quote?symbol=AAPL&token=a", &json!({"c": 2.0, "pc": 1.0})).unwrap();
+        drop(recorder);
+        assert!(!fs::read_to_string(&path).unwrap().contains("token"));
+
+        let player = Cassette::replay(&path).unwrap();
+        assert!(player.is_replay());
+        // The token isn't part of the match, and the last response repeats
+        assert_eq!(player.replay_response("/quote?symbol=AAPL&token=b").unwrap()["c"], 1.0);
+        assert_eq!(player.replay_response("/quote?symbol=AAPL&token=b").unwrap()["c"], 2.0);
+        assert_eq!(player.replay_response("/quote?symbol=AAPL&token=b").unwrap()["c"], 2.0);
+        assert!(matches!(player.replay_response("/quote?symbol=MSFT"), Err(ScannerError::Api(_))));
+    }
+}
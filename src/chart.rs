@@ -0,0 +1,315 @@
+/// Block characters used for sparklines, lowest to highest
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Width reserved for the y-axis labels
+const LABEL_WIDTH: usize = 10;
+
+/// Glyph and color for each moving-average overlay, cycled in order
+const OVERLAY_STYLES: [(char, &str); 3] = [('·', "\x1b[33m"), ('∙', "\x1b[36m"), ('°', "\x1b[35m")];
+
+/// One open/high/low/close bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ohlc {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// Render a one-line sparkline for a series of values
+pub fn sparkline(values: &[f64]) -> String {
+    let (min, max) = match bounds(values) {
+        Some(bounds) => bounds,
+        None => return String::new(),
+    };
+    let span = max - min;
+
+    values
+        .iter()
+        .map(|v| {
+            if span == 0.0 {
+                SPARK_CHARS[SPARK_CHARS.len() / 2]
+            } else {
+                let idx = ((v - min) / span * (SPARK_CHARS.len() - 1) as f64).round() as usize;
+                SPARK_CHARS[idx.min(SPARK_CHARS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+/// Render a multi-line price chart with a labelled y-axis
+///
+/// `labels` holds the x-axis captions for the first and last point.
+pub fn line_chart(values: &[f64], labels: (&str, &str), width: usize, height: usize) -> Vec<String> {
+    let points = resample(values, width);
+    let (min, max) = match bounds(&points) {
+        Some(bounds) => bounds,
+        None => return Vec::new(),
+    };
+    let height = height.max(2);
+    let span = if max > min { max - min } else { 1.0 };
+
+    let row_of = |v: f64| ((v - min) / span * (height - 1) as f64).round() as usize;
+
+    // grid[0] is the bottom row
+    let mut grid = vec![vec![' '; points.len()]; height];
+    let mut prev_row: Option<usize> = None;
+
+    for (col, &value) in points.iter().enumerate() {
+        let row = row_of(value);
+
+        // Connect to the previous point so steep moves stay readable
+        if let Some(prev) = prev_row {
+            let (lo, hi) = if prev < row { (prev, row) } else { (row, prev) };
+            for cell in grid.iter_mut().take(hi).skip(lo + 1) {
+                cell[col] = '│';
+            }
+        }
+
+        grid[row][col] = '•';
+        prev_row = Some(row);
+    }
+
+    let mut lines = Vec::with_capacity(height + 2);
+
+    for (idx, row) in grid.iter().enumerate().rev() {
+        let label = if idx == height - 1 {
+            format!("{:>width$.2} ┤", max, width = LABEL_WIDTH)
+        } else if idx == 0 {
+            format!("{:>width$.2} ┤", min, width = LABEL_WIDTH)
+        } else if idx == (height - 1) / 2 {
+            format!("{:>width$.2} ┤", min + (max - min) / 2.0, width = LABEL_WIDTH)
+        } else {
+            format!("{:>width$} │", "", width = LABEL_WIDTH)
+        };
+
+        lines.push(format!("{}{}", label, row.iter().collect::<String>()));
+    }
+
+    lines.push(format!("{:>width$} └{}", "", "─".repeat(points.len()), width = LABEL_WIDTH));
+
+    let (first, last) = labels;
+    let gap = points.len().saturating_sub(first.chars().count() + last.chars().count()).max(1);
+    lines.push(format!("{:>width$}  {}{}{}", "", first, " ".repeat(gap), last, width = LABEL_WIDTH));
+
+    lines
+}
+
+/// Render OHLC bars as a candlestick chart with optional moving-average overlays
+///
+/// Each column is one candle; when there are more candles than `width` they are merged
+/// into wider buckets. Overlays must have one entry per input candle.
+pub fn candlestick_chart(
+    candles: &[Ohlc],
+    overlays: &[Vec<Option<f64>>],
+    labels: (&str, &str),
+    width: usize,
+    height: usize,
+) -> Vec<String> {
+    if candles.is_empty() {
+        return Vec::new();
+    }
+
+    let ranges = buckets(candles.len(), width);
+    let bars: Vec<Ohlc> = ranges.iter().map(|range| merge(&candles[range.clone()])).collect();
+    let overlay_points: Vec<Vec<Option<f64>>> = overlays
+        .iter()
+        .map(|series| ranges.iter().map(|range| series.get(range.end - 1).copied().flatten()).collect())
+        .collect();
+
+    let min = bars.iter().map(|b| b.low).fold(f64::INFINITY, f64::min);
+    let max = bars.iter().map(|b| b.high).fold(f64::NEG_INFINITY, f64::max);
+    let height = height.max(2);
+    let span = if max > min { max - min } else { 1.0 };
+
+    let row_of = |v: f64| (((v - min) / span * (height - 1) as f64).round() as usize).min(height - 1);
+
+    // grid[0] is the bottom row; each cell is a glyph plus an optional color
+    let mut grid: Vec<Vec<(char, Option<&str>)>> = vec![vec![(' ', None); bars.len()]; height];
+
+    for (col, bar) in bars.iter().enumerate() {
+        let color = if bar.close >= bar.open { "\x1b[32m" } else { "\x1b[31m" };
+        let (body_lo, body_hi) = if bar.open < bar.close {
+            (row_of(bar.open), row_of(bar.close))
+        } else {
+            (row_of(bar.close), row_of(bar.open))
+        };
+
+        for (row, cell) in grid.iter_mut().enumerate().take(row_of(bar.high) + 1).skip(row_of(bar.low)) {
+            let glyph = if (body_lo..=body_hi).contains(&row) { '█' } else { '│' };
+            cell[col] = (glyph, Some(color));
+        }
+    }
+
+    for (series, &(glyph, color)) in overlay_points.iter().zip(OVERLAY_STYLES.iter().cycle()) {
+        for (col, value) in series.iter().enumerate() {
+            if let Some(value) = value {
+                let cell = &mut grid[row_of(*value)][col];
+                if cell.0 != '█' {
+                    *cell = (glyph, Some(color));
+                }
+            }
+        }
+    }
+
+    let mut lines = Vec::with_capacity(height + 2);
+
+    for (idx, row) in grid.iter().enumerate().rev() {
+        let label = if idx == height - 1 {
+            format!("{:>width$.2} ┤", max, width = LABEL_WIDTH)
+        } else if idx == 0 {
+            format!("{:>width$.2} ┤", min, width = LABEL_WIDTH)
+        } else {
+            format!("{:>width$} │", "", width = LABEL_WIDTH)
+        };
+
+        let cells: String = row
+            .iter()
+            .map(|(glyph, color)| match color {
+                Some(color) => format!("{}{}\x1b[0m", color, glyph),
+                None => glyph.to_string(),
+            })
+            .collect();
+
+        lines.push(format!("{}{}", label, cells));
+    }
+
+    lines.push(format!("{:>width$} └{}", "", "─".repeat(bars.len()), width = LABEL_WIDTH));
+
+    let (first, last) = labels;
+    let gap = bars.len().saturating_sub(first.chars().count() + last.chars().count()).max(1);
+    lines.push(format!("{:>width$}  {}{}{}", "", first, " ".repeat(gap), last, width = LABEL_WIDTH));
+
+    lines
+}
+
+/// Describe the overlay glyphs for a chart legend, e.g. `· MA20  ∙ MA50`
+pub fn overlay_legend(periods: &[usize]) -> String {
+    periods
+        .iter()
+        .zip(OVERLAY_STYLES.iter().cycle())
+        .map(|(period, (glyph, color))| format!("{}{}\x1b[0m MA{}", color, glyph, period))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Simple moving average; `None` until enough values have been seen
+pub fn moving_average(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    if period == 0 {
+        return vec![None; values.len()];
+    }
+
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            sum += v;
+            if i >= period {
+                sum -= values[i - period];
+            }
+            if i + 1 >= period {
+                Some(sum / period as f64)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Split `len` items into at most `width` contiguous ranges
+fn buckets(len: usize, width: usize) -> Vec<std::ops::Range<usize>> {
+    let count = len.min(width.max(1));
+    (0..count)
+        .map(|i| (i * len / count)..((i + 1) * len / count))
+        .collect()
+}
+
+fn merge(bars: &[Ohlc]) -> Ohlc {
+    Ohlc {
+        open: bars[0].open,
+        high: bars.iter().map(|b| b.high).fold(f64::NEG_INFINITY, f64::max),
+        low: bars.iter().map(|b| b.low).fold(f64::INFINITY, f64::min),
+        close: bars[bars.len() - 1].close,
+    }
+}
+
+/// Downsample a series to at most `width` points, keeping the first and last values
+pub fn resample(values: &[f64], width: usize) -> Vec<f64> {
+    if values.len() <= width || width < 2 {
+        return values.to_vec();
+    }
+
+    (0..width)
+        .map(|i| values[i * (values.len() - 1) / (width - 1)])
+        .collect()
+}
+
+fn bounds(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    Some((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_range() {
+        assert_eq!(sparkline(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]), "▁▂▃▄▅▆▇█");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_sparkline_flat() {
+        assert_eq!(sparkline(&[5.0, 5.0, 5.0]).chars().count(), 3);
+    }
+
+    #[test]
+    fn test_line_chart_dimensions() {
+        let values: Vec<f64> = (0..200).map(|i| (i as f64 / 10.0).sin()).collect();
+        let lines = line_chart(&values, ("start", "end"), 40, 10);
+
+        // height rows + axis + labels
+        assert_eq!(lines.len(), 12);
+        assert!(lines[0].contains('┤'));
+        assert_eq!(lines[10].chars().filter(|&c| c == '─').count(), 40);
+    }
+
+    #[test]
+    fn test_resample_keeps_endpoints() {
+        let values: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let sampled = resample(&values, 10);
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(sampled[0], 0.0);
+        assert_eq!(sampled[9], 99.0);
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let ma = moving_average(&[1.0, 2.0, 3.0, 4.0], 2);
+        assert_eq!(ma, vec![None, Some(1.5), Some(2.5), Some(3.5)]);
+    }
+
+    #[test]
+    fn test_candles_merged_to_width() {
+        let candles: Vec<Ohlc> = (0..60)
+            .map(|i| {
+                let base = 100.0 + i as f64;
+                Ohlc { open: base, high: base + 2.0, low: base - 2.0, close: base + 1.0 }
+            })
+            .collect();
+
+        let lines = candlestick_chart(&candles, &[moving_average(&[0.0; 60], 5)], ("a", "b"), 30, 8);
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[8].chars().filter(|&c| c == '─').count(), 30);
+
+        let merged = merge(&candles[0..2]);
+        assert_eq!(merged, Ohlc { open: 100.0, high: 103.0, low: 98.0, close: 102.0 });
+    }
+}
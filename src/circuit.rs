@@ -0,0 +1,138 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::errors::{Result, ScannerError};
+use crate::rt::Instant;
+
+/// Stops sending requests after repeated failures, such as a bad API key or an outage
+///
+/// After `threshold` consecutive failures the circuit opens and requests fail immediately.
+/// Once `cooldown` has passed one trial request is let through; success closes the circuit
+/// and failure opens it for another cooldown.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    failures: u32,
+    opened_at: Option<Instant>,
+    last_error: String,
+}
+
+impl CircuitBreaker {
+    /// A `threshold` of 0 disables the breaker
+    pub fn new(threshold: u32, cooldown_secs: u64) -> Self {
+        Self {
+            threshold,
+            cooldown: Duration::from_secs(cooldown_secs),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Fail fast while the circuit is open
+    pub fn check(&self) -> Result<()> {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&self, now: Instant) -> Result<()> {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        let Some(opened_at) = state.opened_at else {
+            return Ok(());
+        };
+
+        let elapsed = now.saturating_duration_since(opened_at);
+        if elapsed >= self.cooldown {
+            // Half-open: this request is the trial, the rest wait for its outcome
+            state.opened_at = Some(now);
+            return Ok(());
+        }
+
+        Err(ScannerError::CircuitOpen {
+            failures: state.failures,
+            last_error: state.last_error.clone(),
+            retry_in: self.cooldown - elapsed,
+        })
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        if state.opened_at.is_some() {
+            log::info!("API requests are succeeding again");
+        }
+        *state = State::default();
+    }
+
+    pub fn record_failure(&self, error: &ScannerError) {
+        self.record_failure_at(error, Instant::now());
+    }
+
+    fn record_failure_at(&self, error: &ScannerError, now: Instant) {
+        if self.threshold == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        state.failures += 1;
+        state.last_error = error.to_string();
+        if state.failures >= self.threshold {
+            if state.opened_at.is_none() {
+                log::error!(
+                    "{} consecutive API failures, pausing requests for {}s: {}",
+                    state.failures,
+                    self.cooldown.as_secs(),
+                    error
+                );
+            }
+            state.opened_at = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> ScannerError {
+        ScannerError::InvalidApiKey
+    }
+
+    #[test]
+    fn test_trips_after_threshold() {
+        let breaker = CircuitBreaker::new(2, 30);
+        let start = Instant::now();
+
+        breaker.record_failure_at(&failure(), start);
+        assert!(breaker.check_at(start).is_ok());
+
+        breaker.record_failure_at(&failure(), start);
+        let err = breaker.check_at(start).unwrap_err().to_string();
+        assert!(err.contains("2 consecutive failures"));
+        assert!(err.contains("401"));
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, 30);
+        let start = Instant::now();
+        breaker.record_failure_at(&failure(), start);
+
+        let later = start + Duration::from_secs(31);
+        assert!(breaker.check_at(later).is_ok());
+        // Only the trial request goes through
+        assert!(breaker.check_at(later).is_err());
+
+        breaker.record_success();
+        assert!(breaker.check_at(later).is_ok());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let breaker = CircuitBreaker::new(0, 30);
+        for _ in 0..10 {
+            breaker.record_failure(&failure());
+        }
+        assert!(breaker.check().is_ok());
+    }
+}
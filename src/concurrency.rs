@@ -0,0 +1,167 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// How a request went, as far as concurrency is concerned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Feedback {
+    /// Answered, taking this long
+    Completed(Duration),
+    /// Rejected with HTTP 429
+    Throttled,
+}
+
+/// In-flight request limit that halves on rate limiting or slow responses and grows by one
+/// after a full window of healthy ones, up to `concurrent_requests`
+pub struct AdaptiveConcurrency {
+    semaphore: Semaphore,
+    max: usize,
+    slow: Duration,
+    enabled: bool,
+    state: Mutex<State>,
+}
+
+struct State {
+    limit: usize,
+    /// Permits still to be retired after a decrease, taken as they are released
+    debt: usize,
+    successes: usize,
+}
+
+/// Slot for one in-flight request
+pub struct Permit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    owner: &'a AdaptiveConcurrency,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.owner.state.lock().expect("concurrency lock poisoned");
+        if state.debt > 0 {
+            state.debt -= 1;
+            if let Some(permit) = self.permit.take() {
+                permit.forget();
+            }
+        }
+    }
+}
+
+impl AdaptiveConcurrency {
+    /// With `enabled` false the limit stays at `max`
+    pub fn new(max: usize, slow_response_ms: u64, enabled: bool) -> Self {
+        let max = max.max(1);
+        Self {
+            semaphore: Semaphore::new(max),
+            max,
+            slow: Duration::from_millis(slow_response_ms),
+            enabled,
+            state: Mutex::new(State {
+                limit: max,
+                debt: 0,
+                successes: 0,
+            }),
+        }
+    }
+
+    pub async fn acquire(&self) -> Permit<'_> {
+        let permit = self.semaphore.acquire().await.expect("concurrency semaphore is never closed");
+        Permit {
+            permit: Some(permit),
+            owner: self,
+        }
+    }
+
+    /// Current in-flight limit
+    pub fn limit(&self) -> usize {
+        self.state.lock().expect("concurrency lock poisoned").limit
+    }
+
+    pub fn record(&self, feedback: Feedback) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut state = self.state.lock().expect("concurrency lock poisoned");
+        match feedback {
+            Feedback::Completed(elapsed) if elapsed < self.slow => {
+                state.successes += 1;
+                if state.successes >= state.limit && state.limit < self.max {
+                    state.successes = 0;
+                    state.limit += 1;
+                    if state.debt > 0 {
+                        state.debt -= 1;
+                    } else {
+                        self.semaphore.add_permits(1);
+                    }
+                    log::debug!("Raised concurrency to {}", state.limit);
+                }
+            }
+            _ => {
+                state.successes = 0;
+                if state.limit == 1 {
+                    return;
+                }
+                let reduced = state.limit / 2;
+                state.debt += state.limit - reduced;
+                state.limit = reduced;
+                // Retire idle permits now; busy ones are retired when their requests finish
+                while state.debt > 0 {
+                    match self.semaphore.try_acquire() {
+                        Ok(permit) => {
+                            permit.forget();
+                            state.debt -= 1;
+                        }
+                        Err(_) => break,
+                    }
+                }
+                log::info!("Reduced concurrency to {} after {:?}", state.limit, feedback);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: Feedback = Feedback::Completed(Duration::from_millis(100));
+
+    #[test]
+    fn test_halves_on_throttle_and_recovers() {
+        let limiter = AdaptiveConcurrency::new(8, 2000, true);
+
+        limiter.record(Feedback::Throttled);
+        assert_eq!(limiter.limit(), 4);
+        assert_eq!(limiter.semaphore.available_permits(), 4);
+
+        limiter.record(Feedback::Completed(Duration::from_secs(5)));
+        assert_eq!(limiter.limit(), 2);
+
+        for _ in 0..2 {
+            limiter.record(FAST);
+        }
+        assert_eq!(limiter.limit(), 3);
+        assert_eq!(limiter.semaphore.available_permits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_busy_permits_retired_on_release() {
+        let limiter = AdaptiveConcurrency::new(2, 2000, true);
+        let first = limiter.acquire().await;
+        let second = limiter.acquire().await;
+
+        limiter.record(Feedback::Throttled);
+        drop(first);
+        drop(second);
+
+        assert_eq!(limiter.limit(), 1);
+        assert_eq!(limiter.semaphore.available_permits(), 1);
+    }
+
+    #[test]
+    fn test_disabled_keeps_max() {
+        let limiter = AdaptiveConcurrency::new(4, 2000, false);
+        limiter.record(Feedback::Throttled);
+        assert_eq!(limiter.limit(), 4);
+    }
+}
@@ -1,23 +1,70 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::actions::NotificationsConfig;
+use crate::alerts::AlertsConfig;
+use crate::alphavantage::AlphaVantageConfig;
 use crate::errors::{Result, ScannerError};
+use crate::expr;
+use crate::influx::InfluxConfig;
+use crate::kafka::KafkaConfig;
+use crate::mqtt::MqttConfig;
+use crate::output::OutputFormat;
+use crate::polygon::PolygonConfig;
+use crate::portfolio::PortfolioConfig;
+use crate::provider::ProviderKind;
+use crate::redis::{self, RedisConfig};
+use crate::sinks;
+use crate::tiingo::TiingoConfig;
+use crate::twelvedata::TwelveDataConfig;
+
+const ENV_PREFIX: &str = "FINNHUB_SCANNER_";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Finnhub API key
+    #[serde(default)]
     pub api_key: String,
+
+    /// File holding the API key, such as a Docker or Kubernetes secret; overrides `api_key`
+    pub api_key_file: Option<PathBuf>,
     
     /// Optional path to symbols file
     pub symbols_file: Option<PathBuf>,
+
+    /// Check symbols against Finnhub's exchange listings before fetching, dropping unknown ones
+    #[serde(default = "default_true")]
+    pub validate_symbols: bool,
     
-    /// Number of concurrent requests
+    /// Number of concurrent requests (the ceiling when adaptive concurrency is on)
     #[serde(default = "default_concurrent_requests")]
     pub concurrent_requests: usize,
+
+    /// Lower concurrency on HTTP 429 or slow responses and raise it again when healthy
+    #[serde(default = "default_true")]
+    pub adaptive_concurrency: bool,
+
+    /// Responses slower than this count as a sign of overload
+    #[serde(default = "default_slow_response")]
+    pub slow_response_ms: u64,
     
-    /// Rate limit delay in milliseconds
-    #[serde(default = "default_rate_limit_delay")]
-    pub rate_limit_delay_ms: u64,
+    /// API requests allowed per minute by your Finnhub plan
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+
+    /// Requests that may be sent back to back before pacing kicks in
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+
+    /// API calls a day your Finnhub plan allows, shown against today's calls by `usage`
+    pub daily_call_limit: Option<u64>,
+
+    /// Count API calls per endpoint in the data directory's `usage.ndjson`
+    #[serde(default = "default_true")]
+    pub track_usage: bool,
     
     /// Request timeout in seconds
     #[serde(default = "default_timeout")]
@@ -26,68 +73,604 @@ pub struct Config {
     /// Default output format
     #[serde(default)]
     pub default_output: String,
+
+    /// Expression applied by `scan` and `watch` when `--filter` is not given
+    pub filter: Option<String>,
+
+    /// How long fetched quotes are reused, in seconds (0 disables the cache)
+    #[serde(default = "default_cache_ttl")]
+    pub cache_ttl_secs: u64,
+
+    /// Directory for the on-disk cache
+    pub cache_dir: Option<PathBuf>,
+
+    /// Cache lifetimes for slow-changing endpoints
+    #[serde(default)]
+    pub cache_ttls: CacheTtls,
+
+    /// Retry policy for failed API requests
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    /// Connection settings for the API client
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Consecutive failed requests before the client stops trying for a while (0 disables)
+    #[serde(default = "default_circuit_threshold")]
+    pub circuit_breaker_threshold: u32,
+
+    /// How long requests are skipped once the circuit breaker trips
+    #[serde(default = "default_circuit_cooldown")]
+    pub circuit_breaker_cooldown_secs: u64,
+
+    /// Local history settings
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Watch mode settings
+    #[serde(default)]
+    pub watch: WatchConfig,
+
+    /// `serve` settings
+    #[serde(default)]
+    pub server: ServerConfig,
+
+    /// Databases and local streams quotes are written to, as with `--sink`
+    /// (`postgres://...`, `unix:///path`, `pipe:///path`)
+    #[serde(default)]
+    pub sinks: Vec<String>,
+
+    /// Write quote updates to InfluxDB
+    #[serde(default)]
+    pub influxdb: Option<InfluxConfig>,
+
+    /// Publish quote updates and alerts to an MQTT broker
+    #[serde(default)]
+    pub mqtt: Option<MqttConfig>,
+
+    /// Publish quote updates and alerts on Redis channels and keep the latest quotes in keys
+    #[serde(default)]
+    pub redis: Option<RedisConfig>,
+
+    /// Produce quote updates and alerts to Kafka topics
+    #[serde(default)]
+    pub kafka: Option<KafkaConfig>,
+
+    /// Providers tried in order when `--provider` (Finnhub by default) is rate limited or down,
+    /// e.g. `["yahoo", "alphavantage"]`
+    #[serde(default)]
+    pub fallback_providers: Vec<ProviderKind>,
+
+    /// Key and rate limit for `--provider alphavantage`
+    #[serde(default)]
+    pub alphavantage: AlphaVantageConfig,
+
+    /// Key and rate limit for `--provider polygon`
+    #[serde(default)]
+    pub polygon: PolygonConfig,
+
+    /// Key and rate limit for `--provider tiingo`
+    #[serde(default)]
+    pub tiingo: TiingoConfig,
+
+    /// Key and rate limit for `--provider twelvedata`
+    #[serde(default)]
+    pub twelvedata: TwelveDataConfig,
+
+    /// Alert rules and their actions
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+
+    /// Actions run when a scan completes
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Holdings for `portfolio`
+    #[serde(default)]
+    pub portfolio: PortfolioConfig,
+
+    /// Names that stand for whole argument lists, e.g. `mm = "scan -f momentum.txt --gainers-only"`
+    /// makes `finnhub-scanner mm` run that scan
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Symbol lists named in symbol input as `@<NAME>`, e.g. `semis = ["NVDA", "AMD", "AVGO"]`;
+    /// these take precedence over the built-in groups
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheTtls {
+    /// Company profiles
+    #[serde(default = "default_week_ttl")]
+    pub profile_secs: u64,
+
+    /// Peer lists
+    #[serde(default = "default_week_ttl")]
+    pub peers_secs: u64,
+
+    /// Exchange symbol universes
+    #[serde(default = "default_day_ttl")]
+    pub symbols_secs: u64,
+
+    /// Basic financial metrics
+    #[serde(default = "default_day_ttl")]
+    pub metrics_secs: u64,
+
+    /// Index constituents for `@sp500`-style symbol groups
+    #[serde(default = "default_week_ttl")]
+    pub constituents_secs: u64,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        Self {
+            profile_secs: default_week_ttl(),
+            peers_secs: default_week_ttl(),
+            symbols_secs: default_day_ttl(),
+            metrics_secs: default_day_ttl(),
+            constituents_secs: default_week_ttl(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// Idle connections kept open per host (reqwest keeps any number by default)
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// How long an idle pooled connection is kept before closing
+    #[serde(default = "default_pool_idle_timeout")]
+    pub pool_idle_timeout_secs: u64,
+
+    /// TCP keep-alive probe interval; unset leaves keep-alive off
+    pub tcp_keepalive_secs: Option<u64>,
+
+    /// Negotiate HTTP/2 when the server offers it; false forces HTTP/1.1
+    #[serde(default = "default_true")]
+    pub http2: bool,
+
+    /// Disable Nagle's algorithm so small requests go out immediately
+    #[serde(default = "default_true")]
+    pub tcp_nodelay: bool,
+
+    /// Proxy for all outbound requests (`http://`, `https://`, `socks5://` or `socks5h://`);
+    /// when unset, `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` apply
+    pub proxy: Option<String>,
+
+    /// Comma-separated hosts that bypass `proxy`
+    pub no_proxy: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_secs: default_pool_idle_timeout(),
+            tcp_keepalive_secs: None,
+            http2: true,
+            tcp_nodelay: true,
+            proxy: None,
+            no_proxy: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// The configured proxy, if any
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(&self) -> Result<Option<reqwest::Proxy>> {
+        let Some(url) = &self.proxy else {
+            return Ok(None);
+        };
+
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| ScannerError::Config(format!("Invalid proxy '{}': {}", url, e)))?;
+        Ok(Some(proxy.no_proxy(self.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string))))
+    }
+
+    /// Client builder with these connection settings and `timeout` applied
+    ///
+    /// On wasm32 requests go through the browser's `fetch`, which manages connections and
+    /// proxies itself and has no client-wide timeout, so callers also set `timeout` on each
+    /// request there.
+    pub fn client_builder(&self, timeout: Duration) -> Result<reqwest::ClientBuilder> {
+        let builder = reqwest::Client::builder();
+        #[cfg(target_arch = "wasm32")]
+        let _ = timeout;
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = {
+            let mut builder = builder
+                .timeout(timeout)
+                .pool_idle_timeout(Duration::from_secs(self.pool_idle_timeout_secs))
+                .tcp_keepalive(self.tcp_keepalive_secs.map(Duration::from_secs))
+                .tcp_nodelay(self.tcp_nodelay);
+            if let Some(max_idle) = self.pool_max_idle_per_host {
+                builder = builder.pool_max_idle_per_host(max_idle);
+            }
+            if !self.http2 {
+                builder = builder.http1_only();
+            }
+            if let Some(proxy) = self.proxy()? {
+                builder = builder.proxy(proxy);
+            }
+            builder
+        };
+        Ok(builder)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total attempts per request, including the first (1 disables retries)
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled for each one after it
+    #[serde(default = "default_base_delay")]
+    pub base_delay_ms: u64,
+
+    /// Upper bound on any single delay
+    #[serde(default = "default_max_delay")]
+    pub max_delay_ms: u64,
+
+    /// Randomize each delay between half and all of its length so parallel retries spread out
+    #[serde(default = "default_true")]
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            base_delay_ms: default_base_delay(),
+            max_delay_ms: default_max_delay(),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before retry number `retry` (0-based); `random` is in `[0, 1)`
+    pub fn delay(&self, retry: u32, random: f64) -> Duration {
+        let exponential = self.base_delay_ms.saturating_mul(2u64.saturating_pow(retry));
+        let capped = exponential.min(self.max_delay_ms);
+        let millis = if self.jitter {
+            capped / 2 + (capped as f64 / 2.0 * random) as u64
+        } else {
+            capped
+        };
+        Duration::from_millis(millis)
+    }
+}
+
+/// Whether a response status is worth retrying: rate limits and server errors
+pub fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Default tick log used by watch and the history commands
+    pub file: Option<PathBuf>,
+
+    /// Drop history records older than this many days
+    pub keep_days: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Refresh interval used when `--interval` is not given
+    #[serde(default = "default_watch_interval")]
+    pub interval_secs: u64,
+
+    /// Slow down polling while the exchange is closed
+    #[serde(default)]
+    pub market_hours: bool,
+
+    /// Exchange whose market status is checked
+    #[serde(default = "default_exchange")]
+    pub exchange: String,
+
+    /// Refresh interval while the market is closed (0 pauses quote fetches entirely)
+    #[serde(default = "default_closed_interval")]
+    pub closed_interval_secs: u64,
+
+    /// How often market status is re-checked
+    #[serde(default = "default_status_check")]
+    pub status_check_secs: u64,
+
+    /// Treat pre-market and post-market sessions as open
+    #[serde(default)]
+    pub extended_hours: bool,
+
+    /// Symbols refreshed on their own interval
+    #[serde(default)]
+    pub groups: Vec<SymbolGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Address `serve` listens on
+    #[serde(default = "default_bind")]
+    pub bind: String,
+
+    /// Seconds between refreshes of the served quotes
+    #[serde(default = "default_watch_interval")]
+    pub interval_secs: u64,
+
+    /// Address `grpc` listens on
+    #[serde(default = "default_grpc_bind")]
+    pub grpc_bind: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: default_bind(),
+            interval_secs: default_watch_interval(),
+            grpc_bind: default_grpc_bind(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolGroup {
+    pub symbols: Vec<String>,
+    pub interval_secs: u64,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_watch_interval(),
+            market_hours: false,
+            exchange: default_exchange(),
+            closed_interval_secs: default_closed_interval(),
+            status_check_secs: default_status_check(),
+            extended_hours: false,
+            groups: Vec::new(),
+        }
+    }
+}
+
+fn default_watch_interval() -> u64 {
+    60
+}
+
+fn default_bind() -> String {
+    String::from("127.0.0.1:8080")
+}
+
+fn default_grpc_bind() -> String {
+    String::from("127.0.0.1:50051")
+}
+
+fn default_exchange() -> String {
+    String::from("US")
+}
+
+fn default_closed_interval() -> u64 {
+    900
+}
+
+fn default_status_check() -> u64 {
+    300
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_base_delay() -> u64 {
+    250
+}
+
+fn default_max_delay() -> u64 {
+    5_000
+}
+
+fn default_circuit_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_cooldown() -> u64 {
+    30
+}
+
+fn default_pool_idle_timeout() -> u64 {
+    90
+}
+
+fn default_slow_response() -> u64 {
+    2_000
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn default_concurrent_requests() -> usize {
     5
 }
 
-fn default_rate_limit_delay() -> u64 {
-    200
+fn default_requests_per_minute() -> u32 {
+    60
+}
+
+fn default_rate_limit_burst() -> u32 {
+    5
 }
 
 fn default_timeout() -> u64 {
     10
 }
 
+fn default_cache_ttl() -> u64 {
+    30
+}
+
+fn default_day_ttl() -> u64 {
+    86_400
+}
+
+fn default_week_ttl() -> u64 {
+    7 * 86_400
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             api_key: String::from("YOUR_API_KEY_HERE"),
+            api_key_file: None,
             symbols_file: Some(PathBuf::from("symbols.txt")),
+            validate_symbols: true,
             concurrent_requests: default_concurrent_requests(),
-            rate_limit_delay_ms: default_rate_limit_delay(),
+            adaptive_concurrency: true,
+            slow_response_ms: default_slow_response(),
+            requests_per_minute: default_requests_per_minute(),
+            rate_limit_burst: default_rate_limit_burst(),
+            daily_call_limit: None,
+            track_usage: true,
             timeout_secs: default_timeout(),
             default_output: String::from("table"),
+            filter: None,
+            cache_ttl_secs: default_cache_ttl(),
+            cache_dir: None,
+            cache_ttls: CacheTtls::default(),
+            retry: RetryConfig::default(),
+            http: HttpConfig::default(),
+            circuit_breaker_threshold: default_circuit_threshold(),
+            circuit_breaker_cooldown_secs: default_circuit_cooldown(),
+            history: HistoryConfig::default(),
+            watch: WatchConfig::default(),
+            server: ServerConfig::default(),
+            sinks: Vec::new(),
+            influxdb: None,
+            mqtt: None,
+            redis: None,
+            kafka: None,
+            fallback_providers: Vec::new(),
+            alphavantage: AlphaVantageConfig::default(),
+            polygon: PolygonConfig::default(),
+            tiingo: TiingoConfig::default(),
+            twelvedata: TwelveDataConfig::default(),
+            alerts: AlertsConfig::default(),
+            notifications: NotificationsConfig::default(),
+            portfolio: PortfolioConfig::default(),
+            aliases: BTreeMap::new(),
+            groups: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
-    /// Load config from TOML file
-    pub fn from_file(path: &Path) -> Result<Self> {
+    /// Load config from TOML file, with `[profiles.<profile>]` laid over the top-level settings
+    /// and `FINNHUB_SCANNER_*` variables filling in what neither sets; `api_key` (e.g. read from
+    /// stdin) takes precedence over everything
+    pub fn from_file(path: &Path, api_key: Option<String>, profile: Option<&str>) -> Result<Self> {
+        let config = Self::read_file(path, api_key, profile)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Like `from_file`, but without validating the result; see `check`
+    pub fn read_file(path: &Path, api_key: Option<String>, profile: Option<&str>) -> Result<Self> {
         let content = fs::read_to_string(path)
             .map_err(|e| ScannerError::Config(format!("Failed to read config file: {}", e)))?;
         
-        let config: Config = toml::from_str(&content)
+        let table: toml::Table = toml::from_str(&content)
             .map_err(|e| ScannerError::Config(format!("Failed to parse config: {}", e)))?;
-        
-        config.validate()?;
-        Ok(config)
+
+        Self::from_table(table, api_key, profile, true)
+    }
+
+    /// The arguments `[aliases]` gives `name`, split the way a shell would split them
+    pub fn alias(&self, name: &str) -> Result<Option<Vec<String>>> {
+        let Some(expansion) = self.aliases.get(name) else {
+            return Ok(None);
+        };
+        match shlex::split(expansion) {
+            Some(args) if !args.is_empty() => Ok(Some(args)),
+            Some(_) => Err(ScannerError::Config(format!("aliases.{} is empty", name))),
+            None => Err(ScannerError::Config(format!("aliases.{} has an unclosed quote", name))),
+        }
+    }
+
+    /// Names of the `[profiles.<NAME>]` sections in a config file, for shell completion
+    pub fn profile_names(path: &Path) -> Result<Vec<String>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ScannerError::Config(format!("Failed to read config file: {}", e)))?;
+        let table: toml::Table = toml::from_str(&content)
+            .map_err(|e| ScannerError::Config(format!("Failed to parse config: {}", e)))?;
+
+        Ok(match table.get("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles.keys().cloned().collect(),
+            _ => Vec::new(),
+        })
     }
     
     /// Load config from environment variables or use defaults
-    pub fn from_env_or_default() -> Result<Self> {
-        let mut config = Config::default();
-        
-        // Check for API key in environment
-        if let Ok(key) = std::env::var("FINNHUB_API_KEY") {
-            config.api_key = key;
-        }
-        
-        // Check for symbols file in environment
-        if let Ok(file) = std::env::var("SYMBOLS_FILE") {
-            config.symbols_file = Some(PathBuf::from(file));
-        }
-        
+    pub fn from_env_or_default(api_key: Option<String>) -> Result<Self> {
+        let config = Self::read_env(api_key)?;
         config.validate()?;
         Ok(config)
     }
+
+    /// Like `from_env_or_default`, but without validating the result
+    pub fn read_env(api_key: Option<String>) -> Result<Self> {
+        Self::from_table(default_table()?, api_key, None, false)
+    }
+
+    /// Build the config from `table`; `from_file` says whether it was read from a config file,
+    /// whose settings environment variables don't override
+    fn from_table(
+        table: toml::Table,
+        api_key: Option<String>,
+        profile: Option<&str>,
+        from_file: bool,
+    ) -> Result<Self> {
+        let profile = profile
+            .map(str::to_string)
+            .or_else(|| std::env::var(format!("{}PROFILE", ENV_PREFIX)).ok());
+        let table = apply_profile(table, profile.as_deref())?;
+        let vars: Vec<(String, String)> = std::env::vars().collect();
+        let mut table = layer_env(table, &vars, from_file)?;
+        migrate_rate_limit_delay(&mut table)?;
+
+        let mut config: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| ScannerError::Config(format!("Failed to parse config: {}", e)))?;
+
+        warn_unknown_env(&config, &vars);
+        config.resolve_api_key(api_key)?;
+        Ok(config)
+    }
+
+    /// Apply an explicitly supplied key, or else read `api_key_file` if one is set
+    fn resolve_api_key(&mut self, api_key: Option<String>) -> Result<()> {
+        if let Some(key) = api_key {
+            self.api_key = key;
+        } else if let Some(path) = &self.api_key_file {
+            let file = fs::File::open(path).map_err(|e| {
+                ScannerError::Config(format!("Failed to read API key file {}: {}", path.display(), e))
+            })?;
+            self.api_key = read_api_key(file)?;
+        }
+        Ok(())
+    }
     
     /// Save config to TOML file
-    pub fn save_to_file(&self, path: &str) -> Result<()> {
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
         let content = toml::to_string_pretty(self)
             .map_err(|e| ScannerError::Config(format!("Failed to serialize config: {}", e)))?;
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| ScannerError::Config(format!("Failed to create config directory: {}", e)))?;
+        }
         
         fs::write(path, content)
             .map_err(|e| ScannerError::Config(format!("Failed to write config file: {}", e)))?;
@@ -95,11 +678,173 @@ impl Config {
         Ok(())
     }
     
+    /// Output format named by `default_output`
+    pub fn default_output(&self) -> Result<OutputFormat> {
+        if self.default_output.is_empty() {
+            return Ok(OutputFormat::default());
+        }
+        OutputFormat::from_name(&self.default_output)
+            .ok_or_else(|| ScannerError::Config(format!("Unknown default_output '{}'", self.default_output)))
+    }
+
+    /// Every problem found in the configuration, for `config validate`; unlike `validate`
+    /// this also looks at the symbols file, intervals, expressions and alert rules
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.api_key.is_empty() || self.api_key == "YOUR_API_KEY_HERE" {
+            problems.push(
+                "api_key is not set: add it, set api_key_file, or export FINNHUB_API_KEY".to_string(),
+            );
+        }
+        if self.concurrent_requests == 0 {
+            problems.push("concurrent_requests must be at least 1".to_string());
+        }
+        if self.requests_per_minute == 0 {
+            problems.push(
+                "requests_per_minute must be at least 1 (check your Finnhub plan's limit)".to_string(),
+            );
+        }
+        if self.alphavantage.requests_per_minute == 0 {
+            problems.push("alphavantage.requests_per_minute must be at least 1".to_string());
+        }
+        if self.polygon.requests_per_minute == 0 {
+            problems.push("polygon.requests_per_minute must be at least 1".to_string());
+        }
+        if self.tiingo.requests_per_minute == 0 {
+            problems.push("tiingo.requests_per_minute must be at least 1".to_string());
+        }
+        if self.twelvedata.requests_per_minute == 0 {
+            problems.push("twelvedata.requests_per_minute must be at least 1".to_string());
+        }
+        if self.daily_call_limit == Some(0) {
+            problems.push("daily_call_limit must be at least 1, or left out".to_string());
+        }
+        for name in self.aliases.keys() {
+            if let Err(e) = self.alias(name) {
+                problems.push(e.to_string());
+            }
+        }
+        if self.timeout_secs == 0 {
+            problems.push("timeout_secs must be at least 1".to_string());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = self.http.proxy() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.default_output() {
+            problems.push(format!("{} (expected table, json, csv, compact or ndjson)", e));
+        }
+        if let Some(filter) = &self.filter {
+            if let Err(e) = expr::parse(filter) {
+                problems.push(format!("filter '{}': {}", filter, e));
+            }
+        }
+
+        // Validating must not swallow symbols piped in for the run itself
+        if let Some(path) = self.symbols_file.as_deref().filter(|path| !is_stdin(path)) {
+            if let Err(e) = load_symbols_from_file(path) {
+                problems.push(format!(
+                    "symbols_file {}: {} (relative paths are resolved from the working directory)",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+
+        if self.retry.max_attempts == 0 {
+            problems.push("retry.max_attempts must be at least 1 (1 disables retries)".to_string());
+        }
+        if self.retry.base_delay_ms > self.retry.max_delay_ms {
+            problems.push("retry.base_delay_ms is larger than retry.max_delay_ms".to_string());
+        }
+
+        if self.watch.interval_secs == 0 {
+            problems.push("watch.interval_secs must be at least 1".to_string());
+        }
+        if self.watch.market_hours && self.watch.status_check_secs == 0 {
+            problems.push(
+                "watch.status_check_secs must be at least 1 when market_hours is on".to_string(),
+            );
+        }
+        for (i, group) in self.watch.groups.iter().enumerate() {
+            if group.symbols.is_empty() {
+                problems.push(format!("watch.groups[{}] has no symbols", i));
+            }
+            if group.interval_secs == 0 {
+                problems.push(format!("watch.groups[{}].interval_secs must be at least 1", i));
+            }
+        }
+
+        if self.server.interval_secs == 0 {
+            problems.push("server.interval_secs must be at least 1".to_string());
+        }
+        if self.server.bind.parse::<std::net::SocketAddr>().is_err() {
+            problems.push(format!(
+                "server.bind '{}' is not an address like 127.0.0.1:8080",
+                self.server.bind
+            ));
+        }
+        if self.server.grpc_bind.parse::<std::net::SocketAddr>().is_err() {
+            problems.push(format!(
+                "server.grpc_bind '{}' is not an address like 127.0.0.1:50051",
+                self.server.grpc_bind
+            ));
+        }
+
+        for url in &self.sinks {
+            if let Err(e) = sinks::check_url(url) {
+                problems.push(format!("sinks: {}", e));
+            }
+        }
+        if let Some(influx) = &self.influxdb {
+            if reqwest::Url::parse(&influx.url).is_err() {
+                problems.push(format!("influxdb.url '{}' is not a URL like http://localhost:8086", influx.url));
+            }
+            if influx.bucket.is_empty() {
+                problems.push("influxdb.bucket must be set".to_string());
+            }
+        }
+        if let Some(mqtt) = &self.mqtt {
+            if let Err(e) = mqtt.address() {
+                problems.push(e.to_string());
+            }
+            if mqtt.qos > 2 {
+                problems.push(format!("mqtt.qos must be 0, 1 or 2, not {}", mqtt.qos));
+            }
+        }
+        if let Some(redis) = &self.redis {
+            if !redis::URL_SCHEMES.iter().any(|scheme| redis.url.starts_with(scheme)) {
+                // The URL isn't echoed since it may hold the password
+                problems.push(format!("redis.url should start with {}", redis::URL_SCHEMES.join(", ")));
+            }
+        }
+        if let Some(kafka) = &self.kafka {
+            if kafka.brokers.trim().is_empty() {
+                problems.push("kafka.brokers must list at least one host:port".to_string());
+            }
+        }
+
+        if self.alerts.interval_secs == 0 {
+            problems.push("alerts.interval_secs must be at least 1".to_string());
+        }
+        for rule in &self.alerts.rules {
+            if let Err(e) = expr::parse_alert(&rule.condition) {
+                problems.push(format!("alert rule '{}': {}", rule.name, e));
+            }
+            if rule.actions.is_empty() {
+                problems.push(format!("alert rule '{}' has no actions", rule.name));
+            }
+        }
+
+        problems
+    }
+
     /// Validate configuration
     fn validate(&self) -> Result<()> {
         if self.api_key.is_empty() || self.api_key == "YOUR_API_KEY_HERE" {
             return Err(ScannerError::Config(
-                "API key not configured. Set FINNHUB_API_KEY environment variable or update config file".to_string()
+                "API key not configured. Set FINNHUB_API_KEY, api_key_file or --api-key-stdin, or update config file".to_string()
             ));
         }
         
@@ -108,15 +853,265 @@ impl Config {
                 "concurrent_requests must be greater than 0".to_string()
             ));
         }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.http.proxy()?;
         
         Ok(())
     }
 }
 
-/// Load symbols from a text file (one per line)
+/// `Config::default()` as a TOML table
+fn default_table() -> Result<toml::Table> {
+    match toml::Value::try_from(Config::default()) {
+        Ok(toml::Value::Table(table)) => Ok(table),
+        Ok(_) => Err(ScannerError::Config("Default config is not a table".to_string())),
+        Err(e) => Err(ScannerError::Config(format!("Failed to serialize config: {}", e))),
+    }
+}
+
+/// Environment variables laid over `table`, or under it when it was read from a config file, so an
+/// explicit file keeps the last word as it always had
+fn layer_env(table: toml::Table, vars: &[(String, String)], from_file: bool) -> Result<toml::Table> {
+    let defaults = default_table()?;
+    if !from_file {
+        let mut table = table;
+        apply_env(&mut table, &defaults, vars.iter().cloned());
+        return Ok(table);
+    }
+
+    let mut env = toml::Table::new();
+    apply_env(&mut env, &defaults, vars.iter().cloned());
+    // A key in the file isn't replaced by a key file from the environment either
+    if table.contains_key("api_key") {
+        env.remove("api_key_file");
+    }
+    merge_tables(&mut env, table);
+    Ok(env)
+}
+
+/// The `__`-separated config key an environment variable sets, if any
+fn env_key(name: &str) -> Option<String> {
+    let key = match name {
+        "FINNHUB_API_KEY" => "api_key",
+        "FINNHUB_API_KEY_FILE" => "api_key_file",
+        "SYMBOLS_FILE" => "symbols_file",
+        "ALPHAVANTAGE_API_KEY" => "alphavantage__api_key",
+        "POLYGON_API_KEY" => "polygon__api_key",
+        "TIINGO_API_KEY" => "tiingo__api_key",
+        "TWELVEDATA_API_KEY" => "twelvedata__api_key",
+        _ => {
+            return name
+                .strip_prefix(ENV_PREFIX)
+                .filter(|rest| !rest.is_empty() && *rest != "PROFILE")
+                .map(str::to_lowercase)
+        }
+    };
+    Some(key.to_string())
+}
+
+/// Warn about `FINNHUB_SCANNER_*` variables that don't name a setting, such as a misspelt one
+fn warn_unknown_env(config: &Config, vars: &[(String, String)]) {
+    let Ok(toml::Value::Table(known)) = toml::Value::try_from(config) else {
+        return;
+    };
+    for (name, _) in vars.iter().filter(|(name, _)| name.starts_with(ENV_PREFIX)) {
+        let Some(key) = env_key(name) else {
+            continue;
+        };
+        let path: Vec<&str> = key.split("__").collect();
+        if lookup(&known, &path).is_none() {
+            log::warn!("{} doesn't match any config setting and is ignored", name);
+        }
+    }
+}
+
+/// Override config values from `FINNHUB_SCANNER_<FIELD>` variables, with `__` separating nested
+/// keys (`FINNHUB_SCANNER_WATCH__INTERVAL_SECS`). Values are read as TOML, so numbers, booleans
+/// and arrays work, except for fields whose current or default value is a string. The older
+/// `FINNHUB_API_KEY`, `FINNHUB_API_KEY_FILE`, `SYMBOLS_FILE` and the other providers' key
+/// variables (`ALPHAVANTAGE_API_KEY`, `POLYGON_API_KEY`, `TIINGO_API_KEY`,
+/// `TWELVEDATA_API_KEY`) are applied first.
+fn apply_env(
+    table: &mut toml::Table,
+    defaults: &toml::Table,
+    vars: impl IntoIterator<Item = (String, String)>,
+) {
+    let mut vars: Vec<(String, String)> = vars.into_iter().collect();
+    vars.sort_by_key(|(name, _)| name.starts_with(ENV_PREFIX));
+
+    for (name, raw) in vars {
+        let Some(key) = env_key(&name) else {
+            continue;
+        };
+
+        let path: Vec<&str> = key.split("__").collect();
+        let string_field = matches!(
+            lookup(table, &path).or_else(|| lookup(defaults, &path)),
+            Some(toml::Value::String(_))
+        );
+        let value = if string_field {
+            toml::Value::String(raw)
+        } else {
+            format!("value = {}", raw)
+                .parse::<toml::Table>()
+                .ok()
+                .and_then(|mut parsed| parsed.remove("value"))
+                .unwrap_or(toml::Value::String(raw))
+        };
+
+        log::debug!("{} overrides {}", name, path.join("."));
+        insert_path(table, &path, value);
+    }
+}
+
+/// Turn the old `rate_limit_delay_ms`, a pause after each batch of `concurrent_requests`, into the
+/// `requests_per_minute` it allowed. An explicit `requests_per_minute` wins.
+fn migrate_rate_limit_delay(table: &mut toml::Table) -> Result<()> {
+    let Some(delay) = table.remove("rate_limit_delay_ms") else {
+        return Ok(());
+    };
+    let delay = delay.as_integer().filter(|delay| *delay >= 0).ok_or_else(|| {
+        ScannerError::Config("rate_limit_delay_ms must be a whole number of milliseconds".to_string())
+    })?;
+    if table.contains_key("requests_per_minute") {
+        log::warn!("rate_limit_delay_ms is deprecated and ignored, since requests_per_minute is set");
+        return Ok(());
+    }
+
+    let batch = table
+        .get("concurrent_requests")
+        .and_then(toml::Value::as_integer)
+        .unwrap_or(default_concurrent_requests() as i64)
+        .max(1);
+    let per_minute = (batch.saturating_mul(60_000) / delay.max(1)).clamp(1, u32::MAX as i64);
+    log::warn!(
+        "rate_limit_delay_ms is deprecated; pacing requests at requests_per_minute = {} instead",
+        per_minute
+    );
+    table.insert("requests_per_minute".to_string(), toml::Value::Integer(per_minute));
+    Ok(())
+}
+
+fn lookup<'a>(table: &'a toml::Table, path: &[&str]) -> Option<&'a toml::Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+fn insert_path(table: &mut toml::Table, path: &[&str], value: toml::Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for key in parents {
+        let entry = table
+            .entry(key.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !entry.is_table() {
+            *entry = toml::Value::Table(toml::Table::new());
+        }
+        table = entry.as_table_mut().expect("entry was just made a table");
+    }
+    table.insert(last.to_string(), value);
+}
+
+/// Remove the `profiles` table and merge the selected profile, if any, into the rest
+fn apply_profile(mut table: toml::Table, profile: Option<&str>) -> Result<toml::Table> {
+    let mut profiles = match table.remove("profiles") {
+        Some(toml::Value::Table(profiles)) => profiles,
+        Some(_) => return Err(ScannerError::Config("profiles must be a table".to_string())),
+        None => toml::Table::new(),
+    };
+
+    let Some(name) = profile else {
+        return Ok(table);
+    };
+
+    match profiles.remove(name) {
+        Some(toml::Value::Table(overrides)) => {
+            merge_tables(&mut table, overrides);
+            Ok(table)
+        }
+        Some(_) => Err(ScannerError::Config(format!("Profile '{}' must be a table", name))),
+        None => {
+            let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+            Err(ScannerError::Config(format!(
+                "Unknown profile '{}' (available: {})",
+                name,
+                if available.is_empty() { "none".to_string() } else { available.join(", ") }
+            )))
+        }
+    }
+}
+
+/// Nested tables are merged key by key; any other value in `overrides` replaces the base one
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge_tables(base, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Read an API key from a secrets file or stdin, ignoring surrounding whitespace
+pub fn read_api_key(mut reader: impl Read) -> Result<String> {
+    let mut key = String::new();
+    reader
+        .read_to_string(&mut key)
+        .map_err(|e| ScannerError::Config(format!("Failed to read API key: {}", e)))?;
+
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(ScannerError::Config("API key input is empty".to_string()));
+    }
+    Ok(key.to_string())
+}
+
+/// `--symbols-file` value that reads the list from stdin
+pub const STDIN_PATH: &str = "-";
+
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIN_PATH)
+}
+
+/// Whether stdin is a pipe or redirected file, rather than a terminal or `/dev/null` as under
+/// cron and systemd
+pub fn stdin_is_piped() -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        fs::metadata("/dev/stdin").is_ok_and(|meta| meta.file_type().is_fifo() || meta.is_file())
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::IsTerminal;
+        !std::io::stdin().is_terminal()
+    }
+}
+
+/// Load symbols from a text file (one per line), or from stdin for `-`
 pub fn load_symbols_from_file(path: &Path) -> Result<Vec<String>> {
-    let content = fs::read_to_string(path)
+    if is_stdin(path) {
+        return read_symbols(std::io::stdin().lock());
+    }
+    let file = fs::File::open(path)
         .map_err(|e| ScannerError::Io(format!("Failed to read symbols file: {}", e)))?;
+    read_symbols(file)
+}
+
+/// Read symbols one per line, skipping blank lines and `#` comments
+pub fn read_symbols(mut reader: impl Read) -> Result<Vec<String>> {
+    let mut content = String::new();
+    reader
+        .read_to_string(&mut content)
+        .map_err(|e| ScannerError::Io(format!("Failed to read symbols: {}", e)))?;
     
     let symbols: Vec<String> = content
         .lines()
@@ -142,7 +1137,251 @@ mod tests {
     fn test_default_config() {
         let config = Config::default();
         assert_eq!(config.concurrent_requests, 5);
-        assert_eq!(config.rate_limit_delay_ms, 200);
+        assert_eq!(config.requests_per_minute, 60);
+    }
+
+    #[test]
+    fn test_http_config_defaults() {
+        let config: Config = toml::from_str("api_key = \"key\"\n[http]\npool_max_idle_per_host = 16\n").unwrap();
+        assert_eq!(config.http.pool_max_idle_per_host, Some(16));
+        assert_eq!(config.http.pool_idle_timeout_secs, 90);
+        assert!(config.http.http2);
+        assert!(config.http.tcp_nodelay);
+    }
+
+    #[test]
+    fn test_proxy_config() {
+        let mut http = HttpConfig::default();
+        assert!(http.proxy().unwrap().is_none());
+
+        http.proxy = Some("socks5h://127.0.0.1:1080".to_string());
+        http.no_proxy = Some("localhost,.internal".to_string());
+        assert!(http.proxy().unwrap().is_some());
+
+        http.proxy = Some("not a url".to_string());
+        assert!(http.proxy().is_err());
+    }
+
+    #[test]
+    fn test_api_key_file() {
+        let mut key_file = NamedTempFile::new().unwrap();
+        writeln!(key_file, "  secret-key  ").unwrap();
+
+        let mut config_file = NamedTempFile::new().unwrap();
+        writeln!(config_file, "api_key_file = {:?}", key_file.path()).unwrap();
+
+        let config = Config::from_file(config_file.path(), None, None).unwrap();
+        assert_eq!(config.api_key, "secret-key");
+
+        // A key given on stdin wins over the file
+        let config = Config::from_file(config_file.path(), Some("from-stdin".to_string()), None).unwrap();
+        assert_eq!(config.api_key, "from-stdin");
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+api_key = "key"
+symbols_file = "us.txt"
+
+[watch]
+exchange = "US"
+
+[profiles.crypto]
+symbols_file = "crypto.txt"
+filter = "change_pct > 1"
+
+[profiles.crypto.watch]
+interval_secs = 10
+"#
+        )
+        .unwrap();
+
+        let base = Config::from_file(file.path(), None, None).unwrap();
+        assert_eq!(base.symbols_file, Some(PathBuf::from("us.txt")));
+        assert_eq!(base.watch.interval_secs, 60);
+
+        let crypto = Config::from_file(file.path(), None, Some("crypto")).unwrap();
+        assert_eq!(crypto.symbols_file, Some(PathBuf::from("crypto.txt")));
+        assert_eq!(crypto.filter.as_deref(), Some("change_pct > 1"));
+        assert_eq!(crypto.watch.interval_secs, 10);
+        assert_eq!(crypto.watch.exchange, "US");
+
+        let err = Config::from_file(file.path(), None, Some("fx")).unwrap_err().to_string();
+        assert!(err.contains("available: crypto"));
+        assert_eq!(Config::profile_names(file.path()).unwrap(), ["crypto"]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let config: Config = toml::from_str(
+            r#"
+[aliases]
+mm = "scan -f momentum.txt --filter 'change_pct > 2' --sort-by-change"
+broken = "scan --filter 'price > 1"
+"#,
+        )
+        .unwrap();
+
+        let args = config.alias("mm").unwrap().unwrap();
+        assert_eq!(args, ["scan", "-f", "momentum.txt", "--filter", "change_pct > 2", "--sort-by-change"]);
+        assert!(config.alias("scan").unwrap().is_none());
+        assert!(config.alias("broken").is_err());
+        assert!(config.check().iter().any(|problem| problem.contains("aliases.broken")));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut table: toml::Table = toml::from_str("symbols_file = \"us.txt\"\n[watch]\nexchange = \"US\"\n").unwrap();
+        let vars = [
+            ("FINNHUB_SCANNER_CONCURRENT_REQUESTS", "12"),
+            ("FINNHUB_SCANNER_WATCH__INTERVAL_SECS", "15"),
+            ("FINNHUB_SCANNER_API_KEY", "12345"),
+            ("FINNHUB_API_KEY", "legacy"),
+            ("SYMBOLS_FILE", "crypto.txt"),
+            ("FINNHUB_SCANNER_HTTP__HTTP2", "false"),
+            ("ALPHAVANTAGE_API_KEY", "98765"),
+            ("UNRELATED", "1"),
+        ];
+        apply_env(
+            &mut table,
+            &default_table().unwrap(),
+            vars.iter().map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+
+        let config: Config = toml::Value::Table(table).try_into().unwrap();
+        assert_eq!(config.concurrent_requests, 12);
+        assert_eq!(config.watch.interval_secs, 15);
+        assert_eq!(config.watch.exchange, "US");
+        // Numeric-looking keys stay strings, and the prefixed name beats the old one
+        assert_eq!(config.api_key, "12345");
+        assert_eq!(config.symbols_file, Some(PathBuf::from("crypto.txt")));
+        assert!(!config.http.http2);
+        assert_eq!(config.alphavantage.api_key, "98765");
+    }
+
+    #[test]
+    fn test_config_file_wins_over_env() {
+        let file: toml::Table =
+            toml::from_str("api_key = \"from-file\"\n[watch]\nexchange = \"US\"\n").unwrap();
+        let vars: Vec<(String, String)> = [
+            ("FINNHUB_API_KEY", "from-env"),
+            ("FINNHUB_API_KEY_FILE", "/run/secrets/finnhub"),
+            ("FINNHUB_SCANNER_WATCH__EXCHANGE", "TO"),
+            ("FINNHUB_SCANNER_WATCH__INTERVAL_SECS", "15"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let layered = |from_file: bool| -> Config {
+            toml::Value::Table(layer_env(file.clone(), &vars, from_file).unwrap()).try_into().unwrap()
+        };
+
+        let config = layered(true);
+        assert_eq!(config.api_key, "from-file");
+        assert_eq!(config.api_key_file, None);
+        assert_eq!(config.watch.exchange, "US");
+        assert_eq!(config.watch.interval_secs, 15);
+
+        let config = layered(false);
+        assert_eq!(config.api_key, "from-env");
+        assert_eq!(config.watch.exchange, "TO");
+    }
+
+    #[test]
+    fn test_env_keys() {
+        assert_eq!(env_key("FINNHUB_API_KEY").as_deref(), Some("api_key"));
+        assert_eq!(
+            env_key("FINNHUB_SCANNER_WATCH__INTERVAL_SECS").as_deref(),
+            Some("watch__interval_secs")
+        );
+        assert_eq!(env_key("FINNHUB_SCANNER_PROFILE"), None);
+        assert_eq!(env_key("HOME"), None);
+    }
+
+    #[test]
+    fn test_rate_limit_delay_alias() {
+        let migrate = |text: &str| {
+            let mut table: toml::Table = toml::from_str(text).unwrap();
+            migrate_rate_limit_delay(&mut table).map(|_| table)
+        };
+
+        let config: Config = toml::Value::Table(migrate("rate_limit_delay_ms = 200").unwrap()).try_into().unwrap();
+        assert_eq!(config.requests_per_minute, 1500);
+        let table = migrate("rate_limit_delay_ms = 1000\nconcurrent_requests = 2").unwrap();
+        assert_eq!(table["requests_per_minute"].as_integer(), Some(120));
+        assert!(!table.contains_key("rate_limit_delay_ms"));
+
+        let table = migrate("rate_limit_delay_ms = 200\nrequests_per_minute = 30").unwrap();
+        assert_eq!(table["requests_per_minute"].as_integer(), Some(30));
+        assert!(migrate("rate_limit_delay_ms = \"fast\"").is_err());
+    }
+
+    #[test]
+    fn test_check_reports_every_problem() {
+        let config: Config = toml::from_str(
+            r#"
+api_key = "key"
+symbols_file = "/nonexistent/symbols.txt"
+default_output = "xml"
+filter = "price >"
+
+[watch]
+interval_secs = 0
+
+[[alerts.rules]]
+name = "broken"
+condition = "price >> 1"
+actions = [{ type = "log" }]
+"#,
+        )
+        .unwrap();
+
+        let problems = config.check();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert!(problems.iter().any(|p| p.starts_with("symbols_file /nonexistent/symbols.txt")));
+        assert!(problems.iter().any(|p| p.contains("default_output 'xml'")));
+        assert!(problems.iter().any(|p| p.contains("watch.interval_secs")));
+        assert!(problems.iter().any(|p| p.starts_with("alert rule 'broken'")));
+
+        let valid = Config {
+            api_key: "key".to_string(),
+            symbols_file: None,
+            ..Config::default()
+        };
+        assert!(valid.check().is_empty());
+    }
+
+    #[test]
+    fn test_read_api_key_rejects_empty() {
+        assert_eq!(read_api_key("abc123\n".as_bytes()).unwrap(), "abc123");
+        assert!(read_api_key("\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let retry = RetryConfig {
+            jitter: false,
+            ..RetryConfig::default()
+        };
+        assert_eq!(retry.delay(0, 0.0), Duration::from_millis(250));
+        assert_eq!(retry.delay(2, 0.0), Duration::from_millis(1000));
+        assert_eq!(retry.delay(10, 0.0), Duration::from_millis(5000));
+
+        let jittered = RetryConfig::default();
+        assert_eq!(jittered.delay(1, 0.0), Duration::from_millis(250));
+        assert_eq!(jittered.delay(1, 0.5), Duration::from_millis(375));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(reqwest::StatusCode::NOT_FOUND));
     }
 
     #[test]
@@ -163,5 +1402,8 @@ mod tests {
         let file = NamedTempFile::new().unwrap();
         let result = load_symbols_from_file(file.path());
         assert!(result.is_err());
+
+        assert!(matches!(read_symbols("# none\n\n".as_bytes()), Err(ScannerError::NoSymbols)));
+        assert_eq!(read_symbols("spy\r\nqqq\r\n".as_bytes()).unwrap(), vec!["SPY", "QQQ"]);
     }
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+/// Decimal places kept for percentages computed from prices
+pub const PCT_PLACES: u32 = 4;
+
+// Quotes keep `f64` fields either way. With the feature, computed values are worked out from the
+// prices' shortest decimal form and rounded half away from zero, so binary rounding noise
+// doesn't leak into CSV and JSON.
+#[cfg(feature = "decimal")]
+mod imp {
+    use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+    use rust_decimal::{Decimal, RoundingStrategy};
+    use std::str::FromStr;
+
+    /// `value` as written in the API response, e.g. exactly 271.01 rather than 271.00999…
+    pub fn to_decimal(value: f64) -> Decimal {
+        Decimal::from_str(&value.to_string())
+            .ok()
+            .or_else(|| Decimal::from_f64(value))
+            .unwrap_or_default()
+    }
+
+    pub fn round(value: Decimal, places: u32) -> Decimal {
+        value.round_dp_with_strategy(places, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    pub fn change_pct(price: f64, prev_close: f64) -> f64 {
+        let prev_close = to_decimal(prev_close);
+        if prev_close.is_zero() {
+            return 0.0;
+        }
+        let pct = (to_decimal(price) - prev_close) / prev_close * Decimal::ONE_HUNDRED;
+        round(pct, super::PCT_PLACES).to_f64().unwrap_or(0.0)
+    }
+
+    pub fn average(values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        let sum: Decimal = values.iter().map(|value| to_decimal(*value)).sum();
+        round(sum / Decimal::from(values.len()), super::PCT_PLACES)
+            .to_f64()
+            .unwrap_or(0.0)
+    }
+
+    pub fn format_fixed(value: f64, places: u32) -> String {
+        format!("{:.*}", places as usize, round(to_decimal(value), places))
+    }
+}
+
+#[cfg(not(feature = "decimal"))]
+mod imp {
+    pub fn change_pct(price: f64, prev_close: f64) -> f64 {
+        if prev_close != 0.0 {
+            ((price - prev_close) / prev_close) * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn average(values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+
+    pub fn format_fixed(value: f64, places: u32) -> String {
+        format!("{:.*}", places as usize, value)
+    }
+}
+
+#[cfg(feature = "decimal")]
+pub use imp::to_decimal;
+
+/// Percentage change from `prev_close` to `price`; 0 when there's no previous close
+pub fn change_pct(price: f64, prev_close: f64) -> f64 {
+    imp::change_pct(price, prev_close)
+}
+
+/// Mean of `values`, 0 for none
+pub fn average(values: &[f64]) -> f64 {
+    imp::average(values)
+}
+
+/// `value` with exactly `places` decimals, as written to CSV
+pub fn format_fixed(value: f64, places: u32) -> String {
+    imp::format_fixed(value, places)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_pct() {
+        assert_eq!(change_pct(150.0, 100.0), 50.0);
+        assert_eq!(change_pct(150.0, 0.0), 0.0);
+        assert_eq!(average(&[1.0, 2.0, 3.0]), 2.0);
+        assert_eq!(average(&[]), 0.0);
+        assert_eq!(format_fixed(271.01, 2), "271.01");
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal_rounding() {
+        // 2.675 is 2.67499999… as an f64, which `{:.2}` rounds down
+        assert_eq!(format_fixed(2.675, 2), "2.68");
+        assert_eq!(format_fixed(-2.675, 2), "-2.68");
+        assert_eq!(change_pct(271.86, 271.01), 0.3136);
+        assert_eq!(average(&[0.1, 0.2]), 0.15);
+        assert_eq!(to_decimal(0.1).to_string(), "0.1");
+    }
+
+    #[cfg(not(feature = "decimal"))]
+    #[test]
+    fn test_float_rounding() {
+        assert_eq!(format_fixed(2.675, 2), "2.67");
+        assert!((change_pct(271.86, 271.01) - 0.3136).abs() < 1e-4);
+        assert_ne!(change_pct(271.86, 271.01), 0.3136);
+    }
+}
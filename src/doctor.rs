@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::Path;
+use crate::cache;
+use crate::config::Config;
+use crate::finnhub::{FinnhubClient, Probe};
+
+/// Symbol requested to test the network and API key
+const PROBE_SYMBOL: &str = "AAPL";
+
+/// One line of the checklist
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Run every check, in checklist order
+pub async fn run(config: &Config, config_path: Option<&Path>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    checks.push(match config_path {
+        Some(path) => Check::pass("Config file", path.display().to_string()),
+        None => Check::pass("Config file", "none found, using environment and defaults"),
+    });
+
+    let problems = config.check();
+    if problems.is_empty() {
+        checks.push(Check::pass("Config", "no problems found"));
+    }
+    for problem in problems {
+        checks.push(Check::fail("Config", problem));
+    }
+
+    let cache_dir = config.cache_dir.clone().unwrap_or_else(cache::default_cache_dir);
+    checks.push(match check_writable(&cache_dir) {
+        Ok(()) => Check::pass("Cache directory", format!("{} is writable", cache_dir.display())),
+        Err(e) => Check::fail("Cache directory", format!("{}: {}", cache_dir.display(), e)),
+    });
+
+    if config.api_key.is_empty() || config.api_key == "YOUR_API_KEY_HERE" {
+        checks.push(Check::fail("API key", "not configured, skipping network checks"));
+        return checks;
+    }
+
+    let client = match FinnhubClient::from_config(config) {
+        Ok(client) => client,
+        Err(e) => {
+            checks.push(Check::fail("Network", e.to_string()));
+            return checks;
+        }
+    };
+    match client.probe(PROBE_SYMBOL).await {
+        Ok(probe) => {
+            checks.push(Check::pass(
+                "Network",
+                format!("finnhub.io answered in {} ms", probe.elapsed.as_millis()),
+            ));
+            checks.extend(api_checks(&probe));
+        }
+        Err(e) => {
+            let hint = if config.http.proxy.is_some() {
+                " (check http.proxy)"
+            } else {
+                ""
+            };
+            checks.push(Check::fail("Network", format!("{}{}", e, hint)));
+        }
+    }
+
+    checks
+}
+
+/// Print the checklist and report whether everything passed
+pub fn print(checks: &[Check]) -> bool {
+    for check in checks {
+        let mark = if check.passed { "✓" } else { "✗" };
+        println!("{} {}: {}", mark, check.name, check.detail);
+    }
+    checks.iter().all(|check| check.passed)
+}
+
+/// What the probe response says about the key and the rate limit
+fn api_checks(probe: &Probe) -> Vec<Check> {
+    use reqwest::StatusCode;
+
+    let status = probe.status;
+    let key = if status.is_success() || status == StatusCode::TOO_MANY_REQUESTS {
+        Check::pass("API key", "accepted")
+    } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        Check::fail("API key", format!("rejected with HTTP {}", status))
+    } else {
+        Check::fail("API key", format!("could not be checked: HTTP {}", status))
+    };
+
+    let rate = match (probe.remaining, probe.limit) {
+        _ if status == StatusCode::TOO_MANY_REQUESTS => Check::fail(
+            "Rate limit",
+            format!("exhausted{}", reset_hint(probe.reset)),
+        ),
+        (Some(remaining), Some(limit)) => Check::pass(
+            "Rate limit",
+            format!("{} of {} requests left{}", remaining, limit, reset_hint(probe.reset)),
+        ),
+        _ => Check::pass("Rate limit", "not reported by the API"),
+    };
+
+    vec![key, rate]
+}
+
+fn reset_hint(reset: Option<i64>) -> String {
+    let now = chrono::Utc::now().timestamp();
+    match reset {
+        Some(reset) if reset > now => format!(", resets in {}s", reset - now),
+        _ => String::new(),
+    }
+}
+
+/// Create `dir` if needed and prove a file can be written in it
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(".doctor-write-test");
+    fs::write(&probe, b"ok")?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn probe(status: u16, remaining: Option<u32>, limit: Option<u32>) -> Probe {
+        Probe {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            elapsed: Duration::from_millis(120),
+            limit,
+            remaining,
+            reset: None,
+        }
+    }
+
+    #[test]
+    fn test_api_checks() {
+        let checks = api_checks(&probe(200, Some(58), Some(60)));
+        assert!(checks.iter().all(|check| check.passed));
+        assert_eq!(checks[1].detail, "58 of 60 requests left");
+
+        let checks = api_checks(&probe(401, None, None));
+        assert!(!checks[0].passed);
+
+        // Throttled means the key itself was fine
+        let checks = api_checks(&probe(429, Some(0), Some(60)));
+        assert!(checks[0].passed);
+        assert!(!checks[1].passed);
+
+        let checks = api_checks(&probe(200, None, None));
+        assert_eq!(checks[1].detail, "not reported by the API");
+    }
+
+    #[test]
+    fn test_check_writable() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("cache").join("finnhub-scanner");
+        assert!(check_writable(&nested).is_ok());
+        assert!(nested.is_dir());
+        assert_eq!(fs::read_dir(&nested).unwrap().count(), 0);
+    }
+}
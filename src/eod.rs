@@ -0,0 +1,227 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Candles, Quote, StockQuote};
+use crate::provider::{ProviderFuture, QuoteProvider};
+
+/// Serves quotes and daily candles from end-of-day CSV files, without network access
+///
+/// A symbol's file is `<SYMBOL>.csv` or, as in Stooq's downloads, `<symbol>.us.txt` or
+/// `<symbol>.txt`. Columns are found by their header, so both Stooq's
+/// `Date,Open,High,Low,Close,Volume` and its bulk `<TICKER>,<PER>,<DATE>,...,<VOL>` layouts work,
+/// with dates as `2026-10-14` or `20261014`. A quote is the last day's bar, up to `as_of` when
+/// given, with the day before's close as the previous close.
+pub struct EodCsvProvider {
+    dir: PathBuf,
+    as_of: Option<NaiveDate>,
+}
+
+/// One day's prices
+#[derive(Debug, Clone, PartialEq)]
+struct Bar {
+    date: NaiveDate,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl EodCsvProvider {
+    pub fn new(dir: &Path, as_of: Option<NaiveDate>) -> Result<Self> {
+        if !dir.is_dir() {
+            return Err(ScannerError::InvalidInput(format!(
+                "CSV directory {} does not exist",
+                dir.display()
+            )));
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            as_of,
+        })
+    }
+
+    /// The symbol's bars in date order, up to `as_of`
+    fn bars(&self, symbol: &str) -> Result<Vec<Bar>> {
+        let lower = symbol.to_lowercase();
+        let path = [
+            format!("{}.csv", symbol),
+            format!("{}.csv", lower),
+            format!("{}.us.txt", lower),
+            format!("{}.txt", lower),
+        ]
+        .into_iter()
+        .map(|name| self.dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+
+        let text = fs::read_to_string(&path)
+            .map_err(|e| ScannerError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+        let mut bars = parse_csv(&text).map_err(|e| ScannerError::Parse(format!("{}: {}", path.display(), e)))?;
+        bars.sort_by_key(|bar| bar.date);
+        if let Some(as_of) = self.as_of {
+            bars.retain(|bar| bar.date <= as_of);
+        }
+        Ok(bars)
+    }
+}
+
+impl QuoteProvider for EodCsvProvider {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            let bars = self.bars(symbol)?;
+            let (last, earlier) = bars
+                .split_last()
+                .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+            let quote = Quote {
+                c: last.close,
+                pc: earlier.last().map_or(last.open, |bar| bar.close),
+                h: last.high,
+                l: last.low,
+                o: last.open,
+            };
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        Box::pin(async move {
+            if resolution != "D" {
+                return Err(ScannerError::InvalidInput(format!(
+                    "--provider csv only has daily candles (D), not '{}'",
+                    resolution
+                )));
+            }
+
+            let mut candles = Candles {
+                s: "ok".to_string(),
+                ..Candles::default()
+            };
+            // What-if runs chart the days before `as_of` rather than before today
+            let shift = self
+                .as_of
+                .map(|as_of| to - as_of.and_time(NaiveTime::MIN).and_utc().timestamp() - 86_400)
+                .unwrap_or(0);
+            for bar in self.bars(symbol)? {
+                let time = bar.date.and_time(NaiveTime::MIN).and_utc().timestamp();
+                if time < from - shift || time > to - shift {
+                    continue;
+                }
+                candles.t.push(time);
+                candles.o.push(bar.open);
+                candles.h.push(bar.high);
+                candles.l.push(bar.low);
+                candles.c.push(bar.close);
+                candles.v.push(bar.volume);
+            }
+
+            if candles.c.is_empty() {
+                return Err(ScannerError::NoCandleData(symbol.to_string()));
+            }
+            Ok(candles)
+        })
+    }
+}
+
+fn parse_csv(text: &str) -> std::result::Result<Vec<Bar>, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or("empty file")?
+        .split(',')
+        .map(|name| name.trim().trim_matches(|c| c == '<' || c == '>').to_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|name| names.contains(&name.as_str()))
+            .ok_or_else(|| format!("no {} column", names[0]))
+    };
+    let (date, open, high, low, close) = (
+        column(&["date"])?,
+        column(&["open"])?,
+        column(&["high"])?,
+        column(&["low"])?,
+        column(&["close"])?,
+    );
+    let volume = column(&["volume", "vol"]).ok();
+
+    lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+            let number = |index: usize| {
+                field(index)
+                    .parse::<f64>()
+                    .map_err(|_| format!("line {}: invalid number '{}'", idx + 2, field(index)))
+            };
+            let date = NaiveDate::parse_from_str(field(date), "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(field(date), "%Y%m%d"))
+                .map_err(|_| format!("line {}: invalid date '{}'", idx + 2, field(date)))?;
+            Ok(Bar {
+                date,
+                open: number(open)?,
+                high: number(high)?,
+                low: number(low)?,
+                close: number(close)?,
+                volume: volume.map(number).transpose()?.unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const DAILY: &str = "Date,Open,High,Low,Close,Volume\n\
+        2026-10-12,100,102,99,101,1000\n\
+        2026-10-14,104,106,103,105,3000\n\
+        2026-10-13,101,104,100,103,2000\n";
+
+    #[test]
+    fn test_parse_stooq_layouts() {
+        let bars = parse_csv(DAILY).unwrap();
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[0].close, 101.0);
+
+        let bulk = "<TICKER>,<PER>,<DATE>,<TIME>,<OPEN>,<HIGH>,<LOW>,<CLOSE>,<VOL>,<OPENINT>\n\
+            AAPL.US,D,20261014,000000,104,106,103,105,3000,0\n";
+        let bars = parse_csv(bulk).unwrap();
+        assert_eq!(bars[0].date, NaiveDate::from_ymd_opt(2026, 10, 14).unwrap());
+        assert_eq!(bars[0].volume, 3000.0);
+
+        assert!(parse_csv("Date,Close\n2026-10-14,1\n").unwrap_err().contains("open"));
+    }
+
+    #[tokio::test]
+    async fn test_quote_as_of() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("aapl.us.txt"), DAILY).unwrap();
+
+        let latest = EodCsvProvider::new(dir.path(), None).unwrap();
+        let quote = latest.fetch_quote("AAPL").await.unwrap();
+        assert_eq!(quote.price, 105.0);
+        assert_eq!(quote.prev_close, 103.0);
+
+        let what_if = EodCsvProvider::new(dir.path(), NaiveDate::from_ymd_opt(2026, 10, 13)).unwrap();
+        let quote = what_if.fetch_quote("AAPL").await.unwrap();
+        assert_eq!(quote.price, 103.0);
+        assert_eq!(quote.prev_close, 101.0);
+
+        assert!(matches!(latest.fetch_quote("MSFT").await, Err(ScannerError::SymbolNotFound(_))));
+    }
+}
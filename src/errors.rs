@@ -1,73 +1,266 @@
-use std::fmt;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 pub type Result<T> = std::result::Result<T, ScannerError>;
 
-#[derive(Debug)]
+/// Exit status when a command ran cleanly, and when `scan` found matches
+pub const EXIT_OK: u8 = 0;
+
+/// `scan` ran, but no quote matched the filters; `verify` found prices that disagree
+pub const EXIT_NO_MATCHES: u8 = 1;
+
+/// `scan` ran, but some symbols couldn't be fetched; `verify` is missing some prices
+pub const EXIT_PARTIAL: u8 = 2;
+
+/// Bad command line, config or input; also what clap's usage errors exit with here
+pub const EXIT_USAGE: u8 = 3;
+
+/// Exit status for a scan that fetched all but `failed` symbols and kept `matched` quotes
+pub fn scan_exit_code(matched: usize, failed: usize) -> u8 {
+    if failed > 0 {
+        EXIT_PARTIAL
+    } else if matched == 0 {
+        EXIT_NO_MATCHES
+    } else {
+        EXIT_OK
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
 pub enum ScannerError {
     /// Configuration errors
+    #[error("Configuration error: {0}")]
     Config(String),
-    
-    /// API errors
+
+    /// API errors without a more specific variant below
+    #[error("API error: {0}")]
     Api(String),
-    
+
+    /// An error status from the API other than 401 and 429
+    #[error("API error: HTTP {status}: {context}")]
+    Http { status: u16, context: String },
+
+    /// HTTP 401: the API key is missing, mistyped or revoked
+    #[error("API error: invalid API key (HTTP 401)")]
+    InvalidApiKey,
+
+    /// HTTP 429; `retry_after` is the server's `Retry-After`, when it sent one
+    #[error("API error: rate limited (HTTP 429){}", retry_hint(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// The API has no quote for this symbol
+    #[error("API error: no data for {0}")]
+    SymbolNotFound(String),
+
+    /// The API has no candles for this symbol in the requested range
+    #[error("API error: No candle data for {0}")]
+    NoCandleData(String),
+
+    /// Requests are paused by the circuit breaker after repeated failures
+    #[error(
+        "API error: Skipping request after {failures} consecutive failures (last: {last_error}); retrying in {}s",
+        .retry_in.as_secs().max(1)
+    )]
+    CircuitOpen {
+        failures: u32,
+        last_error: String,
+        retry_in: Duration,
+    },
+
     /// Network errors
+    #[error("Network error: {0}")]
     Network(String),
-    
+
+    /// A request that failed without a response, such as a timeout or refused connection
+    #[error("Network error: {}", describe_request(.0))]
+    Request(#[source] reqwest::Error),
+
     /// I/O errors
+    #[error("I/O error: {0}")]
     Io(String),
-    
+
+    /// I/O errors from the operating system
+    #[error("I/O error: {0}")]
+    Os(#[from] std::io::Error),
+
     /// Data parsing errors
+    #[error("Parse error: {0}")]
     Parse(String),
-    
+
+    /// JSON that doesn't decode into the expected type
+    #[error("Parse error: JSON parsing failed: {0}")]
+    Json(#[from] serde_json::Error),
+
     /// No symbols provided
+    #[error("No symbols provided. Use --symbols, --symbols-file, or configure symbols_file in config")]
     NoSymbols,
-    
+
     /// Invalid input
+    #[error("Invalid input: {0}")]
     InvalidInput(String),
+
+    /// Another error, with what was being done when it happened
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<ScannerError>,
+    },
+}
+
+fn retry_hint(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(wait) => format!(", retry after {}s", wait.as_secs()),
+        None => String::new(),
+    }
 }
 
-impl fmt::Display for ScannerError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+fn describe_request(err: &reqwest::Error) -> String {
+    if err.is_timeout() {
+        return format!("Request timeout: {}", err);
+    }
+    // Browser `fetch` errors don't say whether the connection failed
+    #[cfg(not(target_arch = "wasm32"))]
+    if err.is_connect() {
+        return format!("Connection failed: {}", err);
+    }
+    format!("Request failed: {}", err)
+}
+
+impl ScannerError {
+    /// Exit status for a run that stopped on this error, always above `EXIT_PARTIAL`
+    pub fn exit_code(&self) -> u8 {
         match self {
-            ScannerError::Config(msg) => write!(f, "Configuration error: {}", msg),
-            ScannerError::Api(msg) => write!(f, "API error: {}", msg),
-            ScannerError::Network(msg) => write!(f, "Network error: {}", msg),
-            ScannerError::Io(msg) => write!(f, "I/O error: {}", msg),
-            ScannerError::Parse(msg) => write!(f, "Parse error: {}", msg),
-            ScannerError::NoSymbols => write!(f, "No symbols provided. Use --symbols, --symbols-file, or configure symbols_file in config"),
-            ScannerError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            ScannerError::Config(_) | ScannerError::NoSymbols | ScannerError::InvalidInput(_) => EXIT_USAGE,
+            ScannerError::Api(_)
+            | ScannerError::Http { .. }
+            | ScannerError::InvalidApiKey
+            | ScannerError::RateLimited { .. }
+            | ScannerError::SymbolNotFound(_)
+            | ScannerError::NoCandleData(_)
+            | ScannerError::CircuitOpen { .. } => 4,
+            ScannerError::Request(e) if e.is_status() => 4,
+            ScannerError::Network(_) | ScannerError::Request(_) => 5,
+            ScannerError::Io(_) | ScannerError::Os(_) => 6,
+            ScannerError::Parse(_) | ScannerError::Json(_) => 7,
+            ScannerError::Context { source, .. } => source.exit_code(),
         }
     }
-}
 
-impl std::error::Error for ScannerError {}
+    /// Wrap the error with `context`, keeping its kind for `code` and `exit_code`
+    pub fn context(self, context: &str) -> Self {
+        ScannerError::Context {
+            context: context.to_string(),
+            source: Box::new(self),
+        }
+    }
 
-// Convert from reqwest errors
-impl From<reqwest::Error> for ScannerError {
-    fn from(err: reqwest::Error) -> Self {
-        if err.is_timeout() {
-            ScannerError::Network(format!("Request timeout: {}", err))
-        } else if err.is_connect() {
-            ScannerError::Network(format!("Connection failed: {}", err))
-        } else if err.is_status() {
-            ScannerError::Api(format!("HTTP error: {}", err))
-        } else {
-            ScannerError::Network(format!("Request failed: {}", err))
+    /// The error underneath any context, for matching on its variant
+    pub fn root(&self) -> &ScannerError {
+        match self {
+            ScannerError::Context { source, .. } => source.root(),
+            error => error,
         }
     }
+
+    /// Stable identifier for scripts, e.g. telling `unauthorized` (bad API key) from `rate_limited`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScannerError::Config(_) => "config",
+            ScannerError::Api(_) => "api",
+            ScannerError::Http { status: 403, .. } => "forbidden",
+            ScannerError::Http { status, .. } if *status >= 500 => "server_error",
+            ScannerError::Http { .. } => "api",
+            ScannerError::InvalidApiKey => "unauthorized",
+            ScannerError::RateLimited { .. } => "rate_limited",
+            ScannerError::SymbolNotFound(_) | ScannerError::NoCandleData(_) => "no_data",
+            ScannerError::CircuitOpen { .. } => "circuit_open",
+            ScannerError::Request(e) if e.is_timeout() => "timeout",
+            ScannerError::Request(e) if e.is_status() => "api",
+            ScannerError::Network(_) | ScannerError::Request(_) => "network",
+            ScannerError::Io(_) | ScannerError::Os(_) => "io",
+            ScannerError::Parse(_) | ScannerError::Json(_) => "parse",
+            ScannerError::NoSymbols => "no_symbols",
+            ScannerError::InvalidInput(_) => "invalid_input",
+            ScannerError::Context { source, .. } => source.code(),
+        }
+    }
+}
+
+/// How failures are written to stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ErrorFormat {
+    /// Human-readable messages
+    #[default]
+    Text,
+    /// One JSON object per line, with an error `code`
+    Json,
 }
 
-// Convert from I/O errors
-impl From<std::io::Error> for ScannerError {
-    fn from(err: std::io::Error) -> Self {
-        ScannerError::Io(err.to_string())
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Set once at startup from `--error-format`
+pub fn set_format(format: ErrorFormat) {
+    JSON_ERRORS.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+pub fn json_format() -> bool {
+    JSON_ERRORS.load(Ordering::Relaxed)
+}
+
+/// A failure as written by `--error-format json`
+#[derive(Serialize)]
+pub struct ErrorReport<'a> {
+    pub level: &'a str,
+    pub code: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<&'a str>,
+    /// Set on the error that ended the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<u8>,
+    pub message: String,
+}
+
+/// Report the error that ended the run
+pub fn report(error: &ScannerError) {
+    if !json_format() {
+        eprintln!("Error: {}", error);
+        return;
     }
+    let report = ErrorReport {
+        level: "error",
+        code: error.code(),
+        symbol: None,
+        exit_code: Some(error.exit_code()),
+        message: error.to_string(),
+    };
+    eprintln!("{}", serde_json::to_string(&report).unwrap_or_default());
 }
 
-// Convert from serde_json errors
-impl From<serde_json::Error> for ScannerError {
-    fn from(err: serde_json::Error) -> Self {
-        ScannerError::Parse(format!("JSON parsing failed: {}", err))
+/// Report a symbol that couldn't be fetched while the rest of the run carries on
+pub fn report_symbol(symbol: &str, error: &ScannerError) {
+    if !json_format() {
+        log::warn!("{}: {}", symbol, error);
+        return;
+    }
+    if !log::log_enabled!(log::Level::Warn) {
+        return;
+    }
+    let report = ErrorReport {
+        level: "warn",
+        code: error.code(),
+        symbol: Some(symbol),
+        exit_code: None,
+        message: error.to_string(),
+    };
+    eprintln!("{}", serde_json::to_string(&report).unwrap_or_default());
+}
+
+impl From<reqwest::Error> for ScannerError {
+    fn from(err: reqwest::Error) -> Self {
+        ScannerError::Request(err)
     }
 }
 
@@ -86,4 +279,54 @@ mod tests {
         let err = ScannerError::NoSymbols;
         assert!(err.to_string().contains("No symbols provided"));
     }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(ScannerError::InvalidApiKey.code(), "unauthorized");
+        let wrapped = ScannerError::RateLimited {
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        let wrapped = wrapped.context("All requests failed");
+        assert_eq!(wrapped.code(), "rate_limited");
+        assert_eq!(
+            wrapped.to_string(),
+            "All requests failed: API error: rate limited (HTTP 429), retry after 30s"
+        );
+        assert!(matches!(wrapped.root(), ScannerError::RateLimited { .. }));
+        assert!(std::error::Error::source(&wrapped).is_some());
+
+        let forbidden = ScannerError::Http {
+            status: 403,
+            context: "AAPL".to_string(),
+        };
+        assert_eq!(forbidden.code(), "forbidden");
+        assert_eq!(forbidden.to_string(), "API error: HTTP 403: AAPL");
+        assert_eq!(ScannerError::SymbolNotFound("XYZ".to_string()).code(), "no_data");
+        assert_eq!(ScannerError::NoCandleData("XYZ".to_string()).code(), "no_data");
+        assert_eq!(ScannerError::NoSymbols.code(), "no_symbols");
+    }
+
+    #[test]
+    fn test_sources_are_kept() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        let err = ScannerError::from(io).context("Failed to read symbols");
+        assert_eq!(err.code(), "io");
+        assert_eq!(err.exit_code(), 6);
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(std::error::Error::source(source).is_some());
+
+        let json = serde_json::from_str::<u32>("x").unwrap_err();
+        assert_eq!(ScannerError::from(json).code(), "parse");
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(scan_exit_code(3, 0), EXIT_OK);
+        assert_eq!(scan_exit_code(0, 0), EXIT_NO_MATCHES);
+        assert_eq!(scan_exit_code(3, 1), EXIT_PARTIAL);
+        assert_eq!(scan_exit_code(0, 1), EXIT_PARTIAL);
+
+        assert_eq!(ScannerError::NoSymbols.exit_code(), EXIT_USAGE);
+        assert!(ScannerError::Network("timeout".to_string()).exit_code() > EXIT_PARTIAL);
+    }
 }
\ No newline at end of file
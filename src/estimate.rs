@@ -0,0 +1,445 @@
+use std::time::Duration;
+use crate::config::Config;
+use crate::finnhub::FinnhubClient;
+use crate::groups;
+use crate::provider::ProviderKind;
+use crate::resolve;
+use crate::watch;
+
+/// Requests a run would send to one endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointCalls {
+    /// Whose endpoint it is, for its rate limit
+    pub provider: ProviderKind,
+    pub path: &'static str,
+    pub calls: usize,
+    /// Lookups answered from a cache instead
+    pub cached: usize,
+    /// Only some of `calls` may be needed, e.g. profiles for the rows that pass the filter
+    pub at_most: bool,
+    pub note: &'static str,
+}
+
+/// API calls a run would make, for `--dry-run`
+#[derive(Debug, Default)]
+pub struct Estimate {
+    pub endpoints: Vec<EndpointCalls>,
+}
+
+impl Estimate {
+    pub fn total(&self) -> usize {
+        self.endpoints.iter().map(|endpoint| endpoint.calls).sum()
+    }
+
+    /// Calls going to each provider, in the order they're first used
+    fn by_provider(&self) -> Vec<(ProviderKind, usize)> {
+        let mut providers: Vec<(ProviderKind, usize)> = Vec::new();
+        for endpoint in &self.endpoints {
+            match providers.iter_mut().find(|(provider, _)| *provider == endpoint.provider) {
+                Some((_, calls)) => *calls += endpoint.calls,
+                None => providers.push((endpoint.provider, endpoint.calls)),
+            }
+        }
+        providers
+    }
+
+    /// How long the providers' rate limiters alone would stretch the run
+    pub fn duration(&self, config: &Config) -> Duration {
+        self.by_provider()
+            .into_iter()
+            .filter_map(|(provider, calls)| {
+                let (per_minute, burst) = provider.rate_limit(config)?;
+                Some(rate_limited(calls, per_minute, burst))
+            })
+            .sum()
+    }
+
+    pub fn print(&self, symbols: usize, config: &Config) {
+        println!("Dry run for {} symbols; no requests sent", symbols);
+        for endpoint in &self.endpoints {
+            let calls = format!("{}{}", if endpoint.at_most { "up to " } else { "" }, endpoint.calls);
+            let path = format!("{} {}", endpoint.provider.name(), endpoint.path);
+            println!(
+                "   GET {:<30} {:>10} calls  ({}; {} cached)",
+                path, calls, endpoint.note, endpoint.cached
+            );
+        }
+
+        let limits: Vec<String> = self
+            .by_provider()
+            .into_iter()
+            .filter(|(_, calls)| *calls > 0)
+            .map(|(provider, _)| match provider.rate_limit(config) {
+                Some((per_minute, burst)) => {
+                    format!("{} at {} requests/minute (burst {})", provider.name(), per_minute, burst)
+                }
+                None => format!("{} not rate limited", provider.name()),
+            })
+            .collect();
+        let at_most = self.endpoints.iter().any(|endpoint| endpoint.at_most);
+        println!(
+            "   Total: {}{} calls, about {}{}",
+            if at_most { "up to " } else { "" },
+            self.total(),
+            watch::format_elapsed(self.duration(config)),
+            if limits.is_empty() { String::new() } else { format!(" with {}", limits.join(" and ")) }
+        );
+    }
+}
+
+/// Requests `scan` would send for `symbols`, fetching quotes from `provider`
+///
+/// `symbols` may still hold `@group` entries and company names (see
+/// [`crate::resolve::prepare_offline`]); they're counted as the Finnhub lookups they'd need, and a
+/// name as one more quote. Local providers and a `--replay` cassette send no requests.
+pub fn scan(
+    client: &FinnhubClient,
+    symbols: &[String],
+    enrich: bool,
+    provider: ProviderKind,
+    replay: bool,
+) -> Estimate {
+    let (pending_groups, symbols): (Vec<String>, Vec<String>) =
+        symbols.iter().cloned().partition(|symbol| groups::is_group(symbol));
+    let names = symbols.iter().filter(|symbol| !resolve::looks_like_ticker(symbol)).count();
+
+    let mut endpoints = Vec::new();
+    if !pending_groups.is_empty() {
+        endpoints.push(EndpointCalls {
+            provider: ProviderKind::Finnhub,
+            path: "/index/constituents",
+            calls: pending_groups.len(),
+            cached: 0,
+            at_most: false,
+            note: "uncached @groups, whose members aren't counted",
+        });
+    }
+    if names > 0 {
+        endpoints.push(EndpointCalls {
+            provider: ProviderKind::Finnhub,
+            path: "/search",
+            calls: names,
+            cached: 0,
+            at_most: false,
+            note: "one per company name",
+        });
+    }
+    if replay {
+        return Estimate { endpoints };
+    }
+
+    if let Some(path) = provider.quote_path() {
+        // Only Finnhub's client keeps a quote cache
+        let cached = if provider == ProviderKind::Finnhub { client.cached_quotes(&symbols) } else { 0 };
+        endpoints.push(EndpointCalls {
+            provider,
+            path,
+            calls: symbols.len() - cached,
+            cached,
+            at_most: false,
+            note: "one per symbol",
+        });
+    }
+    if enrich {
+        let cached = client.cached_profiles(&symbols);
+        endpoints.push(EndpointCalls {
+            provider: ProviderKind::Finnhub,
+            path: "/stock/profile2",
+            calls: symbols.len() - cached,
+            cached,
+            at_most: true,
+            note: "--enrich, for rows that pass the filter",
+        });
+    }
+    Estimate { endpoints }
+}
+
+/// Finnhub's free tier allows this many calls a minute
+pub const FREE_TIER_PER_MINUTE: u32 = 60;
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// What `plan` is asked to budget for
+#[derive(Debug, Clone)]
+pub struct PlanOptions {
+    pub symbols: usize,
+    /// `--enrich` company profiles
+    pub profiles: bool,
+    pub metrics: bool,
+    pub targets: bool,
+    pub candles: bool,
+    /// Seconds between watch refreshes; `None` plans for `runs` one-off scans
+    pub interval: Option<u64>,
+    pub runs: u64,
+    /// Hours a day the watch runs
+    pub hours: f64,
+    /// Calls a minute the Finnhub plan allows
+    pub per_minute: u32,
+    pub daily_limit: Option<u64>,
+}
+
+/// Calls to one endpoint in a planned day
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedCalls {
+    pub path: &'static str,
+    pub per_run: u64,
+    pub per_day: u64,
+    /// Seconds a cached response stays fresh, so later runs skip the endpoint
+    pub cache_ttl: Option<u64>,
+}
+
+/// A day's API budget, for `plan`
+#[derive(Debug)]
+pub struct Plan {
+    pub runs_per_day: u64,
+    pub endpoints: Vec<PlannedCalls>,
+}
+
+impl Plan {
+    pub fn new(options: &PlanOptions, config: &Config) -> Self {
+        let runs_per_day = match options.interval {
+            Some(interval) => (options.hours.max(0.0) * 3600.0 / interval.max(1) as f64).ceil() as u64,
+            None => options.runs,
+        };
+        let symbols = options.symbols as u64;
+        let ttls = &config.cache_ttls;
+
+        let mut endpoints = vec![("/quote", true, None)];
+        endpoints.push(("/stock/profile2", options.profiles, Some(ttls.profile_secs)));
+        endpoints.push(("/stock/metric", options.metrics, Some(ttls.metrics_secs)));
+        endpoints.push(("/stock/price-target", options.targets, None));
+        endpoints.push(("/stock/candle", options.candles, None));
+
+        let endpoints = endpoints
+            .into_iter()
+            .filter(|(_, enabled, _)| *enabled)
+            .map(|(path, _, cache_ttl)| {
+                // A cached endpoint is fetched again once per TTL, never more often than it runs
+                let fetches = match cache_ttl {
+                    Some(ttl) => runs_per_day.min((SECS_PER_DAY / ttl.max(1) as f64).ceil() as u64),
+                    None => runs_per_day,
+                };
+                PlannedCalls {
+                    path,
+                    per_run: symbols,
+                    per_day: symbols * fetches,
+                    cache_ttl,
+                }
+            })
+            .collect();
+
+        Plan {
+            runs_per_day,
+            endpoints,
+        }
+    }
+
+    /// Calls in a run with a cold cache
+    pub fn per_run(&self) -> u64 {
+        self.endpoints.iter().map(|endpoint| endpoint.per_run).sum()
+    }
+
+    /// Calls in a run once cached endpoints are warm
+    pub fn uncached_per_run(&self) -> u64 {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.cache_ttl.is_none())
+            .map(|endpoint| endpoint.per_run)
+            .sum()
+    }
+
+    pub fn per_day(&self) -> u64 {
+        self.endpoints.iter().map(|endpoint| endpoint.per_day).sum()
+    }
+
+    /// Ways the plan would run into Finnhub's limits or its own rate limiter
+    pub fn warnings(&self, options: &PlanOptions, config: &Config) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let per_minute = options.per_minute.max(1);
+        let tier = if per_minute == FREE_TIER_PER_MINUTE { "the free tier's" } else { "the plan's" };
+
+        if let Some(interval) = options.interval {
+            let calls = self.uncached_per_run();
+            let rate = calls as f64 * 60.0 / interval.max(1) as f64;
+            if rate > per_minute as f64 {
+                let shortest = (calls * 60).div_ceil(per_minute as u64);
+                warnings.push(format!(
+                    "Refreshing every {}s averages {:.0} calls a minute, over {} {}; use --interval {} or more",
+                    interval, rate, tier, per_minute, shortest
+                ));
+            }
+            let refresh = rate_limited(calls as usize, config.requests_per_minute, config.rate_limit_burst);
+            if refresh > Duration::from_secs(interval) {
+                warnings.push(format!(
+                    "Each refresh takes about {} at requests_per_minute = {}, longer than the {}s interval",
+                    watch::format_elapsed(refresh),
+                    config.requests_per_minute,
+                    interval
+                ));
+            }
+        }
+        if config.requests_per_minute > per_minute {
+            warnings.push(format!(
+                "requests_per_minute = {} lets the scanner go over {} {} calls a minute, expect HTTP 429s",
+                config.requests_per_minute, tier, per_minute
+            ));
+        }
+        if let Some(limit) = options.daily_limit {
+            if self.per_day() > limit {
+                warnings.push(format!(
+                    "{} calls a day is over the daily limit of {}",
+                    self.per_day(),
+                    limit
+                ));
+            }
+        }
+        warnings
+    }
+
+    pub fn print(&self, options: &PlanOptions, config: &Config) {
+        match options.interval {
+            Some(interval) => println!(
+                "Plan for {} symbols, refreshing every {}s for {}h a day ({} runs)",
+                options.symbols, interval, options.hours, self.runs_per_day
+            ),
+            None => println!("Plan for {} symbols, {} scan(s) a day", options.symbols, self.runs_per_day),
+        }
+        for endpoint in &self.endpoints {
+            let cached = match endpoint.cache_ttl {
+                Some(ttl) => format!("  (cached for {})", watch::format_elapsed(Duration::from_secs(ttl))),
+                None => String::new(),
+            };
+            println!(
+                "   GET {:<20} {:>8} per run {:>10} per day{}",
+                endpoint.path, endpoint.per_run, endpoint.per_day, cached
+            );
+        }
+        println!(
+            "   Total: {} calls per run, {} per day; a cold run takes about {}",
+            self.per_run(),
+            self.per_day(),
+            watch::format_elapsed(rate_limited(
+                self.per_run() as usize,
+                config.requests_per_minute,
+                config.rate_limit_burst
+            ))
+        );
+
+        let warnings = self.warnings(options, config);
+        if warnings.is_empty() {
+            println!("✓ Fits within {} calls a minute", options.per_minute);
+        }
+        for warning in warnings {
+            println!("⚠ {}", warning);
+        }
+    }
+}
+
+/// Time for `calls` requests through a token bucket that starts full: the first `burst`
+/// go out at once and the rest at `requests_per_minute`
+pub fn rate_limited(calls: usize, requests_per_minute: u32, burst: u32) -> Duration {
+    let waiting = calls.saturating_sub(burst.max(1) as usize);
+    Duration::from_secs_f64(waiting as f64 * 60.0 / requests_per_minute.max(1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limited() {
+        assert_eq!(rate_limited(10, 60, 30), Duration::ZERO);
+        assert_eq!(rate_limited(150, 60, 30), Duration::from_secs(120));
+        assert_eq!(rate_limited(5, 0, 0), Duration::from_secs(240));
+    }
+
+    #[test]
+    fn test_total() {
+        let estimate = Estimate {
+            endpoints: vec![
+                EndpointCalls {
+                    provider: ProviderKind::Finnhub,
+                    path: "/quote",
+                    calls: 95,
+                    cached: 25,
+                    at_most: false,
+                    note: "quotes",
+                },
+                EndpointCalls {
+                    provider: ProviderKind::Finnhub,
+                    path: "/stock/profile2",
+                    calls: 40,
+                    cached: 80,
+                    at_most: true,
+                    note: "--enrich",
+                },
+            ],
+        };
+        assert_eq!(estimate.total(), 135);
+    }
+
+    #[test]
+    fn test_scan_counts_pending_lookups() {
+        let client = FinnhubClient::builder().api_key("test").build().unwrap();
+        let symbols = ["@sp500", "Apple Inc", "MSFT"].map(String::from);
+
+        let estimate = scan(&client, &symbols, false, ProviderKind::Finnhub, false);
+        let calls: Vec<_> = estimate.endpoints.iter().map(|e| (e.path, e.calls)).collect();
+        assert_eq!(calls, [("/index/constituents", 1), ("/search", 1), ("/quote", 2)]);
+    }
+
+    #[test]
+    fn test_scan_uses_the_selected_provider() {
+        let client = FinnhubClient::builder().api_key("test").build().unwrap();
+        let symbols: Vec<String> = (0..100).map(|i| format!("S{}", i)).collect();
+        let config = Config::default();
+
+        assert_eq!(scan(&client, &symbols, false, ProviderKind::Mock, false).total(), 0);
+        assert_eq!(scan(&client, &symbols, false, ProviderKind::Csv, false).total(), 0);
+        assert_eq!(scan(&client, &symbols, true, ProviderKind::Finnhub, true).total(), 0);
+
+        let yahoo = scan(&client, &symbols, false, ProviderKind::Yahoo, false);
+        assert_eq!(yahoo.total(), 100);
+        assert_eq!(yahoo.duration(&config), Duration::ZERO);
+
+        let tiingo = scan(&client, &symbols, false, ProviderKind::Tiingo, false);
+        let (per_minute, _) = ProviderKind::Tiingo.rate_limit(&config).unwrap();
+        assert_eq!(tiingo.duration(&config), rate_limited(100, per_minute, 1));
+    }
+
+    fn options(symbols: usize, interval: Option<u64>) -> PlanOptions {
+        PlanOptions {
+            symbols,
+            profiles: true,
+            metrics: false,
+            targets: false,
+            candles: true,
+            interval,
+            runs: 1,
+            hours: 6.5,
+            per_minute: FREE_TIER_PER_MINUTE,
+            daily_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_calls() {
+        let plan = Plan::new(&options(20, Some(60)), &Config::default());
+        assert_eq!(plan.runs_per_day, 390);
+        assert_eq!(plan.per_run(), 60);
+        assert_eq!(plan.uncached_per_run(), 40);
+        // Profiles are cached for a week, so they're only fetched on the first run
+        assert_eq!(plan.per_day(), 40 * 390 + 20);
+    }
+
+    #[test]
+    fn test_plan_warnings() {
+        let config = Config::default();
+        let quiet = options(20, Some(60));
+        assert!(Plan::new(&quiet, &config).warnings(&quiet, &config).is_empty());
+
+        let busy = options(50, Some(30));
+        let warnings = Plan::new(&busy, &config).warnings(&busy, &config);
+        assert!(warnings[0].contains("use --interval 100 or more"), "{:?}", warnings);
+    }
+}
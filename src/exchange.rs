@@ -0,0 +1,120 @@
+/// A stock exchange as it appears in symbol input
+#[derive(Debug, PartialEq)]
+pub struct Exchange {
+    /// Codes accepted before a colon, as in `TSX:SHOP`
+    pub prefixes: &'static [&'static str],
+    /// What Finnhub appends to the ticker, as in `SHOP.TO`; empty for US listings
+    pub suffix: &'static str,
+    /// ISO 4217 code prices are quoted in (`GBX` is pence)
+    pub currency: &'static str,
+}
+
+const EXCHANGES: &[Exchange] = &[
+    Exchange { prefixes: &["NYSE", "NASDAQ", "AMEX", "ARCA", "BATS", "US"], suffix: "", currency: "USD" },
+    Exchange { prefixes: &["TSX", "TSE"], suffix: ".TO", currency: "CAD" },
+    Exchange { prefixes: &["TSXV", "CVE"], suffix: ".V", currency: "CAD" },
+    Exchange { prefixes: &["LSE", "LON"], suffix: ".L", currency: "GBX" },
+    Exchange { prefixes: &["XETRA", "XETR", "ETR"], suffix: ".DE", currency: "EUR" },
+    Exchange { prefixes: &["FRA", "FWB"], suffix: ".F", currency: "EUR" },
+    Exchange { prefixes: &["EPA", "PAR"], suffix: ".PA", currency: "EUR" },
+    Exchange { prefixes: &["AMS"], suffix: ".AS", currency: "EUR" },
+    Exchange { prefixes: &["BIT", "MIL"], suffix: ".MI", currency: "EUR" },
+    Exchange { prefixes: &["BME", "MCE"], suffix: ".MC", currency: "EUR" },
+    Exchange { prefixes: &["SIX", "SWX"], suffix: ".SW", currency: "CHF" },
+    Exchange { prefixes: &["TYO", "JPX"], suffix: ".T", currency: "JPY" },
+    Exchange { prefixes: &["HKEX", "HKG"], suffix: ".HK", currency: "HKD" },
+    Exchange { prefixes: &["ASX"], suffix: ".AX", currency: "AUD" },
+    Exchange { prefixes: &["NSE"], suffix: ".NS", currency: "INR" },
+    Exchange { prefixes: &["BSE", "BOM"], suffix: ".BO", currency: "INR" },
+    Exchange { prefixes: &["KRX"], suffix: ".KS", currency: "KRW" },
+    Exchange { prefixes: &["SGX"], suffix: ".SI", currency: "SGD" },
+];
+
+/// The exchange a symbol is listed on, from an exchange prefix or Finnhub's suffix
+///
+/// `None` for prefixes that aren't stock exchanges, such as `BINANCE:` or `OANDA:`.
+pub fn of_symbol(symbol: &str) -> Option<&'static Exchange> {
+    if let Some((prefix, _)) = symbol.split_once(':') {
+        return EXCHANGES
+            .iter()
+            .find(|exchange| exchange.prefixes.iter().any(|p| p.eq_ignore_ascii_case(prefix)));
+    }
+    let suffix = &symbol[symbol.rfind('.')?..];
+    EXCHANGES
+        .iter()
+        .find(|exchange| !exchange.suffix.is_empty() && exchange.suffix.eq_ignore_ascii_case(suffix))
+}
+
+/// Rewrite `EXCHANGE:TICKER` as Finnhub expects it: `TSX:SHOP` is `SHOP.TO`, `LSE:VOD.L` is
+/// `VOD.L` and `NASDAQ:AAPL` is `AAPL`
+///
+/// Other symbols, including crypto and forex pairs such as `BINANCE:BTCUSDT`, are left alone.
+pub fn normalize(symbol: &str) -> String {
+    let Some((_, ticker)) = symbol.split_once(':') else {
+        return symbol.to_string();
+    };
+    match of_symbol(symbol) {
+        Some(exchange) if ticker.to_uppercase().ends_with(exchange.suffix) => ticker.to_string(),
+        Some(exchange) => format!("{}{}", ticker, exchange.suffix),
+        None => symbol.to_string(),
+    }
+}
+
+/// Finnhub's code for the exchange listing a symbol, for `/stock/symbol`: `US`, `TO`, `L`, ...
+pub fn listing_code(symbol: &str) -> &'static str {
+    match of_symbol(symbol) {
+        Some(exchange) if !exchange.suffix.is_empty() => &exchange.suffix[1..],
+        _ => "US",
+    }
+}
+
+/// Currency a symbol's prices are in, `USD` unless its exchange says otherwise
+pub fn currency(symbol: &str) -> &'static str {
+    of_symbol(symbol).map_or("USD", |exchange| exchange.currency)
+}
+
+/// What goes in front of a price: `$`, `C$`, `€`, or the currency code and a space
+pub fn price_sign(currency: &str) -> &'static str {
+    match currency {
+        "USD" => "$",
+        "CAD" => "C$",
+        "EUR" => "€",
+        "JPY" => "¥",
+        "HKD" => "HK$",
+        "AUD" => "A$",
+        "INR" => "₹",
+        "KRW" => "₩",
+        "SGD" => "S$",
+        "GBX" => "GBp ",
+        "CHF" => "CHF ",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("TSX:SHOP"), "SHOP.TO");
+        assert_eq!(normalize("LSE:VOD.L"), "VOD.L");
+        assert_eq!(normalize("lse:vod"), "vod.L");
+        assert_eq!(normalize("NASDAQ:AAPL"), "AAPL");
+        assert_eq!(normalize("BINANCE:BTCUSDT"), "BINANCE:BTCUSDT");
+        assert_eq!(normalize("BRK.B"), "BRK.B");
+    }
+
+    #[test]
+    fn test_currency() {
+        assert_eq!(currency("SHOP.TO"), "CAD");
+        assert_eq!(currency("TSX:SHOP"), "CAD");
+        assert_eq!(currency("VOD.L"), "GBX");
+        assert_eq!(currency("7203.T"), "JPY");
+        assert_eq!(currency("BRK.B"), "USD");
+        assert_eq!(currency("AAPL"), "USD");
+        assert_eq!(price_sign(currency("SAP.DE")), "€");
+        assert_eq!(listing_code("SHOP.TO"), "TO");
+        assert_eq!(listing_code("BRK.B"), "US");
+    }
+}
@@ -0,0 +1,620 @@
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::StockQuote;
+
+/// Quote fields that expressions may reference
+pub const FIELDS: &[&str] = &["price", "prev_close", "change", "change_pct", "high", "low", "open"];
+
+/// Fields only alert rules provide, accepted by `parse_alert` and rejected by `parse`
+pub const ALERT_FIELDS: &[&str] = &["volume_ratio", "session_high", "session_low"];
+
+/// Built-in functions and their arity
+const FUNCTIONS: &[(&str, usize)] = &[("abs", 1), ("min", 2), ("max", 2), ("pct_move", 1)];
+
+/// Parsed condition expression, e.g. `change_pct > 3 and price < 200`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Bool(bool),
+    Field(String),
+    Call(String, Vec<Expr>),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    /// `lhs crosses above|below rhs`, comparing against the previous evaluation
+    Cross(Direction, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Above,
+    Below,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+/// Result of evaluating an expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Bool(bool),
+}
+
+/// Source of field values during evaluation
+pub trait Env {
+    fn field(&self, name: &str) -> Option<f64>;
+
+    /// Values from the previous evaluation, needed by `crosses`
+    fn previous(&self) -> Option<&dyn Env> {
+        None
+    }
+
+    /// Percent price change over the last `window_secs`, needed by `pct_move`
+    fn pct_move(&self, _window_secs: f64) -> Option<f64> {
+        None
+    }
+}
+
+impl Env for StockQuote {
+    fn field(&self, name: &str) -> Option<f64> {
+        match name {
+            "price" => Some(self.price),
+            "prev_close" => Some(self.prev_close),
+            "change" => Some(self.price - self.prev_close),
+            "change_pct" => Some(self.change_pct),
+            "high" => Some(self.high),
+            "low" => Some(self.low),
+            "open" => Some(self.open),
+            _ => None,
+        }
+    }
+}
+
+/// Parse an expression, rejecting unknown fields and functions up front
+pub fn parse(source: &str) -> Result<Expr> {
+    parse_with(source, false)
+}
+
+/// Parse an alert rule's condition, which may also use `ALERT_FIELDS`
+pub fn parse_alert(source: &str) -> Result<Expr> {
+    parse_with(source, true)
+}
+
+fn parse_with(source: &str, alert: bool) -> Result<Expr> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0, alert };
+    let expr = parser.parse_or()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(invalid(source, &format!("unexpected {:?}", token)));
+    }
+
+    Ok(expr)
+}
+
+impl Expr {
+    pub fn eval(&self, env: &dyn Env) -> Result<Value> {
+        match self {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Field(name) => env
+                .field(name)
+                .map(Value::Number)
+                .ok_or_else(|| ScannerError::InvalidInput(format!("No value for field '{}'", name))),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(env)?.as_number())
+                    .collect::<Result<Vec<f64>>>()?;
+                match name.as_str() {
+                    "abs" => Ok(Value::Number(args[0].abs())),
+                    "min" => Ok(Value::Number(args[0].min(args[1]))),
+                    "max" => Ok(Value::Number(args[0].max(args[1]))),
+                    // No price history yet means no measurable move
+                    "pct_move" => Ok(Value::Number(env.pct_move(args[0]).unwrap_or(0.0))),
+                    _ => Err(ScannerError::InvalidInput(format!("Unknown function '{}'", name))),
+                }
+            }
+            Expr::Neg(inner) => Ok(Value::Number(-inner.eval(env)?.as_number()?)),
+            Expr::Not(inner) => Ok(Value::Bool(!inner.eval(env)?.as_bool()?)),
+            Expr::Binary(op, lhs, rhs) => match op {
+                // Short-circuit so `a and b` doesn't evaluate `b` needlessly
+                BinOp::And => Ok(Value::Bool(lhs.eval(env)?.as_bool()? && rhs.eval(env)?.as_bool()?)),
+                BinOp::Or => Ok(Value::Bool(lhs.eval(env)?.as_bool()? || rhs.eval(env)?.as_bool()?)),
+                _ => {
+                    let a = lhs.eval(env)?.as_number()?;
+                    let b = rhs.eval(env)?.as_number()?;
+                    Ok(match op {
+                        BinOp::Add => Value::Number(a + b),
+                        BinOp::Sub => Value::Number(a - b),
+                        BinOp::Mul => Value::Number(a * b),
+                        BinOp::Div => {
+                            if b == 0.0 {
+                                return Err(ScannerError::InvalidInput("Division by zero".to_string()));
+                            }
+                            Value::Number(a / b)
+                        }
+                        BinOp::Gt => Value::Bool(a > b),
+                        BinOp::Ge => Value::Bool(a >= b),
+                        BinOp::Lt => Value::Bool(a < b),
+                        BinOp::Le => Value::Bool(a <= b),
+                        BinOp::Eq => Value::Bool((a - b).abs() < 1e-9),
+                        BinOp::Ne => Value::Bool((a - b).abs() >= 1e-9),
+                        BinOp::And | BinOp::Or => unreachable!(),
+                    })
+                }
+            },
+            Expr::Cross(direction, lhs, rhs) => {
+                // Without a previous value there is nothing to have crossed from
+                let Some(previous) = env.previous() else {
+                    return Ok(Value::Bool(false));
+                };
+                let (before, before_level) = (lhs.eval(previous)?.as_number()?, rhs.eval(previous)?.as_number()?);
+                let (now, level) = (lhs.eval(env)?.as_number()?, rhs.eval(env)?.as_number()?);
+
+                Ok(Value::Bool(match direction {
+                    Direction::Above => before <= before_level && now > level,
+                    Direction::Below => before >= before_level && now < level,
+                }))
+            }
+        }
+    }
+
+    /// Evaluate as a condition; numeric results are an error
+    pub fn matches(&self, env: &dyn Env) -> Result<bool> {
+        self.eval(env)?.as_bool()
+    }
+
+    /// Whether the expression reads the given field anywhere
+    pub fn references(&self, field: &str) -> bool {
+        match self {
+            Expr::Number(_) | Expr::Bool(_) => false,
+            Expr::Field(name) => name == field,
+            Expr::Call(_, args) => args.iter().any(|arg| arg.references(field)),
+            Expr::Neg(inner) | Expr::Not(inner) => inner.references(field),
+            Expr::Binary(_, lhs, rhs) | Expr::Cross(_, lhs, rhs) => lhs.references(field) || rhs.references(field),
+        }
+    }
+
+    /// Longest constant `pct_move` window in seconds, i.e. how much price history evaluation needs
+    pub fn max_window(&self) -> Option<f64> {
+        let children = match self {
+            Expr::Number(_) | Expr::Bool(_) | Expr::Field(_) => return None,
+            Expr::Call(name, args) if name == "pct_move" => {
+                return match args.first() {
+                    Some(Expr::Number(secs)) => Some(*secs),
+                    other => other.and_then(|arg| arg.max_window()),
+                };
+            }
+            Expr::Call(_, args) => args.iter().collect::<Vec<_>>(),
+            Expr::Neg(inner) | Expr::Not(inner) => vec![inner.as_ref()],
+            Expr::Binary(_, lhs, rhs) | Expr::Cross(_, lhs, rhs) => vec![lhs.as_ref(), rhs.as_ref()],
+        };
+
+        children.into_iter().filter_map(Expr::max_window).reduce(f64::max)
+    }
+}
+
+impl Value {
+    fn as_number(self) -> Result<f64> {
+        match self {
+            Value::Number(n) => Ok(n),
+            Value::Bool(_) => Err(ScannerError::InvalidInput(
+                "Expected a number but found a true/false value".to_string(),
+            )),
+        }
+    }
+
+    fn as_bool(self) -> Result<bool> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            Value::Number(_) => Err(ScannerError::InvalidInput(
+                "Expected a condition but found a number (missing comparison?)".to_string(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn invalid(source: &str, reason: &str) -> ScannerError {
+    ScannerError::InvalidInput(format!("Invalid expression '{}': {}", source, reason))
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| invalid(source, &format!("bad number '{}'", text)))?;
+
+            // Allow `change_pct > 3%` as a readable spelling of `> 3`
+            if chars.get(i) == Some(&'%') {
+                i += 1;
+            }
+
+            // Duration suffixes (`90s`, `15m`, `1h`) become seconds, e.g. `pct_move(15m)`
+            let unit = match chars.get(i) {
+                Some('s') => Some(1.0),
+                Some('m') => Some(60.0),
+                Some('h') => Some(3600.0),
+                _ => None,
+            };
+            let standalone = !chars.get(i + 1).is_some_and(|n| n.is_ascii_alphanumeric() || *n == '_');
+            let number = match unit {
+                Some(scale) if standalone => {
+                    i += 1;
+                    number * scale
+                }
+                _ => number,
+            };
+            tokens.push(Token::Number(number));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect::<String>().to_lowercase()));
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (op, len) = match (c, next) {
+                ('>', Some('=')) => (">=", 2),
+                ('<', Some('=')) => ("<=", 2),
+                ('=', Some('=')) => ("==", 2),
+                ('!', Some('=')) => ("!=", 2),
+                ('&', Some('&')) => ("and", 2),
+                ('|', Some('|')) => ("or", 2),
+                ('>', _) => (">", 1),
+                ('<', _) => ("<", 1),
+                ('=', _) => ("==", 1),
+                ('!', _) => ("not", 1),
+                ('+', _) => ("+", 1),
+                ('-', _) => ("-", 1),
+                ('*', _) => ("*", 1),
+                ('/', _) => ("/", 1),
+                ('(', _) => {
+                    tokens.push(Token::LParen);
+                    i += 1;
+                    continue;
+                }
+                (')', _) => {
+                    tokens.push(Token::RParen);
+                    i += 1;
+                    continue;
+                }
+                (',', _) => {
+                    tokens.push(Token::Comma);
+                    i += 1;
+                    continue;
+                }
+                _ => return Err(invalid(source, &format!("unexpected character '{}'", c))),
+            };
+            tokens.push(Token::Op(op));
+            i += len;
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Whether `ALERT_FIELDS` are allowed
+    alert: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume the next token if it is the given operator or keyword
+    fn eat(&mut self, op: &str) -> bool {
+        let matched = match self.peek() {
+            Some(Token::Op(o)) => *o == op,
+            Some(Token::Ident(word)) => word == op,
+            _ => false,
+        };
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn error(&self, reason: &str) -> ScannerError {
+        ScannerError::InvalidInput(format!("Invalid expression: {}", reason))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat("or") {
+            let rhs = self.parse_and()?;
+            expr = Expr::Binary(BinOp::Or, Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.eat("and") {
+            let rhs = self.parse_not()?;
+            expr = Expr::Binary(BinOp::And, Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.eat("not") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let lhs = self.parse_sum()?;
+
+        if self.eat("crosses") {
+            let direction = if self.eat("above") {
+                Direction::Above
+            } else if self.eat("below") {
+                Direction::Below
+            } else {
+                return Err(self.error("expected 'above' or 'below' after 'crosses'"));
+            };
+            let rhs = self.parse_sum()?;
+            return Ok(Expr::Cross(direction, Box::new(lhs), Box::new(rhs)));
+        }
+
+        let op = match self.peek() {
+            Some(Token::Op(">")) => BinOp::Gt,
+            Some(Token::Op(">=")) => BinOp::Ge,
+            Some(Token::Op("<")) => BinOp::Lt,
+            Some(Token::Op("<=")) => BinOp::Le,
+            Some(Token::Op("==")) => BinOp::Eq,
+            Some(Token::Op("!=")) => BinOp::Ne,
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+
+        let rhs = self.parse_sum()?;
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_term()?;
+        loop {
+            let op = if self.eat("+") {
+                BinOp::Add
+            } else if self.eat("-") {
+                BinOp::Sub
+            } else {
+                return Ok(expr);
+            };
+            let rhs = self.parse_term()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinOp::Mul
+            } else if self.eat("/") {
+                BinOp::Div
+            } else {
+                return Ok(expr);
+            };
+            let rhs = self.parse_unary()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(self.error("missing ')'")),
+                }
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                _ if self.peek() == Some(&Token::LParen) => {
+                    self.pos += 1;
+                    let args = self.parse_args()?;
+                    self.check_call(&name, args.len())?;
+                    Ok(Expr::Call(name, args))
+                }
+                _ if FIELDS.contains(&name.as_str()) => Ok(Expr::Field(name)),
+                _ if ALERT_FIELDS.contains(&name.as_str()) && self.alert => Ok(Expr::Field(name)),
+                _ if ALERT_FIELDS.contains(&name.as_str()) => {
+                    Err(self.error(&format!("'{}' is only available in alert rules", name)))
+                }
+                _ => Err(self.error(&format!(
+                    "unknown field '{}' (known fields: {})",
+                    name,
+                    FIELDS.join(", ")
+                ))),
+            },
+            Some(token) => Err(self.error(&format!("unexpected {:?}", token))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_args(&mut self) -> Result<Vec<Expr>> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.parse_or()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => return Ok(args),
+                _ => return Err(self.error("expected ',' or ')' in function call")),
+            }
+        }
+    }
+
+    fn check_call(&self, name: &str, arity: usize) -> Result<()> {
+        match FUNCTIONS.iter().find(|(f, _)| *f == name) {
+            Some((_, expected)) if *expected == arity => Ok(()),
+            Some((_, expected)) => Err(self.error(&format!(
+                "{}() takes {} argument(s), got {}",
+                name, expected, arity
+            ))),
+            None => Err(self.error(&format!("unknown function '{}'", name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_comparison() {
+        let expr = parse("change_pct > 3").unwrap();
+        assert!(expr.matches(&StockQuote::test("TEST", 100.0, 95.0)).unwrap());
+        assert!(!expr.matches(&StockQuote::test("TEST", 100.0, 99.0)).unwrap());
+    }
+
+    #[test]
+    fn test_boolean_logic_and_precedence() {
+        let expr = parse("price > 50 and change_pct < -2 or abs(change_pct) >= 10%").unwrap();
+        assert!(expr.matches(&StockQuote::test("TEST", 60.0, 62.0)).unwrap());
+        assert!(expr.matches(&StockQuote::test("TEST", 10.0, 8.9)).unwrap());
+        assert!(!expr.matches(&StockQuote::test("TEST", 10.0, 10.4)).unwrap());
+
+        let expr = parse("not (price - 10 * 2 > 0)").unwrap();
+        assert!(expr.matches(&StockQuote::test("TEST", 15.0, 15.0)).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_unknown_names() {
+        assert!(parse("volume > 10").is_err());
+        assert!(parse_alert("volume_ratio > 3").unwrap().references("volume_ratio"));
+        let err = parse("volume_ratio > 3").unwrap_err().to_string();
+        assert!(err.contains("'volume_ratio' is only available in alert rules"), "{}", err);
+        assert!(parse("price > session_high").is_err());
+        assert!(parse("sqrt(price) > 10").is_err());
+        assert!(parse("abs(price, 2) > 10").is_err());
+        assert!(parse("price >").is_err());
+        assert!(parse("price > 10 10").is_err());
+    }
+
+    struct WithPrevious<'a>(&'a StockQuote, &'a StockQuote);
+
+    impl Env for WithPrevious<'_> {
+        fn field(&self, name: &str) -> Option<f64> {
+            self.0.field(name)
+        }
+
+        fn previous(&self) -> Option<&dyn Env> {
+            Some(self.1)
+        }
+    }
+
+    #[test]
+    fn test_crosses() {
+        let above = parse("price crosses above 250").unwrap();
+        let below = parse("price CROSSES BELOW 220").unwrap();
+        let at = |price: f64| StockQuote::test("TEST", price, price);
+        let (low, mid, high) = (at(215.0), at(240.0), at(255.0));
+
+        assert!(above.matches(&WithPrevious(&high, &mid)).unwrap());
+        assert!(!above.matches(&WithPrevious(&high, &high)).unwrap());
+        assert!(below.matches(&WithPrevious(&low, &mid)).unwrap());
+        assert!(!below.matches(&WithPrevious(&mid, &low)).unwrap());
+
+        // First evaluation has no previous quote
+        assert!(!above.matches(&high).unwrap());
+        assert!(parse("price crosses 250").is_err());
+    }
+
+    struct Moving(StockQuote, f64);
+
+    impl Env for Moving {
+        fn field(&self, name: &str) -> Option<f64> {
+            self.0.field(name)
+        }
+
+        fn pct_move(&self, window_secs: f64) -> Option<f64> {
+            (window_secs == 900.0).then_some(self.1)
+        }
+    }
+
+    #[test]
+    fn test_pct_move_and_durations() {
+        let expr = parse("abs(pct_move(15m)) > 2").unwrap();
+        assert!(expr.matches(&Moving(StockQuote::test("TEST", 100.0, 100.0), -2.5)).unwrap());
+        assert!(!expr.matches(&Moving(StockQuote::test("TEST", 100.0, 100.0), 1.0)).unwrap());
+        // No history behaves like no move
+        assert!(!expr.matches(&StockQuote::test("TEST", 100.0, 100.0)).unwrap());
+
+        assert_eq!(expr.max_window(), Some(900.0));
+        assert_eq!(parse("pct_move(90s) > 1 or pct_move(1h) > 3").unwrap().max_window(), Some(3600.0));
+        assert_eq!(parse("price > 10").unwrap().max_window(), None);
+    }
+
+    #[test]
+    fn test_numeric_result_is_not_a_condition() {
+        let expr = parse("price * 2").unwrap();
+        assert_eq!(expr.eval(&StockQuote::test("TEST", 10.0, 10.0)).unwrap(), Value::Number(20.0));
+        assert!(expr.matches(&StockQuote::test("TEST", 10.0, 10.0)).is_err());
+    }
+}
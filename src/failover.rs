@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Candles, StockQuote, SymbolError};
+use crate::provider::{ProviderFuture, QuoteProvider};
+
+/// Error codes that mean a provider is unavailable rather than that the symbol is bad
+const FAILOVER_CODES: &[&str] = &[
+    "rate_limited",
+    "circuit_open",
+    "timeout",
+    "network",
+    "server_error",
+    "forbidden",
+    "unauthorized",
+];
+
+/// Asks each provider in turn for the symbols the ones before it couldn't serve
+///
+/// A symbol moves on to the next provider only when its failure says the provider is rate
+/// limited, down or refusing the key; unknown symbols fail as they would on the primary alone.
+/// Quotes carry the name of the provider that served them in `source`.
+pub struct FailoverProvider {
+    providers: Vec<Arc<dyn QuoteProvider>>,
+}
+
+impl FailoverProvider {
+    /// `providers` in order of preference, the primary first
+    pub fn new(providers: Vec<Arc<dyn QuoteProvider>>) -> Self {
+        Self { providers }
+    }
+}
+
+fn fails_over(code: &str) -> bool {
+    FAILOVER_CODES.contains(&code)
+}
+
+fn tagged(quote: &StockQuote, source: &str) -> StockQuote {
+    StockQuote {
+        source: Some(source.to_string()),
+        ..quote.clone()
+    }
+}
+
+impl QuoteProvider for FailoverProvider {
+    fn name(&self) -> &str {
+        self.providers.first().map_or("failover", |provider| provider.name())
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            let mut first_error = None;
+            for provider in &self.providers {
+                match provider.fetch_quote(symbol).await {
+                    Ok(quote) => return Ok(tagged(&quote, provider.name())),
+                    Err(e) if fails_over(e.code()) => {
+                        log::info!("{}: {} failed ({}), trying the next provider", symbol, provider.name(), e);
+                        first_error.get_or_insert(e);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(first_error.unwrap_or_else(|| ScannerError::SymbolNotFound(symbol.to_string())))
+        })
+    }
+
+    fn fetch_quotes_with<'a>(
+        &'a self,
+        symbols: &'a [String],
+        on_quote: &'a mut (dyn FnMut(&StockQuote) + Send),
+    ) -> ProviderFuture<'a, Result<(Vec<StockQuote>, Vec<SymbolError>)>> {
+        Box::pin(async move {
+            let mut quotes: HashMap<String, StockQuote> = HashMap::new();
+            let mut failed: HashMap<String, SymbolError> = HashMap::new();
+            let mut primary_error = None;
+            let mut pending = symbols.to_vec();
+
+            for (idx, provider) in self.providers.iter().enumerate() {
+                if pending.is_empty() {
+                    break;
+                }
+                if idx > 0 {
+                    log::warn!("Fetching {} symbols from {} instead", pending.len(), provider.name());
+                }
+
+                let source = provider.name();
+                let result = provider
+                    .fetch_quotes_with(&pending, &mut |quote| on_quote(&tagged(quote, source)))
+                    .await;
+                let errors = match result {
+                    Ok((served, errors)) => {
+                        for quote in served {
+                            failed.remove(&quote.symbol);
+                            quotes.insert(quote.symbol.clone(), tagged(&quote, source));
+                        }
+                        errors
+                    }
+                    // Every symbol failed the same way
+                    Err(e) => {
+                        let errors: Vec<SymbolError> = pending
+                            .iter()
+                            .map(|symbol| SymbolError {
+                                symbol: symbol.clone(),
+                                code: e.code(),
+                                error: e.root().to_string(),
+                            })
+                            .collect();
+                        if idx == 0 {
+                            primary_error = Some(e);
+                        }
+                        errors
+                    }
+                };
+
+                pending = errors
+                    .iter()
+                    .filter(|error| fails_over(error.code))
+                    .map(|error| error.symbol.clone())
+                    .collect();
+                for error in errors {
+                    // The first provider's reason is the one worth reporting
+                    failed.entry(error.symbol.clone()).or_insert(error);
+                }
+            }
+
+            let failed: Vec<SymbolError> = symbols.iter().filter_map(|symbol| failed.remove(symbol)).collect();
+            if quotes.is_empty() {
+                if let Some(e) = primary_error {
+                    return Err(e);
+                }
+                if let Some(first) = failed.first() {
+                    return Err(ScannerError::Api(first.error.clone())
+                        .context(&format!("All requests failed. First error: {}", first.symbol)));
+                }
+            }
+            let quotes = symbols.iter().filter_map(|symbol| quotes.remove(symbol)).collect();
+            Ok((quotes, failed))
+        })
+    }
+
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        Box::pin(async move {
+            // Providers without candles at this resolution count as unavailable too
+            let mut first_error = None;
+            for provider in &self.providers {
+                match provider.fetch_candles(symbol, resolution, from, to).await {
+                    Ok(candles) => return Ok(candles),
+                    Err(e) => {
+                        log::info!("{}: no candles from {} ({})", symbol, provider.name(), e);
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+            Err(first_error.unwrap_or_else(|| ScannerError::NoCandleData(symbol.to_string())))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finnhub::Quote;
+
+    /// Fails every symbol with `error`, or serves them all at `price`
+    struct Stub {
+        name: &'static str,
+        price: f64,
+        error: Option<fn(&str) -> ScannerError>,
+    }
+
+    impl QuoteProvider for Stub {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+            Box::pin(async move {
+                if let Some(error) = self.error {
+                    return Err(error(symbol));
+                }
+                let quote = Quote {
+                    c: self.price,
+                    pc: self.price,
+                    h: self.price,
+                    l: self.price,
+                    o: self.price,
+                };
+                Ok(StockQuote::from_quote(symbol.to_string(), quote))
+            })
+        }
+    }
+
+    fn chain(primary_error: Option<fn(&str) -> ScannerError>) -> FailoverProvider {
+        FailoverProvider::new(vec![
+            Arc::new(Stub {
+                name: "primary",
+                price: 1.0,
+                error: primary_error,
+            }) as Arc<dyn QuoteProvider>,
+            Arc::new(Stub {
+                name: "backup",
+                price: 2.0,
+                error: None,
+            }),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_rate_limited() {
+        let symbols = vec!["AAPL".to_string(), "MSFT".to_string()];
+        let limited = chain(Some(|_| ScannerError::RateLimited { retry_after: None }));
+        let mut seen = Vec::new();
+        let (quotes, failed) = limited
+            .fetch_quotes_with(&symbols, &mut |quote| seen.push(quote.source.clone()))
+            .await
+            .unwrap();
+
+        assert!(failed.is_empty());
+        assert_eq!(quotes.iter().map(|quote| quote.symbol.as_str()).collect::<Vec<_>>(), ["AAPL", "MSFT"]);
+        assert_eq!(quotes[0].price, 2.0);
+        assert_eq!(quotes[0].source.as_deref(), Some("backup"));
+        assert_eq!(seen, vec![Some("backup".to_string()); 2]);
+
+        let healthy = chain(None).fetch_quote("AAPL").await.unwrap();
+        assert_eq!(healthy.source.as_deref(), Some("primary"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_symbols_do_not_fall_back() {
+        let symbols = vec!["NOPE".to_string()];
+        let unknown = chain(Some(|symbol| ScannerError::SymbolNotFound(symbol.to_string())));
+        let err = unknown.fetch_quotes(&symbols).await.unwrap_err();
+        assert!(matches!(err.root(), ScannerError::SymbolNotFound(_)));
+        assert!(matches!(unknown.fetch_quote("NOPE").await, Err(ScannerError::SymbolNotFound(_))));
+    }
+}
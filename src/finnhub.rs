@@ -1,9 +1,21 @@
-use serde::Deserialize;
+use chrono::NaiveDate;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
-use crate::config::Config;
+use crate::cache::{DiskCache, Endpoint, QuoteCache};
+use crate::cassette::Cassette;
+use crate::circuit::CircuitBreaker;
+use crate::concurrency::{AdaptiveConcurrency, Feedback};
+use crate::config::{is_retryable, CacheTtls, Config, HttpConfig, RetryConfig};
 use crate::errors::{Result, ScannerError};
+use crate::exchange;
+use crate::ratelimit::{RateLimiter, TokenBucket};
+use crate::rt::{self, Instant};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Quote {
     pub c: f64,  // current price
     pub pc: f64, // previous close
@@ -15,7 +27,7 @@ pub struct Quote {
     pub o: f64,  // open
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StockQuote {
     pub symbol: String,
     pub price: f64,
@@ -24,128 +36,824 @@ pub struct StockQuote {
     pub high: f64,
     pub low: f64,
     pub open: f64,
+    /// Company name, filled in when the scan is enriched with profiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Industry classification, filled in when the scan is enriched with profiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector: Option<String>,
+    /// Provider that served the quote, when `fallback_providers` is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
+/// A symbol whose quote couldn't be fetched
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolError {
+    pub symbol: String,
+    /// As in `--error-format json`, e.g. `rate_limited`
+    pub code: &'static str,
+    pub error: String,
+}
+
+/// Company profile from the `/stock/profile2` endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyProfile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub ticker: String,
+    #[serde(default)]
+    pub exchange: String,
+    #[serde(default)]
+    pub country: String,
+    #[serde(default)]
+    pub currency: String,
+    #[serde(default)]
+    pub finnhub_industry: String,
+    /// Market capitalization in millions
+    #[serde(default)]
+    pub market_capitalization: f64,
+    #[serde(default)]
+    pub weburl: String,
+}
+
+/// An index's members from `/index/constituents`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexConstituents {
+    #[serde(default)]
+    pub constituents: Vec<String>,
+}
+
+/// One entry of an exchange's symbol universe from `/stock/symbol`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolInfo {
+    pub symbol: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub display_symbol: String,
+    #[serde(default, rename = "type")]
+    pub security_type: String,
+    #[serde(default)]
+    pub currency: String,
+}
+
+/// One result of a `/search` for a company name or partial ticker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolMatch {
+    pub symbol: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub display_symbol: String,
+    #[serde(default, rename = "type")]
+    pub security_type: String,
+}
+
+#[derive(Deserialize)]
+struct SymbolSearch {
+    #[serde(default)]
+    result: Vec<SymbolMatch>,
+}
+
+/// One article from the `/company-news` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsItem {
+    #[serde(default)]
+    pub id: i64,
+    /// Publication time as a unix timestamp
+    pub datetime: i64,
+    #[serde(default)]
+    pub headline: String,
+    #[serde(default)]
+    pub source: String,
+    /// Symbol the article was requested for
+    #[serde(default)]
+    pub related: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Current trading session from `/stock/market-status`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketStatus {
+    #[serde(default)]
+    pub is_open: bool,
+    /// "pre-market", "regular", "post-market", or null when closed
+    pub session: Option<String>,
+    pub holiday: Option<String>,
+}
+
+/// Outcome of a single unretried quote request, used by `doctor`
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub status: reqwest::StatusCode,
+    pub elapsed: Duration,
+    /// From Finnhub's `X-Ratelimit-*` headers, when present
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    /// Unix time at which `remaining` resets
+    pub reset: Option<i64>,
+}
+
+impl MarketStatus {
+    /// Whether quotes are moving, counting extended hours only when asked to
+    pub fn is_trading(&self, extended_hours: bool) -> bool {
+        match self.session.as_deref() {
+            Some("regular") => true,
+            Some("pre-market") | Some("post-market") => extended_hours,
+            _ => self.is_open,
+        }
+    }
+}
+
+/// Key metrics from the `/stock/metric` endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BasicFinancials {
+    #[serde(default)]
+    pub metric: HashMap<String, serde_json::Value>,
+}
+
+impl BasicFinancials {
+    /// Look up a numeric metric such as `52WeekHigh` or `beta`
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.metric.get(name).and_then(|value| value.as_f64())
+    }
+}
+
+/// OHLC candles as returned by the `/stock/candle` endpoint
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Candles {
+    #[serde(default)]
+    pub c: Vec<f64>, // close
+    #[serde(default)]
+    pub h: Vec<f64>, // high
+    #[serde(default)]
+    pub l: Vec<f64>, // low
+    #[serde(default)]
+    pub o: Vec<f64>, // open
+    #[serde(default)]
+    pub t: Vec<i64>, // unix timestamps
+    #[serde(default)]
+    pub v: Vec<f64>, // volume
+    pub s: String,   // status: "ok" or "no_data"
+}
+
+/// Candle resolutions accepted by Finnhub
+pub const CANDLE_RESOLUTIONS: &[&str] = &["1", "5", "15", "30", "60", "D", "W", "M"];
+
 impl StockQuote {
     pub fn from_quote(symbol: String, quote: Quote) -> Self {
-        let change_pct = if quote.pc != 0.0 {
-            ((quote.c - quote.pc) / quote.pc) * 100.0
-        } else {
-            0.0
-        };
-
         Self {
             symbol,
             price: quote.c,
             prev_close: quote.pc,
-            change_pct,
+            change_pct: crate::decimal::change_pct(quote.c, quote.pc),
             high: quote.h,
             low: quote.l,
             open: quote.o,
+            name: None,
+            sector: None,
+            source: None,
         }
     }
+
+    /// A quote that opened at `prev_close` and has traded between it and `price` since
+    #[cfg(test)]
+    pub(crate) fn test(symbol: &str, price: f64, prev_close: f64) -> Self {
+        let quote = Quote {
+            c: price,
+            pc: prev_close,
+            h: price.max(prev_close),
+            l: price.min(prev_close),
+            o: prev_close,
+        };
+        Self::from_quote(symbol.to_string(), quote)
+    }
 }
 
+/// Where requests go unless [`FinnhubClientBuilder::base_url`] says otherwise
+pub const DEFAULT_BASE_URL: &str = "https://finnhub.io/api/v1";
+
 pub struct FinnhubClient {
     api_key: String,
+    base_url: String,
     client: reqwest::Client,
-    config: Config,
+    timeout: Duration,
+    retry: RetryConfig,
+    cache_ttls: CacheTtls,
+    batch_size: usize,
+    cache: Option<Arc<QuoteCache>>,
+    disk_cache: Option<Arc<DiskCache>>,
+    cassette: Option<Arc<Cassette>>,
+    limiter: Arc<dyn RateLimiter>,
+    breaker: Arc<CircuitBreaker>,
+    concurrency: Arc<AdaptiveConcurrency>,
 }
 
-impl FinnhubClient {
-    pub fn new(api_key: String, config: Config) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
-            .build()
-            .expect("Failed to build HTTP client");
+/// Settings for a [`FinnhubClient`], starting from the same defaults as [`Config`]
+#[derive(Debug, Clone)]
+pub struct FinnhubClientBuilder {
+    api_key: String,
+    base_url: String,
+    timeout: Duration,
+    http: HttpConfig,
+    client: Option<reqwest::Client>,
+    retry: RetryConfig,
+    limiter: Option<Arc<dyn RateLimiter>>,
+    requests_per_minute: u32,
+    rate_limit_burst: u32,
+    concurrent_requests: usize,
+    slow_response_ms: u64,
+    adaptive_concurrency: bool,
+    circuit_breaker_threshold: u32,
+    circuit_breaker_cooldown_secs: u64,
+    cache_ttls: CacheTtls,
+}
+
+impl Default for FinnhubClientBuilder {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            client: None,
+            limiter: None,
+            ..Self::from_config(&Config::default())
+        }
+    }
+}
+
+impl FinnhubClientBuilder {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            api_key: config.api_key.clone(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: Duration::from_secs(config.timeout_secs),
+            http: config.http.clone(),
+            client: None,
+            retry: config.retry.clone(),
+            limiter: None,
+            requests_per_minute: config.requests_per_minute,
+            rate_limit_burst: config.rate_limit_burst,
+            concurrent_requests: config.concurrent_requests,
+            slow_response_ms: config.slow_response_ms,
+            adaptive_concurrency: config.adaptive_concurrency,
+            circuit_breaker_threshold: config.circuit_breaker_threshold,
+            circuit_breaker_cooldown_secs: config.circuit_breaker_cooldown_secs,
+            cache_ttls: config.cache_ttls.clone(),
+        }
+    }
 
+    /// Take the API key and every client setting from a loaded config file
+    ///
+    /// The base URL and an injected HTTP client or rate limiter are kept.
+    pub fn config(self, config: &Config) -> Self {
         Self {
-            api_key,
+            base_url: self.base_url,
+            client: self.client,
+            limiter: self.limiter,
+            ..Self::from_config(config)
+        }
+    }
+
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// API root the endpoint paths are appended to, e.g. a mock server in tests
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Per-request timeout; ignored when an HTTP client is injected
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Attempts and backoff for timeouts, connection errors, 5xx and 429
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Requests per minute, with up to `burst` sent without waiting
+    pub fn rate_limit(mut self, requests_per_minute: u32, burst: u32) -> Self {
+        self.requests_per_minute = requests_per_minute;
+        self.rate_limit_burst = burst;
+        self
+    }
+
+    /// Wait on `limiter` before each request instead of a token bucket built from `rate_limit`
+    pub fn rate_limiter(mut self, limiter: Arc<dyn RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Send requests through `client` instead of one built from the timeout and `[http]` settings
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> Result<FinnhubClient> {
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        reqwest::Url::parse(&base_url)
+            .map_err(|e| ScannerError::Config(format!("Invalid base URL '{}': {}", base_url, e)))?;
+        if self.limiter.is_none() && self.requests_per_minute == 0 {
+            return Err(ScannerError::Config("requests_per_minute must be at least 1".to_string()));
+        }
+
+        let client = match self.client {
+            Some(client) => client,
+            None => self.http.client_builder(self.timeout)?.build()?,
+        };
+
+        Ok(FinnhubClient {
+            api_key: self.api_key,
+            base_url,
             client,
-            config,
+            timeout: self.timeout,
+            retry: self.retry,
+            cache_ttls: self.cache_ttls,
+            batch_size: self.concurrent_requests.max(1),
+            cache: None,
+            disk_cache: None,
+            cassette: None,
+            limiter: self
+                .limiter
+                .unwrap_or_else(|| Arc::new(TokenBucket::new(self.requests_per_minute, self.rate_limit_burst))),
+            breaker: Arc::new(CircuitBreaker::new(
+                self.circuit_breaker_threshold,
+                self.circuit_breaker_cooldown_secs,
+            )),
+            concurrency: Arc::new(AdaptiveConcurrency::new(
+                self.concurrent_requests,
+                self.slow_response_ms,
+                self.adaptive_concurrency,
+            )),
+        })
+    }
+}
+
+impl FinnhubClient {
+    pub fn builder() -> FinnhubClientBuilder {
+        FinnhubClientBuilder::default()
+    }
+
+    /// A client with the API key and settings from `config`
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::builder().config(config).build()
+    }
+
+    /// Serve repeated lookups from `cache` while its entries are fresh
+    pub fn with_cache(mut self, cache: QuoteCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Cache slow-changing endpoints (profiles, peers, symbols, metrics) on disk
+    pub fn with_disk_cache(mut self, cache: DiskCache) -> Self {
+        self.disk_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Record responses to `cassette`, or answer every request from it when it's replaying
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// How many of `symbols` the quote cache would answer, without sending anything
+    pub fn cached_quotes(&self, symbols: &[String]) -> usize {
+        match &self.cache {
+            Some(cache) => symbols.iter().filter(|symbol| cache.get(symbol).is_some()).count(),
+            None => 0,
         }
     }
 
+    /// How many of `symbols` have a fresh profile in the disk cache
+    pub fn cached_profiles(&self, symbols: &[String]) -> usize {
+        let Some(cache) = &self.disk_cache else {
+            return 0;
+        };
+        let ttl = Endpoint::Profile.ttl(&self.cache_ttls);
+        symbols
+            .iter()
+            .filter(|symbol| cache.get::<CompanyProfile>(Endpoint::Profile, symbol, ttl).is_some())
+            .count()
+    }
+
+    /// An index's members from the disk cache, while they're fresh
+    pub fn cached_index_constituents(&self, index: &str) -> Option<Vec<String>> {
+        let ttl = Endpoint::Constituents.ttl(&self.cache_ttls);
+        let cached: IndexConstituents = self.disk_cache.as_ref()?.get(Endpoint::Constituents, index, ttl)?;
+        Some(cached.constituents)
+    }
+
+    /// GET an endpoint and decode the JSON body
+    async fn get_json<T: DeserializeOwned>(&self, url: &str, context: &str) -> Result<T> {
+        let Some(cassette) = &self.cassette else {
+            let response = self.get_with_retry(url, context).await?;
+            return Ok(response.json().await?);
+        };
+
+        let request = url.strip_prefix(self.base_url.as_str()).unwrap_or(url);
+        if cassette.is_replay() {
+            return Ok(serde_json::from_value(cassette.replay_response(request)?)?);
+        }
+
+        let body: serde_json::Value = self.get_with_retry(url, context).await?.json().await?;
+        if let Err(e) = cassette.record_response(request, &body) {
+            log::warn!("Failed to record {}: {}", context, e);
+        }
+        Ok(serde_json::from_value(body)?)
+    }
+
+    /// GET through the circuit breaker, rate limiter and retry policy
+    async fn get_with_retry(&self, url: &str, context: &str) -> Result<reqwest::Response> {
+        self.breaker.check()?;
+        let result = self.send_with_retry(url, context).await;
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(e) => self.breaker.record_failure(e),
+        }
+        result
+    }
+
+    /// GET `url`; browser clients have no timeout of their own, so there it's set per request
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        if cfg!(target_arch = "wasm32") {
+            request.timeout(self.timeout)
+        } else {
+            request
+        }
+    }
+
+    /// Exponential backoff on timeouts, connection errors, 5xx and 429
+    async fn send_with_retry(&self, url: &str, context: &str) -> Result<reqwest::Response> {
+        let retry = &self.retry;
+        let mut attempt = 1;
+        loop {
+            // Retries spend tokens too, so a struggling API isn't hit any harder
+            self.limiter.acquire().await;
+            let started = Instant::now();
+            let sent = self.get(url).send().await;
+            if sent.is_ok() {
+                crate::usage::record(url.strip_prefix(self.base_url.as_str()).unwrap_or(url));
+            }
+            match &sent {
+                Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    self.concurrency.record(Feedback::Throttled)
+                }
+                Ok(_) => self.concurrency.record(Feedback::Completed(started.elapsed())),
+                Err(e) if e.is_timeout() => self.concurrency.record(Feedback::Completed(started.elapsed())),
+                Err(_) => {}
+            }
+
+            let error = match sent {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if !is_retryable(response.status()) => return Err(status_error(&response, context)),
+                Ok(response) => status_error(&response, context),
+                Err(e) => ScannerError::from(e),
+            };
+
+            if attempt >= retry.max_attempts {
+                return Err(error);
+            }
+
+            // Wait at least as long as the server asked to
+            let delay = match &error {
+                ScannerError::RateLimited {
+                    retry_after: Some(wait),
+                } => retry.delay(attempt - 1, jitter()).max(*wait),
+                _ => retry.delay(attempt - 1, jitter()),
+            };
+            log::debug!("{} failed ({}), retrying in {:?}", context, error, delay);
+            rt::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// GET an endpoint through the disk cache, using the endpoint's configured TTL
+    async fn get_cached_json<T>(&self, endpoint: Endpoint, key: &str, url: &str) -> Result<T>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let ttl = endpoint.ttl(&self.cache_ttls);
+
+        if let Some(value) = self.disk_cache.as_ref().and_then(|cache| cache.get(endpoint, key, ttl)) {
+            log::debug!("Cache hit for {} {}", endpoint.name(), key);
+            return Ok(value);
+        }
+
+        log::debug!("Fetching {} for {}", endpoint.name(), key);
+        let value: T = self.get_json(url, key).await?;
+
+        if let Some(cache) = &self.disk_cache {
+            if let Err(e) = cache.put(endpoint, key, &value) {
+                log::warn!("Failed to cache {} for {}: {}", endpoint.name(), key, e);
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Latest quote for a symbol, which may also be written `EXCHANGE:TICKER` (see [`exchange::normalize`])
     pub async fn fetch_quote(&self, symbol: &str) -> Result<Quote> {
+        let symbol = &exchange::normalize(symbol);
+        if let Some(quote) = self.cache.as_ref().and_then(|cache| cache.get(symbol)) {
+            log::debug!("Cache hit for {}", symbol);
+            return Ok(quote);
+        }
+
         let url = format!(
-            "https://finnhub.io/api/v1/quote?symbol={}&token={}",
-            symbol, self.api_key
+            "{}/quote?symbol={}&token={}",
+            self.base_url, symbol, self.api_key
         );
 
         log::debug!("Fetching quote for {}", symbol);
 
-        let response = self.client.get(&url).send().await?;
+        let quote: Quote = self.get_json(&url, symbol).await?;
 
-        if !response.status().is_success() {
-            return Err(ScannerError::Api(format!(
-                "HTTP {}: {}",
-                response.status(),
-                symbol
+        // Validate we got actual data
+        if quote.c == 0.0 && quote.pc == 0.0 {
+            return Err(ScannerError::SymbolNotFound(symbol.to_string()));
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.insert(symbol, quote.clone());
+        }
+
+        Ok(quote)
+    }
+
+    /// Send one quote request straight to the API, bypassing caches, retries and the rate limiter
+    pub async fn probe(&self, symbol: &str) -> Result<Probe> {
+        let url = format!(
+            "{}/quote?symbol={}&token={}",
+            self.base_url, symbol, self.api_key
+        );
+
+        let started = Instant::now();
+        let response = self.get(&url).send().await?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Ok(Probe {
+            status: response.status(),
+            elapsed: started.elapsed(),
+            limit: header("x-ratelimit-limit").and_then(|v| v.parse().ok()),
+            remaining: header("x-ratelimit-remaining").and_then(|v| v.parse().ok()),
+            reset: header("x-ratelimit-reset").and_then(|v| v.parse().ok()),
+        })
+    }
+
+    pub async fn fetch_candles(
+        &self,
+        symbol: &str,
+        resolution: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Candles> {
+        if !CANDLE_RESOLUTIONS.contains(&resolution) {
+            return Err(ScannerError::InvalidInput(format!(
+                "Unsupported resolution '{}'. Use one of: {}",
+                resolution,
+                CANDLE_RESOLUTIONS.join(", ")
             )));
         }
 
-        let quote: Quote = response.json().await?;
+        let url = format!(
+            "{}/stock/candle?symbol={}&resolution={}&from={}&to={}&token={}",
+            self.base_url, symbol, resolution, from, to, self.api_key
+        );
 
-        // Validate we got actual data
-        if quote.c == 0.0 && quote.pc == 0.0 {
-            return Err(ScannerError::Api(format!("No data for {}", symbol)));
+        log::debug!("Fetching {} candles for {}", resolution, symbol);
+
+        let candles: Candles = self.get_json(&url, symbol).await?;
+
+        if candles.s != "ok" || candles.c.is_empty() {
+            return Err(ScannerError::NoCandleData(symbol.to_string()));
         }
 
-        Ok(quote)
+        Ok(candles)
     }
 
-    pub async fn fetch_quotes(&self, symbols: &[String]) -> Result<Vec<StockQuote>> {
-        let mut results = Vec::new();
-        let mut errors = Vec::new();
+    pub async fn fetch_profile(&self, symbol: &str) -> Result<CompanyProfile> {
+        let url = format!(
+            "{}/stock/profile2?symbol={}&token={}",
+            self.base_url, symbol, self.api_key
+        );
+        self.get_cached_json(Endpoint::Profile, symbol, &url).await
+    }
+
+    pub async fn fetch_peers(&self, symbol: &str) -> Result<Vec<String>> {
+        let url = format!(
+            "{}/stock/peers?symbol={}&token={}",
+            self.base_url, symbol, self.api_key
+        );
+        self.get_cached_json(Endpoint::Peers, symbol, &url).await
+    }
+
+    pub async fn fetch_metrics(&self, symbol: &str) -> Result<BasicFinancials> {
+        let url = format!(
+            "{}/stock/metric?symbol={}&metric=all&token={}",
+            self.base_url, symbol, self.api_key
+        );
+        self.get_cached_json(Endpoint::Metrics, symbol, &url).await
+    }
 
-        // Process in chunks to respect rate limits
-        for chunk in symbols.chunks(self.config.concurrent_requests) {
-            let mut tasks = Vec::new();
+    pub async fn fetch_symbols(&self, exchange: &str) -> Result<Vec<SymbolInfo>> {
+        let url = format!(
+            "{}/stock/symbol?exchange={}&token={}",
+            self.base_url, exchange, self.api_key
+        );
+        self.get_cached_json(Endpoint::Symbols, exchange, &url).await
+    }
 
-            for symbol in chunk {
-                let client = self.clone();
-                let symbol = symbol.clone();
+    /// Symbols matching a company name or partial ticker, best match first
+    pub async fn search_symbols(&self, query: &str) -> Result<Vec<SymbolMatch>> {
+        let mut url = reqwest::Url::parse(&format!("{}/search", self.base_url))
+            .map_err(|e| ScannerError::Config(format!("Invalid base URL {}: {}", self.base_url, e)))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("token", &self.api_key);
+        let search: SymbolSearch = self.get_json(url.as_str(), query).await?;
+        Ok(search.result)
+    }
 
-                let task = tokio::spawn(async move {
-                    (symbol.clone(), client.fetch_quote(&symbol).await)
-                });
+    /// Current members of an index such as `^GSPC`, from `/index/constituents`
+    pub async fn fetch_index_constituents(&self, index: &str) -> Result<Vec<String>> {
+        let url = format!(
+            "{}/index/constituents?symbol={}&token={}",
+            self.base_url, index, self.api_key
+        );
+        let constituents: IndexConstituents = self.get_cached_json(Endpoint::Constituents, index, &url).await?;
+        Ok(constituents.constituents)
+    }
 
-                tasks.push(task);
-            }
+    pub async fn fetch_company_news(&self, symbol: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<NewsItem>> {
+        let url = format!(
+            "{}/company-news?symbol={}&from={}&to={}&token={}",
+            self.base_url, symbol, from, to, self.api_key
+        );
+        log::debug!("Fetching news for {}", symbol);
+        self.get_json(&url, symbol).await
+    }
 
-            // Collect results
-            for task in tasks {
-                match task.await {
-                    Ok((symbol, Ok(quote))) => {
-                        results.push(StockQuote::from_quote(symbol, quote));
-                    }
-                    Ok((symbol, Err(e))) => {
-                        log::warn!("{}: {}", symbol, e);
-                        errors.push(format!("{}: {}", symbol, e));
+    pub async fn fetch_market_status(&self, exchange: &str) -> Result<MarketStatus> {
+        let url = format!(
+            "{}/stock/market-status?exchange={}&token={}",
+            self.base_url, exchange, self.api_key
+        );
+        self.get_json(&url, exchange).await
+    }
+
+    /// Fill in company name and sector from company profiles
+    ///
+    /// Profiles come from the disk cache when possible; lookups that fail are logged
+    /// and leave the quote unchanged.
+    pub async fn enrich_with_profiles(&self, quotes: &mut [StockQuote]) {
+        for chunk in quotes.chunks_mut(self.batch_size) {
+            // Joined on the caller's task rather than spawned, so this also runs without tokio
+            let lookups = chunk.iter().map(|quote| self.fetch_profile(&quote.symbol));
+            let profiles = futures::future::join_all(lookups).await;
+
+            for (quote, profile) in chunk.iter_mut().zip(profiles) {
+                match profile {
+                    Ok(profile) => {
+                        if !profile.name.is_empty() {
+                            quote.name = Some(profile.name);
+                        }
+                        if !profile.finnhub_industry.is_empty() {
+                            quote.sector = Some(profile.finnhub_industry);
+                        }
                     }
-                    Err(e) => {
-                        log::error!("Task failed: {}", e);
-                        errors.push(format!("Task error: {}", e));
+                    Err(e) => log::warn!("{}: profile lookup failed: {}", quote.symbol, e),
+                }
+            }
+        }
+    }
+
+    /// Fetch quotes concurrently, yielding each as it completes
+    ///
+    /// In-flight requests are bounded by the adaptive concurrency limit. The requests run as
+    /// futures on the caller's task, so the client is borrowed rather than cloned per symbol.
+    pub fn stream_quotes<'a>(
+        &'a self,
+        symbols: &'a [String],
+    ) -> impl Stream<Item = (String, Result<StockQuote>)> + 'a {
+        symbols
+            .iter()
+            .map(move |symbol| async move {
+                let _permit = self.concurrency.acquire().await;
+                let quote = self
+                    .fetch_quote(symbol)
+                    .await
+                    .map(|quote| StockQuote::from_quote(symbol.clone(), quote));
+                (symbol.clone(), quote)
+            })
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    pub async fn fetch_quotes(&self, symbols: &[String]) -> Result<Vec<StockQuote>> {
+        let (quotes, _) = self.fetch_quotes_with(symbols, |_| {}).await?;
+        Ok(quotes)
+    }
+
+    /// Like `fetch_quotes`, calling `on_quote` with each quote as soon as it arrives, and also
+    /// returning the symbols that failed
+    ///
+    /// The returned quotes are in the caller's symbol order; `on_quote` sees them in
+    /// completion order.
+    pub async fn fetch_quotes_with(
+        &self,
+        symbols: &[String],
+        mut on_quote: impl FnMut(&StockQuote),
+    ) -> Result<(Vec<StockQuote>, Vec<SymbolError>)> {
+        let mut results = Vec::new();
+        let mut failed = Vec::new();
+        let mut first_error = None;
+        let mut pending = Vec::new();
+
+        // Cached quotes don't need a request, so they don't count against the rate limit
+        for symbol in symbols {
+            match self.cache.as_ref().and_then(|cache| cache.get(symbol)) {
+                Some(quote) => {
+                    let quote = StockQuote::from_quote(symbol.clone(), quote);
+                    on_quote(&quote);
+                    results.push(quote);
+                }
+                None => pending.push(symbol.clone()),
+            }
+        }
+
+        if !results.is_empty() {
+            log::debug!("{} of {} quotes served from cache", results.len(), symbols.len());
+        }
+
+        let mut stream = self.stream_quotes(&pending);
+        while let Some((symbol, result)) = stream.next().await {
+            match result {
+                Ok(quote) => {
+                    on_quote(&quote);
+                    results.push(quote);
+                }
+                Err(e) => {
+                    crate::errors::report_symbol(&symbol, &e);
+                    failed.push(SymbolError {
+                        symbol: symbol.clone(),
+                        code: e.code(),
+                        error: e.to_string(),
+                    });
+                    if first_error.is_none() {
+                        first_error = Some((symbol, e));
                     }
                 }
             }
+        }
+        drop(stream);
+        log::debug!("Requested {} quotes, concurrency limit now {}", pending.len(), self.concurrency.limit());
 
-            // Rate limiting between chunks
-            tokio::time::sleep(Duration::from_millis(self.config.rate_limit_delay_ms)).await;
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.save() {
+                log::warn!("Failed to save quote cache: {}", e);
+            }
         }
 
-        if results.is_empty() && !errors.is_empty() {
-            return Err(ScannerError::Api(format!(
-                "All requests failed. First error: {}",
-                errors[0]
-            )));
+        // Keep the caller's symbol order regardless of which quotes came from cache
+        let order: HashMap<&str, usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(idx, symbol)| (symbol.as_str(), idx))
+            .collect();
+        results.sort_by_key(|quote| order.get(quote.symbol.as_str()).copied());
+        failed.sort_by_key(|failure| order.get(failure.symbol.as_str()).copied());
+
+        // Keeping the first error's kind lets a bad key or an outage be told apart
+        if let (true, Some((symbol, e))) = (results.is_empty(), first_error) {
+            return Err(e.context(&format!("All requests failed. First error: {}", symbol)));
         }
 
-        if !errors.is_empty() {
-            log::info!("Completed with {} errors", errors.len());
+        if !failed.is_empty() {
+            log::info!("Completed with {} errors", failed.len());
         }
 
-        Ok(results)
+        Ok((results, failed))
     }
 }
 
@@ -153,12 +861,50 @@ impl Clone for FinnhubClient {
     fn clone(&self) -> Self {
         Self {
             api_key: self.api_key.clone(),
+            base_url: self.base_url.clone(),
             client: self.client.clone(),
-            config: self.config.clone(),
+            timeout: self.timeout,
+            retry: self.retry.clone(),
+            cache_ttls: self.cache_ttls.clone(),
+            batch_size: self.batch_size,
+            cache: self.cache.clone(),
+            disk_cache: self.disk_cache.clone(),
+            cassette: self.cassette.clone(),
+            limiter: self.limiter.clone(),
+            breaker: self.breaker.clone(),
+            concurrency: self.concurrency.clone(),
         }
     }
 }
 
+/// The typed error for an unsuccessful response
+fn status_error(response: &reqwest::Response, context: &str) -> ScannerError {
+    match response.status() {
+        reqwest::StatusCode::UNAUTHORIZED => ScannerError::InvalidApiKey,
+        reqwest::StatusCode::TOO_MANY_REQUESTS => ScannerError::RateLimited {
+            retry_after: response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs),
+        },
+        status => ScannerError::Http {
+            status: status.as_u16(),
+            context: context.to_string(),
+        },
+    }
+}
+
+/// A value in `[0, 1)` for spreading out retry delays; doesn't need to be a good random number
+fn jitter() -> f64 {
+    let nanos = rt::SystemTime::now()
+        .duration_since(rt::UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+    nanos as f64 / 1_000_000_000.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,4 +937,48 @@ mod tests {
         let stock_quote = StockQuote::from_quote("TEST".to_string(), quote);
         assert_eq!(stock_quote.change_pct, 0.0);
     }
+
+    #[test]
+    fn test_builder_base_url() {
+        let client = FinnhubClient::builder()
+            .api_key("test")
+            .base_url("http://localhost:8080/api/v1/")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "http://localhost:8080/api/v1");
+
+        let invalid = FinnhubClient::builder().base_url("not a url").build();
+        assert!(matches!(invalid, Err(ScannerError::Config(_))));
+    }
+
+    #[test]
+    fn test_builder_config_keeps_base_url() {
+        let config = Config {
+            api_key: "from-config".to_string(),
+            concurrent_requests: 3,
+            ..Config::default()
+        };
+        let client = FinnhubClient::builder()
+            .base_url("http://localhost:8080")
+            .config(&config)
+            .build()
+            .unwrap();
+        assert_eq!(client.api_key, "from-config");
+        assert_eq!(client.base_url, "http://localhost:8080");
+        assert_eq!(client.batch_size, 3);
+
+        let unlimited = FinnhubClient::builder().rate_limit(0, 1).build();
+        assert!(unlimited.is_err());
+    }
+
+    #[test]
+    fn test_market_status_sessions() {
+        let status: MarketStatus =
+            serde_json::from_str(r#"{"exchange":"US","isOpen":true,"session":"pre-market","holiday":null}"#).unwrap();
+        assert!(!status.is_trading(false));
+        assert!(status.is_trading(true));
+
+        let closed: MarketStatus = serde_json::from_str(r#"{"isOpen":false,"session":null}"#).unwrap();
+        assert!(!closed.is_trading(true));
+    }
 }
\ No newline at end of file
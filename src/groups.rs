@@ -0,0 +1,148 @@
+use std::collections::{BTreeMap, HashSet};
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::FinnhubClient;
+
+/// Starts a group name in symbol input, as in `@sp500`
+pub const PREFIX: char = '@';
+
+/// What a built-in group stands for
+enum Members {
+    Fixed(&'static [&'static str]),
+    /// The index whose constituents are fetched
+    Index(&'static str),
+}
+
+const BUILTIN: &[(&str, Members)] = &[
+    ("sp500", Members::Index("^GSPC")),
+    ("nasdaq100", Members::Index("^NDX")),
+    ("dow30", Members::Index("^DJI")),
+    ("faang", Members::Fixed(&["META", "AAPL", "AMZN", "NFLX", "GOOGL"])),
+    ("mag7", Members::Fixed(&["AAPL", "MSFT", "GOOGL", "AMZN", "NVDA", "META", "TSLA"])),
+];
+
+/// Whether a symbol-list entry names a group rather than a symbol
+pub fn is_group(token: &str) -> bool {
+    token.starts_with(PREFIX)
+}
+
+/// Members of a group entry, or the index they have to be fetched for
+enum Lookup {
+    Members(Vec<String>),
+    Index(&'static str),
+}
+
+/// What the group `name` (without the `@`) stands for
+///
+/// Names are matched without regard to case, `[groups]` in the config before the built-in ones.
+fn lookup(name: &str, groups: &BTreeMap<String, Vec<String>>) -> Result<Lookup> {
+    let name = name.to_lowercase();
+    if let Some((_, members)) = groups.iter().find(|(group, _)| group.to_lowercase() == name) {
+        return Ok(Lookup::Members(members.iter().map(|member| member.to_uppercase()).collect()));
+    }
+    match BUILTIN.iter().find(|(group, _)| *group == name) {
+        Some((_, Members::Fixed(members))) => {
+            Ok(Lookup::Members(members.iter().map(|member| member.to_string()).collect()))
+        }
+        Some((_, Members::Index(index))) => Ok(Lookup::Index(index)),
+        None => {
+            let known: Vec<&str> = groups
+                .keys()
+                .map(String::as_str)
+                .chain(BUILTIN.iter().map(|(group, _)| *group))
+                .collect();
+            Err(ScannerError::InvalidInput(format!(
+                "Unknown symbol group @{} (known: {})",
+                name,
+                known.join(", ")
+            )))
+        }
+    }
+}
+
+/// Replace each `@group` in `symbols` with its members, keeping the first of any repeats
+///
+/// Index groups are fetched through `client`, so its disk cache keeps them for
+/// `cache_ttls.constituents_secs`.
+pub async fn expand(
+    symbols: Vec<String>,
+    groups: &BTreeMap<String, Vec<String>>,
+    client: &FinnhubClient,
+) -> Result<Vec<String>> {
+    let mut expanded = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let Some(name) = symbol.strip_prefix(PREFIX) else {
+            expanded.push(symbol);
+            continue;
+        };
+        match lookup(name, groups)? {
+            Lookup::Members(members) => expanded.extend(members),
+            Lookup::Index(index) => {
+                let members = client
+                    .fetch_index_constituents(index)
+                    .await
+                    .map_err(|e| e.context(&format!("Symbol group @{}", name.to_lowercase())))?;
+                log::debug!("@{} has {} members", name.to_lowercase(), members.len());
+                expanded.extend(members);
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    expanded.retain(|symbol| seen.insert(symbol.clone()));
+    Ok(expanded)
+}
+
+/// Like [`expand`] without sending requests; index groups whose members aren't in the disk
+/// cache are kept as they are
+pub fn expand_cached(
+    symbols: Vec<String>,
+    groups: &BTreeMap<String, Vec<String>>,
+    client: &FinnhubClient,
+) -> Result<Vec<String>> {
+    let mut expanded = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let Some(name) = symbol.strip_prefix(PREFIX) else {
+            expanded.push(symbol);
+            continue;
+        };
+        match lookup(name, groups)? {
+            Lookup::Members(members) => expanded.extend(members),
+            Lookup::Index(index) => match client.cached_index_constituents(index) {
+                Some(members) => expanded.extend(members),
+                None => expanded.push(symbol),
+            },
+        }
+    }
+
+    let mut seen = HashSet::new();
+    expanded.retain(|symbol| seen.insert(symbol.clone()));
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expand_fixed_and_configured_groups() {
+        let client = FinnhubClient::builder().api_key("test").build().unwrap();
+        let groups = BTreeMap::from([("semis".to_string(), vec!["nvda".to_string(), "AMD".to_string()])]);
+        let symbols = ["@FAANG", "NVDA", "@Semis"].map(String::from).to_vec();
+
+        let expanded = expand(symbols, &groups, &client).await.unwrap();
+        assert_eq!(expanded, ["META", "AAPL", "AMZN", "NFLX", "GOOGL", "NVDA", "AMD"]);
+
+        let err = expand(vec!["@NOPE".to_string()], &groups, &client).await.unwrap_err();
+        assert!(err.to_string().contains("known: semis, sp500"));
+    }
+
+    #[test]
+    fn test_expand_cached_keeps_uncached_indices() {
+        let client = FinnhubClient::builder().api_key("test").build().unwrap();
+        let symbols = ["@sp500", "@mag7", "TSLA"].map(String::from).to_vec();
+
+        let expanded = expand_cached(symbols, &BTreeMap::new(), &client).unwrap();
+        assert_eq!(expanded.len(), 8);
+        assert_eq!(expanded[0], "@sp500");
+    }
+}
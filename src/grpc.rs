@@ -0,0 +1,247 @@
+use crate::config::Config;
+use crate::errors::Result;
+
+/// Command-line options for `grpc`
+pub struct GrpcOptions {
+    /// Address to listen on, e.g. 127.0.0.1:50051
+    pub bind: String,
+    /// Default seconds between refreshes for `SubscribeQuotes`
+    pub interval: u64,
+}
+
+/// Serve the `scanner.v1.Scanner` service from `proto/scanner.proto` until Ctrl+C
+#[cfg(feature = "grpc")]
+pub async fn run(config: &Config, symbols: Vec<String>, options: GrpcOptions) -> Result<()> {
+    service::run(config, symbols, options).await
+}
+
+#[cfg(not(feature = "grpc"))]
+pub async fn run(_config: &Config, _symbols: Vec<String>, _options: GrpcOptions) -> Result<()> {
+    Err(crate::errors::ScannerError::InvalidInput(
+        "The gRPC server requires building with --features grpc".to_string(),
+    ))
+}
+
+// Handlers have to return tonic's Status, so helpers feeding them do too
+#[cfg(feature = "grpc")]
+#[allow(clippy::result_large_err)]
+mod service {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use chrono::Utc;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::ReceiverStream;
+    use tonic::{Request, Response, Status};
+    use super::GrpcOptions;
+    use crate::config::Config;
+    use crate::errors::{Result, ScannerError};
+    use crate::expr::{self, Expr};
+    use crate::finnhub::{FinnhubClient, StockQuote};
+    use crate::output;
+    use crate::systemd;
+
+    mod proto {
+        tonic::include_proto!("scanner.v1");
+    }
+
+    use proto::scanner_server::{Scanner, ScannerServer};
+
+    struct ScannerService {
+        client: FinnhubClient,
+        /// Used when a request names no symbols
+        symbols: Vec<String>,
+        interval: u64,
+    }
+
+    impl ScannerService {
+        fn symbols(&self, requested: Vec<String>) -> std::result::Result<Vec<String>, Status> {
+            let symbols: Vec<String> = if requested.is_empty() {
+                self.symbols.clone()
+            } else {
+                requested.iter().map(|s| s.trim().to_uppercase()).collect()
+            };
+            if symbols.is_empty() {
+                return Err(Status::invalid_argument(
+                    "No symbols requested and the server has none configured",
+                ));
+            }
+            Ok(symbols)
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Scanner for ScannerService {
+        async fn scan(
+            &self,
+            request: Request<proto::ScanRequest>,
+        ) -> std::result::Result<Response<proto::ScanResponse>, Status> {
+            let request = request.into_inner();
+            let filter = parse_filter(&request.filter)?;
+            let symbols = self.symbols(request.symbols)?;
+
+            let quotes: Vec<StockQuote> = self
+                .client
+                .fetch_quotes(&symbols)
+                .await
+                .map_err(status)?
+                .into_iter()
+                .filter(|quote| matches(&filter, quote))
+                .collect();
+
+            let summary = output::calculate_summary(&quotes);
+            let now = Utc::now().timestamp();
+            Ok(Response::new(proto::ScanResponse {
+                quotes: quotes.iter().map(|quote| to_proto(quote, now)).collect(),
+                summary: Some(proto::Summary {
+                    total: summary.total as u64,
+                    gainers: summary.gainers as u64,
+                    losers: summary.losers as u64,
+                    avg_change: summary.avg_change,
+                }),
+            }))
+        }
+
+        type SubscribeQuotesStream = ReceiverStream<std::result::Result<proto::Quote, Status>>;
+
+        async fn subscribe_quotes(
+            &self,
+            request: Request<proto::SubscribeRequest>,
+        ) -> std::result::Result<Response<Self::SubscribeQuotesStream>, Status> {
+            let request = request.into_inner();
+            let filter = parse_filter(&request.filter)?;
+            let symbols = self.symbols(request.symbols)?;
+            let secs = match request.interval_secs {
+                0 => self.interval,
+                secs => secs,
+            };
+
+            // Every subscription shares the client, and so its rate limiter
+            let client = self.client.clone();
+            let (tx, rx) = mpsc::channel(symbols.len() * 2);
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(Duration::from_secs(secs.max(1)));
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                while !tx.is_closed() {
+                    ticker.tick().await;
+                    let quotes = match client.fetch_quotes(&symbols).await {
+                        Ok(quotes) => quotes,
+                        Err(e) => {
+                            log::warn!("Subscription refresh failed: {}", e);
+                            continue;
+                        }
+                    };
+                    let now = Utc::now().timestamp();
+                    for quote in quotes.iter().filter(|quote| matches(&filter, quote)) {
+                        if tx.send(Ok(to_proto(quote, now))).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+
+            Ok(Response::new(ReceiverStream::new(rx)))
+        }
+    }
+
+    pub async fn run(config: &Config, symbols: Vec<String>, options: GrpcOptions) -> Result<()> {
+        let addr: SocketAddr = options.bind.parse().map_err(|_| {
+            ScannerError::InvalidInput(format!(
+                "Invalid gRPC address '{}', expected e.g. 127.0.0.1:50051",
+                options.bind
+            ))
+        })?;
+
+        log::info!("Serving gRPC on {}. Press Ctrl+C to exit.", addr);
+        let service = ScannerService {
+            client: FinnhubClient::from_config(config)?,
+            symbols,
+            interval: options.interval,
+        };
+        systemd::ready(&format!("Serving gRPC on {}", addr));
+        tonic::transport::Server::builder()
+            .add_service(ScannerServer::new(service))
+            .serve_with_shutdown(addr, async {
+                systemd::shutdown_signal().await;
+                systemd::stopping();
+            })
+            .await
+            .map_err(|e| ScannerError::Io(format!("gRPC server error: {}", e)))
+    }
+
+    fn parse_filter(raw: &str) -> std::result::Result<Option<Expr>, Status> {
+        if raw.trim().is_empty() {
+            return Ok(None);
+        }
+        expr::parse(raw)
+            .map(Some)
+            .map_err(|e| Status::invalid_argument(e.to_string()))
+    }
+
+    fn matches(filter: &Option<Expr>, quote: &StockQuote) -> bool {
+        match filter {
+            Some(filter) => filter.matches(quote).unwrap_or(false),
+            None => true,
+        }
+    }
+
+    fn to_proto(quote: &StockQuote, fetched_at: i64) -> proto::Quote {
+        proto::Quote {
+            symbol: quote.symbol.clone(),
+            price: quote.price,
+            prev_close: quote.prev_close,
+            change_pct: quote.change_pct,
+            high: quote.high,
+            low: quote.low,
+            open: quote.open,
+            fetched_at,
+        }
+    }
+
+    fn status(error: ScannerError) -> Status {
+        match error.root() {
+            ScannerError::InvalidInput(_) | ScannerError::NoSymbols => {
+                Status::invalid_argument(error.to_string())
+            }
+            ScannerError::InvalidApiKey => Status::unauthenticated(error.to_string()),
+            _ if matches!(error.exit_code(), 4 | 5) => Status::unavailable(error.to_string()),
+            _ => Status::internal(error.to_string()),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn service(symbols: &[&str]) -> ScannerService {
+            let config = Config {
+                api_key: "test".to_string(),
+                ..Config::default()
+            };
+            ScannerService {
+                client: FinnhubClient::from_config(&config).unwrap(),
+                symbols: symbols.iter().map(|s| s.to_string()).collect(),
+                interval: 60,
+            }
+        }
+
+        #[test]
+        fn test_request_symbols() {
+            let service = service(&["AAPL"]);
+            assert_eq!(service.symbols(Vec::new()).unwrap(), vec!["AAPL"]);
+            assert_eq!(service.symbols(vec![" tsla ".to_string()]).unwrap(), vec!["TSLA"]);
+
+            let err = self::service(&[]).symbols(Vec::new()).unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[tokio::test]
+        async fn test_scan_rejects_bad_filter() {
+            let request = Request::new(proto::ScanRequest {
+                symbols: vec!["AAPL".to_string()],
+                filter: "price >".to_string(),
+            });
+            let err = service(&[]).scan(request).await.unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        }
+    }
+}
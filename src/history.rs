@@ -0,0 +1,573 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use crate::decimal;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::StockQuote;
+
+const CSV_HEADER: &str = "timestamp,symbol,price,prev_close,change_pct,high,low,open";
+
+/// On-disk format for tick logs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// One JSON object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl LogFormat {
+    /// Pick the format from the file extension (`.csv` or anything else for NDJSON)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => LogFormat::Csv,
+            _ => LogFormat::Ndjson,
+        }
+    }
+}
+
+/// A single logged quote read back from a tick log
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryRecord {
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub quote: StockQuote,
+}
+
+impl HistoryRecord {
+    /// Parsed record timestamp, if it is valid RFC 3339
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
+/// Bulk export formats for `history export`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+    /// Apache Parquet (requires the `parquet` feature)
+    Parquet,
+}
+
+#[derive(Serialize)]
+struct TickRecord<'a> {
+    timestamp: &'a str,
+    #[serde(flatten)]
+    quote: &'a StockQuote,
+}
+
+/// Appends every watch refresh to a log file
+pub struct TickLogger {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    format: LogFormat,
+}
+
+impl TickLogger {
+    /// Open a log file for appending, writing the CSV header if the file is new
+    pub fn open(path: &Path) -> Result<Self> {
+        let format = LogFormat::from_path(path);
+        let writer = open_writer(path, format)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+            format,
+        })
+    }
+
+    /// Drop records older than `cutoff`, then reopen the rewritten file
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> Result<PruneStats> {
+        self.writer.flush()?;
+        let stats = prune_file(&self.path, cutoff)?;
+        self.writer = open_writer(&self.path, self.format)?;
+        Ok(stats)
+    }
+
+    /// Append one tick worth of quotes and flush so the file is always current
+    pub fn append(&mut self, timestamp: &str, quotes: &[StockQuote]) -> Result<()> {
+        for quote in quotes {
+            write_record(&mut self.writer, self.format, timestamp, quote)?;
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn write_record(writer: &mut impl Write, format: LogFormat, timestamp: &str, quote: &StockQuote) -> Result<()> {
+    match format {
+        LogFormat::Ndjson => {
+            let record = TickRecord { timestamp, quote };
+            writeln!(writer, "{}", serde_json::to_string(&record)?)?;
+        }
+        LogFormat::Csv => {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                timestamp,
+                quote.symbol,
+                decimal::format_fixed(quote.price, 2),
+                decimal::format_fixed(quote.prev_close, 2),
+                decimal::format_fixed(quote.change_pct, 2),
+                decimal::format_fixed(quote.high, 2),
+                decimal::format_fixed(quote.low, 2),
+                decimal::format_fixed(quote.open, 2)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn open_writer(path: &Path, format: LogFormat) -> Result<BufWriter<File>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to open log file: {}", e)))?;
+
+    let is_empty = file.metadata()?.len() == 0;
+    let mut writer = BufWriter::new(file);
+
+    if format == LogFormat::Csv && is_empty {
+        writeln!(writer, "{}", CSV_HEADER)?;
+        writer.flush()?;
+    }
+
+    Ok(writer)
+}
+
+/// Outcome of a prune pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PruneStats {
+    pub kept: usize,
+    pub removed: usize,
+}
+
+/// Rewrite a tick log keeping only records at or after `cutoff`
+///
+/// Rows whose timestamp cannot be parsed are kept rather than silently discarded.
+pub fn prune_file(path: &Path, cutoff: DateTime<Utc>) -> Result<PruneStats> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to read history file: {}", e)))?;
+    let format = LogFormat::from_path(path);
+
+    let mut kept_lines = Vec::new();
+    let mut stats = PruneStats { kept: 0, removed: 0 };
+
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        if format == LogFormat::Csv && line.starts_with("timestamp,") {
+            kept_lines.push(line);
+            continue;
+        }
+
+        match line_timestamp(line, format) {
+            Some(ts) if ts < cutoff => stats.removed += 1,
+            _ => {
+                stats.kept += 1;
+                kept_lines.push(line);
+            }
+        }
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut rewritten = kept_lines.join("\n");
+    if !rewritten.is_empty() {
+        rewritten.push('\n');
+    }
+
+    fs::write(&tmp_path, rewritten)
+        .map_err(|e| ScannerError::Io(format!("Failed to write history file: {}", e)))?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| ScannerError::Io(format!("Failed to replace history file: {}", e)))?;
+
+    Ok(stats)
+}
+
+fn line_timestamp(line: &str, format: LogFormat) -> Option<DateTime<Utc>> {
+    let raw = match format {
+        LogFormat::Ndjson => {
+            let value: serde_json::Value = serde_json::from_str(line).ok()?;
+            value.get("timestamp")?.as_str()?.to_string()
+        }
+        LogFormat::Csv => line.split(',').next()?.trim().to_string(),
+    };
+
+    parse_timestamp(&raw)
+}
+
+pub fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+/// Parse an export bound given as RFC 3339 or a local `YYYY-MM-DD` date
+///
+/// Dates used as an end bound cover the whole day.
+pub fn parse_time_bound(raw: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Some(ts) = parse_timestamp(raw) {
+        return Ok(ts);
+    }
+
+    let date = chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|_| {
+        ScannerError::InvalidInput(format!("Invalid date '{}'. Use YYYY-MM-DD or RFC 3339", raw))
+    })?;
+    let date = if end_of_day { date.succ_opt().unwrap_or(date) } else { date };
+
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|dt| dt.and_local_timezone(chrono::Local).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok_or_else(|| ScannerError::InvalidInput(format!("Invalid date '{}'", raw)))
+}
+
+/// Keep records within `[from, to)` and, if given, for the listed symbols only
+pub fn filter_records(
+    records: Vec<HistoryRecord>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    symbols: &[String],
+) -> Vec<HistoryRecord> {
+    records
+        .into_iter()
+        .filter(|record| symbols.is_empty() || symbols.contains(&record.quote.symbol))
+        .filter(|record| {
+            if from.is_none() && to.is_none() {
+                return true;
+            }
+            match record.time() {
+                Some(ts) => !matches!(from, Some(from) if ts < from) && !matches!(to, Some(to) if ts >= to),
+                None => false,
+            }
+        })
+        .collect()
+}
+
+/// Write records as CSV or NDJSON
+pub fn export_text(records: &[HistoryRecord], format: ExportFormat, writer: &mut impl Write) -> Result<()> {
+    let format = match format {
+        ExportFormat::Csv => {
+            writeln!(writer, "{}", CSV_HEADER)?;
+            LogFormat::Csv
+        }
+        ExportFormat::Ndjson => LogFormat::Ndjson,
+        ExportFormat::Parquet => {
+            return Err(ScannerError::InvalidInput(
+                "Parquet output must be written to a file with --output".to_string(),
+            ))
+        }
+    };
+
+    for record in records {
+        write_record(writer, format, &record.timestamp, &record.quote)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write records to a Parquet file with a UTC millisecond timestamp column
+#[cfg(feature = "parquet")]
+pub fn export_parquet(records: &[HistoryRecord], path: &Path) -> Result<()> {
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    let parquet_err = |e: parquet::errors::ParquetError| ScannerError::Io(format!("Parquet write failed: {}", e));
+
+    let schema = parse_message_type(
+        "message tick {
+            REQUIRED INT64 timestamp (TIMESTAMP(MILLIS,true));
+            REQUIRED BYTE_ARRAY symbol (UTF8);
+            REQUIRED DOUBLE price;
+            REQUIRED DOUBLE prev_close;
+            REQUIRED DOUBLE change_pct;
+            REQUIRED DOUBLE high;
+            REQUIRED DOUBLE low;
+            REQUIRED DOUBLE open;
+        }",
+    )
+    .map_err(parquet_err)?;
+
+    // Parquet needs a real timestamp, so rows without one are left out
+    let rows: Vec<(i64, &StockQuote)> = records
+        .iter()
+        .filter_map(|record| record.time().map(|ts| (ts.timestamp_millis(), &record.quote)))
+        .collect();
+
+    let file = File::create(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to create export file: {}", e)))?;
+    let props = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), props).map_err(parquet_err)?;
+    let mut row_group = writer.next_row_group().map_err(parquet_err)?;
+
+    let timestamps: Vec<i64> = rows.iter().map(|(ts, _)| *ts).collect();
+    let symbols: Vec<ByteArray> = rows.iter().map(|(_, q)| ByteArray::from(q.symbol.as_str())).collect();
+    let doubles: [Vec<f64>; 6] = [
+        rows.iter().map(|(_, q)| q.price).collect(),
+        rows.iter().map(|(_, q)| q.prev_close).collect(),
+        rows.iter().map(|(_, q)| q.change_pct).collect(),
+        rows.iter().map(|(_, q)| q.high).collect(),
+        rows.iter().map(|(_, q)| q.low).collect(),
+        rows.iter().map(|(_, q)| q.open).collect(),
+    ];
+
+    let mut column_idx = 0;
+    while let Some(mut column) = row_group.next_column().map_err(parquet_err)? {
+        match column_idx {
+            0 => column.typed::<Int64Type>().write_batch(&timestamps, None, None),
+            1 => column.typed::<ByteArrayType>().write_batch(&symbols, None, None),
+            n => column.typed::<DoubleType>().write_batch(&doubles[n - 2], None, None),
+        }
+        .map_err(parquet_err)?;
+        column.close().map_err(parquet_err)?;
+        column_idx += 1;
+    }
+
+    row_group.close().map_err(parquet_err)?;
+    writer.close().map_err(parquet_err)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn export_parquet(_records: &[HistoryRecord], _path: &Path) -> Result<()> {
+    Err(ScannerError::InvalidInput(
+        "Parquet export requires building with --features parquet".to_string(),
+    ))
+}
+
+/// Cutoff timestamp for a `keep_days` retention window; windows reaching past chrono's range keep everything
+pub fn retention_cutoff(keep_days: u64) -> DateTime<Utc> {
+    i64::try_from(keep_days)
+        .ok()
+        .and_then(chrono::TimeDelta::try_days)
+        .and_then(|window| Utc::now().checked_sub_signed(window))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// Read every record from a tick log, in file order
+pub fn read_records(path: &Path) -> Result<Vec<HistoryRecord>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to read history file: {}", e)))?;
+
+    let lines = content.lines().filter(|line| !line.trim().is_empty());
+
+    match LogFormat::from_path(path) {
+        LogFormat::Ndjson => lines
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect(),
+        LogFormat::Csv => lines
+            .filter(|line| !line.starts_with("timestamp,"))
+            .map(parse_csv_record)
+            .collect(),
+    }
+}
+
+fn parse_csv_record(line: &str) -> Result<HistoryRecord> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() != 8 {
+        return Err(ScannerError::Parse(format!("Malformed history row: {}", line)));
+    }
+
+    let number = |value: &str| {
+        value
+            .parse::<f64>()
+            .map_err(|e| ScannerError::Parse(format!("Invalid number '{}': {}", value, e)))
+    };
+
+    Ok(HistoryRecord {
+        timestamp: fields[0].to_string(),
+        quote: StockQuote {
+            symbol: fields[1].to_string(),
+            price: number(fields[2])?,
+            prev_close: number(fields[3])?,
+            change_pct: number(fields[4])?,
+            high: number(fields[5])?,
+            low: number(fields[6])?,
+            open: number(fields[7])?,
+            name: None,
+            sector: None,
+            source: None,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(LogFormat::from_path(Path::new("ticks.csv")), LogFormat::Csv);
+        assert_eq!(LogFormat::from_path(Path::new("ticks.ndjson")), LogFormat::Ndjson);
+        assert_eq!(LogFormat::from_path(Path::new("ticks")), LogFormat::Ndjson);
+    }
+
+    #[test]
+    fn test_ndjson_append() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ticks.ndjson");
+
+        let mut logger = TickLogger::open(&path).unwrap();
+        logger.append("2026-01-02T15:30:00+00:00", &[StockQuote::test("AAPL", 101.0, 100.0)]).unwrap();
+        logger.append("2026-01-02T15:31:00+00:00", &[StockQuote::test("MSFT", 101.0, 100.0)]).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let value: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(value["symbol"], "MSFT");
+        assert_eq!(value["timestamp"], "2026-01-02T15:31:00+00:00");
+    }
+
+    #[test]
+    fn test_csv_header_written_once() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ticks.csv");
+
+        for _ in 0..2 {
+            let mut logger = TickLogger::open(&path).unwrap();
+            logger.append("2026-01-02T15:30:00+00:00", &[StockQuote::test("AAPL", 101.0, 100.0)]).unwrap();
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.matches("timestamp,symbol").count(), 1);
+        assert_eq!(content.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_read_records_round_trip() {
+        let dir = tempdir().unwrap();
+
+        for name in ["ticks.ndjson", "ticks.csv"] {
+            let path = dir.path().join(name);
+            let mut logger = TickLogger::open(&path).unwrap();
+            let quotes = [StockQuote::test("AAPL", 101.0, 100.0), StockQuote::test("MSFT", 101.0, 100.0)];
+            logger.append("2026-01-02T15:30:00+00:00", &quotes).unwrap();
+
+            let records = read_records(&path).unwrap();
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[1].quote.symbol, "MSFT");
+            assert_eq!(records[0].quote.price, 101.0);
+        }
+    }
+
+    #[test]
+    fn test_prune_drops_old_records() {
+        let dir = tempdir().unwrap();
+        let cutoff = DateTime::parse_from_rfc3339("2026-01-02T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        for name in ["ticks.ndjson", "ticks.csv"] {
+            let path = dir.path().join(name);
+            let mut logger = TickLogger::open(&path).unwrap();
+            logger.append("2026-01-01T15:30:00+00:00", &[StockQuote::test("OLD", 101.0, 100.0)]).unwrap();
+            logger.append("2026-01-03T15:30:00+00:00", &[StockQuote::test("NEW", 101.0, 100.0)]).unwrap();
+
+            let stats = logger.prune(cutoff).unwrap();
+            assert_eq!(stats, PruneStats { kept: 1, removed: 1 });
+
+            // The logger keeps appending to the rewritten file
+            logger.append("2026-01-04T15:30:00+00:00", &[StockQuote::test("NEWER", 101.0, 100.0)]).unwrap();
+
+            let symbols: Vec<String> = read_records(&path)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.quote.symbol)
+                .collect();
+            assert_eq!(symbols, vec!["NEW", "NEWER"]);
+        }
+    }
+
+    #[test]
+    fn test_retention_cutoff_saturates() {
+        assert!(retention_cutoff(1) < Utc::now());
+        assert_eq!(retention_cutoff(u64::MAX), DateTime::<Utc>::MIN_UTC);
+        assert_eq!(retention_cutoff(i64::MAX as u64 / 86_400), DateTime::<Utc>::MIN_UTC);
+    }
+
+    #[test]
+    fn test_filter_records_by_range_and_symbol() {
+        let record = |ts: &str, symbol: &str| HistoryRecord {
+            timestamp: ts.to_string(),
+            quote: StockQuote::test(symbol, 101.0, 100.0),
+        };
+        let records = vec![
+            record("2026-01-01T12:00:00+00:00", "AAPL"),
+            record("2026-01-02T12:00:00+00:00", "AAPL"),
+            record("2026-01-02T12:00:00+00:00", "MSFT"),
+            record("2026-01-03T12:00:00+00:00", "AAPL"),
+        ];
+
+        let from = parse_time_bound("2026-01-02T00:00:00+00:00", false).unwrap();
+        let to = parse_time_bound("2026-01-03T00:00:00+00:00", true).unwrap();
+        let filtered = filter_records(records, Some(from), Some(to), &["AAPL".to_string()]);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].timestamp, "2026-01-02T12:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_date_bound_covers_whole_day() {
+        let start = parse_time_bound("2026-03-10", false).unwrap();
+        let end = parse_time_bound("2026-03-10", true).unwrap();
+        assert_eq!((end - start).num_hours(), 24);
+        assert!(parse_time_bound("last tuesday", false).is_err());
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let records = vec![HistoryRecord {
+            timestamp: "2026-01-02T12:00:00+00:00".to_string(),
+            quote: StockQuote::test("AAPL", 101.0, 100.0),
+        }];
+
+        let mut out = Vec::new();
+        export_text(&records, ExportFormat::Csv, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().next(), Some(CSV_HEADER));
+        assert!(text.contains("AAPL,101.00"));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_export_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ticks.parquet");
+        let records = vec![
+            HistoryRecord {
+                timestamp: "2026-01-02T12:00:00+00:00".to_string(),
+                quote: StockQuote::test("AAPL", 101.0, 100.0),
+            },
+            HistoryRecord {
+                timestamp: "not a timestamp".to_string(),
+                quote: StockQuote::test("BAD", 101.0, 100.0),
+            },
+        ];
+
+        export_parquet(&records, &path).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+    }
+}
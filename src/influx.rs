@@ -0,0 +1,160 @@
+use std::time::Duration;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use crate::config::HttpConfig;
+use crate::errors::Result;
+use crate::finnhub::StockQuote;
+use crate::history;
+
+/// Measurement written when none is configured, and by `--output influx`
+pub const DEFAULT_MEASUREMENT: &str = "quote";
+
+/// `[influxdb]`: where quote updates are written through the HTTP write API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxConfig {
+    /// Server URL, e.g. `http://localhost:8086`
+    pub url: String,
+
+    /// InfluxDB 2 bucket; with 1.8+ use `database/retention_policy`
+    pub bucket: String,
+
+    pub org: Option<String>,
+
+    /// API token; with 1.8+ use `username:password`
+    pub token: Option<String>,
+
+    #[serde(default = "default_measurement")]
+    pub measurement: String,
+}
+
+fn default_measurement() -> String {
+    String::from(DEFAULT_MEASUREMENT)
+}
+
+/// One quote as a line of InfluxDB line protocol, tagged by symbol (and sector when known)
+pub fn line(measurement: &str, quote: &StockQuote, timestamp_ns: i64) -> Option<String> {
+    let fields: Vec<String> = [
+        ("price", quote.price),
+        ("prev_close", quote.prev_close),
+        ("change_pct", quote.change_pct),
+        ("high", quote.high),
+        ("low", quote.low),
+        ("open", quote.open),
+    ]
+    .iter()
+    .filter(|(_, value)| value.is_finite())
+    .map(|(name, value)| format!("{}={}", name, value))
+    .collect();
+    if fields.is_empty() || quote.symbol.is_empty() {
+        return None;
+    }
+
+    let mut series = format!("{},symbol={}", escape(measurement, &[',', ' ']), escape_tag(&quote.symbol));
+    if let Some(sector) = quote.sector.as_deref().filter(|sector| !sector.is_empty()) {
+        series.push_str(",sector=");
+        series.push_str(&escape_tag(sector));
+    }
+    Some(format!("{} {} {}", series, fields.join(","), timestamp_ns))
+}
+
+/// Nanoseconds since the epoch for an RFC 3339 timestamp, or for now if it doesn't parse
+pub fn timestamp_ns(timestamp: &str) -> i64 {
+    history::parse_timestamp(timestamp)
+        .unwrap_or_else(Utc::now)
+        .timestamp_nanos_opt()
+        .unwrap_or_default()
+}
+
+fn escape_tag(value: &str) -> String {
+    escape(value, &[',', '=', ' '])
+}
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Writes each refresh's quotes to InfluxDB in one request
+pub struct InfluxSink {
+    http: reqwest::Client,
+    /// Also set per request, see [`HttpConfig::client_builder`]
+    timeout: Duration,
+    config: InfluxConfig,
+}
+
+impl InfluxSink {
+    pub fn connect(config: &InfluxConfig, http: &HttpConfig, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            http: http.client_builder(timeout)?.build()?,
+            timeout,
+            config: config.clone(),
+        })
+    }
+
+    pub async fn quotes(&self, timestamp: &str, quotes: &[StockQuote]) {
+        let timestamp_ns = timestamp_ns(timestamp);
+        let body: Vec<String> = quotes
+            .iter()
+            .filter_map(|quote| line(&self.config.measurement, quote, timestamp_ns))
+            .collect();
+        if body.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.write(body.join("\n")).await {
+            log::warn!("Failed to write quotes to InfluxDB: {}", e);
+        }
+    }
+
+    async fn write(&self, body: String) -> std::result::Result<(), String> {
+        let url = format!("{}/api/v2/write", self.config.url.trim_end_matches('/'));
+        let mut query = vec![("bucket", self.config.bucket.as_str()), ("precision", "ns")];
+        if let Some(org) = &self.config.org {
+            query.push(("org", org));
+        }
+
+        let mut request = self.http.post(url).timeout(self.timeout).query(&query).body(body);
+        if let Some(token) = &self.config.token {
+            request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        let status = response.status();
+        let detail = response.text().await.unwrap_or_default();
+        Err(format!("HTTP {}: {}", status, detail.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        assert_eq!(
+            line("quote", &StockQuote::test("AAPL", 190.0, 200.0), 1704209400000000000).unwrap(),
+            "quote,symbol=AAPL price=190,prev_close=200,change_pct=-5,high=200,low=190,open=200 \
+             1704209400000000000"
+        );
+    }
+
+    #[test]
+    fn test_line_escaping() {
+        let mut quote = StockQuote::test("AAPL", 190.0, 200.0);
+        quote.sector = Some("Consumer Electronics, Hardware".to_string());
+        quote.high = f64::NAN;
+        let line = line("stock quote", &quote, 0).unwrap();
+        assert!(line.starts_with(r"stock\ quote,symbol=AAPL,sector=Consumer\ Electronics\,\ Hardware "));
+        assert!(!line.contains("high="));
+
+        assert_eq!(timestamp_ns("2024-01-02T15:30:00+00:00"), 1704209400000000000);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// `[kafka]`: cluster and topics quote updates and alerts are produced to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaConfig {
+    /// Bootstrap servers, e.g. `kafka1:9092,kafka2:9092`
+    pub brokers: String,
+
+    /// Topic for quote updates, keyed by symbol; empty disables
+    #[serde(default = "default_quote_topic")]
+    pub quote_topic: String,
+
+    /// Topic for fired alerts, keyed by symbol; empty disables
+    #[serde(default = "default_alert_topic")]
+    pub alert_topic: String,
+
+    /// Extra librdkafka producer properties, such as `security.protocol` or `sasl.username`
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+fn default_quote_topic() -> String {
+    String::from("market.quotes")
+}
+
+fn default_alert_topic() -> String {
+    String::from("market.alerts")
+}
+
+#[cfg(feature = "kafka")]
+pub use sink::KafkaSink;
+
+#[cfg(feature = "kafka")]
+mod sink {
+    use std::time::Duration;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{DeliveryFuture, FutureProducer, FutureRecord, Producer};
+    use super::KafkaConfig;
+    use crate::alerts::AlertRecord;
+    use crate::errors::{Result, ScannerError};
+    use crate::finnhub::StockQuote;
+    use crate::sinks::QuoteMessage;
+
+    /// How long queued messages may take to be delivered when the sink is dropped
+    const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub struct KafkaSink {
+        producer: FutureProducer,
+        config: KafkaConfig,
+    }
+
+    impl KafkaSink {
+        pub fn connect(config: &KafkaConfig) -> Result<Self> {
+            let mut client = ClientConfig::new();
+            client
+                .set("bootstrap.servers", &config.brokers)
+                .set("client.id", "finnhub-scanner");
+            for (key, value) in &config.properties {
+                client.set(key, value);
+            }
+            let producer = client
+                .create()
+                .map_err(|e| ScannerError::Config(format!("Invalid [kafka] settings: {}", e)))?;
+
+            Ok(Self {
+                producer,
+                config: config.clone(),
+            })
+        }
+
+        /// Produce the quotes fetched by one refresh
+        pub fn quotes(&self, timestamp: &str, quotes: &[StockQuote]) {
+            if self.config.quote_topic.is_empty() {
+                return;
+            }
+            let deliveries = quotes
+                .iter()
+                .filter_map(|quote| {
+                    let payload = serde_json::to_vec(&QuoteMessage { timestamp, quote }).unwrap_or_default();
+                    self.produce(&self.config.quote_topic, &quote.symbol, &payload)
+                })
+                .collect();
+            report(deliveries);
+        }
+
+        /// Produce alerts fired by one refresh
+        pub fn alerts(&self, records: &[AlertRecord]) {
+            if self.config.alert_topic.is_empty() {
+                return;
+            }
+            let deliveries = records
+                .iter()
+                .filter_map(|record| {
+                    let payload = serde_json::to_vec(record).unwrap_or_default();
+                    self.produce(&self.config.alert_topic, &record.symbol, &payload)
+                })
+                .collect();
+            report(deliveries);
+        }
+
+        /// Queue one message; messages with the same key land on the same partition, in order
+        fn produce(&self, topic: &str, key: &str, payload: &[u8]) -> Option<DeliveryFuture> {
+            let record = FutureRecord::to(topic).key(key).payload(payload);
+            match self.producer.send_result(record) {
+                Ok(delivery) => Some(delivery),
+                Err((e, _)) => {
+                    log::warn!("Dropped Kafka message for {}: {}", topic, e);
+                    None
+                }
+            }
+        }
+    }
+
+    impl Drop for KafkaSink {
+        fn drop(&mut self) {
+            if let Err(e) = self.producer.flush(FLUSH_TIMEOUT) {
+                log::warn!("Some Kafka messages were not delivered: {}", e);
+            }
+        }
+    }
+
+    /// Log failed deliveries in the background, so refreshes don't wait on the cluster
+    fn report(deliveries: Vec<DeliveryFuture>) {
+        if deliveries.is_empty() {
+            return;
+        }
+        tokio::spawn(async move {
+            for delivery in deliveries {
+                match delivery.await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err((e, _))) => log::warn!("Failed to deliver Kafka message: {}", e),
+                    Err(_) => log::warn!("Kafka message was dropped before delivery"),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let config: KafkaConfig = toml::from_str(
+            r#"
+            brokers = "localhost:9092"
+
+            [properties]
+            "security.protocol" = "SASL_SSL"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.quote_topic, "market.quotes");
+        assert_eq!(config.alert_topic, "market.alerts");
+        assert_eq!(config.properties["security.protocol"], "SASL_SSL");
+    }
+}
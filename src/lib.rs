@@ -0,0 +1,149 @@
+//! Stock scanning on top of the [Finnhub](https://finnhub.io) API.
+//!
+//! The `finnhub-scanner` binary is a thin command line over this library, so other programs can
+//! fetch, filter and render quotes the same way it does:
+//!
+//! - [`finnhub`]: the rate-limited, retrying API client and the quote models
+//! - [`provider`]: the [`QuoteProvider`] trait, for other quote sources and test doubles
+//! - [`ratelimit`]: the [`ratelimit::RateLimiter`] trait, for limits shared between processes
+//! - [`output`]: row filters, sorting, summaries and the table, JSON, CSV and NDJSON renderers
+//! - [`expr`]: condition expressions such as `price < 50 and change_pct > 2`
+//! - [`config`]: settings read from TOML and the environment
+//! - [`alerts`], [`watch`], [`server`], [`grpc`], [`history`]: the commands' building blocks
+//!
+//! The client, providers, filters and renderers also build for `wasm32-unknown-unknown`
+//! (`--no-default-features`), for dashboards that fetch and filter quotes in the browser. There
+//! requests go through `fetch`, and the long-running pieces — `watch`, `alerts run`, the servers
+//! and config reloading — are left out.
+//!
+//! ```no_run
+//! use finnhub_scanner::{FinnhubClient, QuoteFilter};
+//! use std::time::Duration;
+//!
+//! # async fn scan() -> finnhub_scanner::Result<()> {
+//! let client = FinnhubClient::builder()
+//!     .api_key("your_api_key")
+//!     .timeout(Duration::from_secs(10))
+//!     .rate_limit(60, 10)
+//!     .build()?;
+//!
+//! let symbols = vec!["AAPL".to_string(), "MSFT".to_string()];
+//! let filter = QuoteFilter {
+//!     gainers_only: true,
+//!     ..QuoteFilter::default()
+//! };
+//! for quote in filter.apply(client.fetch_quotes(&symbols).await?) {
+//!     println!("{} {:.2} ({:+.2}%)", quote.symbol, quote.price, quote.change_pct);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Notification actions: terminal, desktop, webhook, Slack, Discord, email and exec
+pub mod actions;
+/// Alert rules, their evaluation and the alert log
+pub mod alerts;
+/// Alpha Vantage quote provider
+pub mod alphavantage;
+/// Synchronous client (needs the `blocking` feature)
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+/// Broker position exports converted to holdings
+pub mod brokers;
+/// Quote and endpoint caches with TTLs
+pub mod cache;
+/// Recording and replaying API responses
+pub mod cassette;
+/// Terminal sparklines and line charts
+pub mod chart;
+mod circuit;
+mod concurrency;
+/// Configuration from TOML files and environment variables
+pub mod config;
+/// Price arithmetic, in `rust_decimal` with the `decimal` feature
+pub mod decimal;
+/// Health-check checklist
+pub mod doctor;
+/// Offline quotes from end-of-day CSV files
+pub mod eod;
+/// Error type and exit statuses
+pub mod errors;
+/// API call estimates for dry runs and budget planning
+#[cfg(not(target_arch = "wasm32"))]
+pub mod estimate;
+/// Exchange prefixes, suffixes and currencies of international symbols
+pub mod exchange;
+/// Condition expressions for filters and alert rules
+pub mod expr;
+/// Provider chains that fall back when the primary is unavailable
+pub mod failover;
+/// Finnhub API client and quote models
+pub mod finnhub;
+/// Symbol groups such as `@sp500` in symbol input
+pub mod groups;
+/// gRPC server (needs the `grpc` feature)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod grpc;
+/// Tick logs: recording, pruning and export
+pub mod history;
+/// InfluxDB writer
+pub mod influx;
+/// Kafka producer (needs the `kafka` feature)
+pub mod kafka;
+/// Quotes from JSON fixture files for `--provider mock`
+pub mod mock;
+/// MQTT publisher (needs the `mqtt` feature)
+pub mod mqtt;
+/// Filters, sorting, summaries and output formats
+pub mod output;
+/// Per-user config, data and cache directories
+pub mod paths;
+/// Polygon.io quote and aggregates provider
+pub mod polygon;
+/// Holdings files valued at live quotes
+pub mod portfolio;
+/// PostgreSQL sink (needs the `postgres` feature)
+pub mod postgres;
+/// The quote source trait that `scan`, `watch` and `chart` run against
+pub mod provider;
+/// Request pacing: the [`ratelimit::RateLimiter`] trait and the default token bucket
+pub mod ratelimit;
+/// Redis publisher (needs the `redis` feature)
+pub mod redis;
+/// Config and symbols file reloading
+#[cfg(not(target_arch = "wasm32"))]
+pub mod reload;
+/// Company-name lookup and listing checks for symbol input
+pub mod resolve;
+mod rt;
+/// HTTP server for the latest quotes
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+/// Databases and local streams quotes are written to
+pub mod sinks;
+mod socket;
+#[cfg(not(target_arch = "wasm32"))]
+mod systemd;
+/// Tiingo IEX quote and end-of-day price provider
+pub mod tiingo;
+#[cfg(feature = "cli")]
+mod tui;
+/// Twelve Data quote and time series provider
+pub mod twelvedata;
+/// API call accounting
+pub mod usage;
+/// Cross-provider price comparison
+pub mod verify;
+/// Watch sessions and their refresh loop
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
+/// Named symbol lists for `--watchlist`
+pub mod watchlist;
+/// Yahoo Finance quote provider
+pub mod yahoo;
+
+pub use config::Config;
+pub use errors::{Result, ScannerError};
+pub use finnhub::{FinnhubClient, FinnhubClientBuilder, StockQuote};
+pub use output::{OutputFormat, QuoteFilter};
+pub use provider::QuoteProvider;
@@ -1,16 +1,24 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-use std::time::Duration;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::{ArgValueCandidates, CompletionCandidate};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
 
-mod config;
-mod errors;
-mod finnhub;
-mod output;
+use finnhub_scanner::{
+    actions, alerts, brokers, cache, cassette, chart, config, decimal, doctor, errors, estimate, expr,
+    finnhub, groups, grpc, history, output, paths, portfolio, provider, reload, resolve, server, sinks, usage,
+    verify, watch, watchlist,
+};
 
+use brokers::BrokerFormat;
+use cassette::Cassette;
 use config::Config;
 use errors::Result;
 use finnhub::FinnhubClient;
 use output::OutputFormat;
+use provider::ProviderKind;
 
 #[derive(Parser)]
 #[command(name = "finnhub-scanner")]
@@ -19,15 +27,65 @@ use output::OutputFormat;
 #[command(about = "Professional stock market scanner using Finnhub API", long_about = None)]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 
-    /// Path to config file
+    /// Symbols to scan when no command is given: `finnhub-scanner AAPL,MSFT` runs
+    /// `scan --symbols AAPL,MSFT`, and `finnhub-scanner` alone scans the configured symbols
+    #[arg(value_delimiter = ',', value_name = "SYMBOLS")]
+    symbols: Vec<String>,
+
+    /// Path to config file (default: finnhub-scanner/config.toml in the user config directory)
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
 
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
+
+    /// Print only data rows: no banners, summaries or log messages other than errors
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Write errors and log messages to stderr as text or as JSON lines with an error code
+    #[arg(long, value_enum, default_value = "text")]
+    error_format: errors::ErrorFormat,
+
+    /// Always fetch fresh quotes instead of reusing cached ones
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Read the API key from stdin instead of the config or environment
+    #[arg(long)]
+    api_key_stdin: bool,
+
+    /// Take the best search match for symbols that look like company names, instead of asking
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// Apply a [profiles.<NAME>] section of the config file
+    #[arg(short, long, value_name = "NAME", add = ArgValueCandidates::new(profile_candidates))]
+    profile: Option<String>,
+
+    /// Where scan, watch and chart get prices from
+    #[arg(long, value_enum, global = true, default_value = "finnhub")]
+    provider: ProviderKind,
+
+    /// Directory of <SYMBOL>.json quote fixtures for --provider mock, or of end-of-day CSV files
+    /// for --provider csv
+    #[arg(long, value_name = "DIR", global = true)]
+    fixtures: Option<PathBuf>,
+
+    /// Serve --provider csv quotes as of the close of this day (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE", global = true)]
+    as_of: Option<chrono::NaiveDate>,
+
+    /// Save every API response to an NDJSON cassette file
+    #[arg(long, value_name = "FILE", global = true, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer API requests from a cassette saved with --record instead of the network
+    #[arg(long, value_name = "FILE", global = true)]
+    replay: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -38,29 +96,51 @@ enum Commands {
         #[arg(short, long, value_delimiter = ',')]
         symbols: Option<Vec<String>>,
 
-        /// Path to symbols file (one per line)
+        /// Path to symbols file (one per line, - for stdin)
         #[arg(short = 'f', long)]
         symbols_file: Option<PathBuf>,
 
-        /// Output format
-        #[arg(short, long, value_enum, default_value = "table")]
-        output: OutputFormat,
+        /// Scan a saved watchlist (see `watchlist add`)
+        #[arg(
+            short = 'w',
+            long,
+            value_name = "NAME",
+            conflicts_with_all = ["symbols", "symbols_file"],
+            add = ArgValueCandidates::new(watchlist_candidates)
+        )]
+        watchlist: Option<String>,
+
+        /// Output format (defaults to default_output in config)
+        #[arg(short, long, value_enum)]
+        output: Option<OutputFormat>,
 
         /// Sort by absolute change
         #[arg(long)]
         sort_by_change: bool,
 
-        /// Show only gainers
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Add company name and sector columns from (cached) profiles
+        #[arg(long)]
+        enrich: bool,
+
+        /// Add a status column to CSV output, with a row for each symbol that couldn't be fetched
         #[arg(long)]
-        gainers_only: bool,
+        csv_status: bool,
 
-        /// Show only losers
+        /// Output nothing and exit with 2 if any symbol couldn't be fetched
         #[arg(long)]
-        losers_only: bool,
+        strict: bool,
 
-        /// Minimum absolute change threshold (%)
+        /// Print the API calls the scan would make and how long they would take, without sending any
         #[arg(long)]
-        min_change: Option<f64>,
+        dry_run: bool,
+
+        /// Also write the results to a database or local stream: postgres://user@host/db,
+        /// unix:///path/to.sock or pipe:///path/to.fifo (repeatable)
+        #[arg(long = "sink", value_name = "URL")]
+        sinks: Vec<String>,
     },
 
     /// Watch stocks with continuous updates
@@ -69,17 +149,262 @@ enum Commands {
         #[arg(short, long, value_delimiter = ',')]
         symbols: Option<Vec<String>>,
 
-        /// Path to symbols file
+        /// Path to symbols file (- for stdin)
         #[arg(short = 'f', long)]
         symbols_file: Option<PathBuf>,
 
-        /// Update interval in seconds
-        #[arg(short, long, default_value = "60")]
-        interval: u64,
+        /// Watch a saved watchlist, picking up changes to it (see `watchlist add`)
+        #[arg(
+            short = 'w',
+            long,
+            value_name = "NAME",
+            conflicts_with_all = ["symbols", "symbols_file"],
+            add = ArgValueCandidates::new(watchlist_candidates)
+        )]
+        watchlist: Option<String>,
+
+        /// Update interval in seconds (defaults to watch.interval_secs in config)
+        #[arg(short, long)]
+        interval: Option<u64>,
+
+        /// Print each refresh as table, json, csv, compact, ndjson, or influx instead of the dashboard
+        #[arg(short, long, value_enum)]
+        output: Option<OutputFormat>,
+
+        /// Append a timestamped block on each refresh instead of clearing the screen
+        #[arg(long)]
+        no_clear: bool,
+
+        /// Exit after this many refreshes
+        #[arg(long, value_name = "N")]
+        iterations: Option<u64>,
+
+        /// Exit at this local time, e.g. 16:00 (tomorrow if it has already passed)
+        #[arg(long, value_name = "HH:MM")]
+        until: Option<String>,
+
+        /// Slow down polling while the market is closed (see [watch] in the config)
+        #[arg(long)]
+        market_hours: bool,
+
+        /// Refresh some symbols on their own interval, e.g. AAPL,TSLA=10 (repeatable)
+        #[arg(long, value_name = "SYMBOLS=SECS")]
+        symbol_interval: Vec<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Append every refresh to a log file (.csv for CSV, otherwise NDJSON)
+        #[arg(long, value_name = "FILE")]
+        log_file: Option<PathBuf>,
+
+        /// Also write every refresh to a database or local stream: postgres://user@host/db,
+        /// unix:///path/to.sock or pipe:///path/to.fifo (repeatable)
+        #[arg(long = "sink", value_name = "URL")]
+        sinks: Vec<String>,
+
+        /// Evaluate the config's alert rules on every refresh
+        #[arg(long)]
+        alerts: bool,
+
+        /// Ring the terminal bell (or play alerts.sound_file) when an alert fires; implies --alerts
+        #[arg(long)]
+        bell: bool,
+
+        /// Reprint a plain table on each refresh instead of the interactive dashboard
+        #[arg(long)]
+        plain: bool,
+    },
+
+    /// Keep quotes refreshed and serve them as JSON over HTTP
+    Serve {
+        /// Stock symbols to serve (comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        symbols: Option<Vec<String>>,
+
+        /// Path to symbols file (- for stdin)
+        #[arg(short = 'f', long)]
+        symbols_file: Option<PathBuf>,
+
+        /// Address to listen on (defaults to server.bind in config)
+        #[arg(long, value_name = "ADDR")]
+        bind: Option<String>,
+
+        /// Refresh interval in seconds (defaults to server.interval_secs in config)
+        #[arg(short, long)]
+        interval: Option<u64>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Serve Scan and SubscribeQuotes over gRPC (needs --features grpc)
+    Grpc {
+        /// Symbols used when a request names none (comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        symbols: Option<Vec<String>>,
+
+        /// Path to symbols file (- for stdin)
+        #[arg(short = 'f', long)]
+        symbols_file: Option<PathBuf>,
+
+        /// Address to listen on (defaults to server.grpc_bind in config)
+        #[arg(long, value_name = "ADDR")]
+        bind: Option<String>,
+
+        /// Default SubscribeQuotes interval in seconds (defaults to server.interval_secs in config)
+        #[arg(short, long)]
+        interval: Option<u64>,
+    },
+
+    /// Render a price chart in the terminal
+    Chart {
+        /// Stock symbol to chart
+        symbol: String,
+
+        /// Read prices from a watch log instead of fetching candles
+        #[arg(long, value_name = "FILE")]
+        history: Option<PathBuf>,
+
+        /// Draw OHLC candlesticks instead of a close-price line
+        #[arg(long)]
+        candles: bool,
+
+        /// Moving-average periods to overlay on candlesticks (comma-separated)
+        #[arg(long, value_delimiter = ',', value_name = "PERIODS", requires = "candles")]
+        ma: Vec<usize>,
+
+        /// Candle resolution (1, 5, 15, 30, 60, D, W, M)
+        #[arg(short, long, default_value = "D")]
+        resolution: String,
+
+        /// Number of days of candles to fetch
+        #[arg(long, default_value = "30", value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+
+        /// Chart width in columns
+        #[arg(long, default_value = "60")]
+        width: usize,
+
+        /// Chart height in rows
+        #[arg(long, default_value = "15")]
+        height: usize,
+    },
+
+    /// Compare prices from --provider with another provider and report discrepancies
+    Verify {
+        /// Stock symbols to compare (comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        symbols: Option<Vec<String>>,
+
+        /// Path to symbols file (one per line, - for stdin)
+        #[arg(short = 'f', long)]
+        symbols_file: Option<PathBuf>,
+
+        /// Provider to check --provider's prices against
+        #[arg(long, value_enum)]
+        against: ProviderKind,
+
+        /// Largest price difference, in percent, that still counts as agreeing
+        #[arg(long, default_value_t = verify::DEFAULT_TOLERANCE_PCT)]
+        tolerance: f64,
+
+        /// Print the comparison as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Value your holdings at live quotes
+    Portfolio {
+        /// Holdings file (defaults to portfolio.file in config)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Output format (defaults to default_output in config)
+        #[arg(short, long, value_enum)]
+        output: Option<OutputFormat>,
+
+        /// Show each position's and sector's weight instead, flagging concentrations
+        #[arg(short, long)]
+        allocation: bool,
+
+        #[command(subcommand)]
+        command: Option<PortfolioCommand>,
+    },
+
+    /// Evaluate alert rules from the config
+    Alerts {
+        #[command(subcommand)]
+        command: AlertsCommand,
+    },
+
+    /// Manage locally recorded history
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+
+    /// Check the config, network, API key, rate limit and cache directory
+    Doctor,
+
+    /// Work out the API calls a scan or watch would use per run and per day
+    Plan {
+        /// Number of symbols (defaults to the symbols file's length)
+        count: Option<usize>,
+
+        /// Path to symbols file to count (- for stdin)
+        #[arg(short = 'f', long)]
+        symbols_file: Option<PathBuf>,
+
+        /// Watch refresh interval in seconds; without it, plans for one-off scans
+        #[arg(short, long)]
+        interval: Option<u64>,
+
+        /// Hours a day the watch runs
+        #[arg(long, default_value = "6.5")]
+        hours: f64,
+
+        /// Scans a day, when not watching
+        #[arg(long, default_value = "1")]
+        runs: u64,
+
+        /// Fetch company profiles, as scan --enrich does
+        #[arg(long)]
+        enrich: bool,
+
+        /// Fetch basic financials for each symbol
+        #[arg(long)]
+        metrics: bool,
+
+        /// Fetch analyst price targets for each symbol
+        #[arg(long)]
+        targets: bool,
+
+        /// Fetch candles for each symbol
+        #[arg(long)]
+        candles: bool,
+
+        /// Calls a minute your Finnhub plan allows
+        #[arg(long, default_value_t = estimate::FREE_TIER_PER_MINUTE)]
+        per_minute: u32,
+
+        /// Calls a day your Finnhub plan allows (defaults to daily_call_limit in config)
+        #[arg(long)]
+        daily_limit: Option<u64>,
+    },
+
+    /// Show today's API calls per endpoint against the plan's daily limit
+    Usage {
+        /// Also show totals for this many days before today
+        #[arg(long, default_value = "0")]
+        days: u32,
     },
 
     /// Display configuration
     Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommand>,
+
         /// Show current configuration
         #[arg(long)]
         show: bool,
@@ -88,115 +413,1359 @@ enum Commands {
         #[arg(long)]
         init: bool,
     },
+
+    /// Keep named symbol lists for scan and watch --watchlist
+    Watchlist {
+        #[command(subcommand)]
+        command: WatchlistCommand,
+    },
+
+    /// Print a shell completion script
+    Completions {
+        /// Shell to complete in
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Write man pages for the binary and each subcommand, for packaging
+    #[command(hide = true)]
+    Mangen {
+        /// Directory to write the pages to
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out_dir: PathBuf,
+    },
+}
+
+impl Commands {
+    /// Recorded with each run's API calls
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Scan { .. } => "scan",
+            Commands::Watch { .. } => "watch",
+            Commands::Serve { .. } => "serve",
+            Commands::Grpc { .. } => "grpc",
+            Commands::Chart { .. } => "chart",
+            Commands::Verify { .. } => "verify",
+            Commands::Portfolio { .. } => "portfolio",
+            Commands::Alerts { .. } => "alerts",
+            Commands::History { .. } => "history",
+            Commands::Doctor => "doctor",
+            Commands::Plan { .. } => "plan",
+            Commands::Usage { .. } => "usage",
+            Commands::Config { .. } => "config",
+            Commands::Watchlist { .. } => "watchlist",
+            Commands::Completions { .. } => "completions",
+            Commands::Mangen { .. } => "mangen",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check a config file and list every problem found
+    Validate {
+        /// Config file to check (defaults to --config or the user config file)
+        path: Option<PathBuf>,
+
+        /// Also make a request to confirm the API key is accepted
+        #[arg(long)]
+        check_key: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PortfolioCommand {
+    /// Convert broker position exports into the holdings file
+    Import {
+        /// Layout of the exports
+        #[arg(long, value_enum, default_value = "generic")]
+        format: BrokerFormat,
+
+        /// Exports to read; positions from several accounts are kept as separate lots
+        #[arg(required = true, value_name = "FILE")]
+        files: Vec<PathBuf>,
+
+        /// Holdings file to write (defaults to portfolio.file in config)
+        #[arg(long, value_name = "FILE")]
+        to: Option<PathBuf>,
+
+        /// Replace the holdings file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum WatchlistCommand {
+    /// Add symbols to a watchlist, creating it if needed
+    Add {
+        /// Watchlist name
+        #[arg(add = ArgValueCandidates::new(watchlist_candidates))]
+        name: String,
+
+        /// Symbols to add (space- or comma-separated)
+        #[arg(required = true, value_delimiter = ',')]
+        symbols: Vec<String>,
+    },
+
+    /// Remove symbols from a watchlist, or the whole watchlist when none are given
+    Remove {
+        /// Watchlist name
+        #[arg(add = ArgValueCandidates::new(watchlist_candidates))]
+        name: String,
+
+        /// Symbols to remove (space- or comma-separated)
+        #[arg(value_delimiter = ',')]
+        symbols: Vec<String>,
+    },
+
+    /// List the watchlists and how many symbols each holds
+    List,
+
+    /// Print a watchlist's symbols, one per line
+    Show {
+        /// Watchlist name
+        #[arg(add = ArgValueCandidates::new(watchlist_candidates))]
+        name: String,
+    },
+}
+
+/// Row filters shared by `scan` and `watch`
+#[derive(Args)]
+struct FilterArgs {
+    /// Show only gainers
+    #[arg(long)]
+    gainers_only: bool,
+
+    /// Show only losers
+    #[arg(long)]
+    losers_only: bool,
+
+    /// Minimum absolute change threshold (%)
+    #[arg(long)]
+    min_change: Option<f64>,
+
+    /// Only show quotes matching a condition, e.g. "price < 50 and change_pct > 2"
+    /// (defaults to filter in config)
+    #[arg(long, value_name = "EXPR")]
+    filter: Option<String>,
+}
+
+impl FilterArgs {
+    fn into_filter(self, config: &Config) -> Result<output::QuoteFilter> {
+        let expr = self.filter.as_deref().or(config.filter.as_deref());
+        Ok(output::QuoteFilter {
+            gainers_only: self.gainers_only,
+            losers_only: self.losers_only,
+            min_change: self.min_change,
+            expr: expr.map(expr::parse).transpose()?,
+        })
+    }
+}
+
+#[derive(Subcommand)]
+enum AlertsCommand {
+    /// Continuously evaluate alert rules against fresh quotes
+    Run {
+        /// Extra symbols for wildcard rules (comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        symbols: Option<Vec<String>>,
+
+        /// Path to symbols file for wildcard rules (- for stdin)
+        #[arg(short = 'f', long)]
+        symbols_file: Option<PathBuf>,
+
+        /// Evaluation interval in seconds (defaults to alerts.interval_secs in config)
+        #[arg(short, long)]
+        interval: Option<u64>,
+
+        /// Ring the terminal bell (or play alerts.sound_file) when an alert fires
+        #[arg(long)]
+        bell: bool,
+    },
+
+    /// Review or export fired alerts
+    History {
+        /// Alert history file (defaults to alerts.history_file in config)
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        /// Only alerts from this rule
+        #[arg(long)]
+        rule: Option<String>,
+
+        /// Only alerts for symbols matching this pattern (e.g. AAPL or TS*)
+        #[arg(short, long)]
+        symbol: Option<String>,
+
+        /// Start of the range (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the range (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Show only the most recent N alerts
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: alerts::HistoryFormat,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Remove records older than the retention window
+    Prune {
+        /// History file to prune (defaults to history.file in config)
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Keep this many days of records (defaults to history.keep_days in config)
+        #[arg(long)]
+        keep_days: Option<u64>,
+    },
+
+    /// Export recorded history in bulk
+    Export {
+        /// History file to export (defaults to history.file in config)
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Export format
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+
+        /// Start of the range (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the range, inclusive for dates (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Only export these symbols (comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        symbols: Option<Vec<String>>,
+
+        /// Write to a file instead of stdout (required for parquet)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// `history export --format` values
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+    /// Apache Parquet (requires the `parquet` feature)
+    Parquet,
+}
+
+impl From<ExportFormat> for history::ExportFormat {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Csv => history::ExportFormat::Csv,
+            ExportFormat::Ndjson => history::ExportFormat::Ndjson,
+            ExportFormat::Parquet => history::ExportFormat::Parquet,
+        }
+    }
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() -> ExitCode {
+    // Answers the completion scripts' callbacks, when COMPLETE is set, and exits
+    clap_complete::CompleteEnv::with_factory(Cli::command).complete();
+
+    let result = run().await;
+    usage::finish();
+    match result {
+        Ok(status) => ExitCode::from(status),
+        Err(e) => {
+            errors::report(&e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// Run the command line's command, returning the process exit status
+async fn run() -> Result<u8> {
+    // clap would exit with 2 on a usage error, which means partial results here
+    let mut cli = match Cli::try_parse_from(expand_alias(std::env::args_os().collect())?) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return Ok(if e.use_stderr() { errors::EXIT_USAGE } else { errors::EXIT_OK });
+        }
+    };
+    let command = cli.command.take().unwrap_or_else(|| default_scan(&cli.symbols));
 
     // Initialize logger
-    if cli.verbose {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+    let level = if cli.verbose {
+        "debug"
+    } else if cli.quiet {
+        "error"
+    } else {
+        "info"
+    };
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level));
+    errors::set_format(cli.error_format);
+    if cli.error_format == errors::ErrorFormat::Json {
+        // Every line on stderr stays parseable
+        logger.format(|buf, record| {
+            let line = serde_json::json!({
+                "level": record.level().as_str().to_lowercase(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    logger.init();
+
+    if let Commands::Completions { shell } = command {
+        write_completions(shell)?;
+        return Ok(errors::EXIT_OK);
+    }
+    if let Commands::Mangen { out_dir } = &command {
+        let mut root = Cli::command();
+        root.build();
+        std::fs::create_dir_all(out_dir)
+            .map_err(|e| errors::ScannerError::Io(format!("Failed to create {}: {}", out_dir.display(), e)))?;
+        let written = write_man_pages(&root, root.get_name(), out_dir)?;
+        println!("✓ Wrote {} man pages to {}", written, out_dir.display());
+        return Ok(errors::EXIT_OK);
+    }
+
+    // Creating the config must work before there is one to load
+    if let Commands::Config { init: true, .. } = command {
+        let path = cli
+            .config
+            .or_else(paths::config_file)
+            .unwrap_or_else(|| PathBuf::from("config.toml"));
+        if path.exists() {
+            return Err(errors::ScannerError::Config(format!("{} already exists", path.display())));
+        }
+        Config::default().save_to_file(&path)?;
+        println!("✓ Default config created at {}", path.display());
+        println!("  Don't forget to add your Finnhub API key!");
+        return Ok(errors::EXIT_OK);
+    }
+
+    // Watchlists are plain files, so neither a config nor a key is needed
+    if let Commands::Watchlist { command } = command {
+        run_watchlist(command, &watchlist::Watchlists::default())?;
+        return Ok(errors::EXIT_OK);
+    }
+
+    let needs_api_key = cli.provider.needs_api_key()
+        || matches!(&command, Commands::Verify { against, .. } if against.needs_api_key());
+    let api_key = if cli.api_key_stdin {
+        Some(config::read_api_key(std::io::stdin().lock())?)
+    } else if !needs_api_key || cli.replay.is_some() {
+        // Fixtures and cassettes stand in for the API, so any key passes validation
+        Some("mock".to_string())
     } else {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+        None
+    };
+
+    // Validation reports problems instead of stopping at the first one
+    if let Commands::Config {
+        command: Some(ConfigCommand::Validate { path, check_key }),
+        ..
+    } = &command
+    {
+        let path = path
+            .clone()
+            .or_else(|| cli.config.clone())
+            .or_else(paths::config_file)
+            .ok_or_else(|| errors::ScannerError::Config("No config file to validate".to_string()))?;
+        validate_config(&path, api_key, cli.profile.as_deref(), *check_key).await?;
+        return Ok(errors::EXIT_OK);
     }
 
     // Load config
-    let config = if let Some(config_path) = cli.config {
-        Config::from_file(&config_path)?
+    let config_path = cli
+        .config
+        .or_else(|| paths::config_file().filter(|path| path.exists()));
+
+    // Budgeting needs rate-limit settings, not an API key
+    if let Commands::Plan {
+        count,
+        symbols_file,
+        interval,
+        hours,
+        runs,
+        enrich,
+        metrics,
+        targets,
+        candles,
+        per_minute,
+        daily_limit,
+    } = command
+    {
+        let config = match &config_path {
+            Some(path) => Config::read_file(path, api_key, cli.profile.as_deref())?,
+            None => Config::read_env(api_key)?,
+        };
+        let symbols = match count {
+            Some(count) => count,
+            None => {
+                // Only the count matters, so no listings are fetched for it
+                let symbol_options = SymbolOptions {
+                    api_key_stdin: cli.api_key_stdin,
+                    assume_yes: cli.yes,
+                    validate: false,
+                    dry_run: false,
+                };
+                load_symbols(None, symbols_file, &config, &symbol_options).await?.len()
+            }
+        };
+        let options = estimate::PlanOptions {
+            symbols,
+            profiles: enrich,
+            metrics,
+            targets,
+            candles,
+            interval,
+            runs,
+            hours,
+            per_minute,
+            daily_limit: daily_limit.or(config.daily_call_limit),
+        };
+        estimate::Plan::new(&options, &config).print(&options, &config);
+        return Ok(errors::EXIT_OK);
+    }
+
+    if let Commands::Usage { days } = command {
+        let config = match &config_path {
+            Some(path) => Config::read_file(path, api_key, cli.profile.as_deref())?,
+            None => Config::read_env(api_key)?,
+        };
+        usage::print(&usage::read_records(&usage::log_path())?, &config, days);
+        return Ok(errors::EXIT_OK);
+    }
+
+    // Importing only converts files, so neither an API key nor quotes are needed
+    if let Commands::Portfolio {
+        command: Some(PortfolioCommand::Import { format, files, to, force }),
+        ..
+    } = command
+    {
+        let config = match &config_path {
+            Some(path) => Config::read_file(path, api_key, cli.profile.as_deref())?,
+            None => Config::read_env(api_key)?,
+        };
+        let path = to.unwrap_or_else(|| config.portfolio.file.clone());
+        if path.exists() && !force {
+            return Err(errors::ScannerError::InvalidInput(format!(
+                "{} already exists; use --force to replace it",
+                path.display()
+            )));
+        }
+        let mut holdings = Vec::new();
+        for file in &files {
+            holdings.extend(brokers::read_export(format, file)?);
+        }
+        portfolio::write_holdings(&path, &holdings)?;
+        println!("✓ Imported {} positions into {}", holdings.len(), path.display());
+        return Ok(errors::EXIT_OK);
+    }
+
+    let config = if let Commands::Doctor = command {
+        // Troubleshooting has to start even when the config would not load cleanly
+        match &config_path {
+            Some(path) => Config::read_file(path, api_key, cli.profile.as_deref())?,
+            None => Config::read_env(api_key)?,
+        }
+    } else if let Some(config_path) = &config_path {
+        log::debug!("Using config {}", config_path.display());
+        Config::from_file(config_path, api_key, cli.profile.as_deref())?
+    } else if let Some(profile) = &cli.profile {
+        return Err(errors::ScannerError::Config(format!(
+            "Profile '{}' needs a config file (--config or the default location)",
+            profile
+        )));
     } else {
-        Config::from_env_or_default()?
+        Config::from_env_or_default(api_key)?
+    };
+
+    usage::start(&config, command.name());
+
+    // Listings are Finnhub's; other providers and replayed cassettes may not know them
+    let symbol_options = SymbolOptions {
+        api_key_stdin: cli.api_key_stdin,
+        assume_yes: cli.yes,
+        validate: config.validate_symbols && cli.provider == ProviderKind::Finnhub && cli.replay.is_none(),
+        dry_run: matches!(command, Commands::Scan { dry_run: true, .. }),
+    };
+
+    let cassette = match (&cli.record, &cli.replay) {
+        (Some(path), _) => Some(Arc::new(Cassette::record(path)?)),
+        (None, Some(path)) => Some(Arc::new(Cassette::replay(path)?)),
+        (None, None) => None,
+    };
+    let local = provider::LocalData {
+        dir: cli.fixtures.as_deref(),
+        as_of: cli.as_of,
     };
 
-    match cli.command {
+    let mut status = errors::EXIT_OK;
+    match command {
         Commands::Scan {
             symbols,
             symbols_file,
+            watchlist,
             output,
             sort_by_change,
-            gainers_only,
-            losers_only,
-            min_change,
+            filter,
+            enrich,
+            csv_status,
+            strict,
+            dry_run,
+            sinks,
         } => {
-            let symbol_list = load_symbols(symbols, symbols_file, &config)?;
+            let symbols_file = watchlist_file(watchlist, symbols_file)?;
+            let symbol_list = load_symbols(symbols, symbols_file, &config, &symbol_options).await?;
+            let filter = filter.into_filter(&config)?;
+            let output = match output {
+                Some(output) => output,
+                None => config.default_output()?,
+            };
             
-            let client = FinnhubClient::new(config.api_key.clone(), config.clone());
-            let quotes = client.fetch_quotes(&symbol_list).await?;
+            let client = build_client(&config, !cli.no_cache, cassette.as_ref())?;
+            if dry_run {
+                for url in config.sinks.iter().chain(&sinks) {
+                    sinks::check_url(url)?;
+                }
+                // Uncached @groups are still in the list, and aren't symbols yet
+                let count = symbol_list.iter().filter(|symbol| !groups::is_group(symbol)).count();
+                let estimate = estimate::scan(&client, &symbol_list, enrich, cli.provider, cli.replay.is_some());
+                estimate.print(count, &config);
+                return Ok(errors::EXIT_OK);
+            }
+            let mut sinks = sinks::Sinks::connect(&config, &sinks)?;
+
+            // Unsorted, unenriched NDJSON can be written as each quote arrives, unless a failure
+            // later on would have to take it back
+            let stream = output == OutputFormat::Ndjson && !sort_by_change && !enrich && !strict;
+            let provider = provider::open(cli.provider, &config, &local, &client)?;
+            let (quotes, failed) = provider
+                .fetch_quotes_with(&symbol_list, &mut |quote| {
+                    if stream && filter.matches(quote) {
+                        if let Err(e) = output::print_ndjson(quote) {
+                            log::warn!("{}: {}", quote.symbol, e);
+                        }
+                    }
+                })
+                .await?;
+            if strict && !failed.is_empty() {
+                log::error!(
+                    "{} of {} symbols failed; --strict leaves the output empty",
+                    failed.len(),
+                    symbol_list.len()
+                );
+                return Ok(errors::EXIT_PARTIAL);
+            }
             
-            let filtered = output::filter_quotes(
-                quotes,
-                gainers_only,
-                losers_only,
-                min_change,
-            );
+            let filtered = filter.apply(quotes);
             
-            let sorted = if sort_by_change {
+            let mut sorted = if sort_by_change {
                 output::sort_by_change(filtered)
             } else {
                 filtered
             };
+
+            // Enrich after filtering so profile lookups are only spent on displayed rows
+            if enrich {
+                client.enrich_with_profiles(&mut sorted).await;
+            }
             
-            output::display(&sorted, output)?;
+            if !stream {
+                output::display(&sorted, &failed, output, cli.quiet, csv_status)?;
+            }
+            sinks.quotes(&chrono::Utc::now().to_rfc3339(), &sorted).await;
+            sinks.close().await;
+
+            if !config.notifications.on_scan_complete.is_empty() {
+                let runner = actions::ActionRunner::new(config.timeout_secs, config.notifications.clone())?
+                    .with_http(&config.http);
+                runner
+                    .report(&actions::ScanReport::new(&sorted), &config.notifications.on_scan_complete)
+                    .await;
+            }
+            status = errors::scan_exit_code(sorted.len(), failed.len());
         }
 
         Commands::Watch {
             symbols,
             symbols_file,
+            watchlist,
             interval,
+            output,
+            no_clear,
+            iterations,
+            until,
+            market_hours,
+            symbol_interval,
+            filter,
+            log_file,
+            sinks,
+            alerts,
+            bell,
+            plain,
         } => {
-            let symbol_list = load_symbols(symbols, symbols_file, &config)?;
-            let client = FinnhubClient::new(config.api_key.clone(), config.clone());
-            
-            log::info!("Starting watch mode. Press Ctrl+C to exit.");
-            
-            loop {
-                match client.fetch_quotes(&symbol_list).await {
-                    Ok(quotes) => {
-                        output::clear_screen();
-                        output::display(&quotes, OutputFormat::Table)?;
-                        log::info!("Updated at: {}", chrono::Local::now().format("%H:%M:%S"));
+            // Only a list read from a file can change under us; a watchlist is one
+            let symbols_file = watchlist_file(watchlist, symbols_file)?;
+            let (symbol_list, reload_file) =
+                load_symbols_with_source(symbols, symbols_file, &config, &symbol_options).await?;
+            let options = watch::WatchOptions {
+                interval: interval.unwrap_or(config.watch.interval_secs),
+                filter: filter.into_filter(&config)?,
+                log_file,
+                sinks,
+                alerts,
+                bell,
+                plain,
+                output,
+                no_clear,
+                iterations,
+                until: until.as_deref().map(watch::parse_until).transpose()?,
+                market_hours,
+                groups: symbol_interval
+                    .iter()
+                    .map(|raw| watch::parse_symbol_interval(raw))
+                    .collect::<Result<_>>()?,
+                symbols_file: reload_file,
+                config_file: config_path.map(|path| (path, cli.profile.clone())),
+                quiet: cli.quiet,
+                // Watch's own client serves Finnhub quotes unless another provider is involved
+                provider: if cli.provider == ProviderKind::Finnhub && config.fallback_providers.is_empty() {
+                    None
+                } else {
+                    let client = build_client(&config, false, cassette.as_ref())?;
+                    Some(provider::open(cli.provider, &config, &local, &client)?)
+                },
+                cassette: cassette.clone(),
+            };
+            watch::run(&config, symbol_list, options).await?;
+        }
+
+        Commands::Serve {
+            symbols,
+            symbols_file,
+            bind,
+            interval,
+            filter,
+        } => {
+            let symbol_list = load_symbols(symbols, symbols_file, &config, &symbol_options).await?;
+            let options = server::ServeOptions {
+                bind: bind.unwrap_or_else(|| config.server.bind.clone()),
+                interval: interval.unwrap_or(config.server.interval_secs),
+                filter: filter.into_filter(&config)?,
+            };
+            server::run(&config, symbol_list, options).await?;
+        }
+
+        Commands::Grpc {
+            symbols,
+            symbols_file,
+            bind,
+            interval,
+        } => {
+            // Clients can name their own symbols, so a server-side list is optional
+            let loaded = load_symbols(symbols, symbols_file, &config, &symbol_options).await;
+            let symbol_list = match loaded {
+                Ok(list) => list,
+                Err(errors::ScannerError::NoSymbols) => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            let options = grpc::GrpcOptions {
+                bind: bind.unwrap_or_else(|| config.server.grpc_bind.clone()),
+                interval: interval.unwrap_or(config.server.interval_secs),
+            };
+            grpc::run(&config, symbol_list, options).await?;
+        }
+
+        Commands::Chart {
+            symbol,
+            history,
+            candles,
+            ma,
+            resolution,
+            days,
+            width,
+            height,
+        } => {
+            let symbol = symbol.to_uppercase();
+
+            if candles && history.is_some() {
+                return Err(errors::ScannerError::InvalidInput(
+                    "--candles needs OHLC data from the API and cannot be combined with --history".to_string(),
+                ));
+            }
+
+            let mut bars = Vec::new();
+            let points: Vec<(String, f64)> = if let Some(path) = history {
+                history::read_records(&path)?
+                    .into_iter()
+                    .filter(|record| record.quote.symbol == symbol)
+                    .map(|record| (record.timestamp, record.quote.price))
+                    .collect()
+            } else {
+                let client = build_client(&config, false, cassette.as_ref())?;
+                let provider = provider::open(cli.provider, &config, &local, &client)?;
+                let to = chrono::Utc::now().timestamp();
+                let from = to - i64::from(days) * 86_400;
+                let data = provider.fetch_candles(&symbol, &resolution, from, to).await?;
+
+                let lengths = [data.o.len(), data.h.len(), data.l.len()];
+                if candles && lengths.iter().any(|&len| len != data.c.len()) {
+                    return Err(errors::ScannerError::Api(format!(
+                        "Candles for {} have mismatched open/high/low/close series",
+                        symbol
+                    )));
+                }
+                bars = data
+                    .o
+                    .iter()
+                    .zip(&data.h)
+                    .zip(&data.l)
+                    .zip(&data.c)
+                    .map(|(((&open, &high), &low), &close)| chart::Ohlc { open, high, low, close })
+                    .collect();
+
+                data.t
+                    .iter()
+                    .zip(&data.c)
+                    .map(|(&t, &close)| (candle_label(t, &resolution), close))
+                    .collect()
+            };
+
+            if points.is_empty() {
+                return Err(errors::ScannerError::InvalidInput(format!(
+                    "No price data found for {}",
+                    symbol
+                )));
+            }
+
+            let values: Vec<f64> = points.iter().map(|(_, price)| *price).collect();
+            let (first_label, first) = &points[0];
+            let (last_label, last) = &points[points.len() - 1];
+            let change_pct = decimal::change_pct(*last, *first);
+
+            println!(
+                "\n{}  {:.2} → {:.2} ({})  {}",
+                symbol,
+                first,
+                last,
+                output::format_change(change_pct),
+                chart::sparkline(&chart::resample(&values, 20))
+            );
+            println!();
+
+            let labels = (short_label(first_label), short_label(last_label));
+            let lines = if candles {
+                let overlays: Vec<Vec<Option<f64>>> = ma
+                    .iter()
+                    .map(|&period| chart::moving_average(&values, period))
+                    .collect();
+                chart::candlestick_chart(&bars, &overlays, labels, width, height)
+            } else {
+                chart::line_chart(&values, labels, width, height)
+            };
+
+            for line in lines {
+                println!("{}", line);
+            }
+
+            if candles && !ma.is_empty() {
+                println!("\n{:>12}{}", "", chart::overlay_legend(&ma));
+            }
+            println!();
+        }
+
+        Commands::Verify {
+            symbols,
+            symbols_file,
+            against,
+            tolerance,
+            json,
+        } => {
+            if against == cli.provider {
+                return Err(errors::ScannerError::InvalidInput(
+                    "--against must name a different provider than --provider".to_string(),
+                ));
+            }
+            let symbol_list = load_symbols(symbols, symbols_file, &config, &symbol_options).await?;
+            // Fresh quotes from both sides, without fallbacks blurring where each came from
+            let client = build_client(&config, false, cassette.as_ref())?;
+            let mut config = config.clone();
+            config.fallback_providers.clear();
+            let primary = provider::open(cli.provider, &config, &local, &client)?;
+            let secondary = provider::open(against, &config, &local, &client)?;
+
+            let comparisons =
+                verify::compare(primary.as_ref(), secondary.as_ref(), &symbol_list, tolerance).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&comparisons)?);
+            } else {
+                verify::print_table(&comparisons, primary.name(), secondary.name(), tolerance);
+            }
+            status = verify::exit_code(&comparisons);
+        }
+
+        Commands::Portfolio { file, output, allocation, .. } => {
+            let path = file.unwrap_or_else(|| config.portfolio.file.clone());
+            let holdings = portfolio::read_holdings(&path)?;
+            let symbol_list = portfolio::symbols(&holdings);
+            if symbol_list.is_empty() {
+                return Err(errors::ScannerError::NoSymbols);
+            }
+            let output = match output {
+                Some(output) => output,
+                None => config.default_output()?,
+            };
+
+            let client = build_client(&config, !cli.no_cache, cassette.as_ref())?;
+            let provider = provider::open(cli.provider, &config, &local, &client)?;
+            let (mut quotes, failed) = provider.fetch_quotes_with(&symbol_list, &mut |_| {}).await?;
+            if allocation {
+                // Sectors come from company profiles, which are cached on disk
+                client.enrich_with_profiles(&mut quotes).await;
+            }
+            let positions = portfolio::positions(&holdings, &quotes);
+            if allocation {
+                let threshold = config.portfolio.concentration_pct;
+                portfolio::display_allocation(&positions, threshold, &failed, output, cli.quiet)?;
+            } else {
+                let totals = portfolio::totals(&positions, portfolio::realized_pnl(&holdings));
+                portfolio::display(&positions, &totals, &failed, output, cli.quiet)?;
+            }
+            status = errors::scan_exit_code(positions.len(), failed.len());
+        }
+
+        Commands::Alerts { command } => match command {
+            AlertsCommand::Run {
+                symbols,
+                symbols_file,
+                interval,
+                bell,
+            } => {
+                let mut engine = alerts::AlertEngine::new(&config.alerts)?;
+                if engine.is_empty() {
+                    return Err(errors::ScannerError::Config(
+                        "No alert rules configured. Add [[alerts.rules]] entries to the config file".to_string(),
+                    ));
+                }
+
+                // Rules naming a symbol are always watched; wildcards apply to the symbol list
+                let loaded = load_symbols(symbols, symbols_file, &config, &symbol_options).await;
+                let mut symbol_list = match loaded {
+                    Ok(list) => list,
+                    Err(errors::ScannerError::NoSymbols) => Vec::new(),
+                    Err(e) => return Err(e),
+                };
+                for symbol in engine.symbols() {
+                    if !symbol_list.contains(&symbol) {
+                        symbol_list.push(symbol);
                     }
-                    Err(e) => {
-                        log::error!("Failed to fetch quotes: {}", e);
+                }
+                if symbol_list.is_empty() {
+                    return Err(errors::ScannerError::NoSymbols);
+                }
+
+                let client = build_client(&config, false, cassette.as_ref())?;
+                let mut runner = actions::ActionRunner::new(config.timeout_secs, config.notifications.clone())?
+                    .with_http(&config.http);
+                if bell {
+                    runner = runner.with_bell(config.alerts.sound_file.clone());
+                }
+                let interval = interval.unwrap_or(config.alerts.interval_secs);
+                let config_file = config_path.as_deref().and_then(|path| {
+                    reload::ConfigFile::watch(path, cli.profile.clone(), &config)
+                        .map_err(|e| log::warn!("Config file changes won't be picked up: {}", e))
+                        .ok()
+                });
+                let mut sinks = sinks::Sinks::connect(&config, &[])?;
+                alerts::run(&client, &runner, &symbol_list, &mut engine, &sinks, interval, config_file).await?;
+                sinks.close().await;
+            }
+
+            AlertsCommand::History {
+                file,
+                rule,
+                symbol,
+                from,
+                to,
+                limit,
+                format,
+                output,
+            } => {
+                let path = file.unwrap_or_else(|| config.alerts.history_file.clone());
+                let from = from.map(|raw| history::parse_time_bound(&raw, false)).transpose()?;
+                let to = to.map(|raw| history::parse_time_bound(&raw, true)).transpose()?;
+
+                let mut records = alerts::filter_history(
+                    alerts::read_history(&path)?,
+                    from,
+                    to,
+                    rule.as_deref(),
+                    symbol.as_deref(),
+                );
+                if let Some(limit) = limit {
+                    records.drain(..records.len().saturating_sub(limit));
+                }
+
+                match output {
+                    Some(out) => {
+                        let file = std::fs::File::create(&out).map_err(|e| {
+                            errors::ScannerError::Io(format!("Failed to create export file: {}", e))
+                        })?;
+                        alerts::write_history(&records, format, &mut std::io::BufWriter::new(file))?;
+                        log::info!("Exported {} alerts", records.len());
                     }
+                    None => alerts::write_history(&records, format, &mut std::io::stdout().lock())?,
                 }
-                
-                tokio::time::sleep(Duration::from_secs(interval)).await;
             }
+        },
+
+        Commands::History { command } => match command {
+            HistoryCommand::Prune { file, keep_days } => {
+                let path = history_file(file, &config)?;
+                let keep_days = keep_days.or(config.history.keep_days).ok_or_else(|| {
+                    errors::ScannerError::InvalidInput(
+                        "No retention window given. Use --keep-days or set history.keep_days in config".to_string(),
+                    )
+                })?;
+
+                let stats = history::prune_file(&path, history::retention_cutoff(keep_days))?;
+                println!(
+                    "✓ Pruned {}: removed {} records, kept {}",
+                    path.display(),
+                    stats.removed,
+                    stats.kept
+                );
+            }
+
+            HistoryCommand::Export {
+                file,
+                format,
+                from,
+                to,
+                symbols,
+                output,
+            } => {
+                let path = history_file(file, &config)?;
+                let from = from.map(|raw| history::parse_time_bound(&raw, false)).transpose()?;
+                let to = to.map(|raw| history::parse_time_bound(&raw, true)).transpose()?;
+                let symbols: Vec<String> = symbols
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|s| s.to_uppercase())
+                    .collect();
+
+                let records = history::filter_records(history::read_records(&path)?, from, to, &symbols);
+
+                let format = history::ExportFormat::from(format);
+                match (format, output) {
+                    (history::ExportFormat::Parquet, Some(out)) => {
+                        history::export_parquet(&records, &out)?;
+                    }
+                    (_, Some(out)) => {
+                        let file = std::fs::File::create(&out).map_err(|e| {
+                            errors::ScannerError::Io(format!("Failed to create export file: {}", e))
+                        })?;
+                        history::export_text(&records, format, &mut std::io::BufWriter::new(file))?;
+                    }
+                    (_, None) => {
+                        history::export_text(&records, format, &mut std::io::stdout().lock())?;
+                    }
+                }
+
+                log::info!("Exported {} records", records.len());
+            }
+        },
+
+        Commands::Doctor => {
+            let checks = doctor::run(&config, config_path.as_deref()).await;
+            if !doctor::print(&checks) {
+                let failed = checks.iter().filter(|check| !check.passed).count();
+                return Err(errors::ScannerError::Config(format!("{} check(s) failed", failed)));
+            }
+        }
+
+        Commands::Completions { .. }
+        | Commands::Mangen { .. }
+        | Commands::Plan { .. }
+        | Commands::Usage { .. }
+        | Commands::Watchlist { .. } => {
+            unreachable!("handled before the config is validated")
         }
 
-        Commands::Config { show, init } => {
-            if init {
-                let default_config = Config::default();
-                default_config.save_to_file("config.toml")?;
-                println!("✓ Default config created at config.toml");
-                println!("  Don't forget to add your Finnhub API key!");
-            } else if show {
+        Commands::Config { show, .. } => {
+            if show {
                 println!("{:#?}", config);
             }
         }
     }
 
-    Ok(())
+    Ok(status)
+}
+
+/// How symbol lists are read and checked, from the global options
+struct SymbolOptions {
+    api_key_stdin: bool,
+    /// Take the best search match for company names (`--yes`)
+    assume_yes: bool,
+    /// Drop symbols missing from Finnhub's exchange listings
+    validate: bool,
+    /// Send no requests: uncached index groups and company names are kept for the estimate
+    dry_run: bool,
 }
 
-fn load_symbols(
+async fn load_symbols(
     symbols: Option<Vec<String>>,
     symbols_file: Option<PathBuf>,
     config: &Config,
+    options: &SymbolOptions,
 ) -> Result<Vec<String>> {
-    // Priority: CLI args > file arg > config file > default
+    load_symbols_with_source(symbols, symbols_file, config, options)
+        .await
+        .map(|(symbols, _)| symbols)
+}
+
+/// Like `load_symbols`, also returning the file the list came from (not stdin)
+///
+/// Entries are prepared as in [`resolve::prepare`] wherever the list came from, and symbols
+/// missing from their exchange's listing are dropped when `options.validate` is set.
+async fn load_symbols_with_source(
+    symbols: Option<Vec<String>>,
+    symbols_file: Option<PathBuf>,
+    config: &Config,
+    options: &SymbolOptions,
+) -> Result<(Vec<String>, Option<PathBuf>)> {
+    let (symbols, source) = read_symbol_input(symbols, symbols_file, config, options.api_key_stdin)?;
+
+    // Index groups, name searches and listings come from Finnhub, all but searches through the
+    // disk cache; a list of plain tickers makes no requests unless it's validated
+    let client = build_client(config, true, None)?;
+    if options.dry_run {
+        return Ok((resolve::prepare_offline(&symbols, &config.groups, &client)?, source));
+    }
+    let mut symbols = resolve::prepare(&symbols, &config.groups, &client, options.assume_yes).await?;
+
+    if options.validate {
+        match resolve::unlisted(&symbols, &client).await {
+            Ok(missing) if !missing.is_empty() => {
+                log::warn!("Skipping symbols not listed on their exchange: {}", missing.join(", "));
+                symbols.retain(|symbol| !missing.contains(symbol));
+                if symbols.is_empty() {
+                    return Err(errors::ScannerError::NoSymbols);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Symbols weren't checked against exchange listings: {}", e),
+        }
+    }
+    Ok((symbols, source))
+}
+
+/// The symbols file behind `--watchlist`, which stands in for `--symbols-file`
+fn watchlist_file(watchlist: Option<String>, symbols_file: Option<PathBuf>) -> Result<Option<PathBuf>> {
+    match watchlist {
+        Some(name) => watchlist::Watchlists::default().existing_path(&name).map(Some),
+        None => Ok(symbols_file),
+    }
+}
+
+fn run_watchlist(command: WatchlistCommand, watchlists: &watchlist::Watchlists) -> Result<()> {
+    match command {
+        WatchlistCommand::Add { name, symbols } => {
+            let added = watchlists.add(&name, &symbols)?;
+            let total = watchlists.load(&name)?.len();
+            println!("✓ Added {} symbols to {} ({} total)", added, name, total);
+        }
+        WatchlistCommand::Remove { name, symbols } if symbols.is_empty() => {
+            watchlists.delete(&name)?;
+            println!("✓ Deleted watchlist {}", name);
+        }
+        WatchlistCommand::Remove { name, symbols } => {
+            let removed = watchlists.remove(&name, &symbols)?;
+            let total = watchlists.load(&name)?.len();
+            println!("✓ Removed {} symbols from {} ({} left)", removed, name, total);
+        }
+        WatchlistCommand::List => {
+            let lists = watchlists.list()?;
+            if lists.is_empty() {
+                log::info!("No watchlists yet; create one with `watchlist add <NAME> <SYMBOLS>`");
+            }
+            for (name, count) in lists {
+                println!("{:<20} {} symbols", name, count);
+            }
+        }
+        WatchlistCommand::Show { name } => {
+            for symbol in watchlists.load(&name)? {
+                println!("{}", symbol);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_symbol_input(
+    symbols: Option<Vec<String>>,
+    symbols_file: Option<PathBuf>,
+    config: &Config,
+    api_key_stdin: bool,
+) -> Result<(Vec<String>, Option<PathBuf>)> {
+    // Priority: CLI args > file arg > piped stdin > config file
     if let Some(syms) = symbols {
-        return Ok(syms.iter().map(|s| s.to_uppercase()).collect());
+        return Ok((syms.iter().map(|s| s.to_uppercase()).collect(), None));
     }
-    
+
     if let Some(path) = symbols_file {
-        return config::load_symbols_from_file(&path);
+        if config::is_stdin(&path) {
+            if api_key_stdin {
+                return Err(errors::ScannerError::InvalidInput(
+                    "stdin can't hold both the API key and the symbols; use --api-key-stdin or --symbols-file -"
+                        .to_string(),
+                ));
+            }
+            return Ok((config::load_symbols_from_file(&path)?, None));
+        }
+        return Ok((config::load_symbols_from_file(&path)?, Some(path)));
+    }
+
+    // e.g. `grep ... | finnhub-scanner scan`; nothing piped falls through to the config
+    if !api_key_stdin && config::stdin_is_piped() {
+        match config::read_symbols(std::io::stdin().lock()) {
+            Err(errors::ScannerError::NoSymbols) => {}
+            loaded => return Ok((loaded?, None)),
+        }
     }
     
     if let Some(path) = &config.symbols_file {
-        return config::load_symbols_from_file(path);
+        let source = Some(path.clone()).filter(|path| !config::is_stdin(path));
+        return Ok((config::load_symbols_from_file(path)?, source));
     }
     
     Err(errors::ScannerError::NoSymbols)
-}
\ No newline at end of file
+}
+
+/// `config validate`: print every problem in a config file, failing if there are any
+async fn validate_config(
+    path: &Path,
+    api_key: Option<String>,
+    profile: Option<&str>,
+    check_key: bool,
+) -> Result<()> {
+    let config = Config::read_file(path, api_key, profile)?;
+    let mut problems = config.check();
+
+    if check_key && config.api_key != "YOUR_API_KEY_HERE" && !config.api_key.is_empty() {
+        let checked = match FinnhubClient::from_config(&config) {
+            Ok(client) => client.fetch_quote("AAPL").await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match checked {
+            Ok(()) => println!("✓ API key accepted"),
+            Err(e) => problems.push(format!("API key check failed: {}", e)),
+        }
+    }
+
+    if problems.is_empty() {
+        println!("✓ {} is valid", path.display());
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("✗ {}", problem);
+    }
+    Err(errors::ScannerError::Config(format!(
+        "{} problem(s) found in {}",
+        problems.len(),
+        path.display()
+    )))
+}
+
+/// Replace an `[aliases]` name where the command would be with the arguments it stands for
+///
+/// Aliases come from the config file and profile named by `--config` and `--profile`, if given
+/// before the alias. Built-in commands can't be shadowed.
+fn expand_alias(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let root = Cli::command();
+    let (mut config_path, mut profile) = (None, None);
+
+    // Skip the global options, and their values, in front of the command
+    let mut at = 1;
+    while let Some(arg) = args.get(at).and_then(|arg| arg.to_str()) {
+        if !arg.starts_with('-') || arg == "--" {
+            break;
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(OsString::from(value))),
+            None => (arg, None),
+        };
+        let takes_value = root.get_arguments().any(|option| {
+            option.get_action().takes_values()
+                && (option.get_long().is_some_and(|long| flag == format!("--{}", long))
+                    || option.get_short().is_some_and(|short| flag == format!("-{}", short)))
+        });
+        let value = match (takes_value, inline) {
+            (true, None) => {
+                at += 1;
+                args.get(at).cloned()
+            }
+            (_, inline) => inline,
+        };
+        match flag {
+            "-c" | "--config" => config_path = value.map(PathBuf::from),
+            "-p" | "--profile" => profile = value.and_then(|value| value.into_string().ok()),
+            _ => {}
+        }
+        at += 1;
+    }
+
+    let Some(name) = args.get(at).and_then(|arg| arg.to_str()) else {
+        return Ok(args);
+    };
+    if root.find_subcommand(name).is_some() {
+        return Ok(args);
+    }
+    let Some(path) = config_path.or_else(|| paths::config_file().filter(|path| path.exists())) else {
+        return Ok(args);
+    };
+    // A key is never needed to read aliases, so don't go looking for one
+    let Some(expansion) = Config::read_file(&path, Some(String::new()), profile.as_deref())?.alias(name)? else {
+        return Ok(args);
+    };
+
+    let mut expanded = args[..at].to_vec();
+    expanded.extend(expansion.into_iter().map(OsString::from));
+    expanded.extend(args[at + 1..].iter().cloned());
+    Ok(expanded)
+}
+
+/// The `scan` that runs when no command is given, with every option at its default
+fn default_scan(symbols: &[String]) -> Commands {
+    let mut args = vec!["finnhub-scanner".to_string(), "scan".to_string()];
+    if !symbols.is_empty() {
+        args.push(format!("--symbols={}", symbols.join(",")));
+    }
+    Cli::try_parse_from(args)
+        .ok()
+        .and_then(|cli| cli.command)
+        .expect("scan parses with only --symbols")
+}
+
+/// `completions`: print the script that registers completion for `shell`
+///
+/// The script calls back into this binary, so values such as profile names are read from the
+/// config file each time rather than baked in.
+fn write_completions(shell: clap_complete::Shell) -> Result<()> {
+    let name = Cli::command().get_name().to_string();
+    let completer = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| name.clone());
+    let shells = clap_complete::env::Shells::builtins();
+    let env = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| errors::ScannerError::InvalidInput(format!("No completions for {}", shell)))?;
+    env.write_registration("COMPLETE", &name, &name, &completer, &mut std::io::stdout())
+        .map_err(|e| errors::ScannerError::Io(format!("Failed to write completions: {}", e)))
+}
+
+/// `mangen`: write `<title>.1` for `command`, then `<title>-<subcommand>.1` for each visible
+/// subcommand, returning how many pages were written
+fn write_man_pages(command: &clap::Command, title: &str, dir: &Path) -> Result<usize> {
+    let path = dir.join(format!("{}.1", title));
+    let mut page = Vec::new();
+    clap_mangen::Man::new(command.clone())
+        .title(title)
+        .render(&mut page)
+        .and_then(|()| std::fs::write(&path, page))
+        .map_err(|e| errors::ScannerError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+
+    let mut written = 1;
+    for subcommand in command.get_subcommands().filter(|sub| !sub.is_hide_set() && sub.get_name() != "help") {
+        written += write_man_pages(subcommand, &format!("{}-{}", title, subcommand.get_name()), dir)?;
+    }
+    Ok(written)
+}
+
+/// `--profile` values: the profiles in the user config file
+fn profile_candidates() -> Vec<CompletionCandidate> {
+    paths::config_file()
+        .and_then(|path| Config::profile_names(&path).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// `--watchlist` and `watchlist` name values: the saved watchlists
+fn watchlist_candidates() -> Vec<CompletionCandidate> {
+    watchlist::Watchlists::default()
+        .list()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, _)| CompletionCandidate::new(name))
+        .collect()
+}
+
+/// Format a candle timestamp for the chart's x-axis
+fn candle_label(timestamp: i64, resolution: &str) -> String {
+    let format = match resolution {
+        "D" | "W" | "M" => "%Y-%m-%d",
+        _ => "%m-%d %H:%M",
+    };
+
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.with_timezone(&chrono::Local).format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// Trim an RFC 3339 history timestamp down to minute precision
+fn short_label(timestamp: &str) -> &str {
+    timestamp.get(..16).unwrap_or(timestamp)
+}
+
+/// Build an API client, attaching the on-disk caches unless disabled, and a cassette if given
+fn build_client(config: &Config, use_cache: bool, cassette: Option<&Arc<Cassette>>) -> Result<FinnhubClient> {
+    let mut client = FinnhubClient::from_config(config)?;
+    if let Some(cassette) = cassette {
+        client = client.with_cassette(cassette.clone());
+    }
+    if !use_cache {
+        return Ok(client);
+    }
+
+    let dir = config.cache_dir.clone().unwrap_or_else(cache::default_cache_dir);
+    let client = client.with_disk_cache(cache::DiskCache::new(&dir));
+
+    if config.cache_ttl_secs > 0 {
+        Ok(client.with_cache(cache::QuoteCache::open(&dir, config.cache_ttl_secs)))
+    } else {
+        Ok(client)
+    }
+}
+
+/// Resolve the history file from the CLI or the config
+fn history_file(file: Option<PathBuf>, config: &Config) -> Result<PathBuf> {
+    file.or_else(|| config.history.file.clone()).ok_or_else(|| {
+        errors::ScannerError::InvalidInput(
+            "No history file given. Use --file or set history.file in config".to_string(),
+        )
+    })
+}
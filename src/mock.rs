@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Quote, StockQuote};
+use crate::provider::{ProviderFuture, QuoteProvider};
+
+/// Serves quotes from `<SYMBOL>.json` files instead of the API
+///
+/// Each file holds a Finnhub `/quote` response, or an array of them to step through one per
+/// fetch (wrapping around), so a watch session sees prices move. Files are read on every
+/// fetch, so fixtures can be edited while a session runs.
+pub struct MockProvider {
+    dir: PathBuf,
+    /// Next array index to serve per symbol
+    cursors: Mutex<HashMap<String, usize>>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Fixture {
+    One(Quote),
+    Sequence(Vec<Quote>),
+}
+
+impl MockProvider {
+    pub fn new(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            return Err(ScannerError::InvalidInput(format!(
+                "Fixtures directory {} does not exist",
+                dir.display()
+            )));
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            cursors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn read(&self, symbol: &str) -> Result<Quote> {
+        let path = self.dir.join(format!("{}.json", symbol));
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            // Same as the API answering with an empty quote
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ScannerError::SymbolNotFound(symbol.to_string()));
+            }
+            Err(e) => {
+                return Err(ScannerError::Io(format!("Failed to read {}: {}", path.display(), e)));
+            }
+        };
+        let fixture: Fixture = serde_json::from_str(&text)
+            .map_err(|e| ScannerError::Parse(format!("Invalid fixture {}: {}", path.display(), e)))?;
+
+        match fixture {
+            Fixture::One(quote) => Ok(quote),
+            Fixture::Sequence(quotes) if quotes.is_empty() => Err(ScannerError::SymbolNotFound(symbol.to_string())),
+            Fixture::Sequence(mut quotes) => {
+                let mut cursors = self.cursors.lock().expect("fixture cursor lock poisoned");
+                let cursor = cursors.entry(symbol.to_string()).or_insert(0);
+                let quote = quotes.swap_remove(*cursor % quotes.len());
+                *cursor += 1;
+                Ok(quote)
+            }
+        }
+    }
+}
+
+impl QuoteProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            let quote = self.read(symbol)?;
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_single_and_sequence_fixtures() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("AAPL.json"), r#"{"c": 110.0, "pc": 100.0}"#).unwrap();
+        fs::write(dir.path().join("MSFT.json"), r#"[{"c": 1.0, "pc": 1.0}, {"c": 2.0, "pc": 1.0}]"#).unwrap();
+        let mock = MockProvider::new(dir.path()).unwrap();
+
+        let quote = mock.fetch_quote("AAPL").await.unwrap();
+        assert_eq!(quote.price, 110.0);
+        assert_eq!(quote.change_pct, 10.0);
+
+        let mut prices = Vec::new();
+        for _ in 0..3 {
+            prices.push(mock.fetch_quote("MSFT").await.unwrap().price);
+        }
+        assert_eq!(prices, vec![1.0, 2.0, 1.0]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_and_invalid_fixtures() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("BAD.json"), "not json").unwrap();
+        let mock = MockProvider::new(dir.path()).unwrap();
+
+        assert!(matches!(mock.fetch_quote("NONE").await, Err(ScannerError::SymbolNotFound(_))));
+        assert!(matches!(mock.fetch_quote("BAD").await, Err(ScannerError::Parse(_))));
+        assert!(MockProvider::new(&dir.path().join("missing")).is_err());
+    }
+}
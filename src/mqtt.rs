@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use crate::alerts::AlertRecord;
+use crate::errors::{Result, ScannerError};
+
+/// `[mqtt]`: broker and topics that quote updates and alerts are published to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// `host` or `host:port`; the port defaults to 1883, or 8883 with `tls`
+    pub broker: String,
+
+    #[serde(default)]
+    pub tls: bool,
+
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    /// Defaults to `finnhub-scanner-<pid>`
+    pub client_id: Option<String>,
+
+    /// Topic for each quote update; `{symbol}` is replaced
+    #[serde(default = "default_quote_topic")]
+    pub quote_topic: String,
+
+    /// Topic for each fired alert; `{symbol}` and `{rule}` are replaced
+    #[serde(default = "default_alert_topic")]
+    pub alert_topic: String,
+
+    /// 0 (at most once), 1 (at least once) or 2 (exactly once)
+    #[serde(default)]
+    pub qos: u8,
+
+    /// Keep the latest quote per topic on the broker for new subscribers
+    #[serde(default)]
+    pub retain: bool,
+}
+
+fn default_quote_topic() -> String {
+    String::from("market/quotes/{symbol}")
+}
+
+fn default_alert_topic() -> String {
+    String::from("market/alerts/{symbol}")
+}
+
+impl MqttConfig {
+    /// Host and port to connect to
+    pub fn address(&self) -> Result<(String, u16)> {
+        let default_port = if self.tls { 8883 } else { 1883 };
+        let (host, port) = match self.broker.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| {
+                    ScannerError::Config(format!("mqtt.broker '{}' has an invalid port", self.broker))
+                })?;
+                (host, port)
+            }
+            None => (self.broker.as_str(), default_port),
+        };
+        if host.is_empty() {
+            return Err(ScannerError::Config("mqtt.broker is missing the host".to_string()));
+        }
+        Ok((host.to_string(), port))
+    }
+
+    /// Topic a quote for `symbol` is published to
+    pub fn quote_topic(&self, symbol: &str) -> String {
+        self.quote_topic.replace("{symbol}", symbol)
+    }
+
+    /// Topic an alert is published to
+    pub fn alert_topic(&self, record: &AlertRecord) -> String {
+        self.alert_topic
+            .replace("{symbol}", &record.symbol)
+            .replace("{rule}", &record.rule)
+    }
+}
+
+#[cfg(feature = "mqtt")]
+pub use sink::MqttSink;
+
+#[cfg(feature = "mqtt")]
+mod sink {
+    use std::time::Duration;
+    use rumqttc::{AsyncClient, Event, MqttOptions, Outgoing, Packet, QoS, TlsConfiguration, Transport};
+    use tokio::task::JoinHandle;
+    use super::MqttConfig;
+    use crate::alerts::AlertRecord;
+    use crate::errors::{Result, ScannerError};
+    use crate::finnhub::StockQuote;
+    use crate::sinks::QuoteMessage;
+
+    /// Publishes queued while the broker is unreachable, before new ones are dropped
+    const QUEUE_CAPACITY: usize = 256;
+
+    /// How long to wait before reconnecting to a broker that went away
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+    /// How long queued messages may take to go out when the sink is closed
+    const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Connection to the broker, kept alive by a background task
+    pub struct MqttSink {
+        client: AsyncClient,
+        config: MqttConfig,
+        qos: QoS,
+        connection: JoinHandle<()>,
+    }
+
+    impl MqttSink {
+        pub fn connect(config: &MqttConfig) -> Result<Self> {
+            let (host, port) = config.address()?;
+            let qos = match config.qos {
+                0 => QoS::AtMostOnce,
+                1 => QoS::AtLeastOnce,
+                2 => QoS::ExactlyOnce,
+                other => {
+                    return Err(ScannerError::Config(format!("mqtt.qos must be 0, 1 or 2, not {}", other)))
+                }
+            };
+
+            let client_id = config
+                .client_id
+                .clone()
+                .unwrap_or_else(|| format!("finnhub-scanner-{}", std::process::id()));
+            let mut options = MqttOptions::new(client_id, host, port);
+            options.set_keep_alive(Duration::from_secs(30));
+            if let Some(username) = &config.username {
+                options.set_credentials(username, config.password.clone().unwrap_or_default());
+            }
+            if config.tls {
+                options.set_transport(Transport::tls_with_config(TlsConfiguration::Native));
+            }
+
+            let (client, mut events) = AsyncClient::new(options, QUEUE_CAPACITY);
+            let broker = config.broker.clone();
+            // The event loop does the actual connecting and sending, and reconnects when polled again
+            let connection = tokio::spawn(async move {
+                let mut connected = false;
+                loop {
+                    match events.poll().await {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            log::info!("Connected to MQTT broker {}", broker);
+                            connected = true;
+                        }
+                        // Requested by `close`, after everything queued before it
+                        Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                        Ok(_) => {}
+                        Err(e) => {
+                            if connected {
+                                log::warn!("Lost connection to MQTT broker {}: {}", broker, e);
+                            } else {
+                                log::debug!("Failed to connect to MQTT broker {}: {}", broker, e);
+                            }
+                            connected = false;
+                            tokio::time::sleep(RECONNECT_DELAY).await;
+                        }
+                    }
+                }
+            });
+
+            Ok(Self {
+                client,
+                config: config.clone(),
+                qos,
+                connection,
+            })
+        }
+
+        /// Publish the quotes fetched by one refresh
+        pub fn quotes(&self, timestamp: &str, quotes: &[StockQuote]) {
+            for quote in quotes {
+                let payload = serde_json::to_vec(&QuoteMessage { timestamp, quote }).unwrap_or_default();
+                self.publish(self.config.quote_topic(&quote.symbol), payload, self.config.retain);
+            }
+        }
+
+        /// Publish alerts fired by one refresh
+        pub fn alerts(&self, records: &[AlertRecord]) {
+            for record in records {
+                let payload = serde_json::to_vec(record).unwrap_or_default();
+                self.publish(self.config.alert_topic(record), payload, false);
+            }
+        }
+
+        /// Send what is still queued and disconnect
+        pub async fn close(&mut self) {
+            if self.client.disconnect().await.is_err() {
+                return;
+            }
+            if tokio::time::timeout(CLOSE_TIMEOUT, &mut self.connection).await.is_err() {
+                log::warn!("Gave up sending queued MQTT messages to {}", self.config.broker);
+            }
+        }
+
+        /// Queue a message without waiting; it is dropped if the queue is full
+        fn publish(&self, topic: String, payload: Vec<u8>, retain: bool) {
+            if let Err(e) = self.client.try_publish(&topic, self.qos, retain, payload) {
+                log::warn!("Dropped MQTT message for {}: {}", topic, e);
+            }
+        }
+    }
+
+    impl Drop for MqttSink {
+        fn drop(&mut self) {
+            self.connection.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(broker: &str) -> MqttConfig {
+        toml::from_str(&format!("broker = \"{}\"", broker)).unwrap()
+    }
+
+    #[test]
+    fn test_broker_address() {
+        assert_eq!(config("localhost").address().unwrap(), ("localhost".to_string(), 1883));
+        assert_eq!(config("10.0.0.5:1884").address().unwrap(), ("10.0.0.5".to_string(), 1884));
+
+        let mut tls = config("broker.example.com");
+        tls.tls = true;
+        assert_eq!(tls.address().unwrap().1, 8883);
+
+        assert!(config("localhost:mqtt").address().is_err());
+        assert!(config(":1883").address().is_err());
+    }
+
+    #[test]
+    fn test_topics() {
+        let mut config = config("localhost");
+        assert_eq!(config.quote_topic("AAPL"), "market/quotes/AAPL");
+
+        config.alert_topic = "home/stocks/{symbol}/{rule}".to_string();
+        let record = AlertRecord {
+            timestamp: "2024-01-02T15:30:00+00:00".to_string(),
+            rule: "big-move".to_string(),
+            symbol: "TSLA".to_string(),
+            condition: "abs(change_pct) > 3".to_string(),
+            price: 250.0,
+            change_pct: -4.0,
+            results: Vec::new(),
+        };
+        assert_eq!(config.alert_topic(&record), "home/stocks/TSLA/big-move");
+    }
+}
@@ -1,10 +1,14 @@
-use clap::ValueEnum;
 use serde::Serialize;
 use std::io::{self, Write};
+use crate::decimal;
 use crate::errors::Result;
-use crate::finnhub::StockQuote;
+use crate::exchange;
+use crate::expr::Expr;
+use crate::finnhub::{StockQuote, SymbolError};
+use crate::influx;
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum OutputFormat {
     /// Pretty table format
     Table,
@@ -14,6 +18,25 @@ pub enum OutputFormat {
     Csv,
     /// Compact format
     Compact,
+    /// One JSON object per quote per line
+    Ndjson,
+    /// InfluxDB line protocol, e.g. for `influx write` or Telegraf
+    Influx,
+}
+
+impl OutputFormat {
+    /// The format named in a config file, ignoring case, as on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "table" => Some(OutputFormat::Table),
+            "json" => Some(OutputFormat::Json),
+            "csv" => Some(OutputFormat::Csv),
+            "compact" => Some(OutputFormat::Compact),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            "influx" => Some(OutputFormat::Influx),
+            _ => None,
+        }
+    }
 }
 
 impl Default for OutputFormat {
@@ -22,28 +45,58 @@ impl Default for OutputFormat {
     }
 }
 
-pub fn display(quotes: &[StockQuote], format: OutputFormat) -> Result<()> {
+/// Print quotes, and the symbols in `failed` where the format has room for them
+///
+/// `quiet` leaves out the table's rules, summary and failures and the color codes, for `--quiet`.
+/// `status` adds a CSV status column, with a row per failed symbol.
+pub fn display(
+    quotes: &[StockQuote],
+    failed: &[SymbolError],
+    format: OutputFormat,
+    quiet: bool,
+    status: bool,
+) -> Result<()> {
     match format {
+        OutputFormat::Table if quiet => {
+            print!("{}", render_rows(quotes));
+            Ok(())
+        }
         OutputFormat::Table => {
-            display_table(quotes);
+            display_table(quotes, failed);
             Ok(())
         }
-        OutputFormat::Json => display_json(quotes),
-        OutputFormat::Csv => display_csv(quotes),
+        OutputFormat::Json => display_json(quotes, failed),
+        OutputFormat::Csv => display_csv(quotes, failed, status),
         OutputFormat::Compact => {
-            display_compact(quotes);
+            display_compact(quotes, quiet);
+            Ok(())
+        }
+        OutputFormat::Ndjson => display_ndjson(quotes),
+        OutputFormat::Influx => {
+            display_influx(quotes);
             Ok(())
         }
     }
 }
 
-fn display_table(quotes: &[StockQuote]) {
-    println!("\n{}", "=".repeat(75));
-    println!(
+fn display_table(quotes: &[StockQuote], failed: &[SymbolError]) {
+    let enriched = is_enriched(quotes);
+    let international = is_international(quotes);
+    let width = 75 + if enriched { 22 } else { 0 } + if international { 5 } else { 0 };
+
+    println!("\n{}", "=".repeat(width));
+    print!(
         "{:<8} {:>12} {:>12} {:>12} {:>12}",
         "SYMBOL", "PRICE", "PREV CLOSE", "CHANGE", "DAY RANGE"
     );
-    println!("{}", "=".repeat(75));
+    if international {
+        print!("  {:<3}", "CCY");
+    }
+    if enriched {
+        print!("  {:<20}", "SECTOR");
+    }
+    println!();
+    println!("{}", "=".repeat(width));
 
     for quote in quotes {
         let range = if quote.high > 0.0 && quote.low > 0.0 {
@@ -52,7 +105,7 @@ fn display_table(quotes: &[StockQuote]) {
             "N/A".to_string()
         };
 
-        println!(
+        print!(
             "{:<8} {:>12.2} {:>12.2} {} {:>12}",
             quote.symbol,
             quote.price,
@@ -60,44 +113,157 @@ fn display_table(quotes: &[StockQuote]) {
             format_change(quote.change_pct),
             range
         );
+        if international {
+            print!("  {:<3}", exchange::currency(&quote.symbol));
+        }
+        if enriched {
+            print!("  {:<20}", truncate(quote.sector.as_deref().unwrap_or("-"), 20));
+        }
+        println!();
     }
 
-    println!("{}", "=".repeat(75));
+    println!("{}", "=".repeat(width));
+    if !failed.is_empty() {
+        println!("\n{}", render_failed(failed));
+    }
     display_summary(quotes);
 }
 
-fn display_json(quotes: &[StockQuote]) -> Result<()> {
+/// Footer naming the symbols that couldn't be fetched, with why
+pub fn render_failed(failed: &[SymbolError]) -> String {
+    let symbols: Vec<String> = failed
+        .iter()
+        .map(|failure| format!("{} ({})", failure.symbol, failure.code))
+        .collect();
+    format!("⚠ Failed symbols ({}): {}", failed.len(), symbols.join(", "))
+}
+
+fn display_ndjson(quotes: &[StockQuote]) -> Result<()> {
+    for quote in quotes {
+        print_ndjson(quote)?;
+    }
+    Ok(())
+}
+
+fn display_influx(quotes: &[StockQuote]) {
+    let timestamp_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    for quote in quotes {
+        if let Some(line) = influx::line(influx::DEFAULT_MEASUREMENT, quote, timestamp_ns) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Print one quote as a line of NDJSON
+pub fn print_ndjson(quote: &StockQuote) -> Result<()> {
+    println!("{}", serde_json::to_string(quote)?);
+    Ok(())
+}
+
+fn display_json(quotes: &[StockQuote], failed: &[SymbolError]) -> Result<()> {
     #[derive(Serialize)]
     struct JsonOutput<'a> {
         quotes: &'a [StockQuote],
         summary: Summary,
+        /// Symbols that couldn't be fetched, so a partial scan says so
+        errors: &'a [SymbolError],
     }
 
     let summary = calculate_summary(quotes);
-    let output = JsonOutput { quotes, summary };
+    let output = JsonOutput {
+        quotes,
+        summary,
+        errors: failed,
+    };
     
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
-fn display_csv(quotes: &[StockQuote]) -> Result<()> {
-    println!("symbol,price,prev_close,change_pct,high,low,open");
+fn display_csv(quotes: &[StockQuote], failed: &[SymbolError], status: bool) -> Result<()> {
+    let enriched = is_enriched(quotes);
+
+    print!("symbol,price,prev_close,change_pct,high,low,open");
+    if enriched {
+        print!(",name,sector");
+    }
+    if status {
+        print!(",status");
+    }
+    println!();
+
     for quote in quotes {
-        println!(
-            "{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}",
+        print!(
+            "{},{},{},{},{},{},{}",
             quote.symbol,
-            quote.price,
-            quote.prev_close,
-            quote.change_pct,
-            quote.high,
-            quote.low,
-            quote.open
+            decimal::format_fixed(quote.price, 2),
+            decimal::format_fixed(quote.prev_close, 2),
+            decimal::format_fixed(quote.change_pct, 2),
+            decimal::format_fixed(quote.high, 2),
+            decimal::format_fixed(quote.low, 2),
+            decimal::format_fixed(quote.open, 2)
         );
+        if enriched {
+            print!(
+                ",{},{}",
+                csv_field(quote.name.as_deref().unwrap_or("")),
+                csv_field(quote.sector.as_deref().unwrap_or(""))
+            );
+        }
+        if status {
+            print!(",ok");
+        }
+        println!();
+    }
+
+    if status {
+        let empty = if enriched { ",,,,,,,," } else { ",,,,,," };
+        for failure in failed {
+            println!("{}{},{}", csv_field(&failure.symbol), empty, failure.code);
+        }
     }
     Ok(())
 }
 
-fn display_compact(quotes: &[StockQuote]) {
+/// Whether any quote is priced in a currency other than US dollars
+fn is_international(quotes: &[StockQuote]) -> bool {
+    quotes.iter().any(|q| exchange::currency(&q.symbol) != "USD")
+}
+
+/// Whether any quote carries profile data worth showing
+fn is_enriched(quotes: &[StockQuote]) -> bool {
+    quotes.iter().any(|q| q.name.is_some() || q.sector.is_some())
+}
+
+/// Quote a CSV field when it contains separators or quotes
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn truncate(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        value.to_string()
+    } else if max == 0 {
+        String::new()
+    } else {
+        let mut short: String = value.chars().take(max - 1).collect();
+        short.push('…');
+        short
+    }
+}
+
+fn display_compact(quotes: &[StockQuote], plain: bool) {
+    let change = |change_pct: f64| {
+        if plain {
+            format!("{:>+8.2}%", change_pct)
+        } else {
+            format_change(change_pct)
+        }
+    };
     for quote in quotes {
         let arrow = if quote.change_pct > 0.0 {
             "↑"
@@ -107,17 +273,30 @@ fn display_compact(quotes: &[StockQuote]) {
             "→"
         };
 
-        println!(
-            "{:<6} ${:>8.2} {} {}",
-            quote.symbol,
-            quote.price,
-            arrow,
-            format_change(quote.change_pct)
-        );
+        let sign = exchange::price_sign(exchange::currency(&quote.symbol));
+        match &quote.name {
+            Some(name) => println!(
+                "{:<6} {}{:>8.2} {} {}  {}",
+                quote.symbol,
+                sign,
+                quote.price,
+                arrow,
+                change(quote.change_pct),
+                name
+            ),
+            None => println!(
+                "{:<6} {}{:>8.2} {} {}",
+                quote.symbol,
+                sign,
+                quote.price,
+                arrow,
+                change(quote.change_pct)
+            ),
+        }
     }
 }
 
-fn format_change(change_pct: f64) -> String {
+pub fn format_change(change_pct: f64) -> String {
     if change_pct > 0.0 {
         format!("\x1b[32m+{:>7.2}%\x1b[0m", change_pct)
     } else if change_pct < 0.0 {
@@ -127,32 +306,29 @@ fn format_change(change_pct: f64) -> String {
     }
 }
 
-#[derive(Serialize)]
-struct Summary {
-    total: usize,
-    gainers: usize,
-    losers: usize,
-    avg_change: f64,
-    top_gainer: Option<TopStock>,
-    top_loser: Option<TopStock>,
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub total: usize,
+    pub gainers: usize,
+    pub losers: usize,
+    pub avg_change: f64,
+    pub top_gainer: Option<TopStock>,
+    pub top_loser: Option<TopStock>,
 }
 
-#[derive(Serialize)]
-struct TopStock {
-    symbol: String,
-    change_pct: f64,
+#[derive(Debug, Serialize)]
+pub struct TopStock {
+    pub symbol: String,
+    pub change_pct: f64,
 }
 
-fn calculate_summary(quotes: &[StockQuote]) -> Summary {
+pub fn calculate_summary(quotes: &[StockQuote]) -> Summary {
     let total = quotes.len();
     let gainers = quotes.iter().filter(|q| q.change_pct > 0.0).count();
     let losers = quotes.iter().filter(|q| q.change_pct < 0.0).count();
     
-    let avg_change = if total > 0 {
-        quotes.iter().map(|q| q.change_pct).sum::<f64>() / total as f64
-    } else {
-        0.0
-    };
+    let changes: Vec<f64> = quotes.iter().map(|q| q.change_pct).collect();
+    let avg_change = decimal::average(&changes);
 
     let top_gainer = quotes
         .iter()
@@ -206,6 +382,86 @@ fn display_summary(quotes: &[StockQuote]) {
     println!();
 }
 
+/// One uncolored line per quote, without headers, as printed by `--quiet`
+pub fn render_rows(quotes: &[StockQuote]) -> String {
+    let mut out = String::new();
+    for quote in quotes {
+        out.push_str(&format!(
+            "{:<8} {:>12.2} {:>12.2} {:>+8.2}% {:>12}\n",
+            quote.symbol,
+            quote.price,
+            quote.prev_close,
+            quote.change_pct,
+            format!("{:.2}-{:.2}", quote.low, quote.high)
+        ));
+    }
+    out
+}
+
+/// Render quotes and their summary as an uncolored plain-text table, e.g. for email bodies
+pub fn render_text(quotes: &[StockQuote]) -> String {
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "{:<8} {:>12} {:>12} {:>9} {:>12}\n",
+        "SYMBOL", "PRICE", "PREV CLOSE", "CHANGE", "DAY RANGE"
+    ));
+    out.push_str(&render_rows(quotes));
+
+    let summary = calculate_summary(quotes);
+    out.push_str(&format!(
+        "\n{} symbols | {} gainers | {} losers | avg {:+.2}%\n",
+        summary.total, summary.gainers, summary.losers, summary.avg_change
+    ));
+    out
+}
+
+/// Render quotes and their summary as an HTML table with colored changes
+pub fn render_html(quotes: &[StockQuote]) -> String {
+    let mut out = String::from(
+        "<table cellpadding=\"4\" style=\"border-collapse:collapse;font-family:monospace\">\n\
+         <tr><th align=\"left\">Symbol</th><th align=\"right\">Price</th><th align=\"right\">Prev close</th>\
+         <th align=\"right\">Change</th><th align=\"right\">Day range</th></tr>\n",
+    );
+
+    for quote in quotes {
+        let color = if quote.change_pct > 0.0 {
+            "#1e8e3e"
+        } else if quote.change_pct < 0.0 {
+            "#d93025"
+        } else {
+            "inherit"
+        };
+        out.push_str(&format!(
+            "<tr><td>{}</td><td align=\"right\">{:.2}</td><td align=\"right\">{:.2}</td>\
+             <td align=\"right\" style=\"color:{}\">{:+.2}%</td><td align=\"right\">{:.2}-{:.2}</td></tr>\n",
+            html_escape(&quote.symbol),
+            quote.price,
+            quote.prev_close,
+            color,
+            quote.change_pct,
+            quote.low,
+            quote.high
+        ));
+    }
+    out.push_str("</table>\n");
+
+    let summary = calculate_summary(quotes);
+    out.push_str(&format!(
+        "<p>{} symbols | {} gainers | {} losers | avg {:+.2}%</p>\n",
+        summary.total, summary.gainers, summary.losers, summary.avg_change
+    ));
+    out
+}
+
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 pub fn filter_quotes(
     quotes: Vec<StockQuote>,
     gainers_only: bool,
@@ -214,23 +470,58 @@ pub fn filter_quotes(
 ) -> Vec<StockQuote> {
     quotes
         .into_iter()
-        .filter(|q| {
-            if gainers_only && q.change_pct <= 0.0 {
-                return false;
-            }
-            if losers_only && q.change_pct >= 0.0 {
-                return false;
-            }
-            if let Some(min) = min_change {
-                if q.change_pct.abs() < min {
-                    return false;
-                }
-            }
-            true
-        })
+        .filter(|q| passes_change_filters(q, gainers_only, losers_only, min_change))
         .collect()
 }
 
+fn passes_change_filters(q: &StockQuote, gainers_only: bool, losers_only: bool, min_change: Option<f64>) -> bool {
+    if gainers_only && q.change_pct <= 0.0 {
+        return false;
+    }
+    if losers_only && q.change_pct >= 0.0 {
+        return false;
+    }
+    if let Some(min) = min_change {
+        if q.change_pct.abs() < min {
+            return false;
+        }
+    }
+    true
+}
+
+/// Row filters shared by `scan` and `watch`
+#[derive(Debug, Clone, Default)]
+pub struct QuoteFilter {
+    pub gainers_only: bool,
+    pub losers_only: bool,
+    pub min_change: Option<f64>,
+    /// `--filter` expression; quotes it can't be evaluated on are dropped
+    pub expr: Option<Expr>,
+}
+
+impl QuoteFilter {
+    pub fn apply(&self, quotes: Vec<StockQuote>) -> Vec<StockQuote> {
+        quotes.into_iter().filter(|quote| self.matches(quote)).collect()
+    }
+
+    pub fn matches(&self, quote: &StockQuote) -> bool {
+        if !passes_change_filters(quote, self.gainers_only, self.losers_only, self.min_change) {
+            return false;
+        }
+
+        match &self.expr {
+            Some(expr) => match expr.matches(quote) {
+                Ok(matched) => matched,
+                Err(e) => {
+                    log::debug!("Filter skipped {}: {}", quote.symbol, e);
+                    false
+                }
+            },
+            None => true,
+        }
+    }
+}
+
 pub fn sort_by_change(mut quotes: Vec<StockQuote>) -> Vec<StockQuote> {
     quotes.sort_by(|a, b| {
         b.change_pct
@@ -260,9 +551,19 @@ mod tests {
             high: 105.0,
             low: 95.0,
             open: 98.0,
+            name: None,
+            sector: None,
+            source: None,
         }
     }
 
+    #[test]
+    fn test_output_format_from_name() {
+        assert_eq!(OutputFormat::from_name("NDJSON"), Some(OutputFormat::Ndjson));
+        assert_eq!(OutputFormat::from_name("table"), Some(OutputFormat::Table));
+        assert_eq!(OutputFormat::from_name("xml"), None);
+    }
+
     #[test]
     fn test_filter_gainers_only() {
         let quotes = vec![
@@ -314,4 +615,56 @@ mod tests {
         assert_eq!(sorted[1].symbol, "B"); // -5%
         assert_eq!(sorted[2].symbol, "A"); // 2%
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("Technology"), "Technology");
+        assert_eq!(csv_field("Berkshire Hathaway, Inc"), "\"Berkshire Hathaway, Inc\"");
+        assert_eq!(truncate("Semiconductors & Equipment", 10), "Semicondu…");
+        assert_eq!(truncate("Technology", 0), "");
+    }
+
+    #[test]
+    fn test_quote_filter_expression() {
+        let filter = QuoteFilter {
+            gainers_only: true,
+            expr: Some(crate::expr::parse("change > 2").unwrap()),
+            ..QuoteFilter::default()
+        };
+
+        let quotes = vec![
+            create_test_quote("A", 1.0),
+            create_test_quote("B", 5.0),
+            create_test_quote("C", -6.0),
+        ];
+        let filtered = filter.apply(quotes);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].symbol, "B");
+    }
+
+    #[test]
+    fn test_render_text_and_html() {
+        let quotes = vec![create_test_quote("AAPL", 2.5), create_test_quote("A&B", -1.0)];
+
+        let text = render_text(&quotes);
+        assert!(text.contains("+2.50%"));
+        assert!(text.contains("2 symbols | 1 gainers | 1 losers"));
+        assert!(!text.contains('\x1b'));
+
+        let failed = [SymbolError {
+            symbol: "XYZ".to_string(),
+            code: "no_data",
+            error: "API error: no data for XYZ".to_string(),
+        }];
+        assert_eq!(render_failed(&failed), "⚠ Failed symbols (1): XYZ (no_data)");
+
+        let rows = render_rows(&quotes);
+        assert_eq!(rows.lines().count(), 2);
+        assert!(rows.starts_with("AAPL ") && !rows.contains("SYMBOL"));
+
+        let html = render_html(&quotes);
+        assert!(html.contains("<td>A&amp;B</td>"));
+        assert!(html.contains("color:#d93025\">-1.00%"));
+    }
+}
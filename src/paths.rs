@@ -0,0 +1,91 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "finnhub-scanner";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Per-user configuration directory: `$XDG_CONFIG_HOME/finnhub-scanner` (`~/.config` when unset),
+/// `~/Library/Application Support` on macOS, `%APPDATA%` on Windows
+pub fn config_dir() -> Option<PathBuf> {
+    platform_dir("XDG_CONFIG_HOME", ".config", "Library/Application Support", "APPDATA", env_var)
+}
+
+/// Per-user data directory for history and alert logs: `$XDG_DATA_HOME/finnhub-scanner`
+/// (`~/.local/share` when unset), `~/Library/Application Support` on macOS, `%LOCALAPPDATA%` on Windows
+pub fn data_dir() -> Option<PathBuf> {
+    platform_dir("XDG_DATA_HOME", ".local/share", "Library/Application Support", "LOCALAPPDATA", env_var)
+}
+
+/// Per-user cache directory: `$XDG_CACHE_HOME/finnhub-scanner` (`~/.cache` when unset),
+/// `~/Library/Caches` on macOS, `%LOCALAPPDATA%` on Windows
+pub fn cache_dir() -> Option<PathBuf> {
+    platform_dir("XDG_CACHE_HOME", ".cache", "Library/Caches", "LOCALAPPDATA", env_var)
+}
+
+/// Where `config --init` writes and where the config is looked for when `--config` is not given
+pub fn config_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+/// File under the data directory, or in the working directory when there is no home directory
+pub fn data_file(name: &str) -> PathBuf {
+    data_dir().map(|dir| dir.join(name)).unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// The process environment, as read outside tests
+fn env_var(name: &str) -> Option<OsString> {
+    env::var_os(name)
+}
+
+/// The app's directory under the platform's base directory, reading variables with `var`
+fn platform_dir(
+    xdg_var: &str,
+    xdg_default: &str,
+    macos: &str,
+    windows_var: &str,
+    var: impl Fn(&str) -> Option<OsString>,
+) -> Option<PathBuf> {
+    // A set, non-empty, absolute path; the XDG spec says relative values are to be ignored
+    let env_path = |name: &str| var(name).map(PathBuf::from).filter(|path| path.is_absolute());
+    let home = || env_path("HOME");
+
+    let base = if cfg!(windows) {
+        env_path(windows_var)
+    } else if cfg!(target_os = "macos") {
+        // XDG variables are still honoured on macOS when set explicitly
+        env_path(xdg_var).or_else(|| home().map(|home| home.join(macos)))
+    } else {
+        env_path(xdg_var).or_else(|| home().map(|home| home.join(xdg_default)))
+    };
+    base.map(|base| base.join(APP_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_xdg_dirs() {
+        let vars = [
+            ("XDG_CONFIG_HOME", "/tmp/xdg-config"),
+            ("XDG_DATA_HOME", "relative/ignored"),
+            ("HOME", "/home/trader"),
+        ];
+        let var = |name: &str| {
+            let (_, value) = vars.iter().find(|(key, _)| *key == name)?;
+            Some(OsString::from(value))
+        };
+
+        assert_eq!(
+            platform_dir("XDG_CONFIG_HOME", ".config", "", "", var),
+            Some(PathBuf::from("/tmp/xdg-config/finnhub-scanner"))
+        );
+        assert_eq!(
+            platform_dir("XDG_DATA_HOME", ".local/share", "", "", var),
+            Some(PathBuf::from("/home/trader/.local/share/finnhub-scanner"))
+        );
+        assert_eq!(platform_dir("XDG_CACHE_HOME", ".cache", "", "", |_| None), None);
+    }
+}
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Candles, Quote, StockQuote, CANDLE_RESOLUTIONS};
+use crate::provider::{ProviderFuture, QuoteProvider};
+use crate::ratelimit::TokenBucket;
+
+const BASE_URL: &str = "https://api.polygon.io";
+
+/// `[polygon]`: key and rate limit for `--provider polygon`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolygonConfig {
+    /// Also read from `POLYGON_API_KEY`
+    #[serde(default)]
+    pub api_key: String,
+
+    /// The free plan allows 5 requests a minute; paid plans are unlimited
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+}
+
+impl Default for PolygonConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            requests_per_minute: default_requests_per_minute(),
+        }
+    }
+}
+
+fn default_requests_per_minute() -> u32 {
+    5
+}
+
+/// Quotes from Polygon.io ticker snapshots, and candles from its aggregates
+///
+/// Snapshots need a Stocks Starter plan or above; the free plan only serves aggregates.
+pub struct PolygonProvider {
+    api_key: String,
+    client: reqwest::Client,
+    /// Also set per request, see [`crate::config::HttpConfig::client_builder`]
+    timeout: Duration,
+    limiter: TokenBucket,
+}
+
+#[derive(Deserialize)]
+struct SnapshotResponse {
+    ticker: Option<Snapshot>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    #[serde(default)]
+    day: Bar,
+    #[serde(default)]
+    prev_day: Bar,
+    last_trade: Option<Trade>,
+}
+
+#[derive(Deserialize, Default)]
+struct Bar {
+    #[serde(default)]
+    o: f64,
+    #[serde(default)]
+    h: f64,
+    #[serde(default)]
+    l: f64,
+    #[serde(default)]
+    c: f64,
+    #[serde(default)]
+    v: f64,
+    /// Start of the bar in unix milliseconds
+    #[serde(default)]
+    t: i64,
+}
+
+#[derive(Deserialize)]
+struct Trade {
+    p: f64,
+}
+
+#[derive(Deserialize)]
+struct AggregatesResponse {
+    #[serde(default)]
+    results: Vec<Bar>,
+}
+
+impl PolygonProvider {
+    pub fn new(config: &Config) -> Result<Self> {
+        let settings = &config.polygon;
+        if settings.api_key.is_empty() {
+            return Err(ScannerError::Config(
+                "--provider polygon needs an API key. Set POLYGON_API_KEY or [polygon] api_key".to_string(),
+            ));
+        }
+
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let client = config.http.client_builder(timeout)?.build()?;
+        Ok(Self {
+            api_key: settings.api_key.clone(),
+            client,
+            timeout,
+            limiter: TokenBucket::new(settings.requests_per_minute, 1),
+        })
+    }
+
+    /// GET `path`, with the key sent as a bearer token so it stays out of logged URLs
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, symbol: &str) -> Result<T> {
+        self.limiter.acquire().await;
+        let response = self
+            .client
+            .get(format!("{}{}", BASE_URL, path))
+            .bearer_auth(&self.api_key)
+            .timeout(self.timeout)
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            200..=299 => Ok(response.json().await?),
+            401 => Err(ScannerError::InvalidApiKey),
+            403 => Err(ScannerError::Api(format!(
+                "Polygon.io: your plan does not include {} (HTTP 403)",
+                path.split('?').next().unwrap_or(path)
+            ))),
+            404 => Err(ScannerError::SymbolNotFound(symbol.to_string())),
+            429 => Err(ScannerError::RateLimited { retry_after: None }),
+            status => Err(ScannerError::Http {
+                status,
+                context: symbol.to_string(),
+            }),
+        }
+    }
+}
+
+impl QuoteProvider for PolygonProvider {
+    fn name(&self) -> &str {
+        "polygon"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            log::debug!("Fetching Polygon.io snapshot for {}", symbol);
+            let path = format!("/v2/snapshot/locale/us/markets/stocks/tickers/{}", symbol);
+            let response: SnapshotResponse = self.get(&path, symbol).await?;
+            let snapshot = response
+                .ticker
+                .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+            Ok(StockQuote::from_quote(symbol.to_string(), snapshot_quote(snapshot)))
+        })
+    }
+
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        Box::pin(async move {
+            let (multiplier, timespan) = timespan(resolution)?;
+            log::debug!("Fetching Polygon.io {} aggregates for {}", resolution, symbol);
+            // Aggregate ranges take unix milliseconds as well as dates
+            let path = format!(
+                "/v2/aggs/ticker/{}/range/{}/{}/{}/{}?adjusted=true&sort=asc&limit=50000",
+                symbol,
+                multiplier,
+                timespan,
+                from * 1000,
+                to * 1000
+            );
+            let response: AggregatesResponse = self.get(&path, symbol).await?;
+            if response.results.is_empty() {
+                return Err(ScannerError::NoCandleData(symbol.to_string()));
+            }
+            Ok(aggregates_candles(response.results))
+        })
+    }
+}
+
+/// Polygon's `multiplier` and `timespan` for a Finnhub candle resolution
+fn timespan(resolution: &str) -> Result<(u32, &'static str)> {
+    match resolution {
+        "D" => Ok((1, "day")),
+        "W" => Ok((1, "week")),
+        "M" => Ok((1, "month")),
+        minutes => minutes
+            .parse::<u32>()
+            .ok()
+            .filter(|_| CANDLE_RESOLUTIONS.contains(&minutes))
+            .map(|minutes| (minutes, "minute"))
+            .ok_or_else(|| {
+                ScannerError::InvalidInput(format!(
+                    "Unsupported resolution '{}'. Use one of: {}",
+                    resolution,
+                    CANDLE_RESOLUTIONS.join(", ")
+                ))
+            }),
+    }
+}
+
+fn snapshot_quote(snapshot: Snapshot) -> Quote {
+    // Before the open `day` is still empty, so fall back to the last trade
+    let price = if snapshot.day.c > 0.0 {
+        snapshot.day.c
+    } else {
+        snapshot.last_trade.as_ref().map_or(snapshot.prev_day.c, |trade| trade.p)
+    };
+    Quote {
+        c: price,
+        pc: snapshot.prev_day.c,
+        h: snapshot.day.h,
+        l: snapshot.day.l,
+        o: snapshot.day.o,
+    }
+}
+
+fn aggregates_candles(bars: Vec<Bar>) -> Candles {
+    let mut candles = Candles {
+        s: "ok".to_string(),
+        ..Candles::default()
+    };
+    for bar in bars {
+        candles.o.push(bar.o);
+        candles.h.push(bar.h);
+        candles.l.push(bar.l);
+        candles.c.push(bar.c);
+        candles.v.push(bar.v);
+        candles.t.push(bar.t / 1000);
+    }
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_quote() {
+        let response: SnapshotResponse = serde_json::from_str(
+            r#"{"status": "OK", "ticker": {"ticker": "AAPL",
+            "day": {"o": 271.5, "h": 277.84, "l": 269.0, "c": 271.01, "v": 5.1e7},
+            "prevDay": {"o": 270.0, "h": 273.0, "l": 268.0, "c": 271.86, "v": 4.8e7},
+            "lastTrade": {"p": 271.05, "s": 100}}}"#,
+        )
+        .unwrap();
+        let quote = snapshot_quote(response.ticker.unwrap());
+        assert_eq!(quote.c, 271.01);
+        assert_eq!(quote.pc, 271.86);
+        assert_eq!(quote.h, 277.84);
+
+        let premarket: Snapshot = serde_json::from_str(
+            r#"{"day": {"c": 0}, "prevDay": {"c": 271.86}, "lastTrade": {"p": 272.4}}"#,
+        )
+        .unwrap();
+        assert_eq!(snapshot_quote(premarket).c, 272.4);
+    }
+
+    #[test]
+    fn test_aggregates_candles() {
+        let response: AggregatesResponse = serde_json::from_str(
+            r#"{"ticker": "AAPL", "resultsCount": 2, "results": [
+            {"o": 1.0, "h": 2.0, "l": 0.5, "c": 1.5, "v": 100, "t": 1760400000000},
+            {"o": 1.5, "h": 2.5, "l": 1.0, "c": 2.0, "v": 200, "t": 1760486400000}]}"#,
+        )
+        .unwrap();
+        let candles = aggregates_candles(response.results);
+        assert_eq!(candles.c, vec![1.5, 2.0]);
+        assert_eq!(candles.t, vec![1_760_400_000, 1_760_486_400]);
+    }
+
+    #[test]
+    fn test_timespan() {
+        assert_eq!(timespan("D").unwrap(), (1, "day"));
+        assert_eq!(timespan("15").unwrap(), (15, "minute"));
+        assert!(timespan("7").is_err());
+    }
+}
@@ -0,0 +1,715 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::decimal;
+use crate::errors::{Result, ScannerError};
+use crate::exchange;
+use crate::finnhub::{StockQuote, SymbolError};
+use crate::output::{self, OutputFormat};
+use crate::paths;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioConfig {
+    /// Holdings file read by `portfolio`
+    #[serde(default = "default_file")]
+    pub file: PathBuf,
+    /// Weight in percent above which `portfolio --allocation` flags a position or sector
+    #[serde(default = "default_concentration_pct")]
+    pub concentration_pct: f64,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            file: default_file(),
+            concentration_pct: default_concentration_pct(),
+        }
+    }
+}
+
+fn default_file() -> PathBuf {
+    paths::data_file("portfolio.csv")
+}
+
+fn default_concentration_pct() -> f64 {
+    20.0
+}
+
+/// Whether a holdings row added shares or sold them
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Side {
+    #[default]
+    Buy,
+    Sell,
+}
+
+/// One lot, or one sale, from a holdings file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: f64,
+    /// Price paid per share, or received for a sale
+    pub cost_basis: f64,
+}
+
+/// Read a holdings file: CSV with `symbol`, `quantity` and `cost_basis` columns, in any order
+///
+/// Each row is a lot; a symbol bought more than once has a row per purchase. An optional `side`
+/// column marks sales with `sell`, their `cost_basis` being the price received. Blank lines and
+/// lines starting with `#` are skipped.
+pub fn read_holdings(path: &Path) -> Result<Vec<Holding>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| ScannerError::Io(format!("Failed to read holdings file {}: {}", path.display(), e)))?;
+    parse_holdings(&text).map_err(|e| ScannerError::Parse(format!("{}: {}", path.display(), e)))
+}
+
+/// Write a holdings file that [`read_holdings`] reads back, creating its directory if needed
+pub fn write_holdings(path: &Path, holdings: &[Holding]) -> Result<()> {
+    let mut content = String::from("symbol,side,quantity,cost_basis\n");
+    for holding in holdings {
+        let side = match holding.side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        };
+        content.push_str(&format!(
+            "{},{},{},{}\n",
+            holding.symbol,
+            side,
+            holding.quantity,
+            decimal::format_fixed(holding.cost_basis, 4)
+        ));
+    }
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)
+    };
+    write().map_err(|e| {
+        ScannerError::from(e).context(&format!("Failed to write holdings file {}", path.display()))
+    })
+}
+
+fn parse_holdings(text: &str) -> std::result::Result<Vec<Holding>, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+    let header: Vec<String> = lines
+        .next()
+        .ok_or("empty file")?
+        .1
+        .split(',')
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|name| names.contains(&name.as_str()))
+            .ok_or_else(|| format!("no {} column", names[0]))
+    };
+    let (symbol, quantity, cost_basis) = (
+        column(&["symbol", "ticker"])?,
+        column(&["quantity", "qty", "shares"])?,
+        column(&["cost_basis", "cost", "price"])?,
+    );
+    let side = column(&["side", "action"]).ok();
+
+    // Sales are checked against what's held at that point in the file
+    let mut held: HashMap<String, f64> = HashMap::new();
+    lines
+        .map(|(idx, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+            let number = |index: usize| {
+                field(index)
+                    .parse::<f64>()
+                    .map_err(|_| format!("line {}: invalid number '{}'", idx + 1, field(index)))
+            };
+            if field(symbol).is_empty() {
+                return Err(format!("line {}: no symbol", idx + 1));
+            }
+            let holding = Holding {
+                symbol: exchange::normalize(&field(symbol).to_uppercase()),
+                side: match side.map(&field).unwrap_or_default().to_lowercase().as_str() {
+                    "" | "buy" => Side::Buy,
+                    "sell" => Side::Sell,
+                    other => {
+                        return Err(format!("line {}: side '{}' is neither buy nor sell", idx + 1, other));
+                    }
+                },
+                quantity: number(quantity)?,
+                cost_basis: number(cost_basis)?,
+            };
+
+            let shares = held.entry(holding.symbol.clone()).or_default();
+            match holding.side {
+                Side::Buy => *shares += holding.quantity,
+                Side::Sell if holding.quantity > *shares + 1e-9 => {
+                    return Err(format!(
+                        "line {}: sells {} {} with only {} held",
+                        idx + 1,
+                        holding.quantity,
+                        holding.symbol,
+                        shares
+                    ));
+                }
+                Side::Sell => *shares -= holding.quantity,
+            }
+            Ok(holding)
+        })
+        .collect()
+}
+
+/// The symbols held, in the order they first appear
+pub fn symbols(holdings: &[Holding]) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+    for holding in holdings {
+        if !symbols.contains(&holding.symbol) {
+            symbols.push(holding.symbol.clone());
+        }
+    }
+    symbols
+}
+
+/// A symbol's rows replayed in file order, at average cost
+#[derive(Debug, Clone, Copy, Default)]
+struct Ledger {
+    quantity: f64,
+    /// What the shares still held cost
+    cost: f64,
+    realized_pnl: f64,
+}
+
+fn ledgers(holdings: &[Holding]) -> HashMap<&str, Ledger> {
+    let mut ledgers: HashMap<&str, Ledger> = HashMap::new();
+    for holding in holdings {
+        let ledger = ledgers.entry(holding.symbol.as_str()).or_default();
+        match holding.side {
+            Side::Buy => {
+                ledger.quantity += holding.quantity;
+                ledger.cost += holding.quantity * holding.cost_basis;
+            }
+            Side::Sell if ledger.quantity > 0.0 => {
+                let sold = holding.quantity.min(ledger.quantity);
+                let average = ledger.cost / ledger.quantity;
+                ledger.realized_pnl += sold * (holding.cost_basis - average);
+                ledger.cost -= sold * average;
+                ledger.quantity -= sold;
+            }
+            Side::Sell => {}
+        }
+    }
+    ledgers
+}
+
+/// A symbol's lots together, valued at its latest quote
+#[derive(Debug, Clone, Serialize)]
+pub struct Position {
+    pub symbol: String,
+    pub quantity: f64,
+    /// Average price paid per share still held
+    pub cost_basis: f64,
+    pub price: f64,
+    pub market_value: f64,
+    /// Change in market value since the previous close
+    pub day_change: f64,
+    pub day_change_pct: f64,
+    /// Market value less what the shares held cost
+    pub unrealized_pnl: f64,
+    pub unrealized_pnl_pct: f64,
+    /// Gains and losses locked in by the symbol's sales
+    pub realized_pnl: f64,
+    /// Industry from the company profile, when quotes were enriched with profiles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sector: Option<String>,
+}
+
+/// Combine each symbol's rows and value what's still held at `quotes`
+///
+/// Symbols sold off entirely, or without a quote, are left out.
+pub fn positions(holdings: &[Holding], quotes: &[StockQuote]) -> Vec<Position> {
+    let quotes: HashMap<&str, &StockQuote> = quotes
+        .iter()
+        .map(|quote| (quote.symbol.as_str(), quote))
+        .collect();
+    let ledgers = ledgers(holdings);
+
+    symbols(holdings)
+        .into_iter()
+        .filter_map(|symbol| {
+            let ledger = ledgers[symbol.as_str()];
+            let quote = quotes.get(symbol.as_str())?;
+            if ledger.quantity <= 0.0 {
+                return None;
+            }
+            let market_value = ledger.quantity * quote.price;
+            Some(Position {
+                cost_basis: ledger.cost / ledger.quantity,
+                price: quote.price,
+                market_value,
+                day_change: ledger.quantity * (quote.price - quote.prev_close),
+                day_change_pct: quote.change_pct,
+                unrealized_pnl: market_value - ledger.cost,
+                unrealized_pnl_pct: decimal::change_pct(market_value, ledger.cost),
+                realized_pnl: ledger.realized_pnl,
+                sector: quote.sector.clone(),
+                symbol,
+                quantity: ledger.quantity,
+            })
+        })
+        .collect()
+}
+
+/// Gains and losses from every sale in the holdings, including symbols no longer held
+pub fn realized_pnl(holdings: &[Holding]) -> f64 {
+    ledgers(holdings).values().map(|ledger| ledger.realized_pnl).sum()
+}
+
+/// Sums over all positions, in whatever currencies they're priced in
+#[derive(Debug, Clone, Serialize)]
+pub struct Totals {
+    pub market_value: f64,
+    pub day_change: f64,
+    pub day_change_pct: f64,
+    pub unrealized_pnl: f64,
+    pub unrealized_pnl_pct: f64,
+    pub realized_pnl: f64,
+}
+
+/// Totals for `positions`, with `realized_pnl` from [`realized_pnl`] so closed positions count
+pub fn totals(positions: &[Position], realized_pnl: f64) -> Totals {
+    let market_value: f64 = positions.iter().map(|p| p.market_value).sum();
+    let day_change: f64 = positions.iter().map(|p| p.day_change).sum();
+    let unrealized_pnl: f64 = positions.iter().map(|p| p.unrealized_pnl).sum();
+    Totals {
+        market_value,
+        day_change,
+        day_change_pct: decimal::change_pct(market_value, market_value - day_change),
+        unrealized_pnl,
+        unrealized_pnl_pct: decimal::change_pct(market_value, market_value - unrealized_pnl),
+        realized_pnl,
+    }
+}
+
+/// Print positions in any output format but `influx`, with totals where the format has room
+///
+/// `quiet` leaves out the table's rules, totals and failures and the color codes.
+pub fn display(
+    positions: &[Position],
+    totals: &Totals,
+    failed: &[SymbolError],
+    format: OutputFormat,
+    quiet: bool,
+) -> Result<()> {
+    match format {
+        OutputFormat::Table => display_table(positions, totals, failed, quiet),
+        OutputFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonOutput<'a> {
+                positions: &'a [Position],
+                totals: &'a Totals,
+                errors: &'a [SymbolError],
+            }
+            let json = JsonOutput {
+                positions,
+                totals,
+                errors: failed,
+            };
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Csv => {
+            println!(
+                "symbol,quantity,cost_basis,price,market_value,day_change,day_change_pct,\
+                 unrealized_pnl,unrealized_pnl_pct,realized_pnl"
+            );
+            for p in positions {
+                println!(
+                    "{},{},{},{},{},{},{},{},{},{}",
+                    output::csv_field(&p.symbol),
+                    p.quantity,
+                    decimal::format_fixed(p.cost_basis, 4),
+                    decimal::format_fixed(p.price, 2),
+                    decimal::format_fixed(p.market_value, 2),
+                    decimal::format_fixed(p.day_change, 2),
+                    decimal::format_fixed(p.day_change_pct, 2),
+                    decimal::format_fixed(p.unrealized_pnl, 2),
+                    decimal::format_fixed(p.unrealized_pnl_pct, 2),
+                    decimal::format_fixed(p.realized_pnl, 2)
+                );
+            }
+        }
+        OutputFormat::Ndjson => {
+            for p in positions {
+                println!("{}", serde_json::to_string(p)?);
+            }
+        }
+        OutputFormat::Compact => {
+            for p in positions {
+                let sign = exchange::price_sign(exchange::currency(&p.symbol));
+                println!(
+                    "{:<8} {}{:>11.2} {:>+10.2} ({:+.2}%)  P&L {:+.2} ({:+.2}%)",
+                    p.symbol,
+                    sign,
+                    p.market_value,
+                    p.day_change,
+                    p.day_change_pct,
+                    p.unrealized_pnl,
+                    p.unrealized_pnl_pct
+                );
+            }
+        }
+        OutputFormat::Influx => {
+            return Err(ScannerError::InvalidInput(
+                "portfolio has no influx output; use table, json, csv, ndjson or compact".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn display_table(positions: &[Position], totals: &Totals, failed: &[SymbolError], quiet: bool) {
+    const WIDTH: usize = 108;
+    let change = |change_pct: f64| {
+        if quiet {
+            format!("{:>+8.2}%", change_pct)
+        } else {
+            output::format_change(change_pct)
+        }
+    };
+
+    if !quiet {
+        println!("\n{}", "=".repeat(WIDTH));
+        println!(
+            "{:<8} {:>9} {:>10} {:>10} {:>13} {:>12} {:>9} {:>13} {:>9}",
+            "SYMBOL", "QTY", "AVG COST", "PRICE", "VALUE", "DAY CHG", "DAY %", "P&L", "P&L %"
+        );
+        println!("{}", "=".repeat(WIDTH));
+    }
+    for p in positions {
+        println!(
+            "{:<8} {:>9} {:>10.2} {:>10.2} {:>13.2} {:>+12.2} {} {:>+13.2} {}",
+            p.symbol,
+            p.quantity,
+            p.cost_basis,
+            p.price,
+            p.market_value,
+            p.day_change,
+            change(p.day_change_pct),
+            p.unrealized_pnl,
+            change(p.unrealized_pnl_pct)
+        );
+    }
+    if quiet {
+        return;
+    }
+
+    println!("{}", "=".repeat(WIDTH));
+    println!(
+        "{:<8} {:>9} {:>10} {:>10} {:>13.2} {:>+12.2} {} {:>+13.2} {}",
+        "TOTAL",
+        "",
+        "",
+        "",
+        totals.market_value,
+        totals.day_change,
+        change(totals.day_change_pct),
+        totals.unrealized_pnl,
+        change(totals.unrealized_pnl_pct)
+    );
+    if totals.realized_pnl != 0.0 {
+        println!("\nRealized P&L from sales: {:+.2}", totals.realized_pnl);
+    }
+    if !failed.is_empty() {
+        println!("\n{}", output::render_failed(failed));
+    }
+}
+
+/// What a position is grouped under in the allocation: its sector, or failing that the kind
+/// of asset its symbol looks like
+pub fn asset_class(position: &Position) -> String {
+    if let Some(sector) = &position.sector {
+        return sector.clone();
+    }
+    let symbol = position.symbol.as_str();
+    if symbol.starts_with('^') {
+        "Index".to_string()
+    } else if symbol.contains('/') || symbol.starts_with("OANDA:") || symbol.starts_with("FXCM:") {
+        "Forex".to_string()
+    } else if symbol.contains(':') && exchange::of_symbol(symbol).is_none() {
+        "Crypto".to_string()
+    } else {
+        "Unclassified".to_string()
+    }
+}
+
+/// A position's or a sector's share of the portfolio's market value
+#[derive(Debug, Clone, Serialize)]
+pub struct Weight {
+    pub name: String,
+    pub market_value: f64,
+    pub weight_pct: f64,
+    /// Above the configured concentration threshold
+    pub concentrated: bool,
+}
+
+/// Market value grouped by `group`, heaviest first, with groups over `threshold_pct` flagged
+pub fn weights(
+    positions: &[Position],
+    group: impl Fn(&Position) -> String,
+    threshold_pct: f64,
+) -> Vec<Weight> {
+    let total: f64 = positions.iter().map(|p| p.market_value).sum();
+    let mut values: Vec<(String, f64)> = Vec::new();
+    for position in positions {
+        let name = group(position);
+        match values.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, value)) => *value += position.market_value,
+            None => values.push((name, position.market_value)),
+        }
+    }
+
+    let mut weights: Vec<Weight> = values
+        .into_iter()
+        .map(|(name, market_value)| {
+            let weight_pct = if total > 0.0 { market_value / total * 100.0 } else { 0.0 };
+            Weight {
+                name,
+                market_value,
+                weight_pct,
+                concentrated: weight_pct > threshold_pct,
+            }
+        })
+        .collect();
+    weights.sort_by(|a, b| b.weight_pct.total_cmp(&a.weight_pct));
+    weights
+}
+
+/// Show the weight of each position and each sector or asset class, flagging concentrations
+pub fn display_allocation(
+    positions: &[Position],
+    threshold_pct: f64,
+    failed: &[SymbolError],
+    format: OutputFormat,
+    quiet: bool,
+) -> Result<()> {
+    let by_position = weights(positions, |p| p.symbol.clone(), threshold_pct);
+    let by_sector = weights(positions, asset_class, threshold_pct);
+    let groups = [("position", &by_position), ("sector", &by_sector)];
+
+    match format {
+        OutputFormat::Table => {
+            for (title, weights) in [("SYMBOL", &by_position), ("SECTOR", &by_sector)] {
+                display_weights(title, weights, threshold_pct, quiet);
+            }
+            if !quiet && !failed.is_empty() {
+                println!("\n{}", output::render_failed(failed));
+            }
+        }
+        OutputFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonOutput<'a> {
+                positions: &'a [Weight],
+                sectors: &'a [Weight],
+                concentration_pct: f64,
+                errors: &'a [SymbolError],
+            }
+            let json = JsonOutput {
+                positions: &by_position,
+                sectors: &by_sector,
+                concentration_pct: threshold_pct,
+                errors: failed,
+            };
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Csv => {
+            println!("group,name,market_value,weight_pct,concentrated");
+            for (group, weights) in groups {
+                for w in weights {
+                    println!(
+                        "{},{},{},{},{}",
+                        group,
+                        output::csv_field(&w.name),
+                        decimal::format_fixed(w.market_value, 2),
+                        decimal::format_fixed(w.weight_pct, 2),
+                        w.concentrated
+                    );
+                }
+            }
+        }
+        OutputFormat::Ndjson => {
+            #[derive(Serialize)]
+            struct Line<'a> {
+                group: &'a str,
+                #[serde(flatten)]
+                weight: &'a Weight,
+            }
+            for (group, weights) in groups {
+                for weight in weights {
+                    println!("{}", serde_json::to_string(&Line { group, weight })?);
+                }
+            }
+        }
+        OutputFormat::Compact => {
+            for (_, weights) in groups {
+                for w in weights {
+                    let flag = if w.concentrated { "  !" } else { "" };
+                    println!("{:<24} {:>6.2}%{}", w.name, w.weight_pct, flag);
+                }
+            }
+        }
+        OutputFormat::Influx => {
+            return Err(ScannerError::InvalidInput(
+                "portfolio has no influx output; use table, json, csv, ndjson or compact".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn display_weights(title: &str, weights: &[Weight], threshold_pct: f64, quiet: bool) {
+    const WIDTH: usize = 72;
+    const BAR: usize = 20;
+
+    if !quiet {
+        println!("\n{}", "=".repeat(WIDTH));
+        println!("{:<24} {:>13} {:>8}  WEIGHT", title, "VALUE", "%");
+        println!("{}", "=".repeat(WIDTH));
+    }
+    for w in weights {
+        let bar = "#".repeat((w.weight_pct / 100.0 * BAR as f64).round() as usize);
+        let flag = match (w.concentrated, quiet) {
+            (false, _) => String::new(),
+            (true, true) => " !".to_string(),
+            (true, false) => format!(" \x1b[31mover {}%\x1b[0m", threshold_pct),
+        };
+        println!(
+            "{:<24} {:>13.2} {:>7.2}%  {:<BAR$}{}",
+            w.name, w.market_value, w.weight_pct, bar, flag
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_weights() {
+        let holdings = vec![
+            buy("AAPL", 10.0, 100.0),
+            buy("MSFT", 1.0, 100.0),
+            buy("BINANCE:BTCUSDT", 4.0, 1.0),
+        ];
+        let mut quotes = vec![
+            StockQuote::test("AAPL", 150.0, 150.0),
+            StockQuote::test("MSFT", 300.0, 300.0),
+            StockQuote::test("BINANCE:BTCUSDT", 50.0, 50.0),
+        ];
+        for quote in &mut quotes[..2] {
+            quote.sector = Some("Technology".to_string());
+        }
+        let positions = positions(&holdings, &quotes);
+
+        let by_position = weights(&positions, |p| p.symbol.clone(), 50.0);
+        assert_eq!(by_position[0].name, "AAPL");
+        assert_eq!(by_position[0].weight_pct, 75.0);
+        assert!(by_position[0].concentrated);
+        assert!(!by_position[1].concentrated);
+
+        let by_sector = weights(&positions, asset_class, 50.0);
+        assert_eq!(by_sector.len(), 2);
+        assert_eq!((by_sector[0].name.as_str(), by_sector[0].market_value), ("Technology", 1800.0));
+        assert_eq!((by_sector[1].name.as_str(), by_sector[1].weight_pct), ("Crypto", 10.0));
+    }
+
+    fn buy(symbol: &str, quantity: f64, cost_basis: f64) -> Holding {
+        Holding { symbol: symbol.to_string(), side: Side::Buy, quantity, cost_basis }
+    }
+
+    #[test]
+    fn test_parse_holdings() {
+        let holdings = parse_holdings(
+            "# my brokerage account\n\
+             Quantity,Symbol,Cost_Basis\n\
+             10,aapl,150\n\
+             \n\
+             5,TSX:SHOP,80.5\n",
+        )
+        .unwrap();
+        assert_eq!(holdings.len(), 2);
+        assert_eq!(holdings[0], buy("AAPL", 10.0, 150.0));
+        assert_eq!(holdings[1].symbol, "SHOP.TO");
+
+        assert!(parse_holdings("symbol,quantity\nAAPL,1\n").unwrap_err().contains("no cost_basis column"));
+        assert!(parse_holdings("symbol,quantity,cost_basis\nAAPL,ten,1\n").unwrap_err().contains("line 2"));
+
+        let sales = "symbol,side,quantity,cost_basis\nAAPL,buy,10,100\nAAPL,SELL,4,120\n";
+        assert_eq!(parse_holdings(sales).unwrap()[1].side, Side::Sell);
+        let oversold = parse_holdings("symbol,side,quantity,cost_basis\nAAPL,buy,1,100\nAAPL,sell,2,120\n");
+        assert!(oversold.unwrap_err().contains("line 3: sells 2 AAPL with only 1 held"));
+    }
+
+    #[test]
+    fn test_write_holdings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("holdings").join("portfolio.csv");
+        let holdings = vec![buy("AAPL", 10.0, 150.25), buy("SHOP.TO", 2.5, 82.4)];
+        write_holdings(&path, &holdings).unwrap();
+        assert_eq!(read_holdings(&path).unwrap(), holdings);
+    }
+
+    #[test]
+    fn test_positions_and_totals() {
+        let holdings = vec![buy("AAPL", 10.0, 100.0), buy("MSFT", 2.0, 300.0), buy("AAPL", 10.0, 140.0)];
+        let quotes = vec![StockQuote::test("MSFT", 400.0, 410.0), StockQuote::test("AAPL", 150.0, 145.0)];
+
+        let positions = positions(&holdings, &quotes);
+        assert_eq!(positions.len(), 2);
+        let aapl = &positions[0];
+        assert_eq!(aapl.symbol, "AAPL");
+        assert_eq!(aapl.quantity, 20.0);
+        assert_eq!(aapl.cost_basis, 120.0);
+        assert_eq!(aapl.market_value, 3000.0);
+        assert_eq!(aapl.day_change, 100.0);
+        assert_eq!(aapl.unrealized_pnl, 600.0);
+        assert!((aapl.unrealized_pnl_pct - 25.0).abs() < 0.01);
+
+        let totals = totals(&positions, realized_pnl(&holdings));
+        assert_eq!(totals.market_value, 3800.0);
+        assert_eq!(totals.day_change, 80.0);
+        assert_eq!(totals.unrealized_pnl, 800.0);
+        assert_eq!(totals.realized_pnl, 0.0);
+        assert!((totals.day_change_pct - 80.0 / 3720.0 * 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_realized_pnl() {
+        let sell = |symbol: &str, quantity: f64, price: f64| Holding {
+            side: Side::Sell,
+            ..buy(symbol, quantity, price)
+        };
+        let holdings = vec![
+            buy("AAPL", 10.0, 100.0),
+            buy("AAPL", 10.0, 140.0),
+            sell("AAPL", 5.0, 150.0),
+            buy("MSFT", 2.0, 300.0),
+            sell("MSFT", 2.0, 250.0),
+        ];
+        let quotes = vec![StockQuote::test("AAPL", 130.0, 130.0), StockQuote::test("MSFT", 400.0, 400.0)];
+
+        let positions = positions(&holdings, &quotes);
+        assert_eq!(positions.len(), 1);
+        let aapl = &positions[0];
+        assert_eq!(aapl.quantity, 15.0);
+        assert_eq!(aapl.cost_basis, 120.0);
+        assert_eq!(aapl.realized_pnl, 150.0);
+        assert_eq!(aapl.unrealized_pnl, 150.0);
+
+        // MSFT is closed out, but its loss still counts
+        assert_eq!(realized_pnl(&holdings), 50.0);
+    }
+}
@@ -0,0 +1,360 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use crate::alphavantage::AlphaVantageProvider;
+use crate::config::Config;
+use crate::eod::EodCsvProvider;
+use crate::errors::{Result, ScannerError};
+use crate::failover::FailoverProvider;
+use crate::finnhub::{Candles, FinnhubClient, StockQuote, SymbolError};
+use crate::mock::MockProvider;
+use crate::polygon::PolygonProvider;
+use crate::tiingo::TiingoProvider;
+use crate::twelvedata::TwelveDataProvider;
+use crate::yahoo::YahooProvider;
+
+/// Quote sources selectable with `--provider` and listed in `fallback_providers`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ProviderKind {
+    /// The Finnhub API
+    #[default]
+    Finnhub,
+    /// JSON fixture files from --fixtures, without an API key or network
+    Mock,
+    /// Alpha Vantage, with the key and rate limit from `[alphavantage]`
+    #[cfg_attr(feature = "cli", value(name = "alphavantage"))]
+    AlphaVantage,
+    /// Yahoo Finance's unofficial chart endpoint, without an API key
+    Yahoo,
+    /// Polygon.io snapshots and aggregates, with the key from `[polygon]`
+    Polygon,
+    /// Tiingo IEX quotes and end-of-day candles, with the key from `[tiingo]`
+    Tiingo,
+    /// Twelve Data quotes and time series, including forex and crypto, with the key from
+    /// `[twelvedata]`
+    #[cfg_attr(feature = "cli", value(name = "twelvedata"))]
+    TwelveData,
+    /// End-of-day CSV files from --fixtures, e.g. Stooq downloads, without network access
+    Csv,
+}
+
+impl ProviderKind {
+    /// Whether quotes from this source need a Finnhub API key
+    pub fn needs_api_key(self) -> bool {
+        self == ProviderKind::Finnhub
+    }
+
+    /// The name `--provider` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            ProviderKind::Finnhub => "finnhub",
+            ProviderKind::Mock => "mock",
+            ProviderKind::AlphaVantage => "alphavantage",
+            ProviderKind::Yahoo => "yahoo",
+            ProviderKind::Polygon => "polygon",
+            ProviderKind::Tiingo => "tiingo",
+            ProviderKind::TwelveData => "twelvedata",
+            ProviderKind::Csv => "csv",
+        }
+    }
+
+    /// Requests a minute and burst its client allows; `None` when it isn't rate limited
+    pub fn rate_limit(self, config: &Config) -> Option<(u32, u32)> {
+        match self {
+            ProviderKind::Finnhub => Some((config.requests_per_minute, config.rate_limit_burst)),
+            ProviderKind::AlphaVantage => Some((config.alphavantage.requests_per_minute, 1)),
+            ProviderKind::Polygon => Some((config.polygon.requests_per_minute, 1)),
+            ProviderKind::Tiingo => Some((config.tiingo.requests_per_minute, 1)),
+            ProviderKind::TwelveData => Some((config.twelvedata.requests_per_minute, 1)),
+            ProviderKind::Yahoo | ProviderKind::Mock | ProviderKind::Csv => None,
+        }
+    }
+
+    /// The endpoint a quote is fetched from, one request per symbol; `None` for local files
+    pub fn quote_path(self) -> Option<&'static str> {
+        match self {
+            ProviderKind::Finnhub => Some("/quote"),
+            ProviderKind::AlphaVantage => Some("/query"),
+            ProviderKind::Yahoo => Some("/v8/finance/chart"),
+            ProviderKind::Polygon => Some("/v2/snapshot"),
+            ProviderKind::Tiingo => Some("/iex"),
+            ProviderKind::TwelveData => Some("/quote"),
+            ProviderKind::Mock | ProviderKind::Csv => None,
+        }
+    }
+}
+
+/// Where local providers read from
+#[derive(Debug, Clone, Default)]
+pub struct LocalData<'a> {
+    /// `--fixtures`: fixture files for `mock`, CSV files for `csv`
+    pub dir: Option<&'a Path>,
+    /// `--as-of`: the last day `csv` serves, for what-if runs against past prices
+    pub as_of: Option<NaiveDate>,
+}
+
+/// The provider for `kind`, followed by the config's `fallback_providers` when there are any
+///
+/// `finnhub` is the client passed in, so its caches and cassette apply.
+pub fn open(
+    kind: ProviderKind,
+    config: &Config,
+    local: &LocalData,
+    finnhub: &FinnhubClient,
+) -> Result<Arc<dyn QuoteProvider>> {
+    let primary = open_one(kind, config, local, finnhub)?;
+    let fallbacks: Vec<ProviderKind> = config
+        .fallback_providers
+        .iter()
+        .copied()
+        .filter(|fallback| *fallback != kind)
+        .collect();
+    if fallbacks.is_empty() {
+        return Ok(primary);
+    }
+
+    let mut providers = vec![primary];
+    for fallback in fallbacks {
+        providers.push(open_one(fallback, config, local, finnhub)?);
+    }
+    Ok(Arc::new(FailoverProvider::new(providers)))
+}
+
+fn open_one(
+    kind: ProviderKind,
+    config: &Config,
+    local: &LocalData,
+    finnhub: &FinnhubClient,
+) -> Result<Arc<dyn QuoteProvider>> {
+    match kind {
+        ProviderKind::Finnhub => Ok(Arc::new(finnhub.clone())),
+        ProviderKind::Mock => {
+            let dir = local.dir.ok_or_else(|| {
+                ScannerError::InvalidInput("--provider mock needs --fixtures DIR".to_string())
+            })?;
+            Ok(Arc::new(MockProvider::new(dir)?))
+        }
+        ProviderKind::Csv => {
+            let dir = local.dir.ok_or_else(|| {
+                ScannerError::InvalidInput("--provider csv needs --fixtures DIR".to_string())
+            })?;
+            Ok(Arc::new(EodCsvProvider::new(dir, local.as_of)?))
+        }
+        ProviderKind::AlphaVantage => Ok(Arc::new(AlphaVantageProvider::new(config)?)),
+        ProviderKind::Yahoo => Ok(Arc::new(YahooProvider::new(config)?)),
+        ProviderKind::Polygon => Ok(Arc::new(PolygonProvider::new(config)?)),
+        ProviderKind::Tiingo => Ok(Arc::new(TiingoProvider::new(config)?)),
+        ProviderKind::TwelveData => Ok(Arc::new(TwelveDataProvider::new(config)?)),
+    }
+}
+
+/// What provider methods return: a `BoxFuture`, except on wasm32, where requests are browser
+/// promises that can't leave their thread
+#[cfg(not(target_arch = "wasm32"))]
+pub type ProviderFuture<'a, T> = futures::future::BoxFuture<'a, T>;
+#[cfg(target_arch = "wasm32")]
+pub type ProviderFuture<'a, T> = futures::future::LocalBoxFuture<'a, T>;
+
+/// A source of quotes that `scan` and `watch` can run against
+///
+/// Only `fetch_quote` is required; sources with price history also implement `fetch_candles`
+/// for `chart`. The default `fetch_quotes_with` asks for one symbol at a
+/// time and reports failures the same way the Finnhub client does; backends that can batch or
+/// run requests concurrently should override it.
+pub trait QuoteProvider: Send + Sync {
+    /// Short name for logs, e.g. `finnhub`
+    fn name(&self) -> &str;
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>>;
+
+    /// Quotes for `symbols` in the caller's order, calling `on_quote` with each as it arrives,
+    /// and the symbols that failed
+    ///
+    /// Fails only when every symbol does, with the first symbol's error.
+    fn fetch_quotes_with<'a>(
+        &'a self,
+        symbols: &'a [String],
+        on_quote: &'a mut (dyn FnMut(&StockQuote) + Send),
+    ) -> ProviderFuture<'a, Result<(Vec<StockQuote>, Vec<SymbolError>)>> {
+        Box::pin(async move {
+            let mut quotes = Vec::new();
+            let mut failed = Vec::new();
+            let mut first_error = None;
+
+            for symbol in symbols {
+                match self.fetch_quote(symbol).await {
+                    Ok(quote) => {
+                        on_quote(&quote);
+                        quotes.push(quote);
+                    }
+                    Err(e) => {
+                        crate::errors::report_symbol(symbol, &e);
+                        failed.push(SymbolError {
+                            symbol: symbol.clone(),
+                            code: e.code(),
+                            error: e.to_string(),
+                        });
+                        if first_error.is_none() {
+                            first_error = Some((symbol, e));
+                        }
+                    }
+                }
+            }
+
+            if let (true, Some((symbol, e))) = (quotes.is_empty(), first_error) {
+                return Err(e.context(&format!("All requests failed. First error: {}", symbol)));
+            }
+            Ok((quotes, failed))
+        })
+    }
+
+    fn fetch_quotes<'a>(&'a self, symbols: &'a [String]) -> ProviderFuture<'a, Result<Vec<StockQuote>>> {
+        Box::pin(async move {
+            let (quotes, _) = self.fetch_quotes_with(symbols, &mut |_| {}).await?;
+            Ok(quotes)
+        })
+    }
+
+    /// Candles between unix timestamps `from` and `to`, at one of
+    /// [`crate::finnhub::CANDLE_RESOLUTIONS`]
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        let _ = (symbol, resolution, from, to);
+        Box::pin(async move {
+            Err(ScannerError::InvalidInput(format!("--provider {} has no candle data", self.name())))
+        })
+    }
+}
+
+impl QuoteProvider for FinnhubClient {
+    fn name(&self) -> &str {
+        "finnhub"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            let quote = FinnhubClient::fetch_quote(self, symbol).await?;
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+
+    fn fetch_quotes_with<'a>(
+        &'a self,
+        symbols: &'a [String],
+        on_quote: &'a mut (dyn FnMut(&StockQuote) + Send),
+    ) -> ProviderFuture<'a, Result<(Vec<StockQuote>, Vec<SymbolError>)>> {
+        Box::pin(FinnhubClient::fetch_quotes_with(self, symbols, on_quote))
+    }
+
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        Box::pin(FinnhubClient::fetch_candles(self, symbol, resolution, from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quotes every symbol at 10.0 except those starting with `X`
+    struct Fixed;
+
+    impl QuoteProvider for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+            Box::pin(async move {
+                if symbol.starts_with('X') {
+                    return Err(ScannerError::SymbolNotFound(symbol.to_string()));
+                }
+                Ok(StockQuote::from_quote(
+                    symbol.to_string(),
+                    crate::finnhub::Quote {
+                        c: 10.0,
+                        pc: 8.0,
+                        h: 10.0,
+                        l: 8.0,
+                        o: 8.0,
+                    },
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_fetch_quotes_with() {
+        let symbols = vec!["AAPL".to_string(), "XYZ".to_string(), "MSFT".to_string()];
+        let mut seen = Vec::new();
+        let (quotes, failed) = Fixed
+            .fetch_quotes_with(&symbols, &mut |quote| seen.push(quote.symbol.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(seen, vec!["AAPL", "MSFT"]);
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].change_pct, 25.0);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].symbol, "XYZ");
+    }
+
+    #[tokio::test]
+    async fn test_all_failed() {
+        let symbols = vec!["XA".to_string(), "XB".to_string()];
+        let err = Fixed.fetch_quotes(&symbols).await.unwrap_err();
+        assert!(err.to_string().contains("XA"));
+        assert!(matches!(Fixed.fetch_candles("AAPL", "D", 0, 1).await, Err(ScannerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_open() {
+        let client = FinnhubClient::builder().api_key("test").build().unwrap();
+        let config = Config::default();
+        let none = LocalData::default();
+        let open = |kind, config: &Config, local: &LocalData| open(kind, config, local, &client);
+
+        assert_eq!(open(ProviderKind::Finnhub, &config, &none).unwrap().name(), "finnhub");
+        assert!(matches!(open(ProviderKind::Mock, &config, &none), Err(ScannerError::InvalidInput(_))));
+        assert!(matches!(open(ProviderKind::Csv, &config, &none), Err(ScannerError::InvalidInput(_))));
+        assert_eq!(open(ProviderKind::Yahoo, &config, &none).unwrap().name(), "yahoo");
+        for keyed in [
+            ProviderKind::AlphaVantage,
+            ProviderKind::Polygon,
+            ProviderKind::Tiingo,
+            ProviderKind::TwelveData,
+        ] {
+            assert!(matches!(open(keyed, &config, &none), Err(ScannerError::Config(_))));
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let local = LocalData {
+            dir: Some(dir.path()),
+            as_of: None,
+        };
+        assert_eq!(open(ProviderKind::Mock, &config, &local).unwrap().name(), "mock");
+        assert_eq!(open(ProviderKind::Csv, &config, &local).unwrap().name(), "csv");
+
+        let mut config = Config::default();
+        config.alphavantage.api_key = "demo".to_string();
+        assert_eq!(open(ProviderKind::AlphaVantage, &config, &none).unwrap().name(), "alphavantage");
+
+        // A chain is named after its primary
+        config.fallback_providers = vec![ProviderKind::Finnhub, ProviderKind::Yahoo];
+        assert_eq!(open(ProviderKind::Finnhub, &config, &none).unwrap().name(), "finnhub");
+        assert!(matches!(open(ProviderKind::Mock, &config, &none), Err(ScannerError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::provider::ProviderFuture;
+use crate::rt::{self, Instant};
+
+/// Decides when a client may send its next request
+///
+/// The client waits on `acquire` before every request, retries included. By default each client
+/// has its own [`TokenBucket`]; processes sharing one API key can pass
+/// [`FinnhubClientBuilder::rate_limiter`](crate::FinnhubClientBuilder::rate_limiter) a limiter
+/// that keeps its count somewhere they all see, such as a Redis key.
+pub trait RateLimiter: Send + Sync {
+    /// Wait until a request may be sent
+    fn acquire(&self) -> ProviderFuture<'_, ()>;
+}
+
+impl fmt::Debug for dyn RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RateLimiter")
+    }
+}
+
+/// Token bucket shared by every request a client makes
+///
+/// Tokens refill continuously at `requests_per_minute`, and up to `burst` of them can be
+/// saved up so a scan can start without waiting.
+pub struct TokenBucket {
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    per_sec: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Take a token, or return how long until one is available
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.per_sec))
+        }
+    }
+}
+
+impl TokenBucket {
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                capacity,
+                per_sec: requests_per_minute.max(1) as f64 / 60.0,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = self
+                .bucket
+                .lock()
+                .expect("rate limiter lock poisoned")
+                .take(Instant::now());
+            match wait {
+                Some(wait) => rt::sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+}
+
+impl RateLimiter for TokenBucket {
+    fn acquire(&self) -> ProviderFuture<'_, ()> {
+        Box::pin(TokenBucket::acquire(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_burst_then_refill() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 2.0,
+            capacity: 2.0,
+            per_sec: 1.0,
+            updated: start,
+        };
+
+        assert_eq!(bucket.take(start), None);
+        assert_eq!(bucket.take(start), None);
+        assert_eq!(bucket.take(start), Some(Duration::from_secs(1)));
+
+        // Half a second refills half a token
+        let wait = bucket.take(start + Duration::from_millis(500)).unwrap();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert_eq!(bucket.take(start + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_bucket_caps_saved_tokens() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 0.0,
+            capacity: 1.0,
+            per_sec: 1.0,
+            updated: start,
+        };
+
+        let later = start + Duration::from_secs(60);
+        assert_eq!(bucket.take(later), None);
+        assert!(bucket.take(later).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_bucket_as_shared_limiter() {
+        let limiter: std::sync::Arc<dyn RateLimiter> = std::sync::Arc::new(TokenBucket::new(60, 2));
+        let started = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(format!("{:?}", limiter), "RateLimiter");
+    }
+}
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use crate::alerts::AlertRecord;
+
+/// `[redis]`: channels quote updates and alerts are published on, and keys holding the latest quotes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisConfig {
+    /// e.g. `redis://:password@127.0.0.1:6379/0`; `rediss://` for TLS
+    pub url: String,
+
+    /// Channel each quote update is published on; `{symbol}` is replaced, empty disables
+    #[serde(default = "default_quote_channel")]
+    pub quote_channel: String,
+
+    /// Channel each fired alert is published on; `{symbol}` and `{rule}` are replaced, empty disables
+    #[serde(default = "default_alert_channel")]
+    pub alert_channel: String,
+
+    /// Key set to each symbol's latest quote; `{symbol}` is replaced, empty disables
+    #[serde(default = "default_quote_key")]
+    pub quote_key: String,
+
+    /// Expiry for quote keys, so they don't outlive the scanner unnoticed (0 keeps them)
+    #[serde(default = "default_key_ttl")]
+    pub key_ttl_secs: u64,
+}
+
+fn default_quote_channel() -> String {
+    String::from("market:quotes:{symbol}")
+}
+
+fn default_alert_channel() -> String {
+    String::from("market:alerts:{symbol}")
+}
+
+fn default_quote_key() -> String {
+    String::from("market:quote:{symbol}")
+}
+
+fn default_key_ttl() -> u64 {
+    300
+}
+
+/// URL schemes the Redis client accepts
+pub const URL_SCHEMES: [&str; 3] = ["redis://", "rediss://", "unix://"];
+
+impl RedisConfig {
+    /// Channel a quote for `symbol` is published on
+    pub fn quote_channel(&self, symbol: &str) -> String {
+        self.quote_channel.replace("{symbol}", symbol)
+    }
+
+    /// Channel an alert is published on
+    pub fn alert_channel(&self, record: &AlertRecord) -> String {
+        self.alert_channel
+            .replace("{symbol}", &record.symbol)
+            .replace("{rule}", &record.rule)
+    }
+
+    /// Key holding the latest quote for `symbol`
+    pub fn quote_key(&self, symbol: &str) -> String {
+        self.quote_key.replace("{symbol}", symbol)
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use sink::RedisSink;
+
+#[cfg(feature = "redis")]
+mod sink {
+    use std::time::Duration;
+    use ::redis::aio::ConnectionManager;
+    use ::redis::{Client, Pipeline};
+    use tokio::sync::Mutex;
+    use super::RedisConfig;
+    use crate::alerts::AlertRecord;
+    use crate::errors::{Result, ScannerError};
+    use crate::finnhub::StockQuote;
+    use crate::sinks::QuoteMessage;
+
+    /// How long opening the connection may take before the refresh carries on without it
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Redis client whose connection is opened on first use
+    pub struct RedisSink {
+        client: Client,
+        config: RedisConfig,
+        /// Reconnects by itself once established
+        connection: Mutex<Option<ConnectionManager>>,
+    }
+
+    impl RedisSink {
+        pub fn connect(config: &RedisConfig) -> Result<Self> {
+            let client = Client::open(config.url.as_str())
+                .map_err(|e| ScannerError::Config(format!("Invalid redis.url: {}", e)))?;
+            Ok(Self {
+                client,
+                config: config.clone(),
+                connection: Mutex::new(None),
+            })
+        }
+
+        /// Publish, and store, the quotes fetched by one refresh
+        pub async fn quotes(&self, timestamp: &str, quotes: &[StockQuote]) {
+            let mut pipe = ::redis::pipe();
+            for quote in quotes {
+                let payload = serde_json::to_string(&QuoteMessage { timestamp, quote }).unwrap_or_default();
+                if !self.config.quote_channel.is_empty() {
+                    pipe.publish(self.config.quote_channel(&quote.symbol), &payload).ignore();
+                }
+                if !self.config.quote_key.is_empty() {
+                    let key = self.config.quote_key(&quote.symbol);
+                    match self.config.key_ttl_secs {
+                        0 => pipe.set(key, &payload).ignore(),
+                        ttl => pipe.set_ex(key, &payload, ttl).ignore(),
+                    };
+                }
+            }
+            self.send(pipe).await;
+        }
+
+        /// Publish alerts fired by one refresh
+        pub async fn alerts(&self, records: &[AlertRecord]) {
+            if self.config.alert_channel.is_empty() {
+                return;
+            }
+            let mut pipe = ::redis::pipe();
+            for record in records {
+                let payload = serde_json::to_string(record).unwrap_or_default();
+                pipe.publish(self.config.alert_channel(record), payload).ignore();
+            }
+            self.send(pipe).await;
+        }
+
+        async fn send(&self, pipe: Pipeline) {
+            if pipe.cmd_iter().next().is_none() {
+                return;
+            }
+            let Some(mut connection) = self.connection().await else {
+                return;
+            };
+            if let Err(e) = pipe.query_async::<()>(&mut connection).await {
+                log::warn!("Failed to write to Redis: {}", e);
+            }
+        }
+
+        async fn connection(&self) -> Option<ConnectionManager> {
+            let mut connection = self.connection.lock().await;
+            if connection.is_none() {
+                match tokio::time::timeout(CONNECT_TIMEOUT, ConnectionManager::new(self.client.clone())).await {
+                    Ok(Ok(opened)) => {
+                        log::info!("Connected to Redis");
+                        *connection = Some(opened);
+                    }
+                    Ok(Err(e)) => log::warn!("Failed to connect to Redis: {}", e),
+                    Err(_) => log::warn!("Timed out connecting to Redis"),
+                }
+            }
+            connection.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let config: RedisConfig = toml::from_str("url = \"redis://127.0.0.1\"").unwrap();
+        assert_eq!(config.quote_channel("AAPL"), "market:quotes:AAPL");
+        assert_eq!(config.quote_key("AAPL"), "market:quote:AAPL");
+        assert_eq!(config.key_ttl_secs, 300);
+
+        let record = AlertRecord {
+            timestamp: "2024-01-02T15:30:00+00:00".to_string(),
+            rule: "big-move".to_string(),
+            symbol: "TSLA".to_string(),
+            condition: "abs(change_pct) > 3".to_string(),
+            price: 250.0,
+            change_pct: -4.0,
+            results: Vec::new(),
+        };
+        assert_eq!(config.alert_channel(&record), "market:alerts:TSLA");
+    }
+}
@@ -0,0 +1,135 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+
+/// Notices when a file is written, created or replaced
+pub struct FileWatch {
+    changes: mpsc::Receiver<()>,
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatch {
+    pub fn new(path: &Path) -> Result<Self> {
+        let (tx, changes) = mpsc::channel();
+        let name = path.file_name().map(|name| name.to_os_string());
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let touched = event.paths.iter().any(|p| p.file_name() == name.as_deref());
+            if touched && (event.kind.is_modify() || event.kind.is_create()) {
+                let _ = tx.send(());
+            }
+        })
+        .map_err(|e| ScannerError::Io(format!("Failed to watch {}: {}", path.display(), e)))?;
+
+        // Watch the directory, since editors often save by replacing the file
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| ScannerError::Io(format!("Failed to watch {}: {}", dir.display(), e)))?;
+
+        Ok(Self {
+            changes,
+            _watcher: watcher,
+        })
+    }
+
+    /// Whether the file changed since the last call
+    pub fn changed(&self) -> bool {
+        if self.changes.try_recv().is_err() {
+            return false;
+        }
+        while self.changes.try_recv().is_ok() {}
+        true
+    }
+}
+
+/// The config file a long-running command was started from, re-read when it is edited
+pub struct ConfigFile {
+    path: PathBuf,
+    profile: Option<String>,
+    current: Config,
+    watch: FileWatch,
+}
+
+impl ConfigFile {
+    pub fn watch(path: &Path, profile: Option<String>, current: &Config) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            profile,
+            current: current.clone(),
+            watch: FileWatch::new(path)?,
+        })
+    }
+
+    /// The config as last loaded
+    pub fn current(&self) -> &Config {
+        &self.current
+    }
+
+    /// The new config and the top-level sections that differ from the last one, if the file
+    /// changed and still loads. The API key in use is kept, since the client can't swap it.
+    pub fn reload(&mut self) -> Option<(Config, Vec<String>)> {
+        if !self.watch.changed() {
+            return None;
+        }
+
+        let api_key = Some(self.current.api_key.clone());
+        let config = match Config::from_file(&self.path, api_key, self.profile.as_deref()) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("Ignoring edit to {}: {}", self.path.display(), e);
+                return None;
+            }
+        };
+
+        let changed = changed_sections(&self.current, &config);
+        if changed.is_empty() {
+            return None;
+        }
+        self.current = config.clone();
+        Some((config, changed))
+    }
+}
+
+/// Top-level config keys whose values differ, in a stable order
+pub fn changed_sections(old: &Config, new: &Config) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+
+    let mut changed: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_sections() {
+        let old = Config::default();
+        let mut new = old.clone();
+        assert!(changed_sections(&old, &new).is_empty());
+
+        new.filter = Some("price > 10".to_string());
+        new.watch.interval_secs = 15;
+        new.timeout_secs = 30;
+        assert_eq!(changed_sections(&old, &new), vec!["filter", "timeout_secs", "watch"]);
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, BufRead, IsTerminal, Write};
+use crate::errors::{Result, ScannerError};
+use crate::exchange;
+use crate::groups;
+use crate::finnhub::{FinnhubClient, SymbolMatch};
+
+/// Search results offered when asking which one was meant
+const CHOICES: usize = 5;
+
+/// Whether a symbol-list entry can be used as it is, rather than looked up as a company name
+///
+/// Entries with spaces are names. Anything with digits or punctuation (`BRK.B`, `^GSPC`,
+/// `EUR/USD`, `BINANCE:BTCUSDT`) is taken as a ticker, as are plain words of up to five letters.
+pub fn looks_like_ticker(entry: &str) -> bool {
+    if entry.chars().any(char::is_whitespace) {
+        return false;
+    }
+    !entry.chars().all(char::is_alphabetic) || entry.chars().count() <= 5
+}
+
+/// Turn symbol-list entries into symbols
+///
+/// `EXCHANGE:TICKER` entries are rewritten as Finnhub expects, `@group` entries expanded to their
+/// members and company names looked up (see [`resolve`]). Repeats are dropped.
+pub async fn prepare(
+    entries: &[String],
+    groups: &BTreeMap<String, Vec<String>>,
+    client: &FinnhubClient,
+    assume_yes: bool,
+) -> Result<Vec<String>> {
+    let mut symbols: Vec<String> = entries.iter().map(|entry| exchange::normalize(entry)).collect();
+    if symbols.iter().any(|symbol| groups::is_group(symbol)) {
+        symbols = groups::expand(symbols, groups, client).await?;
+    }
+    let mut symbols = resolve(symbols, client, assume_yes).await?;
+
+    let mut seen = HashSet::new();
+    symbols.retain(|symbol| seen.insert(symbol.clone()));
+    Ok(symbols)
+}
+
+/// Like [`prepare`] without sending requests, for `--dry-run`
+///
+/// Index groups missing from the disk cache and company names are kept as they are, so an
+/// estimate can count the lookups they'd need.
+pub fn prepare_offline(
+    entries: &[String],
+    groups: &BTreeMap<String, Vec<String>>,
+    client: &FinnhubClient,
+) -> Result<Vec<String>> {
+    let symbols: Vec<String> = entries.iter().map(|entry| exchange::normalize(entry)).collect();
+    groups::expand_cached(symbols, groups, client)
+}
+
+/// Replace entries that look like company names with the symbols they're searched up as
+///
+/// With `assume_yes` the best match is taken. Otherwise a terminal is asked to pick from the
+/// top matches, and without one the entry is an error that names the best match.
+pub async fn resolve(symbols: Vec<String>, client: &FinnhubClient, assume_yes: bool) -> Result<Vec<String>> {
+    let mut resolved = Vec::with_capacity(symbols.len());
+    for entry in symbols {
+        if looks_like_ticker(&entry) {
+            resolved.push(entry);
+            continue;
+        }
+
+        let matches = client
+            .search_symbols(&entry)
+            .await
+            .map_err(|e| e.context(&format!("Looking up '{}'", entry)))?;
+        let Some(best) = matches.first() else {
+            return Err(ScannerError::InvalidInput(format!("No symbol matches '{}'", entry)));
+        };
+        let choice = if assume_yes {
+            Some(best.symbol.clone())
+        } else if io::stdin().is_terminal() {
+            prompt(&entry, &matches, &mut io::stdin().lock(), &mut io::stderr())?
+        } else {
+            return Err(ScannerError::InvalidInput(format!(
+                "'{}' isn't a ticker; the best match is {} ({}). Use the ticker, or --yes to take it",
+                entry, best.symbol, best.description
+            )));
+        };
+
+        match choice {
+            Some(symbol) => {
+                log::info!("Using {} for '{}'", symbol, entry);
+                if !resolved.contains(&symbol) {
+                    resolved.push(symbol);
+                }
+            }
+            None => log::warn!("Skipping '{}'", entry),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Symbols missing from their exchange's listing, such as typos and delisted tickers
+///
+/// Listings come from `/stock/symbol` through the disk cache (`cache_ttls.symbols_secs`), one per
+/// exchange in the list. Crypto and forex pairs and indices (`BINANCE:BTCUSDT`, `EUR/USD`,
+/// `^GSPC`) aren't listed there, so they're not checked.
+pub async fn unlisted(symbols: &[String], client: &FinnhubClient) -> Result<Vec<String>> {
+    let mut listings: HashMap<&str, HashSet<String>> = HashMap::new();
+    let mut missing = Vec::new();
+    for symbol in symbols {
+        if symbol.contains([':', '/', '^']) {
+            continue;
+        }
+        let code = exchange::listing_code(symbol);
+        if !listings.contains_key(code) {
+            let listing = client
+                .fetch_symbols(code)
+                .await
+                .map_err(|e| e.context(&format!("Listing for exchange {}", code)))?;
+            listings.insert(code, listing.into_iter().map(|info| info.symbol).collect());
+        }
+        if !listings[code].contains(symbol) {
+            missing.push(symbol.clone());
+        }
+    }
+    Ok(missing)
+}
+
+/// Ask which of the top matches was meant; `None` skips the entry
+fn prompt(
+    entry: &str,
+    matches: &[SymbolMatch],
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<Option<String>> {
+    let shown = &matches[..matches.len().min(CHOICES)];
+    writeln!(out, "'{}' isn't a ticker. Did you mean:", entry)?;
+    for (i, candidate) in shown.iter().enumerate() {
+        writeln!(out, "  {}) {:<10} {}", i + 1, candidate.symbol, candidate.description)?;
+    }
+
+    loop {
+        write!(out, "Choose 1-{} (Enter for 1, 0 to skip): ", shown.len())?;
+        out.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match line.trim() {
+            "" => return Ok(Some(shown[0].symbol.clone())),
+            answer => match answer.parse::<usize>() {
+                Ok(0) => return Ok(None),
+                Ok(n) if n <= shown.len() => return Ok(Some(shown[n - 1].symbol.clone())),
+                _ => writeln!(out, "Enter a number from 0 to {}", shown.len())?,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_like_ticker() {
+        for ticker in ["AAPL", "GOOGL", "BRK.B", "^GSPC", "EUR/USD", "BINANCE:BTCUSDT", "7203.T"] {
+            assert!(looks_like_ticker(ticker), "{}", ticker);
+        }
+        for name in ["BERKSHIRE", "NVIDIA", "COCA COLA"] {
+            assert!(!looks_like_ticker(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_prompt() {
+        let matches: Vec<SymbolMatch> = ["BRK.A", "BRK.B"]
+            .iter()
+            .map(|symbol| SymbolMatch {
+                symbol: symbol.to_string(),
+                description: "BERKSHIRE HATHAWAY INC".to_string(),
+                display_symbol: symbol.to_string(),
+                security_type: "Common Stock".to_string(),
+            })
+            .collect();
+        let mut out = Vec::new();
+
+        let choice = prompt("BERKSHIRE", &matches, &mut "9\n2\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(choice.as_deref(), Some("BRK.B"));
+        assert!(String::from_utf8(out).unwrap().contains("Enter a number from 0 to 2"));
+
+        let choose = |answer: &str| {
+            prompt("BERKSHIRE", &matches, &mut answer.as_bytes(), &mut Vec::new()).unwrap()
+        };
+        assert_eq!(choose("\n").as_deref(), Some("BRK.A"));
+        assert_eq!(choose("0\n"), None);
+    }
+}
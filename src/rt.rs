@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+// `std::time::Instant` panics on wasm32-unknown-unknown, so clocks come from web-time there
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Wait without blocking: on tokio's timer natively, on `setTimeout` in the browser
+pub async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+}
@@ -0,0 +1,242 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::get;
+use axum::Router;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::expr;
+use crate::finnhub::{FinnhubClient, StockQuote};
+use crate::output::{self, QuoteFilter, Summary};
+use crate::systemd;
+use crate::watch;
+
+/// Command-line options for `serve`
+pub struct ServeOptions {
+    /// Address to listen on, e.g. 127.0.0.1:8080
+    pub bind: String,
+    pub interval: u64,
+    /// Limits which quotes are served
+    pub filter: QuoteFilter,
+}
+
+/// Latest scan results, shared between the poller and request handlers
+#[derive(Default)]
+struct Snapshot {
+    quotes: Vec<StockQuote>,
+    /// RFC 3339 time of the last successful refresh
+    updated_at: Option<String>,
+    last_error: Option<String>,
+}
+
+type Shared = Arc<RwLock<Snapshot>>;
+
+#[derive(Serialize)]
+struct QuotesBody<'a> {
+    updated_at: Option<&'a str>,
+    quotes: Vec<&'a StockQuote>,
+}
+
+#[derive(Serialize)]
+struct SummaryBody<'a> {
+    updated_at: Option<&'a str>,
+    summary: Summary,
+}
+
+#[derive(Serialize)]
+struct HealthBody<'a> {
+    status: &'static str,
+    updated_at: Option<&'a str>,
+    last_error: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct QuotesQuery {
+    /// Condition expression, as for `--filter`
+    filter: Option<String>,
+}
+
+/// Keep `symbols` refreshed and serve the latest results until Ctrl+C or SIGTERM
+pub async fn run(config: &Config, symbols: Vec<String>, options: ServeOptions) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(&options.bind)
+        .await
+        .map_err(|e| ScannerError::Io(format!("Failed to listen on {}: {}", options.bind, e)))?;
+
+    log::info!(
+        "Serving {} symbols on http://{}, refreshing every {}s. Press Ctrl+C to exit.",
+        symbols.len(),
+        options.bind,
+        options.interval
+    );
+    systemd::ready(&format!("Serving {} symbols on {}", symbols.len(), options.bind));
+
+    // Served quotes are always live, so the quote cache isn't attached
+    let client = FinnhubClient::from_config(config)?;
+    let state = Shared::default();
+    let poller = tokio::spawn(poll(client, symbols, options.interval, options.filter, state.clone()));
+
+    let served = axum::serve(listener, router(state))
+        .with_graceful_shutdown(async {
+            systemd::shutdown_signal().await;
+            systemd::stopping();
+        })
+        .await;
+    poller.abort();
+    served.map_err(|e| ScannerError::Io(format!("Server error: {}", e)))
+}
+
+fn router(state: Shared) -> Router {
+    Router::new()
+        .route("/quotes", get(quotes))
+        .route("/quotes/{symbol}", get(quote))
+        .route("/summary", get(summary))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+async fn poll(
+    client: FinnhubClient,
+    symbols: Vec<String>,
+    interval: u64,
+    filter: QuoteFilter,
+    state: Shared,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        let result = client.fetch_quotes(&symbols).await;
+
+        let mut snapshot = state.write().expect("snapshot lock poisoned");
+        match result {
+            Ok(quotes) => {
+                // Symbols that failed this time keep their previous quote
+                let previous = std::mem::take(&mut snapshot.quotes);
+                snapshot.quotes = filter.apply(watch::merge_quotes(&symbols, previous, quotes));
+                snapshot.updated_at = Some(Utc::now().to_rfc3339());
+                snapshot.last_error = None;
+            }
+            Err(e) => {
+                log::error!("Failed to fetch quotes: {}", e);
+                snapshot.last_error = Some(e.to_string());
+            }
+        }
+        systemd::watchdog();
+    }
+}
+
+async fn quotes(State(state): State<Shared>, Query(query): Query<QuotesQuery>) -> Response {
+    let filter = match query.filter.as_deref().map(expr::parse).transpose() {
+        Ok(filter) => filter,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+
+    let snapshot = state.read().expect("snapshot lock poisoned");
+    let quotes = snapshot
+        .quotes
+        .iter()
+        .filter(|quote| match &filter {
+            Some(filter) => filter.matches(*quote).unwrap_or(false),
+            None => true,
+        })
+        .collect();
+    Json(QuotesBody {
+        updated_at: snapshot.updated_at.as_deref(),
+        quotes,
+    })
+    .into_response()
+}
+
+async fn quote(State(state): State<Shared>, Path(symbol): Path<String>) -> Response {
+    let symbol = symbol.to_uppercase();
+    let snapshot = state.read().expect("snapshot lock poisoned");
+    match snapshot.quotes.iter().find(|quote| quote.symbol == symbol) {
+        Some(quote) => Json(quote).into_response(),
+        None => error(StatusCode::NOT_FOUND, format!("No quote for {}", symbol)),
+    }
+}
+
+async fn summary(State(state): State<Shared>) -> Response {
+    let snapshot = state.read().expect("snapshot lock poisoned");
+    Json(SummaryBody {
+        updated_at: snapshot.updated_at.as_deref(),
+        summary: output::calculate_summary(&snapshot.quotes),
+    })
+    .into_response()
+}
+
+/// 503 until the first refresh succeeds, so orchestrators can wait for readiness
+async fn health(State(state): State<Shared>) -> Response {
+    let snapshot = state.read().expect("snapshot lock poisoned");
+    let (code, status) = match snapshot.updated_at {
+        Some(_) => (StatusCode::OK, "ok"),
+        None => (StatusCode::SERVICE_UNAVAILABLE, "starting"),
+    };
+    let body = HealthBody {
+        status,
+        updated_at: snapshot.updated_at.as_deref(),
+        last_error: snapshot.last_error.as_deref(),
+    };
+    (code, Json(body)).into_response()
+}
+
+fn error(code: StatusCode, message: String) -> Response {
+    (code, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> Shared {
+        Arc::new(RwLock::new(Snapshot {
+            quotes: vec![StockQuote::test("AAPL", 190.0, 187.0), StockQuote::test("TSLA", 250.0, 255.0)],
+            updated_at: Some("2024-01-02T15:30:00+00:00".to_string()),
+            last_error: None,
+        }))
+    }
+
+    async fn body(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_quote_lookup() {
+        let found = quote(State(state()), Path("aapl".to_string())).await;
+        assert_eq!(found.status(), StatusCode::OK);
+        assert_eq!(body(found).await["price"], 190.0);
+
+        let missing = quote(State(state()), Path("MSFT".to_string())).await;
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_quotes_filter() {
+        let query = QuotesQuery {
+            filter: Some("change_pct < 0".to_string()),
+        };
+        let response = quotes(State(state()), Query(query)).await;
+        let json = body(response).await;
+        assert_eq!(json["quotes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["quotes"][0]["symbol"], "TSLA");
+
+        let bad = QuotesQuery {
+            filter: Some("price >".to_string()),
+        };
+        let response = quotes(State(state()), Query(bad)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_health_waits_for_first_refresh() {
+        let response = health(State(Shared::default())).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(health(State(state())).await.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,192 @@
+use std::path::Path;
+
+/// `--sink` schemes for NDJSON streams to co-located processes
+pub const URL_SCHEMES: [&str; 2] = ["unix://", "pipe://"];
+
+/// Whether `url` names a Unix socket to listen on (`unix://`) rather than a named pipe (`pipe://`),
+/// and the path it names
+#[cfg_attr(not(unix), allow(dead_code))]
+pub fn sink_path(url: &str) -> Option<(bool, &Path)> {
+    if let Some(path) = url.strip_prefix("unix://") {
+        return Some((true, Path::new(path)));
+    }
+    url.strip_prefix("pipe://").map(|path| (false, Path::new(path)))
+}
+
+#[cfg(unix)]
+pub use sink::{PipeSink, SocketSink};
+
+#[cfg(unix)]
+mod sink {
+    use std::os::unix::fs::FileTypeExt;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::unix::pipe;
+    use tokio::net::UnixListener;
+    use tokio::sync::{broadcast, Mutex};
+    use tokio::task::JoinHandle;
+    use crate::errors::{Result, ScannerError};
+
+    /// Refreshes buffered for a slow socket client before it starts missing some
+    const CLIENT_BACKLOG: usize = 64;
+
+    /// How long a named pipe may stay full before its reader is given up on
+    const PIPE_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// `open` fails with ENXIO while a pipe has no reader (the same on Linux and macOS)
+    const ENXIO: i32 = 6;
+
+    /// Listens on a Unix socket and writes every refresh to each connected client
+    pub struct SocketSink {
+        path: PathBuf,
+        batches: broadcast::Sender<Arc<str>>,
+        listener: JoinHandle<()>,
+    }
+
+    impl SocketSink {
+        pub fn bind(path: &Path) -> Result<Self> {
+            // A socket left behind by an earlier run would make bind fail
+            if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                std::fs::remove_file(path)?;
+            }
+            let listener = UnixListener::bind(path)
+                .map_err(|e| ScannerError::Io(format!("Failed to listen on {}: {}", path.display(), e)))?;
+            log::info!("Streaming quotes to clients of {}", path.display());
+
+            let (batches, _) = broadcast::channel::<Arc<str>>(CLIENT_BACKLOG);
+            let subscribe = batches.clone();
+            let listener = tokio::spawn(async move {
+                loop {
+                    let mut stream = match listener.accept().await {
+                        Ok((stream, _)) => stream,
+                        Err(e) => {
+                            log::warn!("Failed to accept socket client: {}", e);
+                            continue;
+                        }
+                    };
+                    let mut batches = subscribe.subscribe();
+                    tokio::spawn(async move {
+                        loop {
+                            match batches.recv().await {
+                                Ok(batch) => {
+                                    if stream.write_all(batch.as_bytes()).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                    log::warn!("A socket client fell behind and missed {} refreshes", skipped);
+                                }
+                                Err(broadcast::error::RecvError::Closed) => break,
+                            }
+                        }
+                    });
+                }
+            });
+
+            Ok(Self {
+                path: path.to_path_buf(),
+                batches,
+                listener,
+            })
+        }
+
+        pub fn send(&self, batch: Arc<str>) {
+            // Fails only when nobody is connected
+            let _ = self.batches.send(batch);
+        }
+    }
+
+    impl Drop for SocketSink {
+        fn drop(&mut self) {
+            self.listener.abort();
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Writes every refresh to a named pipe whenever a reader has it open
+    pub struct PipeSink {
+        path: PathBuf,
+        sender: Mutex<Option<pipe::Sender>>,
+    }
+
+    impl PipeSink {
+        pub fn open(path: &Path) -> Result<Self> {
+            let is_fifo = std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_fifo());
+            if !is_fifo {
+                return Err(ScannerError::InvalidInput(format!(
+                    "{} is not a named pipe; create it with mkfifo",
+                    path.display()
+                )));
+            }
+            Ok(Self {
+                path: path.to_path_buf(),
+                sender: Mutex::new(None),
+            })
+        }
+
+        pub async fn send(&self, batch: &str) {
+            let mut sender = self.sender.lock().await;
+            if sender.is_none() {
+                match pipe::OpenOptions::new().open_sender(&self.path) {
+                    Ok(opened) => *sender = Some(opened),
+                    Err(e) if e.raw_os_error() == Some(ENXIO) => return,
+                    Err(e) => {
+                        log::warn!("Failed to open {}: {}", self.path.display(), e);
+                        return;
+                    }
+                }
+            }
+            let Some(open) = sender.as_mut() else {
+                return;
+            };
+
+            // A reader that went away or stopped reading gets EOF; the next one starts afresh
+            match tokio::time::timeout(PIPE_WRITE_TIMEOUT, open.write_all(batch.as_bytes())).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    log::debug!("Reader of {} went away: {}", self.path.display(), e);
+                    *sender = None;
+                }
+                Err(_) => {
+                    log::warn!("Reader of {} isn't keeping up, closing the pipe", self.path.display());
+                    *sender = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sink_path() {
+        assert_eq!(sink_path("unix:///run/scanner.sock"), Some((true, Path::new("/run/scanner.sock"))));
+        assert_eq!(sink_path("pipe://quotes.fifo"), Some((false, Path::new("quotes.fifo"))));
+        assert_eq!(sink_path("postgres://localhost/market"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_clients_receive_batches() {
+        use tokio::io::AsyncBufReadExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quotes.sock");
+        let sink = SocketSink::bind(&path).unwrap();
+
+        let client = tokio::net::UnixStream::connect(&path).await.unwrap();
+        // Give the listener a moment to subscribe the new client
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        sink.send("{\"symbol\":\"AAPL\"}\n".into());
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "{\"symbol\":\"AAPL\"}");
+
+        drop(sink);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Set by systemd for `Type=notify` services
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+
+/// When the watchdog was last fed
+static LAST_PING: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Tell systemd the service is up. Does nothing when not started by systemd.
+pub fn ready(status: &str) {
+    notify(&format!("READY=1\nSTATUS={}", status));
+}
+
+/// Feed the watchdog if `WatchdogSec=` is set, at most every half `WATCHDOG_USEC`
+///
+/// Called after each completed refresh, so a loop that hangs gets the service restarted.
+pub fn watchdog() {
+    let Some(every) = watchdog_interval() else {
+        return;
+    };
+    let mut last = LAST_PING.lock().expect("watchdog lock poisoned");
+    if last.is_some_and(|at| at.elapsed() < every) {
+        return;
+    }
+    if notify("WATCHDOG=1") {
+        *last = Some(Instant::now());
+    }
+}
+
+/// Shown by `systemctl status`
+pub fn status(status: &str) {
+    notify(&format!("STATUS={}", status));
+}
+
+/// Tell systemd a clean shutdown has started
+pub fn stopping() {
+    notify("STOPPING=1");
+}
+
+/// Resolves on Ctrl+C, or on SIGTERM as sent by `systemctl stop` and `docker stop`
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => log::info!("Received SIGTERM, shutting down"),
+                }
+            }
+            Err(e) => {
+                log::warn!("SIGTERM won't shut down cleanly: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Half of `WATCHDOG_USEC`, as systemd recommends, when the watchdog is meant for this process
+fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.parse::<u32>().ok()? != std::process::id() {
+            return None;
+        }
+    }
+    (usec > 0).then(|| Duration::from_micros(usec) / 2)
+}
+
+/// Send one `sd_notify` message; false when not running under systemd
+fn notify(state: &str) -> bool {
+    let Some(socket) = std::env::var_os(NOTIFY_SOCKET) else {
+        return false;
+    };
+    match send(&socket, state) {
+        Ok(()) => true,
+        Err(e) => {
+            log::debug!("Failed to notify systemd: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(unix)]
+fn send(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    // `@` names a socket in Linux's abstract namespace
+    #[cfg(target_os = "linux")]
+    if let Some(name) = socket.as_bytes().strip_prefix(b"@") {
+        use std::os::linux::net::SocketAddrExt;
+        let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+        datagram.send_to_addr(state.as_bytes(), &addr)?;
+        return Ok(());
+    }
+    datagram.send_to(state.as_bytes(), socket)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send(_socket: &std::ffi::OsStr, _state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "not supported on this platform"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+        send(path.as_os_str(), "READY=1\nSTATUS=Watching 3 symbols").unwrap();
+        let mut buf = [0u8; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1\nSTATUS=Watching 3 symbols");
+    }
+}
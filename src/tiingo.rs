@@ -0,0 +1,235 @@
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Candles, Quote, StockQuote};
+use crate::provider::{ProviderFuture, QuoteProvider};
+use crate::ratelimit::TokenBucket;
+
+const BASE_URL: &str = "https://api.tiingo.com";
+
+/// `[tiingo]`: key and rate limit for `--provider tiingo`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiingoConfig {
+    /// Also read from `TIINGO_API_KEY`
+    #[serde(default)]
+    pub api_key: String,
+
+    /// Requests a minute; the free plan also caps requests per hour and per day
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+}
+
+impl Default for TiingoConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            requests_per_minute: default_requests_per_minute(),
+        }
+    }
+}
+
+fn default_requests_per_minute() -> u32 {
+    50
+}
+
+/// Quotes from Tiingo's IEX top-of-book feed, and daily, weekly or monthly candles from its
+/// end-of-day prices
+pub struct TiingoProvider {
+    api_key: String,
+    client: reqwest::Client,
+    /// Also set per request, see [`crate::config::HttpConfig::client_builder`]
+    timeout: Duration,
+    limiter: TokenBucket,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IexQuote {
+    /// Last IEX trade; null outside market hours
+    last: Option<f64>,
+    /// Tiingo's own last price, which falls back to the official close
+    tngo_last: Option<f64>,
+    prev_close: Option<f64>,
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EodPrice {
+    date: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    #[serde(default)]
+    volume: f64,
+}
+
+impl TiingoProvider {
+    pub fn new(config: &Config) -> Result<Self> {
+        let settings = &config.tiingo;
+        if settings.api_key.is_empty() {
+            return Err(ScannerError::Config(
+                "--provider tiingo needs an API key. Set TIINGO_API_KEY or [tiingo] api_key".to_string(),
+            ));
+        }
+
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let client = config.http.client_builder(timeout)?.build()?;
+        Ok(Self {
+            api_key: settings.api_key.clone(),
+            client,
+            timeout,
+            limiter: TokenBucket::new(settings.requests_per_minute, 1),
+        })
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, symbol: &str) -> Result<T> {
+        self.limiter.acquire().await;
+        let response = self
+            .client
+            .get(format!("{}{}", BASE_URL, path))
+            .header(reqwest::header::AUTHORIZATION, format!("Token {}", self.api_key))
+            .timeout(self.timeout)
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            200..=299 => Ok(response.json().await?),
+            401 | 403 => Err(ScannerError::InvalidApiKey),
+            404 => Err(ScannerError::SymbolNotFound(symbol.to_string())),
+            429 => Err(ScannerError::RateLimited { retry_after: None }),
+            status => Err(ScannerError::Http {
+                status,
+                context: symbol.to_string(),
+            }),
+        }
+    }
+}
+
+impl QuoteProvider for TiingoProvider {
+    fn name(&self) -> &str {
+        "tiingo"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            log::debug!("Fetching Tiingo IEX quote for {}", symbol);
+            let quotes: Vec<IexQuote> = self.get(&format!("/iex/{}", symbol), symbol).await?;
+            let quote = quotes
+                .into_iter()
+                .next()
+                .and_then(iex_quote)
+                .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        Box::pin(async move {
+            let frequency = match resolution {
+                "D" => "daily",
+                "W" => "weekly",
+                "M" => "monthly",
+                _ => {
+                    return Err(ScannerError::InvalidInput(format!(
+                        "--provider tiingo only has end-of-day candles (D, W, M), not '{}'",
+                        resolution
+                    )));
+                }
+            };
+            let date = |timestamp: i64| {
+                DateTime::from_timestamp(timestamp, 0)
+                    .map(|time| time.date_naive().to_string())
+                    .unwrap_or_default()
+            };
+
+            log::debug!("Fetching Tiingo {} prices for {}", frequency, symbol);
+            let path = format!(
+                "/tiingo/daily/{}/prices?startDate={}&endDate={}&resampleFreq={}",
+                symbol,
+                date(from),
+                date(to),
+                frequency
+            );
+            let prices: Vec<EodPrice> = self.get(&path, symbol).await?;
+            if prices.is_empty() {
+                return Err(ScannerError::NoCandleData(symbol.to_string()));
+            }
+            eod_candles(prices)
+        })
+    }
+}
+
+fn iex_quote(quote: IexQuote) -> Option<Quote> {
+    Some(Quote {
+        c: quote.last.or(quote.tngo_last)?,
+        pc: quote.prev_close.unwrap_or_default(),
+        h: quote.high.unwrap_or_default(),
+        l: quote.low.unwrap_or_default(),
+        o: quote.open.unwrap_or_default(),
+    })
+}
+
+fn eod_candles(prices: Vec<EodPrice>) -> Result<Candles> {
+    let mut candles = Candles {
+        s: "ok".to_string(),
+        ..Candles::default()
+    };
+    for price in prices {
+        // Dates are midnight UTC, e.g. 2026-10-14T00:00:00.000Z
+        let day = price.date.get(..10).unwrap_or(&price.date);
+        let date = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .map_err(|e| ScannerError::Parse(format!("Tiingo date '{}': {}", price.date, e)))?;
+        candles.t.push(date.and_time(NaiveTime::MIN).and_utc().timestamp());
+        candles.o.push(price.open);
+        candles.h.push(price.high);
+        candles.l.push(price.low);
+        candles.c.push(price.close);
+        candles.v.push(price.volume);
+    }
+    Ok(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iex_quote() {
+        let quotes: Vec<IexQuote> = serde_json::from_str(
+            r#"[{"ticker": "AAPL", "timestamp": "2026-10-14T20:00:00+00:00", "last": null,
+            "tngoLast": 271.01, "prevClose": 271.86, "open": 271.5, "high": 277.84, "low": 269.0,
+            "volume": 51000000}]"#,
+        )
+        .unwrap();
+        let quote = quotes.into_iter().next().and_then(iex_quote).unwrap();
+        assert_eq!(quote.c, 271.01);
+        assert_eq!(quote.pc, 271.86);
+        assert_eq!(quote.l, 269.0);
+    }
+
+    #[test]
+    fn test_eod_candles() {
+        let prices: Vec<EodPrice> = serde_json::from_str(
+            r#"[{"date": "2026-10-13T00:00:00.000Z", "open": 1.0, "high": 2.0, "low": 0.5, "close": 1.5,
+            "volume": 100, "adjClose": 1.5},
+            {"date": "2026-10-14T00:00:00.000Z", "open": 1.5, "high": 2.5, "low": 1.0, "close": 2.0,
+            "volume": 200, "adjClose": 2.0}]"#,
+        )
+        .unwrap();
+        let candles = eod_candles(prices).unwrap();
+        assert_eq!(candles.c, vec![1.5, 2.0]);
+        assert_eq!(candles.t[1] - candles.t[0], 86_400);
+    }
+}
@@ -0,0 +1,604 @@
+use std::time::Duration;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use crate::chart;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::StockQuote;
+use crate::output::{self, QuoteFilter};
+use crate::watch::{self, Session};
+
+/// How often the screen is redrawn between refreshes
+const TICK: Duration = Duration::from_millis(250);
+
+/// How long prices that moved stay highlighted after a refresh
+const FLASH: Duration = Duration::from_millis(1500);
+
+/// Columns the quote table can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Symbol,
+    Price,
+    Change,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Symbol => SortColumn::Price,
+            SortColumn::Price => SortColumn::Change,
+            SortColumn::Change => SortColumn::Symbol,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortColumn::Symbol => "symbol",
+            SortColumn::Price => "price",
+            SortColumn::Change => "change",
+        }
+    }
+}
+
+/// Which quotes the table shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewFilter {
+    All,
+    Gainers,
+    Losers,
+}
+
+impl ViewFilter {
+    fn next(self) -> Self {
+        match self {
+            ViewFilter::All => ViewFilter::Gainers,
+            ViewFilter::Gainers => ViewFilter::Losers,
+            ViewFilter::Losers => ViewFilter::All,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ViewFilter::All => "all",
+            ViewFilter::Gainers => "gainers",
+            ViewFilter::Losers => "losers",
+        }
+    }
+}
+
+/// Changes to the watch session requested from the keyboard
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    AddSymbol(String),
+    RemoveSymbol(String),
+    /// Change the refresh interval by this many seconds
+    AdjustInterval(i64),
+    RefreshNow,
+}
+
+/// Seconds added or removed by `+` and `-`
+const INTERVAL_STEP: i64 = 5;
+
+/// View state that lives only in the dashboard
+struct App {
+    sort: SortColumn,
+    descending: bool,
+    filter: ViewFilter,
+    paused: bool,
+    /// Symbol being typed after `a`, if the prompt is open
+    input: Option<String>,
+    table: TableState,
+    /// Show the news and alert panels under the quotes
+    panels: bool,
+    quit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            sort: SortColumn::Change,
+            descending: true,
+            filter: ViewFilter::All,
+            paused: false,
+            input: None,
+            table: TableState::default().with_selected(0),
+            panels: true,
+            quit: false,
+        }
+    }
+
+    /// Apply a key press to the view, returning any change for the session
+    fn handle_key(&mut self, key: KeyEvent, visible: &[StockQuote]) -> Option<Command> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return None;
+        }
+
+        if let Some(input) = self.input.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let symbol = input.trim().to_uppercase();
+                    self.input = None;
+                    if !symbol.is_empty() {
+                        return Some(Command::AddSymbol(symbol));
+                    }
+                }
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '^') => input.push(c),
+                _ => {}
+            }
+            return None;
+        }
+
+        let rows = visible.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('r') => self.descending = !self.descending,
+            KeyCode::Char('f') => {
+                self.filter = self.filter.next();
+                self.table.select(Some(0));
+            }
+            KeyCode::Char('p') | KeyCode::Char(' ') => {
+                self.paused = !self.paused;
+                if !self.paused {
+                    return Some(Command::RefreshNow);
+                }
+            }
+            KeyCode::Char('u') => return Some(Command::RefreshNow),
+            KeyCode::Char('n') => self.panels = !self.panels,
+            KeyCode::Char('a') => self.input = Some(String::new()),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                let selected = self.table.selected().and_then(|idx| visible.get(idx))?;
+                let symbol = selected.symbol.clone();
+                self.scroll(0, rows.saturating_sub(1));
+                return Some(Command::RemoveSymbol(symbol));
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => return Some(Command::AdjustInterval(INTERVAL_STEP)),
+            KeyCode::Char('-') => return Some(Command::AdjustInterval(-INTERVAL_STEP)),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1, rows),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1, rows),
+            KeyCode::PageDown => self.scroll(10, rows),
+            KeyCode::PageUp => self.scroll(-10, rows),
+            KeyCode::Home => self.table.select(Some(0)),
+            KeyCode::End => self.table.select(Some(rows.saturating_sub(1))),
+            _ => {}
+        }
+        None
+    }
+
+    /// Quotes to show after the command-line filter, filtered and sorted for the current view
+    fn visible(&self, quotes: &[StockQuote], filter: &QuoteFilter) -> Vec<StockQuote> {
+        let mut visible = output::filter_quotes(
+            filter.apply(quotes.to_vec()),
+            self.filter == ViewFilter::Gainers,
+            self.filter == ViewFilter::Losers,
+            None,
+        );
+        sort_quotes(&mut visible, self.sort, self.descending);
+        visible
+    }
+
+    fn scroll(&mut self, delta: isize, rows: usize) {
+        let current = self.table.selected().unwrap_or(0) as isize;
+        let last = rows.saturating_sub(1) as isize;
+        self.table.select(Some((current + delta).clamp(0, last) as usize));
+    }
+}
+
+/// Sort quotes for display by the chosen column
+pub fn sort_quotes(quotes: &mut [StockQuote], column: SortColumn, descending: bool) {
+    quotes.sort_by(|a, b| {
+        let ordering = match column {
+            SortColumn::Symbol => a.symbol.cmp(&b.symbol),
+            SortColumn::Price => a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal),
+            SortColumn::Change => a
+                .change_pct
+                .partial_cmp(&b.change_pct)
+                .unwrap_or(std::cmp::Ordering::Equal),
+        };
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+/// Run the full-screen dashboard until the user quits
+pub async fn run(session: &mut Session) -> Result<()> {
+    // Log output would draw over the alternate screen; errors go to the status bar instead
+    let log_level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, session).await;
+    ratatui::restore();
+
+    log::set_max_level(log_level);
+    result
+}
+
+async fn event_loop(terminal: &mut DefaultTerminal, session: &mut Session) -> Result<()> {
+    let mut keys = spawn_key_reader();
+    let mut app = App::new();
+    let mut next_refresh = Instant::now();
+    let mut redraw = tokio::time::interval(TICK);
+
+    while !app.quit {
+        session.news_enabled = app.panels;
+        if Instant::now() >= next_refresh && session.finished() {
+            break;
+        }
+        if session.reload_symbols().await {
+            next_refresh = Instant::now();
+        }
+        if session.reload_config() {
+            // The interval may have shrunk
+            next_refresh = next_refresh.min(Instant::now() + session.wait_time());
+        }
+        if !app.paused && Instant::now() >= next_refresh {
+            session.refresh().await?;
+            next_refresh = Instant::now() + session.wait_time();
+        }
+
+        let visible = app.visible(&session.quotes, &session.filter);
+        terminal
+            .draw(|frame| render(frame, &mut app, session, &visible, next_refresh))
+            .map_err(|e| ScannerError::Io(format!("Failed to draw dashboard: {}", e)))?;
+
+        let key = tokio::select! {
+            key = keys.recv() => key,
+            _ = redraw.tick() => continue,
+        };
+        let Some(key) = key else {
+            break;
+        };
+
+        match app.handle_key(key, &visible) {
+            Some(Command::AddSymbol(symbol)) => {
+                session.add_symbol(symbol);
+                next_refresh = Instant::now();
+            }
+            Some(Command::RemoveSymbol(symbol)) => session.remove_symbol(&symbol),
+            Some(Command::AdjustInterval(delta)) => {
+                session.set_interval((session.interval as i64 + delta).max(1) as u64);
+                next_refresh = Instant::now() + session.wait_time();
+            }
+            Some(Command::RefreshNow) => {
+                session.refresh_all();
+                if app.paused {
+                    // A one-off update; refreshing stays paused afterwards
+                    session.refresh().await?;
+                }
+                next_refresh = Instant::now();
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Read terminal key presses on a blocking thread and forward them to the event loop
+fn spawn_key_reader() -> mpsc::UnboundedReceiver<KeyEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || loop {
+        match event::poll(TICK) {
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if tx.send(key).is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => return,
+            },
+            Ok(false) if tx.is_closed() => return,
+            Ok(false) => {}
+            Err(_) => return,
+        }
+    });
+
+    rx
+}
+
+fn change_style(change: f64) -> Style {
+    if change > 0.0 {
+        Style::default().fg(Color::Green)
+    } else if change < 0.0 {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    }
+}
+
+/// Solid background for a price that just moved
+fn flash_style(delta: f64) -> Style {
+    let base = Style::default().fg(Color::Black);
+    if delta > 0.0 {
+        base.bg(Color::Green)
+    } else if delta < 0.0 {
+        base.bg(Color::Red)
+    } else {
+        Style::default()
+    }
+}
+
+fn quote_row<'a>(quote: &StockQuote, session: &Session) -> Row<'a> {
+    let delta = session.deltas.get(&quote.symbol).copied();
+    let flashing = session
+        .refreshed
+        .get(&quote.symbol)
+        .is_some_and(|at| at.elapsed() < FLASH);
+
+    let price_style = match delta {
+        Some(delta) if flashing => flash_style(delta),
+        _ => Style::default(),
+    };
+    let tick = match delta {
+        Some(delta) => Cell::from(format!("{:>+8.2}", delta)).style(change_style(delta)),
+        None => Cell::from(format!("{:>8}", "-")),
+    };
+    // Blank until there are two prices to compare
+    let trend = match session.trend.get(&quote.symbol) {
+        Some(prices) if prices.len() > 1 => {
+            let prices: Vec<f64> = prices.iter().copied().collect();
+            let direction = prices[prices.len() - 1] - prices[0];
+            Cell::from(chart::sparkline(&prices)).style(change_style(direction))
+        }
+        _ => Cell::from(""),
+    };
+    let stats = session.stats.get(&quote.symbol);
+    let since_start = match stats {
+        Some(stats) => {
+            let change = stats.since_start(quote.price);
+            Cell::from(format!("{:>+8.2}%", change)).style(change_style(change))
+        }
+        None => Cell::from(format!("{:>9}", "-")),
+    };
+    // Bold marks a symbol trading at its session extreme
+    let extreme = |value: Option<f64>| match value {
+        Some(value) => {
+            let style = if (value - quote.price).abs() < f64::EPSILON {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Cell::from(format!("{:>10.2}", value)).style(style)
+        }
+        None => Cell::from(format!("{:>10}", "-")),
+    };
+
+    Row::new(vec![
+        Cell::from(quote.symbol.clone()),
+        Cell::from(format!("{:>10.2}", quote.price)).style(price_style),
+        tick,
+        trend,
+        Cell::from(format!("{:>10.2}", quote.prev_close)),
+        Cell::from(format!("{:>+8.2}%", quote.change_pct)).style(change_style(quote.change_pct)),
+        since_start,
+        extreme(stats.map(|s| s.high)),
+        extreme(stats.map(|s| s.low)),
+        Cell::from(format!("{:.2}-{:.2}", quote.low, quote.high)),
+    ])
+}
+
+/// Latest company news, one headline per line
+fn news_lines(session: &Session) -> Vec<Line<'static>> {
+    if session.news.is_empty() {
+        return vec![Line::from("No news yet").style(Style::default().fg(Color::DarkGray))];
+    }
+
+    session
+        .news
+        .iter()
+        .map(|item| {
+            let time = chrono::DateTime::from_timestamp(item.datetime, 0)
+                .map(|dt| dt.with_timezone(&chrono::Local).format("%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            Line::from(vec![
+                Span::styled(format!("{} ", time), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:<6} ", item.related), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(item.headline.clone()),
+                Span::styled(format!(" ({})", item.source), Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect()
+}
+
+/// Alerts fired this session, newest first
+fn alert_lines(session: &Session) -> Vec<Line<'static>> {
+    if session.recent_alerts.is_empty() {
+        return vec![Line::from("No alerts fired").style(Style::default().fg(Color::DarkGray))];
+    }
+
+    session
+        .recent_alerts
+        .iter()
+        .map(|alert| {
+            let time = alert
+                .time()
+                .map(|ts| ts.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let failed = alert.results.iter().any(|result| !result.ok);
+            Line::from(vec![
+                Span::styled(format!("{} ", time), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:<6} ", alert.symbol), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{} @ {:.2} ", alert.rule, alert.price)),
+                Span::styled(format!("{:+.2}%", alert.change_pct), change_style(alert.change_pct)),
+                if failed {
+                    Span::styled(" action failed", Style::default().fg(Color::Red))
+                } else {
+                    Span::raw("")
+                },
+            ])
+        })
+        .collect()
+}
+
+fn render(frame: &mut Frame, app: &mut App, session: &Session, quotes: &[StockQuote], next_refresh: Instant) {
+    let panel_height = if app.panels { 10 } else { 0 };
+    let [table_area, panel_area, status_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(panel_height), Constraint::Length(2)])
+            .areas(frame.area());
+
+    if app.panels {
+        let [news_area, alerts_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(panel_area);
+        frame.render_widget(
+            Paragraph::new(news_lines(session)).block(Block::default().borders(Borders::ALL).title(" News ")),
+            news_area,
+        );
+        frame.render_widget(
+            Paragraph::new(alert_lines(session)).block(Block::default().borders(Borders::ALL).title(" Alerts ")),
+            alerts_area,
+        );
+    }
+
+    let header = Row::new(["SYMBOL", "PRICE", "TICK", "TREND", "PREV CLOSE", "CHANGE", "SESSION", "SESS HIGH", "SESS LOW", "DAY RANGE"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = quotes.iter().map(|quote| quote_row(quote, session));
+
+    let title = format!(
+        " Watching {} symbols — showing {} — sorted by {} {} ",
+        session.symbols.len(),
+        app.filter.label(),
+        app.sort.label(),
+        if app.descending { "↓" } else { "↑" }
+    );
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(9),
+            Constraint::Length(watch::TREND_POINTS as u16 + 1),
+            Constraint::Length(11),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Min(15),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, table_area, &mut app.table);
+
+    let updated = session
+        .last_update
+        .map(|ts| ts.format("%H:%M:%S").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    let next = if app.paused {
+        "PAUSED".to_string()
+    } else if session.market_open() == Some(false) {
+        format!("market closed, next in {}s", next_refresh.saturating_duration_since(Instant::now()).as_secs())
+    } else {
+        format!("next in {}s", next_refresh.saturating_duration_since(Instant::now()).as_secs())
+    };
+
+    let mut status = vec![Span::raw(format!(
+        "{} Updated {} | {} (every {}s) | API calls {}/min",
+        watch::spinner(),
+        updated,
+        next,
+        session.current_interval(),
+        session.calls_per_minute()
+    ))];
+    if let Some(error) = &session.last_error {
+        status.push(Span::styled(format!(" | {}", error), Style::default().fg(Color::Red)));
+    }
+    if let Some(alert) = session.fired.last() {
+        status.push(Span::styled(
+            format!(" | 🔔 {} {}", alert.rule, alert.symbol),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
+    let help = match &app.input {
+        Some(input) => Line::from(format!("Add symbol: {}▏ (Enter to add, Esc to cancel)", input))
+            .style(Style::default().fg(Color::Cyan)),
+        None => Line::from("q quit  p pause  u update  s sort  r reverse  f filter  a add  d remove  +/- interval  n panels  ↑/↓ scroll")
+            .style(Style::default().fg(Color::DarkGray)),
+    };
+    frame.render_widget(Paragraph::new(vec![Line::from(status), help]), status_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_quotes() {
+        let mut quotes = vec![
+            StockQuote::test("MSFT", 400.0, 404.0),
+            StockQuote::test("AAPL", 200.0, 196.0),
+            StockQuote::test("TSLA", 250.0, 249.0),
+        ];
+
+        sort_quotes(&mut quotes, SortColumn::Change, true);
+        assert_eq!(quotes[0].symbol, "AAPL");
+
+        sort_quotes(&mut quotes, SortColumn::Price, false);
+        assert_eq!(quotes[0].symbol, "AAPL");
+        assert_eq!(quotes[2].symbol, "MSFT");
+
+        sort_quotes(&mut quotes, SortColumn::Symbol, false);
+        assert_eq!(quotes[1].symbol, "MSFT");
+    }
+
+    fn press(app: &mut App, code: KeyCode, visible: &[StockQuote]) -> Option<Command> {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), visible)
+    }
+
+    #[test]
+    fn test_key_commands() {
+        let mut app = App::new();
+        let visible = vec![StockQuote::test("AAPL", 200.0, 196.0), StockQuote::test("MSFT", 400.0, 404.0)];
+
+        assert_eq!(press(&mut app, KeyCode::Char('p'), &visible), None);
+        assert!(app.paused);
+        assert_eq!(press(&mut app, KeyCode::Char('p'), &visible), Some(Command::RefreshNow));
+
+        press(&mut app, KeyCode::Char('a'), &visible);
+        for c in "nvda".chars() {
+            press(&mut app, KeyCode::Char(c), &visible);
+        }
+        // Letters go to the prompt rather than triggering commands
+        assert!(!app.quit);
+        assert_eq!(press(&mut app, KeyCode::Enter, &visible), Some(Command::AddSymbol("NVDA".to_string())));
+
+        press(&mut app, KeyCode::Down, &visible);
+        assert_eq!(
+            press(&mut app, KeyCode::Char('d'), &visible),
+            Some(Command::RemoveSymbol("MSFT".to_string()))
+        );
+        assert_eq!(press(&mut app, KeyCode::Char('-'), &visible), Some(Command::AdjustInterval(-5)));
+
+        press(&mut app, KeyCode::Char('f'), &visible);
+        assert_eq!(app.visible(&visible, &QuoteFilter::default()).len(), 1);
+
+        press(&mut app, KeyCode::Char('q'), &visible);
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn test_scroll_stays_in_bounds() {
+        let mut app = App::new();
+        app.scroll(-1, 3);
+        assert_eq!(app.table.selected(), Some(0));
+        app.scroll(10, 3);
+        assert_eq!(app.table.selected(), Some(2));
+    }
+}
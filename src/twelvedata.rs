@@ -0,0 +1,303 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Candles, Quote, StockQuote, CANDLE_RESOLUTIONS};
+use crate::provider::{ProviderFuture, QuoteProvider};
+use crate::ratelimit::TokenBucket;
+
+const BASE_URL: &str = "https://api.twelvedata.com";
+
+/// `[twelvedata]`: key and rate limit for `--provider twelvedata`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwelveDataConfig {
+    /// Also read from `TWELVEDATA_API_KEY`
+    #[serde(default)]
+    pub api_key: String,
+
+    /// The free plan allows 8 requests a minute
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+}
+
+impl Default for TwelveDataConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            requests_per_minute: default_requests_per_minute(),
+        }
+    }
+}
+
+fn default_requests_per_minute() -> u32 {
+    8
+}
+
+/// Quotes and time series from Twelve Data, which covers forex pairs and crypto (`EUR/USD`,
+/// `BTC/USD`) as well as equities
+pub struct TwelveDataProvider {
+    api_key: String,
+    client: reqwest::Client,
+    /// Also set per request, see [`crate::config::HttpConfig::client_builder`]
+    timeout: Duration,
+    limiter: TokenBucket,
+}
+
+/// Twelve Data reports errors in the body, usually with HTTP 200
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    code: u16,
+    #[serde(default)]
+    message: String,
+}
+
+/// Prices come back as strings
+#[derive(Deserialize)]
+struct QuoteBody {
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    previous_close: String,
+}
+
+#[derive(Deserialize)]
+struct TimeSeries {
+    #[serde(default)]
+    values: Vec<Bar>,
+}
+
+#[derive(Deserialize)]
+struct Bar {
+    datetime: String,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    #[serde(default)]
+    volume: Option<String>,
+}
+
+impl TwelveDataProvider {
+    pub fn new(config: &Config) -> Result<Self> {
+        let settings = &config.twelvedata;
+        if settings.api_key.is_empty() {
+            return Err(ScannerError::Config(
+                "--provider twelvedata needs an API key. Set TWELVEDATA_API_KEY or [twelvedata] api_key"
+                    .to_string(),
+            ));
+        }
+
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let client = config.http.client_builder(timeout)?.build()?;
+        Ok(Self {
+            api_key: settings.api_key.clone(),
+            client,
+            timeout,
+            limiter: TokenBucket::new(settings.requests_per_minute, 1),
+        })
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        symbol: &str,
+    ) -> Result<T> {
+        self.limiter.acquire().await;
+        // Through `query` so pairs like EUR/USD are encoded
+        let response = self
+            .client
+            .get(format!("{}{}", BASE_URL, endpoint))
+            .query(params)
+            .header(reqwest::header::AUTHORIZATION, format!("apikey {}", self.api_key))
+            .timeout(self.timeout)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_for(response.status().as_u16(), String::new(), symbol));
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        parse_body(body, symbol)
+    }
+}
+
+impl QuoteProvider for TwelveDataProvider {
+    fn name(&self) -> &str {
+        "twelvedata"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            log::debug!("Fetching Twelve Data quote for {}", symbol);
+            let body: QuoteBody = self.get("/quote", &[("symbol", symbol)], symbol).await?;
+            let number = |value: &str| parse_number(value, symbol);
+            let quote = Quote {
+                c: number(&body.close)?,
+                pc: number(&body.previous_close)?,
+                h: number(&body.high)?,
+                l: number(&body.low)?,
+                o: number(&body.open)?,
+            };
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+
+    fn fetch_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+        resolution: &'a str,
+        from: i64,
+        to: i64,
+    ) -> ProviderFuture<'a, Result<Candles>> {
+        Box::pin(async move {
+            let interval = interval(resolution)?;
+            let time = |timestamp: i64| {
+                DateTime::from_timestamp(timestamp, 0)
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default()
+            };
+            let (start, end) = (time(from), time(to));
+
+            log::debug!("Fetching Twelve Data {} time series for {}", interval, symbol);
+            let series: TimeSeries = self
+                .get(
+                    "/time_series",
+                    &[
+                        ("symbol", symbol),
+                        ("interval", interval),
+                        ("start_date", start.as_str()),
+                        ("end_date", end.as_str()),
+                        ("timezone", "UTC"),
+                        ("order", "ASC"),
+                        ("outputsize", "5000"),
+                    ],
+                    symbol,
+                )
+                .await?;
+            if series.values.is_empty() {
+                return Err(ScannerError::NoCandleData(symbol.to_string()));
+            }
+            series_candles(series.values, symbol)
+        })
+    }
+}
+
+/// Twelve Data's `interval` for a Finnhub candle resolution
+fn interval(resolution: &str) -> Result<&'static str> {
+    match resolution {
+        "1" => Ok("1min"),
+        "5" => Ok("5min"),
+        "15" => Ok("15min"),
+        "30" => Ok("30min"),
+        "60" => Ok("1h"),
+        "D" => Ok("1day"),
+        "W" => Ok("1week"),
+        "M" => Ok("1month"),
+        _ => Err(ScannerError::InvalidInput(format!(
+            "Unsupported resolution '{}'. Use one of: {}",
+            resolution,
+            CANDLE_RESOLUTIONS.join(", ")
+        ))),
+    }
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: serde_json::Value, symbol: &str) -> Result<T> {
+    if body.get("status").and_then(|status| status.as_str()) == Some("error") {
+        let error: ErrorBody = serde_json::from_value(body)?;
+        return Err(error_for(error.code, error.message, symbol));
+    }
+    Ok(serde_json::from_value(body)?)
+}
+
+fn error_for(code: u16, message: String, symbol: &str) -> ScannerError {
+    match code {
+        401 | 403 => ScannerError::InvalidApiKey,
+        404 => ScannerError::SymbolNotFound(symbol.to_string()),
+        429 => ScannerError::RateLimited { retry_after: None },
+        400 if !message.is_empty() => ScannerError::InvalidInput(format!("Twelve Data: {}", message)),
+        _ if !message.is_empty() => ScannerError::Api(format!("Twelve Data: {}", message)),
+        status => ScannerError::Http {
+            status,
+            context: symbol.to_string(),
+        },
+    }
+}
+
+fn parse_number(value: &str, symbol: &str) -> Result<f64> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ScannerError::Parse(format!("Twelve Data value for {}: '{}'", symbol, value)))
+}
+
+fn series_candles(bars: Vec<Bar>, symbol: &str) -> Result<Candles> {
+    let mut candles = Candles {
+        s: "ok".to_string(),
+        ..Candles::default()
+    };
+    for bar in bars {
+        // Daily and longer bars have a bare date
+        let time = NaiveDateTime::parse_from_str(&bar.datetime, "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| {
+                NaiveDate::parse_from_str(&bar.datetime, "%Y-%m-%d").map(|date| date.and_time(NaiveTime::MIN))
+            })
+            .map_err(|e| ScannerError::Parse(format!("Twelve Data datetime '{}': {}", bar.datetime, e)))?;
+        candles.t.push(time.and_utc().timestamp());
+        candles.o.push(parse_number(&bar.open, symbol)?);
+        candles.h.push(parse_number(&bar.high, symbol)?);
+        candles.l.push(parse_number(&bar.low, symbol)?);
+        candles.c.push(parse_number(&bar.close, symbol)?);
+        // Forex pairs have no volume
+        let volume = bar.volume.as_deref().map(|volume| parse_number(volume, symbol)).transpose()?;
+        candles.v.push(volume.unwrap_or_default());
+    }
+    Ok(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_quote_and_errors() {
+        let body: QuoteBody = parse_body(
+            json!({"symbol": "EUR/USD", "open": "1.0850", "high": "1.0900", "low": "1.0800",
+                "close": "1.0875", "previous_close": "1.0850", "is_market_open": true}),
+            "EUR/USD",
+        )
+        .unwrap();
+        assert_eq!(parse_number(&body.close, "EUR/USD").unwrap(), 1.0875);
+
+        let not_found = json!({"code": 404, "message": "symbol not found", "status": "error"});
+        assert!(matches!(
+            parse_body::<QuoteBody>(not_found, "NOPE"),
+            Err(ScannerError::SymbolNotFound(_))
+        ));
+        let limited = json!({"code": 429, "message": "You have run out of API credits", "status": "error"});
+        assert!(matches!(
+            parse_body::<QuoteBody>(limited, "AAPL"),
+            Err(ScannerError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn test_series_candles() {
+        let series: TimeSeries = serde_json::from_value(json!({"meta": {"symbol": "BTC/USD"}, "values": [
+            {"datetime": "2026-10-13", "open": "1", "high": "2", "low": "0.5", "close": "1.5"},
+            {"datetime": "2026-10-14", "open": "1.5", "high": "2.5", "low": "1", "close": "2", "volume": "10"}
+        ], "status": "ok"}))
+        .unwrap();
+        let candles = series_candles(series.values, "BTC/USD").unwrap();
+        assert_eq!(candles.c, vec![1.5, 2.0]);
+        assert_eq!(candles.v, vec![0.0, 10.0]);
+        assert_eq!(candles.t[1] - candles.t[0], 86_400);
+
+        assert_eq!(interval("60").unwrap(), "1h");
+        assert!(interval("2").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::paths;
+
+const USAGE_FILE: &str = "usage.ndjson";
+
+/// How often a long-running command writes its counts, so `usage` stays current
+#[cfg(not(target_arch = "wasm32"))]
+const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Runs listed by `usage`
+const RECENT_RUNS: usize = 5;
+
+/// API calls counted since the last flush, as one line of the usage log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: String,
+    pub command: String,
+    /// When the run started; together with `command`, which run the calls belong to
+    pub started_at: String,
+    /// Calls per endpoint path, e.g. `/quote`
+    pub calls: BTreeMap<String, u64>,
+}
+
+impl UsageRecord {
+    pub fn total(&self) -> u64 {
+        self.calls.values().sum()
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|time| time.with_timezone(&Utc).date_naive())
+    }
+}
+
+struct Tracker {
+    path: PathBuf,
+    command: &'static str,
+    started_at: String,
+    /// Not yet written to the log
+    pending: BTreeMap<String, u64>,
+    run: BTreeMap<String, u64>,
+}
+
+static TRACKER: Mutex<Option<Tracker>> = Mutex::new(None);
+
+/// Where calls are counted
+pub fn log_path() -> PathBuf {
+    paths::data_file(USAGE_FILE)
+}
+
+/// Start counting this run's API calls, unless `track_usage` is off
+pub fn start(config: &Config, command: &'static str) {
+    if !config.track_usage {
+        return;
+    }
+    *TRACKER.lock().expect("usage lock poisoned") = Some(Tracker {
+        path: log_path(),
+        command,
+        started_at: Utc::now().to_rfc3339(),
+        pending: BTreeMap::new(),
+        run: BTreeMap::new(),
+    });
+    // Browsers have no usage log to flush to
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(async {
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            flush();
+        }
+    });
+}
+
+/// Count one answered request to `url`; retries and error responses use up the quota too
+pub fn record(url: &str) {
+    let mut tracker = TRACKER.lock().expect("usage lock poisoned");
+    if let Some(tracker) = tracker.as_mut() {
+        let endpoint = endpoint(url);
+        *tracker.pending.entry(endpoint.to_string()).or_default() += 1;
+        *tracker.run.entry(endpoint.to_string()).or_default() += 1;
+    }
+}
+
+/// Append calls counted since the last flush to the usage log
+pub fn flush() {
+    let mut tracker = TRACKER.lock().expect("usage lock poisoned");
+    let Some(tracker) = tracker.as_mut() else {
+        return;
+    };
+    if tracker.pending.is_empty() {
+        return;
+    }
+    let record = UsageRecord {
+        timestamp: Utc::now().to_rfc3339(),
+        command: tracker.command.to_string(),
+        started_at: tracker.started_at.clone(),
+        calls: std::mem::take(&mut tracker.pending),
+    };
+    if let Err(e) = append(&tracker.path, &record) {
+        log::warn!("Failed to record API usage: {}", e);
+    }
+}
+
+/// Flush, and log what the whole run used
+pub fn finish() {
+    flush();
+    let tracker = TRACKER.lock().expect("usage lock poisoned");
+    if let Some(tracker) = tracker.as_ref().filter(|tracker| !tracker.run.is_empty()) {
+        let calls: Vec<String> = tracker
+            .run
+            .iter()
+            .map(|(endpoint, calls)| format!("{} {}", endpoint, calls))
+            .collect();
+        log::info!(
+            "API calls this run: {} ({})",
+            tracker.run.values().sum::<u64>(),
+            calls.join(", ")
+        );
+    }
+}
+
+/// `/quote` for `https://finnhub.io/api/v1/quote?symbol=AAPL&token=...`, leaving the token out
+fn endpoint(url: &str) -> &str {
+    let path = url.split('?').next().unwrap_or(url);
+    match path.find("/api/v1") {
+        Some(start) => &path[start + "/api/v1".len()..],
+        None => path,
+    }
+}
+
+fn append(path: &Path, record: &UsageRecord) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    // One write per line, so concurrent runs don't interleave
+    let line = format!("{}\n", serde_json::to_string(record)?);
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Every record in a usage log; a missing log is an empty one
+pub fn read_records(path: &Path) -> Result<Vec<UsageRecord>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(ScannerError::Io(format!("Failed to read {}: {}", path.display(), e)));
+        }
+    };
+
+    let mut records = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => log::warn!("{}:{}: skipping malformed record: {}", path.display(), number + 1, e),
+        }
+    }
+    Ok(records)
+}
+
+/// Calls per endpoint on `date` (UTC)
+pub fn calls_on(records: &[UsageRecord], date: NaiveDate) -> BTreeMap<String, u64> {
+    let mut calls = BTreeMap::new();
+    for record in records.iter().filter(|record| record.date() == Some(date)) {
+        for (endpoint, count) in &record.calls {
+            *calls.entry(endpoint.clone()).or_default() += count;
+        }
+    }
+    calls
+}
+
+/// Runs with calls on `date`, newest first, with their totals for that day
+pub fn runs_on(records: &[UsageRecord], date: NaiveDate) -> Vec<(String, String, u64)> {
+    let mut runs: Vec<(String, String, u64)> = Vec::new();
+    for record in records.iter().filter(|record| record.date() == Some(date)) {
+        let run = runs
+            .iter_mut()
+            .find(|(started_at, command, _)| *started_at == record.started_at && *command == record.command);
+        match run {
+            Some(run) => run.2 += record.total(),
+            None => runs.push((record.started_at.clone(), record.command.clone(), record.total())),
+        }
+    }
+    runs.sort_by(|a, b| b.0.cmp(&a.0));
+    runs
+}
+
+/// Print today's calls against `daily_call_limit`, and totals for the `days` before it
+pub fn print(records: &[UsageRecord], config: &Config, days: u32) {
+    let today = Utc::now().date_naive();
+    let calls = calls_on(records, today);
+    let total: u64 = calls.values().sum();
+
+    println!("API calls today ({} UTC)", today);
+    for (endpoint, count) in &calls {
+        println!("   {:<24} {:>8}", endpoint, count);
+    }
+    match config.daily_call_limit {
+        Some(limit) => {
+            let used = total as f64 * 100.0 / limit.max(1) as f64;
+            println!("   Total: {} of {} a day ({:.0}%)", total, limit, used);
+            if total >= limit {
+                println!("⚠ Today's calls have reached daily_call_limit");
+            } else if used >= 80.0 {
+                println!("⚠ {} calls left today", limit - total);
+            }
+        }
+        None => println!("   Total: {} (set daily_call_limit to compare against your plan)", total),
+    }
+
+    let runs = runs_on(records, today);
+    if !runs.is_empty() {
+        println!("Recent runs:");
+        for (started_at, command, calls) in runs.iter().take(RECENT_RUNS) {
+            let started = DateTime::parse_from_rfc3339(started_at)
+                .map(|time| time.with_timezone(&Utc).format("%H:%M:%S").to_string())
+                .unwrap_or_else(|_| started_at.clone());
+            println!("   {}  {:<8} {:>8} calls", started, command, calls);
+        }
+    }
+
+    if days > 0 {
+        println!("Earlier days:");
+        for day in (1..=days).filter_map(|back| today.checked_sub_days(chrono::Days::new(back.into()))) {
+            println!("   {}  {:>8}", day, calls_on(records, day).values().sum::<u64>());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: &str, started_at: &str, calls: &[(&str, u64)]) -> UsageRecord {
+        UsageRecord {
+            timestamp: timestamp.to_string(),
+            command: "watch".to_string(),
+            started_at: started_at.to_string(),
+            calls: calls.iter().map(|(endpoint, count)| (endpoint.to_string(), *count)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_endpoint() {
+        assert_eq!(endpoint("https://finnhub.io/api/v1/quote?symbol=AAPL&token=secret"), "/quote");
+        assert_eq!(endpoint("https://finnhub.io/api/v1/stock/profile2?symbol=AAPL"), "/stock/profile2");
+    }
+
+    #[test]
+    fn test_calls_and_runs() {
+        let records = vec![
+            record("2026-01-01T23:59:00+00:00", "2026-01-01T23:00:00+00:00", &[("/quote", 7)]),
+            record("2026-01-02T09:31:00+00:00", "2026-01-02T09:30:00+00:00", &[("/quote", 10)]),
+            record("2026-01-02T09:32:00+00:00", "2026-01-02T09:30:00+00:00", &[("/quote", 10), ("/stock/profile2", 2)]),
+            record("2026-01-02T12:00:00+00:00", "2026-01-02T12:00:00+00:00", &[("/quote", 3)]),
+        ];
+        let day = NaiveDate::from_ymd_opt(2026, 1, 2).unwrap();
+
+        let calls = calls_on(&records, day);
+        assert_eq!(calls["/quote"], 23);
+        assert_eq!(calls["/stock/profile2"], 2);
+
+        let runs = runs_on(&records, day);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].2, 3);
+        assert_eq!(runs[1].2, 22);
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(USAGE_FILE);
+        let written = record("2026-01-02T09:31:00+00:00", "2026-01-02T09:30:00+00:00", &[("/quote", 4)]);
+        append(&path, &written).unwrap();
+        append(&path, &written).unwrap();
+
+        let records = read_records(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].calls["/quote"], 4);
+        assert!(read_records(&dir.path().join("missing.ndjson")).unwrap().is_empty());
+    }
+}
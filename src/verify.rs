@@ -0,0 +1,151 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::decimal;
+use crate::errors::{self, Result};
+use crate::finnhub::StockQuote;
+use crate::provider::QuoteProvider;
+
+/// Price difference allowed between providers, in percent, unless `--tolerance` says otherwise
+pub const DEFAULT_TOLERANCE_PCT: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Prices are within the tolerance
+    Match,
+    /// Prices differ by more than the tolerance
+    Mismatch,
+    /// One of the providers had no quote
+    Missing,
+}
+
+/// One symbol's prices from both providers
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub symbol: String,
+    pub primary: Option<f64>,
+    pub secondary: Option<f64>,
+    /// How far the secondary price is from the primary one, in percent
+    pub diff_pct: Option<f64>,
+    pub verdict: Verdict,
+}
+
+/// Fetch `symbols` from both providers at once and compare their prices
+pub async fn compare(
+    primary: &dyn QuoteProvider,
+    secondary: &dyn QuoteProvider,
+    symbols: &[String],
+    tolerance_pct: f64,
+) -> Result<Vec<Comparison>> {
+    let (mut ignore_primary, mut ignore_secondary) = (|_: &StockQuote| {}, |_: &StockQuote| {});
+    let (primary_quotes, secondary_quotes) = futures::join!(
+        primary.fetch_quotes_with(symbols, &mut ignore_primary),
+        secondary.fetch_quotes_with(symbols, &mut ignore_secondary),
+    );
+    let (primary_quotes, _) = primary_quotes.map_err(|e| e.context(primary.name()))?;
+    let (secondary_quotes, _) = secondary_quotes.map_err(|e| e.context(secondary.name()))?;
+
+    Ok(compare_quotes(symbols, &primary_quotes, &secondary_quotes, tolerance_pct))
+}
+
+fn compare_quotes(
+    symbols: &[String],
+    primary: &[StockQuote],
+    secondary: &[StockQuote],
+    tolerance_pct: f64,
+) -> Vec<Comparison> {
+    let prices = |quotes: &[StockQuote]| -> HashMap<String, f64> {
+        quotes.iter().map(|quote| (quote.symbol.clone(), quote.price)).collect()
+    };
+    let (primary, secondary) = (prices(primary), prices(secondary));
+
+    symbols
+        .iter()
+        .map(|symbol| {
+            let (a, b) = (primary.get(symbol).copied(), secondary.get(symbol).copied());
+            let diff_pct = a.zip(b).map(|(a, b)| decimal::change_pct(b, a));
+            let verdict = match diff_pct {
+                None => Verdict::Missing,
+                Some(diff) if diff.abs() > tolerance_pct => Verdict::Mismatch,
+                Some(_) => Verdict::Match,
+            };
+            Comparison {
+                symbol: symbol.clone(),
+                primary: a,
+                secondary: b,
+                diff_pct,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+/// 1 when any prices disagree, 2 when some are only missing, otherwise 0
+pub fn exit_code(comparisons: &[Comparison]) -> u8 {
+    if comparisons.iter().any(|c| c.verdict == Verdict::Mismatch) {
+        errors::EXIT_NO_MATCHES
+    } else if comparisons.iter().any(|c| c.verdict == Verdict::Missing) {
+        errors::EXIT_PARTIAL
+    } else {
+        errors::EXIT_OK
+    }
+}
+
+pub fn print_table(comparisons: &[Comparison], primary: &str, secondary: &str, tolerance_pct: f64) {
+    let price = |price: Option<f64>| price.map_or("-".to_string(), |price| format!("{:.2}", price));
+
+    println!("\n{}", "=".repeat(62));
+    println!("{:<10} {:>14} {:>14} {:>10}  STATUS", "SYMBOL", primary, secondary, "DIFF");
+    println!("{}", "=".repeat(62));
+    for comparison in comparisons {
+        let diff = comparison.diff_pct.map_or("-".to_string(), |diff| format!("{:+.2}%", diff));
+        let status = match comparison.verdict {
+            Verdict::Match => "ok".to_string(),
+            Verdict::Mismatch => "\x1b[31mMISMATCH\x1b[0m".to_string(),
+            Verdict::Missing => "missing".to_string(),
+        };
+        println!(
+            "{:<10} {:>14} {:>14} {:>10}  {}",
+            comparison.symbol,
+            price(comparison.primary),
+            price(comparison.secondary),
+            diff,
+            status
+        );
+    }
+    println!("{}", "=".repeat(62));
+
+    let mismatched = comparisons.iter().filter(|c| c.verdict == Verdict::Mismatch).count();
+    let missing = comparisons.iter().filter(|c| c.verdict == Verdict::Missing).count();
+    println!(
+        "\n{} compared, {} differ by more than {}%, {} missing from one provider",
+        comparisons.len(),
+        mismatched,
+        tolerance_pct,
+        missing
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_quotes() {
+        let symbols: Vec<String> = ["AAPL", "MSFT", "NVDA"].iter().map(|s| s.to_string()).collect();
+        let primary = [
+            StockQuote::test("AAPL", 100.0, 100.0),
+            StockQuote::test("MSFT", 200.0, 200.0),
+            StockQuote::test("NVDA", 50.0, 50.0),
+        ];
+        let secondary = [StockQuote::test("AAPL", 100.2, 100.2), StockQuote::test("MSFT", 190.0, 190.0)];
+
+        let comparisons = compare_quotes(&symbols, &primary, &secondary, 0.5);
+        let verdicts: Vec<Verdict> = comparisons.iter().map(|c| c.verdict).collect();
+        assert_eq!(verdicts, [Verdict::Match, Verdict::Mismatch, Verdict::Missing]);
+        assert_eq!(comparisons[1].diff_pct, Some(-5.0));
+        assert_eq!(exit_code(&comparisons), errors::EXIT_NO_MATCHES);
+        assert_eq!(exit_code(&comparisons[..1]), errors::EXIT_OK);
+        assert_eq!(exit_code(&[comparisons[2].clone()]), errors::EXIT_PARTIAL);
+    }
+}
@@ -0,0 +1,1021 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use crate::actions::{ActionRunner, ScanReport};
+use crate::alerts::{self, AlertEngine, AlertRecord, SymbolStats};
+use crate::cassette::Cassette;
+use crate::config::{self, Config, SymbolGroup, WatchConfig};
+use crate::errors::{Result, ScannerError};
+use crate::expr;
+use crate::finnhub::{FinnhubClient, NewsItem, StockQuote};
+use crate::history::{self, TickLogger};
+use crate::output::{self, OutputFormat, QuoteFilter};
+use crate::provider::QuoteProvider;
+use crate::reload::{ConfigFile, FileWatch};
+use crate::resolve;
+use crate::sinks::Sinks;
+use crate::systemd;
+#[cfg(feature = "cli")]
+use crate::tui;
+use crate::usage;
+
+/// Articles kept in the dashboard's news feed
+const NEWS_LIMIT: usize = 50;
+
+/// Days of company news requested for each symbol
+const NEWS_DAYS: i64 = 3;
+
+/// How long a symbol's news is reused before it is requested again
+const NEWS_REFRESH: Duration = Duration::from_secs(600);
+
+/// Prices kept per symbol for the dashboard's sparkline
+pub const TREND_POINTS: usize = 20;
+
+/// Fired alerts kept for the dashboard's alert panel
+const RECENT_ALERTS: usize = 20;
+
+/// Command-line options for `watch`
+pub struct WatchOptions {
+    pub interval: u64,
+    /// Limits which quotes are displayed; logging and alerts still see every symbol
+    pub filter: QuoteFilter,
+    pub log_file: Option<PathBuf>,
+    /// `--sink` URLs, added to `sinks` in the config
+    pub sinks: Vec<String>,
+    pub alerts: bool,
+    pub bell: bool,
+    /// Use the clear-and-reprint loop even on a terminal
+    pub plain: bool,
+    /// Print each refresh in this format instead of showing the dashboard
+    pub output: Option<OutputFormat>,
+    /// Append each refresh below the previous one instead of clearing the screen
+    pub no_clear: bool,
+    /// Stop after this many refreshes
+    pub iterations: Option<u64>,
+    /// Stop once this time is reached
+    pub until: Option<DateTime<Local>>,
+    /// Slow down outside trading hours, as if `watch.market_hours` were set
+    pub market_hours: bool,
+    /// `--symbol-interval` groups, added to those in `[[watch.groups]]`
+    pub groups: Vec<SymbolGroup>,
+    /// Symbols file to reload when it changes on disk
+    pub symbols_file: Option<PathBuf>,
+    /// Config file (and profile) to re-read when it changes on disk
+    pub config_file: Option<(PathBuf, Option<String>)>,
+    /// `--quiet`: print only quote rows, without the dashboard, footer or session summary
+    pub quiet: bool,
+    /// Where quotes come from; `None` fetches them from Finnhub
+    pub provider: Option<Arc<dyn QuoteProvider>>,
+    /// `--record` or `--replay` cassette for API responses
+    pub cassette: Option<Arc<Cassette>>,
+}
+
+/// Exchange trading status, re-checked every `status_check_secs`
+struct MarketHours {
+    config: WatchConfig,
+    checked_at: Option<Instant>,
+    trading: bool,
+}
+
+impl MarketHours {
+    /// Re-check status if it's stale, returning true when the market has just opened
+    async fn update(&mut self, client: &FinnhubClient) -> bool {
+        let every = Duration::from_secs(self.config.status_check_secs);
+        if self.checked_at.is_some_and(|at| at.elapsed() < every) {
+            return false;
+        }
+        self.checked_at = Some(Instant::now());
+
+        let was_trading = self.trading;
+        match client.fetch_market_status(&self.config.exchange).await {
+            Ok(status) => {
+                let trading = status.is_trading(self.config.extended_hours);
+                if trading != self.trading {
+                    if trading {
+                        log::info!("{} market is open, resuming normal refreshes", self.config.exchange);
+                    } else if let Some(holiday) = &status.holiday {
+                        log::info!("{} market is closed for {}, slowing refreshes", self.config.exchange, holiday);
+                    } else {
+                        log::info!("{} market is closed, slowing refreshes", self.config.exchange);
+                    }
+                }
+                self.trading = trading;
+            }
+            // Keep polling at the normal rate rather than going quiet on a bad status response
+            Err(e) => {
+                log::warn!("Failed to fetch market status: {}", e);
+                self.trading = true;
+            }
+        }
+        self.trading && !was_trading
+    }
+}
+
+/// A symbols file watched for edits made while the session runs
+struct SymbolsFile {
+    path: PathBuf,
+    watch: FileWatch,
+    /// The file's symbols as last read
+    symbols: Vec<String>,
+}
+
+impl SymbolsFile {
+    async fn watch(path: &Path, config: &Config, client: &FinnhubClient) -> Result<Self> {
+        let watch = FileWatch::new(path)?;
+        let entries = config::load_symbols_from_file(path).unwrap_or_default();
+        Ok(Self {
+            path: path.to_path_buf(),
+            watch,
+            symbols: prepare(&entries, config, client).await.unwrap_or_default(),
+        })
+    }
+
+    /// Symbols added to and removed from the file, if it changed since the last call
+    async fn reload(&mut self, config: &Config, client: &FinnhubClient) -> Option<(Vec<String>, Vec<String>)> {
+        if !self.watch.changed() {
+            return None;
+        }
+
+        let entries = match config::load_symbols_from_file(&self.path) {
+            Ok(entries) => entries,
+            // Most likely caught halfway through a save; the next write event retries
+            Err(e) => {
+                log::debug!("Ignoring symbols file change: {}", e);
+                return None;
+            }
+        };
+        match prepare(&entries, config, client).await {
+            Ok(symbols) => {
+                let diff = diff_symbols(&self.symbols, &symbols);
+                self.symbols = symbols;
+                Some(diff)
+            }
+            Err(e) => {
+                log::warn!("Symbols file changes weren't applied: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Symbols file entries as the command line loads them, see [`resolve::prepare`]
+///
+/// There's no one to ask mid-session, so company names take the best search match.
+async fn prepare(entries: &[String], config: &Config, client: &FinnhubClient) -> Result<Vec<String>> {
+    resolve::prepare(entries, &config.groups, client, true).await
+}
+
+/// State shared by the TUI and plain watch front-ends
+pub struct Session {
+    client: FinnhubClient,
+    provider: Arc<dyn QuoteProvider>,
+    runner: ActionRunner,
+    config: Config,
+    pub symbols: Vec<String>,
+    pub interval: u64,
+    pub filter: QuoteFilter,
+    iterations: Option<u64>,
+    pub until: Option<DateTime<Local>>,
+    /// Refreshes attempted so far
+    pub ticks: u64,
+    started: Instant,
+    market: Option<MarketHours>,
+    symbols_file: Option<SymbolsFile>,
+    config_file: Option<ConfigFile>,
+    bell: bool,
+    dashboard: bool,
+    quiet: bool,
+    tick_logger: Option<TickLogger>,
+    sinks: Sinks,
+    last_prune: Option<NaiveDate>,
+    alert_engine: Option<AlertEngine>,
+    last_summary: Instant,
+    /// Quotes from the latest successful refresh
+    pub quotes: Vec<StockQuote>,
+    pub last_update: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+    /// Alerts fired by the latest refresh
+    pub fired: Vec<AlertRecord>,
+    /// Latest fired alerts, newest first
+    pub recent_alerts: VecDeque<AlertRecord>,
+    alerts_fired: usize,
+    /// Fetch company news alongside quotes, for the dashboard's news panel
+    pub news_enabled: bool,
+    /// Company news for the watched symbols, newest first
+    pub news: Vec<NewsItem>,
+    news_cursor: usize,
+    news_fetched: HashMap<String, Instant>,
+    /// Price change per symbol since its previous fetch
+    pub deltas: HashMap<String, f64>,
+    /// When each symbol was last fetched, used to flash changed cells
+    pub refreshed: HashMap<String, Instant>,
+    /// Refresh interval for symbols in a group; the rest use `interval`
+    intervals: HashMap<String, u64>,
+    /// When each symbol is next due to be fetched
+    next_due: HashMap<String, Instant>,
+    pub stats: HashMap<String, SymbolStats>,
+    /// Latest prices per symbol, oldest first, up to `TREND_POINTS`
+    pub trend: HashMap<String, VecDeque<f64>>,
+    /// Quote requests made within the last minute
+    calls: VecDeque<Instant>,
+}
+
+impl Session {
+    /// `dashboard` stops the `log` alert action printing over the TUI
+    pub async fn new(
+        config: &Config,
+        mut symbols: Vec<String>,
+        options: &WatchOptions,
+        dashboard: bool,
+    ) -> Result<Self> {
+        let mut intervals = HashMap::new();
+        for group in config.watch.groups.iter().chain(&options.groups) {
+            for symbol in &group.symbols {
+                let symbol = symbol.to_uppercase();
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol.clone());
+                }
+                intervals.insert(symbol, group.interval_secs.max(1));
+            }
+        }
+
+        // Watch always wants live prices, so it never reads from the quote cache
+        let mut client = FinnhubClient::from_config(config)?;
+        if let Some(cassette) = &options.cassette {
+            client = client.with_cassette(cassette.clone());
+        }
+        let provider = match &options.provider {
+            Some(provider) => provider.clone(),
+            None => Arc::new(client.clone()),
+        };
+        let tick_logger = options
+            .log_file
+            .clone()
+            .or_else(|| config.history.file.clone())
+            .as_deref()
+            .map(TickLogger::open)
+            .transpose()?;
+        let symbols_file = match options.symbols_file.as_deref() {
+            Some(path) => match SymbolsFile::watch(path, config, &client).await {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    log::warn!("Symbols file changes won't be picked up: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let config_file = options.config_file.as_ref().and_then(|(path, profile)| {
+            match ConfigFile::watch(path, profile.clone(), config) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    log::warn!("Config file changes won't be picked up: {}", e);
+                    None
+                }
+            }
+        });
+        let alert_engine = if options.alerts || options.bell {
+            Some(AlertEngine::new(&config.alerts)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            client,
+            provider,
+            runner: action_runner(config, options.bell, dashboard)?,
+            config: config.clone(),
+            symbols,
+            interval: options.interval,
+            filter: options.filter.clone(),
+            iterations: options.iterations,
+            until: options.until,
+            ticks: 0,
+            started: Instant::now(),
+            market: (options.market_hours || config.watch.market_hours).then(|| MarketHours {
+                config: config.watch.clone(),
+                checked_at: None,
+                trading: true,
+            }),
+            symbols_file,
+            config_file,
+            bell: options.bell,
+            dashboard,
+            quiet: options.quiet,
+            tick_logger,
+            sinks: Sinks::connect(config, &options.sinks)?,
+            last_prune: None,
+            alert_engine,
+            last_summary: Instant::now(),
+            quotes: Vec::new(),
+            last_update: None,
+            last_error: None,
+            fired: Vec::new(),
+            recent_alerts: VecDeque::new(),
+            alerts_fired: 0,
+            news_enabled: false,
+            news: Vec::new(),
+            news_cursor: 0,
+            news_fetched: HashMap::new(),
+            deltas: HashMap::new(),
+            refreshed: HashMap::new(),
+            intervals,
+            next_due: HashMap::new(),
+            stats: HashMap::new(),
+            trend: HashMap::new(),
+            calls: VecDeque::new(),
+        })
+    }
+
+    /// Fetch fresh quotes and run logging, alerts, and periodic summaries
+    ///
+    /// Fetch failures are kept in `last_error` so the session keeps going.
+    pub async fn refresh(&mut self) -> Result<bool> {
+        // Apply retention once per day so long sessions don't grow the log forever
+        if let (Some(logger), Some(keep_days)) = (self.tick_logger.as_mut(), self.config.history.keep_days) {
+            let today = Local::now().date_naive();
+            if self.last_prune != Some(today) {
+                let stats = logger.prune(history::retention_cutoff(keep_days))?;
+                log::debug!("Pruned {} old history records", stats.removed);
+                self.last_prune = Some(today);
+            }
+        }
+
+        if let Some(market) = self.market.as_mut() {
+            if market.update(&self.client).await {
+                // Symbols were scheduled on the slow overnight interval
+                self.next_due.clear();
+            }
+            if !market.trading && market.config.closed_interval_secs == 0 {
+                return Ok(false);
+            }
+        }
+
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .symbols
+            .iter()
+            .filter(|symbol| match self.next_due.get(*symbol) {
+                Some(due) => *due <= now,
+                None => true,
+            })
+            .cloned()
+            .collect();
+        if due.is_empty() {
+            return Ok(false);
+        }
+        // Schedule before fetching so a failing request waits for its interval like any other
+        for symbol in &due {
+            let interval = Duration::from_secs(self.interval_for(symbol));
+            self.next_due.insert(symbol.clone(), now + interval);
+        }
+
+        self.ticks += 1;
+        for _ in &due {
+            self.calls.push_back(now);
+        }
+        while self
+            .calls
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > Duration::from_secs(60))
+        {
+            self.calls.pop_front();
+        }
+
+        let quotes = match self.provider.fetch_quotes(&due).await {
+            Ok(quotes) => quotes,
+            Err(e) => {
+                log::error!("Failed to fetch quotes: {}", e);
+                self.last_error = Some(e.to_string());
+                return Ok(false);
+            }
+        };
+
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        if let Some(logger) = self.tick_logger.as_mut() {
+            logger.append(&timestamp, &quotes)?;
+        }
+        self.sinks.quotes(&timestamp, &quotes).await;
+
+        self.fired.clear();
+        if let Some(engine) = self.alert_engine.as_mut() {
+            engine.refresh_volume(&self.client, &quotes).await;
+            self.fired = self.runner.dispatch(engine.evaluate(&quotes, &self.stats)).await;
+            engine.record(&self.fired);
+            self.sinks.alerts(&self.fired).await;
+        }
+        for record in &self.fired {
+            self.recent_alerts.push_front(record.clone());
+        }
+        self.recent_alerts.truncate(RECENT_ALERTS);
+        self.alerts_fired += self.fired.len();
+
+        alerts::observe(&mut self.stats, &quotes);
+        for quote in &quotes {
+            self.refreshed.insert(quote.symbol.clone(), Instant::now());
+
+            let trend = self.trend.entry(quote.symbol.clone()).or_default();
+            if trend.len() == TREND_POINTS {
+                trend.pop_front();
+            }
+            trend.push_back(quote.price);
+        }
+        self.deltas.extend(tick_deltas(&self.quotes, &quotes));
+        self.quotes = merge_quotes(&self.symbols, std::mem::take(&mut self.quotes), quotes);
+
+        let notifications = &self.config.notifications;
+        let summary_every = Duration::from_secs(notifications.watch_summary_interval_secs);
+        if !notifications.on_watch_summary.is_empty() && self.last_summary.elapsed() >= summary_every {
+            self.runner
+                .report(&ScanReport::new(&self.quotes), &notifications.on_watch_summary)
+                .await;
+            self.last_summary = Instant::now();
+        }
+
+        self.last_update = Some(Local::now());
+        self.last_error = None;
+        self.refresh_news().await;
+        Ok(true)
+    }
+
+    /// Fetch news for the next symbol in rotation, so the feed costs one request per refresh
+    async fn refresh_news(&mut self) {
+        if !self.news_enabled || self.symbols.is_empty() {
+            return;
+        }
+        let symbol = self.symbols[self.news_cursor % self.symbols.len()].clone();
+        self.news_cursor += 1;
+        if self.news_fetched.get(&symbol).is_some_and(|at| at.elapsed() < NEWS_REFRESH) {
+            return;
+        }
+
+        let to = Local::now().date_naive();
+        let from = to - chrono::Duration::days(NEWS_DAYS);
+        self.calls.push_back(Instant::now());
+        self.news_fetched.insert(symbol.clone(), Instant::now());
+        match self.client.fetch_company_news(&symbol, from, to).await {
+            Ok(items) => merge_news(&mut self.news, &symbol, items),
+            Err(e) => log::warn!("Failed to fetch news for {}: {}", symbol, e),
+        }
+    }
+
+    /// Start watching a symbol; it is fetched on the next refresh
+    pub fn add_symbol(&mut self, symbol: String) {
+        if !self.symbols.contains(&symbol) {
+            self.symbols.push(symbol);
+        }
+    }
+
+    pub fn remove_symbol(&mut self, symbol: &str) {
+        self.symbols.retain(|s| s != symbol);
+        self.quotes.retain(|q| q.symbol != symbol);
+        self.next_due.remove(symbol);
+        self.trend.remove(symbol);
+    }
+
+    /// Apply edits to the symbols file, returning true if symbols were added
+    ///
+    /// Only lines that changed in the file count, so symbols added or removed from the dashboard
+    /// stay as they are.
+    pub async fn reload_symbols(&mut self) -> bool {
+        let Some(file) = self.symbols_file.as_mut() else {
+            return false;
+        };
+        let Some((added, removed)) = file.reload(&self.config, &self.client).await else {
+            return false;
+        };
+
+        for symbol in removed {
+            // Group symbols come from the config or command line, not the file
+            if self.symbols.contains(&symbol) && !self.intervals.contains_key(&symbol) {
+                log::info!("Stopped watching {}", symbol);
+                self.remove_symbol(&symbol);
+            }
+        }
+        let added: Vec<String> = added.into_iter().filter(|symbol| !self.symbols.contains(symbol)).collect();
+        for symbol in &added {
+            log::info!("Started watching {}", symbol);
+            self.add_symbol(symbol.clone());
+        }
+        !added.is_empty()
+    }
+
+    /// Apply edits to the config file that are safe mid-session: intervals, market hours,
+    /// the filter, alert rules and notifications. Returns true if anything was applied.
+    pub fn reload_config(&mut self) -> bool {
+        let Some((config, changed)) = self.config_file.as_mut().and_then(ConfigFile::reload) else {
+            return false;
+        };
+
+        let mut applied = Vec::new();
+        for section in changed {
+            match section.as_str() {
+                "filter" => match config.filter.as_deref().map(expr::parse).transpose() {
+                    Ok(filter) => {
+                        self.filter.expr = filter;
+                        applied.push(section);
+                    }
+                    Err(e) => log::warn!("Keeping the previous filter: {}", e),
+                },
+                "watch" => {
+                    if config.watch.interval_secs != self.config.watch.interval_secs {
+                        self.set_interval(config.watch.interval_secs.max(1));
+                    }
+                    if let Some(market) = self.market.as_mut() {
+                        market.config = config.watch.clone();
+                    }
+                    if serde_json::to_value(&config.watch.groups).ok()
+                        != serde_json::to_value(&self.config.watch.groups).ok()
+                    {
+                        log::warn!("watch.groups changed; restart watch to apply it");
+                    }
+                    applied.push(section);
+                }
+                "alerts" => {
+                    if let Some(engine) = self.alert_engine.as_mut() {
+                        if let Err(e) = engine.reload(&config.alerts) {
+                            log::warn!("Keeping the previous alert rules: {}", e);
+                            continue;
+                        }
+                    }
+                    applied.push(section);
+                }
+                "notifications" => {
+                    match action_runner(&config, self.bell, self.dashboard) {
+                        Ok(runner) => self.runner = runner,
+                        Err(e) => {
+                            log::warn!("Keeping the previous notification settings: {}", e);
+                            continue;
+                        }
+                    }
+                    applied.push(section);
+                }
+                _ => log::warn!("{} changed; restart watch to apply it", section),
+            }
+        }
+
+        self.config = config;
+
+        if applied.is_empty() {
+            return false;
+        }
+        log::info!("Reloaded config: {}", applied.join(", "));
+        true
+    }
+
+    /// Make every symbol due on the next refresh
+    pub fn refresh_all(&mut self) {
+        self.next_due.clear();
+    }
+
+    /// Change the default interval, rescheduling symbols that aren't in a group
+    pub fn set_interval(&mut self, interval: u64) {
+        let (old, new) = (Duration::from_secs(self.interval), Duration::from_secs(interval));
+        for (symbol, due) in self.next_due.iter_mut() {
+            if !self.intervals.contains_key(symbol) {
+                *due = (*due + new).checked_sub(old).unwrap_or(*due);
+            }
+        }
+        self.interval = interval;
+    }
+
+    /// Seconds between fetches of one symbol
+    fn interval_for(&self, symbol: &str) -> u64 {
+        match &self.market {
+            Some(market) if !market.trading => self.current_interval(),
+            _ => self.intervals.get(symbol).copied().unwrap_or(self.interval),
+        }
+    }
+
+    /// Quote requests made in the last minute, for comparison with the API rate limit
+    pub fn calls_per_minute(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Symbols with the largest moves since the session started, as (symbol, percent)
+    pub fn movers(&self, count: usize) -> Vec<(String, f64)> {
+        let mut movers: Vec<(String, f64)> = self
+            .quotes
+            .iter()
+            .filter_map(|quote| {
+                let stats = self.stats.get(&quote.symbol)?;
+                Some((quote.symbol.clone(), stats.since_start(quote.price)))
+            })
+            .collect();
+        movers.sort_by(|a, b| b.1.abs().partial_cmp(&a.1.abs()).unwrap_or(std::cmp::Ordering::Equal));
+        movers.truncate(count);
+        movers
+    }
+
+    /// Flush the tick log and sinks and print what the session saw
+    pub async fn finish(&mut self) -> Result<()> {
+        if let Some(logger) = self.tick_logger.as_mut() {
+            logger.flush()?;
+        }
+        self.sinks.close().await;
+        if self.quiet {
+            return Ok(());
+        }
+
+        // stderr keeps the summary out of piped --output streams
+        eprintln!("\n📊 Watch session summary:");
+        eprintln!("   Duration: {}", format_elapsed(self.started.elapsed()));
+        eprintln!("   Refreshes: {}", self.ticks);
+        eprintln!("   Alerts fired: {}", self.alerts_fired);
+        let movers = self.movers(5);
+        if !movers.is_empty() {
+            eprintln!("   Biggest moves since start:");
+            for (symbol, change) in movers {
+                eprintln!("      {:<8} {}", symbol, output::format_change(change));
+            }
+        }
+        eprintln!();
+        Ok(())
+    }
+
+    /// Whether `--iterations` or `--until` says the session should end
+    pub fn finished(&self) -> bool {
+        self.iterations.is_some_and(|limit| self.ticks >= limit)
+            || self.until.is_some_and(|until| Local::now() >= until)
+    }
+
+    /// Whether the exchange is trading, when market hours are being followed
+    pub fn market_open(&self) -> Option<bool> {
+        self.market.as_ref().map(|market| market.trading)
+    }
+
+    /// Seconds between refreshes, stretched while the market is closed
+    pub fn current_interval(&self) -> u64 {
+        match &self.market {
+            Some(market) if !market.trading => match market.config.closed_interval_secs {
+                // Paused: only wake up to check whether the market has opened
+                0 => market.config.status_check_secs,
+                secs => secs,
+            },
+            _ => self.interval,
+        }
+    }
+
+    /// Time until the next symbol is due, cut short if `--until` comes first
+    pub fn wait_time(&self) -> Duration {
+        let now = Instant::now();
+        let mut interval = self
+            .symbols
+            .iter()
+            .map(|symbol| match self.next_due.get(symbol) {
+                Some(due) => due.saturating_duration_since(now),
+                None => Duration::ZERO,
+            })
+            .min()
+            .unwrap_or(Duration::from_secs(self.current_interval()));
+        match &self.market {
+            // Paused for the night: nothing is fetched until a status check says otherwise
+            Some(market) if !market.trading && market.config.closed_interval_secs == 0 => {
+                interval = Duration::from_secs(market.config.status_check_secs);
+            }
+            // Wake up for status checks so the open isn't missed by a long closed interval
+            Some(market) if !market.trading => {
+                interval = interval.min(Duration::from_secs(market.config.status_check_secs));
+            }
+            _ => {}
+        }
+        match self.until {
+            Some(until) => (until - Local::now()).to_std().unwrap_or_default().min(interval),
+            None => interval,
+        }
+    }
+}
+
+/// Action runner for alerts and summaries; `dashboard` keeps the `log` action off the TUI
+fn action_runner(config: &Config, bell: bool, dashboard: bool) -> Result<ActionRunner> {
+    let mut runner = ActionRunner::new(config.timeout_secs, config.notifications.clone())?
+        .with_http(&config.http);
+    if bell {
+        runner = runner.with_bell(config.alerts.sound_file.clone());
+    }
+    if dashboard {
+        runner = runner.without_terminal_log();
+    }
+    Ok(runner)
+}
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Spinner frame that advances every quarter second, to show the process is alive
+pub fn spinner() -> char {
+    let frame = chrono::Utc::now().timestamp_millis() / 250;
+    SPINNER[frame.rem_euclid(SPINNER.len() as i64) as usize]
+}
+
+/// Status line shown under the plain table while waiting for the next refresh
+fn footer(session: &Session, remaining: Duration) -> String {
+    let updated = session
+        .last_update
+        .map(|ts| ts.format("%H:%M:%S").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    let mut line = format!(
+        "{} Last update {} | next refresh in {}s",
+        spinner(),
+        updated,
+        remaining.as_secs_f64().ceil() as u64
+    );
+    if session.market_open() == Some(false) {
+        line.push_str(" | market closed");
+    }
+    if let Some(error) = &session.last_error {
+        line.push_str(&format!(" | {}", error));
+    }
+    line
+}
+
+/// Sleep until the next refresh, redrawing the footer each second on a terminal
+async fn countdown(session: &Session, live: bool) -> Result<()> {
+    let deadline = Instant::now() + session.wait_time();
+    if !live {
+        tokio::time::sleep_until(deadline.into()).await;
+        return Ok(());
+    }
+
+    let mut stdout = std::io::stdout();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        write!(stdout, "\r\x1B[2K{}", footer(session, remaining))?;
+        stdout.flush()?;
+        tokio::time::sleep(remaining.min(Duration::from_millis(250))).await;
+    }
+    write!(stdout, "\r\x1B[2K")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Parse `--until` as a local clock time (`16:00` or `16:00:30`)
+pub fn parse_until(raw: &str) -> Result<DateTime<Local>> {
+    let time = NaiveTime::parse_from_str(raw, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(raw, "%H:%M:%S"))
+        .map_err(|_| ScannerError::InvalidInput(format!("Invalid time '{}', expected HH:MM", raw)))?;
+    next_occurrence(time, Local::now())
+}
+
+/// The next time the clock reads `time`, tomorrow if it has already passed today
+fn next_occurrence(time: NaiveTime, now: DateTime<Local>) -> Result<DateTime<Local>> {
+    let mut date = now.date_naive();
+    if now.time() >= time {
+        date = date.succ_opt().unwrap_or(date);
+    }
+
+    date.and_time(time)
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| ScannerError::InvalidInput(format!("{} does not exist in the local timezone", time)))
+}
+
+/// Add articles to the feed, newest first, dropping duplicates and the oldest overflow
+fn merge_news(feed: &mut Vec<NewsItem>, symbol: &str, items: Vec<NewsItem>) {
+    for mut item in items {
+        if feed.iter().any(|existing| existing.id == item.id && existing.headline == item.headline) {
+            continue;
+        }
+        // Finnhub's `related` can be empty or list several tickers; show the one we asked for
+        item.related = symbol.to_string();
+        feed.push(item);
+    }
+    feed.sort_by_key(|item| std::cmp::Reverse(item.datetime));
+    feed.truncate(NEWS_LIMIT);
+}
+
+/// Format a session length as `1h 05m` or `3m 20s`
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
+
+/// Symbols added to and removed from a watch list
+fn diff_symbols(current: &[String], updated: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = updated.iter().filter(|s| !current.contains(s)).cloned().collect();
+    let removed = current.iter().filter(|s| !updated.contains(s)).cloned().collect();
+    (added, removed)
+}
+
+/// Replace quotes with freshly fetched ones, keeping watch-list order
+pub fn merge_quotes(symbols: &[String], previous: Vec<StockQuote>, fetched: Vec<StockQuote>) -> Vec<StockQuote> {
+    let mut by_symbol: HashMap<String, StockQuote> = previous
+        .into_iter()
+        .chain(fetched)
+        .map(|quote| (quote.symbol.clone(), quote))
+        .collect();
+
+    symbols.iter().filter_map(|symbol| by_symbol.remove(symbol)).collect()
+}
+
+/// Parse `--symbol-interval AAPL,TSLA=10`
+pub fn parse_symbol_interval(raw: &str) -> Result<SymbolGroup> {
+    let invalid = || ScannerError::InvalidInput(format!("Invalid symbol interval '{}', expected SYMBOLS=SECONDS", raw));
+    let (symbols, secs) = raw.split_once('=').ok_or_else(invalid)?;
+    let interval_secs = secs.trim().parse().map_err(|_| invalid())?;
+    let symbols: Vec<String> = symbols
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if symbols.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(SymbolGroup { symbols, interval_secs })
+}
+
+/// Price movement of each symbol between two refreshes; new symbols have no delta
+pub fn tick_deltas(previous: &[StockQuote], current: &[StockQuote]) -> HashMap<String, f64> {
+    let previous: HashMap<&str, f64> = previous.iter().map(|q| (q.symbol.as_str(), q.price)).collect();
+
+    current
+        .iter()
+        .filter_map(|quote| {
+            previous
+                .get(quote.symbol.as_str())
+                .map(|before| (quote.symbol.clone(), quote.price - before))
+        })
+        .collect()
+}
+
+/// Run `watch`, using the dashboard on an interactive terminal
+pub async fn run(config: &Config, symbols: Vec<String>, options: WatchOptions) -> Result<()> {
+    // The dashboard comes with the `cli` feature; library builds always use the plain loop
+    let dashboard = cfg!(feature = "cli")
+        && !options.plain
+        && !options.quiet
+        && !options.no_clear
+        && options.output.is_none()
+        && std::io::stdout().is_terminal();
+    let format = options.output.unwrap_or_default();
+    // A live footer only makes sense when the table is redrawn in place on a terminal
+    let live_footer =
+        format == OutputFormat::Table && !options.quiet && !options.no_clear && std::io::stdout().is_terminal();
+    let mut session = Session::new(config, symbols, &options, dashboard).await?;
+
+    #[cfg(feature = "cli")]
+    if dashboard {
+        tui::run(&mut session).await?;
+        return session.finish().await;
+    }
+
+    log::info!("Starting watch mode. Press Ctrl+C to exit.");
+
+    // The first Ctrl+C or SIGTERM ends the session after the current refresh; a second one exits at once
+    let (stop_tx, mut stop) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        systemd::shutdown_signal().await;
+        systemd::stopping();
+        let _ = stop_tx.send(true);
+        systemd::shutdown_signal().await;
+        usage::finish();
+        std::process::exit(130);
+    });
+    systemd::ready(&format!("Watching {} symbols", session.symbols.len()));
+
+    while !session.finished() && !*stop.borrow() {
+        session.reload_symbols().await;
+        session.reload_config();
+        let refreshed = session.refresh().await?;
+        systemd::watchdog();
+        if refreshed {
+            // Only the table redraws in place; other formats stream so they can be piped
+            if options.no_clear {
+                if !options.quiet && matches!(format, OutputFormat::Table | OutputFormat::Compact) {
+                    println!("\n--- {} ---", Local::now().format("%Y-%m-%d %H:%M:%S"));
+                }
+            } else if format == OutputFormat::Table {
+                output::clear_screen();
+            }
+            let quotes = session.filter.apply(session.quotes.clone());
+            output::display(&quotes, &[], format, options.quiet, false)?;
+            let updated = Local::now().format("%H:%M:%S");
+            log::info!("Updated at: {}", updated);
+            systemd::status(&format!("Updated {} quotes at {}", session.quotes.len(), updated));
+        }
+
+        if session.finished() || *stop.borrow() {
+            break;
+        }
+        tokio::select! {
+            result = countdown(&session, live_footer) => result?,
+            _ = stop.changed() => break,
+        }
+    }
+
+    session.finish().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_tick_deltas() {
+        let deltas = tick_deltas(
+            &[StockQuote::test("AAPL", 200.0, 200.0), StockQuote::test("MSFT", 400.0, 400.0)],
+            &[
+                StockQuote::test("AAPL", 201.5, 201.5),
+                StockQuote::test("MSFT", 399.0, 399.0),
+                StockQuote::test("NVDA", 120.0, 120.0),
+            ],
+        );
+
+        assert_eq!(deltas["AAPL"], 1.5);
+        assert_eq!(deltas["MSFT"], -1.0);
+        assert!(!deltas.contains_key("NVDA"));
+    }
+
+    #[test]
+    fn test_merge_quotes_keeps_order() {
+        let symbols = vec!["AAPL".to_string(), "MSFT".to_string(), "NVDA".to_string()];
+        let merged = merge_quotes(
+            &symbols,
+            vec![StockQuote::test("AAPL", 200.0, 200.0), StockQuote::test("MSFT", 400.0, 400.0)],
+            vec![StockQuote::test("NVDA", 120.0, 120.0), StockQuote::test("AAPL", 201.0, 201.0)],
+        );
+
+        let prices: Vec<(&str, f64)> = merged.iter().map(|q| (q.symbol.as_str(), q.price)).collect();
+        assert_eq!(prices, vec![("AAPL", 201.0), ("MSFT", 400.0), ("NVDA", 120.0)]);
+    }
+
+    #[test]
+    fn test_merge_news() {
+        let item = |id: i64, datetime: i64| NewsItem {
+            id,
+            datetime,
+            headline: format!("headline {}", id),
+            source: "Reuters".to_string(),
+            related: String::new(),
+            url: String::new(),
+        };
+        let mut feed = vec![item(1, 100)];
+
+        merge_news(&mut feed, "AAPL", vec![item(1, 100), item(2, 300), item(3, 200)]);
+
+        let ids: Vec<i64> = feed.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![2, 3, 1]);
+        assert_eq!(feed[0].related, "AAPL");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(200)), "3m 20s");
+        assert_eq!(format_elapsed(Duration::from_secs(2 * 3600 + 5 * 60 + 9)), "2h 05m");
+    }
+
+    #[test]
+    fn test_diff_symbols() {
+        let current = vec!["AAPL".to_string(), "MSFT".to_string()];
+        let updated = vec!["MSFT".to_string(), "NVDA".to_string()];
+
+        let (added, removed) = diff_symbols(&current, &updated);
+        assert_eq!(added, vec!["NVDA"]);
+        assert_eq!(removed, vec!["AAPL"]);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_matches_startup_symbols() {
+        let config = Config::default();
+        let client = FinnhubClient::builder().api_key("test").build().unwrap();
+        let before = prepare(&["TSX:SHOP", "@faang"].map(String::from), &config, &client).await.unwrap();
+        let after = prepare(&["SHOP.TO", "@FAANG", "MSFT"].map(String::from), &config, &client).await.unwrap();
+
+        let (added, removed) = diff_symbols(&before, &after);
+        assert_eq!(added, vec!["MSFT"]);
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn test_parse_symbol_interval() {
+        let group = parse_symbol_interval("aapl, tsla=10").unwrap();
+        assert_eq!(group.symbols, vec!["AAPL", "TSLA"]);
+        assert_eq!(group.interval_secs, 10);
+
+        assert!(parse_symbol_interval("AAPL").is_err());
+        assert!(parse_symbol_interval("=10").is_err());
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let close = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+        let morning = Local.with_ymd_and_hms(2026, 3, 2, 9, 30, 0).unwrap();
+        let evening = Local.with_ymd_and_hms(2026, 3, 2, 17, 0, 0).unwrap();
+
+        assert_eq!(next_occurrence(close, morning).unwrap(), Local.with_ymd_and_hms(2026, 3, 2, 16, 0, 0).unwrap());
+        assert_eq!(next_occurrence(close, evening).unwrap(), Local.with_ymd_and_hms(2026, 3, 3, 16, 0, 0).unwrap());
+        assert!(parse_until("4pm").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use crate::config;
+use crate::errors::{Result, ScannerError};
+use crate::paths;
+
+/// Where watchlists are kept: `watchlists/` under the data directory
+pub fn default_dir() -> PathBuf {
+    paths::data_file("watchlists")
+}
+
+/// Named symbol lists, each an ordinary symbols file `<name>.txt` in one directory
+#[derive(Debug, Clone)]
+pub struct Watchlists {
+    dir: PathBuf,
+}
+
+impl Watchlists {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The symbols file behind a watchlist, whether or not it exists yet
+    pub fn path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+        if !valid {
+            return Err(ScannerError::InvalidInput(format!(
+                "Invalid watchlist name '{}': use letters, digits, - and _",
+                name
+            )));
+        }
+        Ok(self.dir.join(format!("{}.txt", name)))
+    }
+
+    /// The symbols file of a watchlist that exists, for `--watchlist`
+    pub fn existing_path(&self, name: &str) -> Result<PathBuf> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(self.not_found(name));
+        }
+        Ok(path)
+    }
+
+    /// A watchlist's symbols, in the order they were added
+    pub fn load(&self, name: &str) -> Result<Vec<String>> {
+        let content = match fs::read_to_string(self.path(name)?) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Err(self.not_found(name)),
+            Err(e) => return Err(ScannerError::from(e).context(&format!("Failed to read watchlist {}", name))),
+        };
+        match config::read_symbols(content.as_bytes()) {
+            Err(ScannerError::NoSymbols) => Ok(Vec::new()),
+            symbols => symbols,
+        }
+    }
+
+    /// Add symbols not already on the list, creating it if needed; returns how many were new
+    pub fn add(&self, name: &str, symbols: &[String]) -> Result<usize> {
+        let mut list = if self.path(name)?.exists() { self.load(name)? } else { Vec::new() };
+        let before = list.len();
+        for symbol in symbols.iter().map(|s| s.trim().to_uppercase()).filter(|s| !s.is_empty()) {
+            if !list.contains(&symbol) {
+                list.push(symbol);
+            }
+        }
+        self.save(name, &list)?;
+        Ok(list.len() - before)
+    }
+
+    /// Take symbols off a list; returns how many were on it
+    pub fn remove(&self, name: &str, symbols: &[String]) -> Result<usize> {
+        let mut list = self.load(name)?;
+        let before = list.len();
+        list.retain(|symbol| !symbols.iter().any(|s| s.trim().eq_ignore_ascii_case(symbol)));
+        self.save(name, &list)?;
+        Ok(before - list.len())
+    }
+
+    /// Delete a whole watchlist
+    pub fn delete(&self, name: &str) -> Result<()> {
+        match fs::remove_file(self.path(name)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(self.not_found(name)),
+            Err(e) => Err(ScannerError::from(e).context(&format!("Failed to delete watchlist {}", name))),
+        }
+    }
+
+    /// Every watchlist's name and size, by name
+    pub fn list(&self) -> Result<Vec<(String, usize)>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(ScannerError::from(e).context("Failed to read the watchlist directory")),
+        };
+
+        let mut lists = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("txt") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if self.path(name).is_ok() {
+                lists.push((name.to_string(), self.load(name)?.len()));
+            }
+        }
+        lists.sort();
+        Ok(lists)
+    }
+
+    fn save(&self, name: &str, symbols: &[String]) -> Result<()> {
+        let path = self.path(name)?;
+        let write = || -> std::io::Result<()> {
+            fs::create_dir_all(&self.dir)?;
+            let mut content = String::new();
+            for symbol in symbols {
+                content.push_str(symbol);
+                content.push('\n');
+            }
+            fs::write(&path, content)
+        };
+        write().map_err(|e| ScannerError::from(e).context(&format!("Failed to write watchlist {}", name)))
+    }
+
+    fn not_found(&self, name: &str) -> ScannerError {
+        ScannerError::InvalidInput(format!(
+            "No watchlist named '{}'; create it with `watchlist add {} <SYMBOLS>`",
+            name, name
+        ))
+    }
+}
+
+impl Default for Watchlists {
+    fn default() -> Self {
+        Self::new(default_dir())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_add_remove_list() {
+        let dir = tempdir().unwrap();
+        let watchlists = Watchlists::new(dir.path().join("watchlists"));
+        let symbols = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert!(watchlists.list().unwrap().is_empty());
+        assert_eq!(watchlists.add("tech", &symbols(&["aapl", "MSFT", "AAPL"])).unwrap(), 2);
+        assert_eq!(watchlists.add("tech", &symbols(&["NVDA", "msft"])).unwrap(), 1);
+        assert_eq!(watchlists.load("tech").unwrap(), ["AAPL", "MSFT", "NVDA"]);
+
+        assert_eq!(watchlists.remove("tech", &symbols(&["msft", "TSLA"])).unwrap(), 1);
+        watchlists.add("energy", &symbols(&["XOM"])).unwrap();
+        assert_eq!(
+            watchlists.list().unwrap(),
+            [("energy".to_string(), 1), ("tech".to_string(), 2)]
+        );
+
+        watchlists.delete("energy").unwrap();
+        assert!(watchlists.load("energy").unwrap_err().to_string().contains("No watchlist named"));
+        assert!(watchlists.path("../etc").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use serde::Deserialize;
+use std::time::Duration;
+use crate::config::Config;
+use crate::errors::{Result, ScannerError};
+use crate::finnhub::{Quote, StockQuote};
+use crate::provider::{ProviderFuture, QuoteProvider};
+
+const BASE_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+
+/// Yahoo turns away requests without a browser-like user agent
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; finnhub-scanner)";
+
+/// Quotes from Yahoo Finance's unofficial chart endpoint, which needs no API key
+///
+/// The endpoint is undocumented and may change or throttle without notice, so this is meant as
+/// a fallback for simple quotes rather than a replacement for Finnhub.
+pub struct YahooProvider {
+    client: reqwest::Client,
+    /// Also set per request, see [`crate::config::HttpConfig::client_builder`]
+    timeout: Duration,
+}
+
+#[derive(Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Deserialize)]
+struct Chart {
+    #[serde(default)]
+    result: Option<Vec<ChartResult>>,
+    #[serde(default)]
+    error: Option<ChartError>,
+}
+
+#[derive(Deserialize)]
+struct ChartError {
+    code: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct ChartResult {
+    meta: Meta,
+    #[serde(default)]
+    indicators: Option<Indicators>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    regular_market_price: Option<f64>,
+    previous_close: Option<f64>,
+    chart_previous_close: Option<f64>,
+    regular_market_day_high: Option<f64>,
+    regular_market_day_low: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Indicators {
+    #[serde(default)]
+    quote: Vec<Bars>,
+}
+
+#[derive(Deserialize)]
+struct Bars {
+    #[serde(default)]
+    open: Vec<Option<f64>>,
+}
+
+impl YahooProvider {
+    pub fn new(config: &Config) -> Result<Self> {
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let client = config.http.client_builder(timeout)?.user_agent(USER_AGENT).build()?;
+        Ok(Self { client, timeout })
+    }
+}
+
+impl QuoteProvider for YahooProvider {
+    fn name(&self) -> &str {
+        "yahoo"
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Result<StockQuote>> {
+        Box::pin(async move {
+            log::debug!("Fetching Yahoo Finance quote for {}", symbol);
+            let response = self
+                .client
+                .get(format!("{}/{}", BASE_URL, symbol))
+                .query(&[("range", "1d"), ("interval", "1d")])
+                .timeout(self.timeout)
+                .send()
+                .await?;
+            let status = response.status();
+            if status.as_u16() == 429 {
+                return Err(ScannerError::RateLimited { retry_after: None });
+            }
+            // Unknown symbols come back as 404 with the reason in the body
+            if !status.is_success() && status.as_u16() != 404 {
+                return Err(ScannerError::Http {
+                    status: status.as_u16(),
+                    context: symbol.to_string(),
+                });
+            }
+
+            let body: ChartResponse = response.json().await?;
+            let quote = parse_chart(symbol, body)?;
+            Ok(StockQuote::from_quote(symbol.to_string(), quote))
+        })
+    }
+}
+
+fn parse_chart(symbol: &str, body: ChartResponse) -> Result<Quote> {
+    if let Some(error) = body.chart.error {
+        return match error.code.as_str() {
+            "Not Found" => Err(ScannerError::SymbolNotFound(symbol.to_string())),
+            _ => Err(ScannerError::Api(format!("Yahoo Finance: {} ({})", error.description, error.code))),
+        };
+    }
+
+    let result = body
+        .chart
+        .result
+        .and_then(|results| results.into_iter().next())
+        .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+    let meta = result.meta;
+    let price = meta
+        .regular_market_price
+        .ok_or_else(|| ScannerError::SymbolNotFound(symbol.to_string()))?;
+    let open = result
+        .indicators
+        .and_then(|indicators| indicators.quote.into_iter().next())
+        .and_then(|bars| bars.open.into_iter().flatten().last())
+        .unwrap_or_default();
+
+    Ok(Quote {
+        c: price,
+        pc: meta.previous_close.or(meta.chart_previous_close).unwrap_or_default(),
+        h: meta.regular_market_day_high.unwrap_or_default(),
+        l: meta.regular_market_day_low.unwrap_or_default(),
+        o: open,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(json: &str) -> ChartResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_chart() {
+        let quote = parse_chart(
+            "AAPL",
+            chart(
+                r#"{"chart": {"result": [{"meta": {"currency": "USD", "symbol": "AAPL",
+                "regularMarketPrice": 271.01, "chartPreviousClose": 271.86,
+                "regularMarketDayHigh": 277.84, "regularMarketDayLow": 269.0},
+                "timestamp": [1760448600], "indicators": {"quote": [{"open": [271.5], "close": [271.01]}]}}],
+                "error": null}}"#,
+            ),
+        )
+        .unwrap();
+        assert_eq!(quote.c, 271.01);
+        assert_eq!(quote.pc, 271.86);
+        assert_eq!(quote.h, 277.84);
+        assert_eq!(quote.o, 271.5);
+    }
+
+    #[test]
+    fn test_parse_chart_errors() {
+        let not_found = chart(
+            r#"{"chart": {"result": null, "error": {"code": "Not Found",
+            "description": "No data found, symbol may be delisted"}}}"#,
+        );
+        assert!(matches!(parse_chart("NOPE", not_found), Err(ScannerError::SymbolNotFound(_))));
+
+        let bad_request = chart(r#"{"chart": {"result": null, "error": {"code": "Bad Request"}}}"#);
+        assert!(matches!(parse_chart("AAPL", bad_request), Err(ScannerError::Api(_))));
+    }
+}